@@ -351,6 +351,8 @@ fn benchmark_search_patterns(c: &mut Criterion) {
                     reranker: "hybrid",
                     frequency_search: true,
                     exact: false,
+                    fuzzy: false,
+                    explain: false,
                     language: None,
                     max_results: Some(100),
                     max_bytes: None,
@@ -363,6 +365,8 @@ fn benchmark_search_patterns(c: &mut Criterion) {
                     timeout: 30,
                     question: None,
                     no_gitignore: false,
+                    file_filters: Default::default(),
+                    source: None,
                 };
 
                 black_box(perform_probe(&options).unwrap())
@@ -394,6 +398,8 @@ fn benchmark_result_limits(c: &mut Criterion) {
                     reranker: "hybrid",
                     frequency_search: true,
                     exact: false,
+                    fuzzy: false,
+                    explain: false,
                     language: None,
                     max_results: Some(limit),
                     max_bytes: None,
@@ -406,6 +412,8 @@ fn benchmark_result_limits(c: &mut Criterion) {
                     timeout: 30,
                     question: None,
                     no_gitignore: false,
+                    file_filters: Default::default(),
+                    source: None,
                 };
 
                 black_box(perform_probe(&options).unwrap())
@@ -441,6 +449,8 @@ fn benchmark_search_options(c: &mut Criterion) {
                         reranker,
                         frequency_search: true,
                         exact: false,
+                        fuzzy: false,
+                        explain: false,
                         language: None,
                         max_results: Some(50),
                         max_bytes: None,
@@ -453,6 +463,8 @@ fn benchmark_search_options(c: &mut Criterion) {
                         timeout: 30,
                         question: None,
                         no_gitignore: false,
+                        file_filters: Default::default(),
+                        source: None,
                     };
 
                     black_box(perform_probe(&options).unwrap())
@@ -477,6 +489,8 @@ fn benchmark_search_options(c: &mut Criterion) {
                     reranker: "hybrid",
                     frequency_search: freq,
                     exact: false,
+                    fuzzy: false,
+                    explain: false,
                     language: None,
                     max_results: Some(50),
                     max_bytes: None,
@@ -489,6 +503,8 @@ fn benchmark_search_options(c: &mut Criterion) {
                     timeout: 30,
                     question: None,
                     no_gitignore: false,
+                    file_filters: Default::default(),
+                    source: None,
                 };
 
                 black_box(perform_probe(&options).unwrap())
@@ -511,6 +527,8 @@ fn benchmark_search_options(c: &mut Criterion) {
                     reranker: "hybrid",
                     frequency_search: freq,
                     exact: false,
+                    fuzzy: false,
+                    explain: false,
                     language: None,
                     max_results: Some(50),
                     max_bytes: None,
@@ -523,6 +541,8 @@ fn benchmark_search_options(c: &mut Criterion) {
                     timeout: 30,
                     question: None,
                     no_gitignore: false,
+                    file_filters: Default::default(),
+                    source: None,
                 };
 
                 black_box(perform_probe(&options).unwrap())
@@ -566,6 +586,8 @@ fn benchmark_query_complexity(c: &mut Criterion) {
                         reranker: "hybrid",
                         frequency_search: true,
                         exact: false,
+                        fuzzy: false,
+                        explain: false,
                         language: None,
                         max_results: Some(100),
                         max_bytes: None,
@@ -578,6 +600,8 @@ fn benchmark_query_complexity(c: &mut Criterion) {
                         timeout: 30,
                         question: None,
                         no_gitignore: false,
+                        file_filters: Default::default(),
+                        source: None,
                     };
 
                     black_box(perform_probe(&options).unwrap())
@@ -0,0 +1,93 @@
+//! Capturing what commands print.
+//!
+//! Search, extract and query print their results to stdout. The daemon, which
+//! runs them in-process, redirects the stdout file descriptor for the duration
+//! of a command, which catches output from every thread and from `print!` alike.
+
+use anyhow::{bail, Result};
+use std::fs::File;
+use std::io::{Read, Write};
+use std::os::unix::io::FromRawFd;
+use std::thread;
+
+/// Sends everything written to a file descriptor to a callback while in place
+pub struct Redirect {
+    fd: i32,
+    saved_fd: i32,
+    forwarder: thread::JoinHandle<()>,
+}
+
+impl Redirect {
+    /// Redirect `fd` until `restore` is called. `sink` is called from another
+    /// thread with the data as it is written.
+    pub fn new(fd: i32, mut sink: impl FnMut(&[u8]) + Send + 'static) -> Result<Self> {
+        flush_std_streams();
+        let mut pipe = [0i32; 2];
+        // SAFETY: pipe writes two descriptors into the array, and the others are
+        // plain descriptor operations checked for errors
+        let saved_fd = unsafe {
+            if libc::pipe(pipe.as_mut_ptr()) != 0 {
+                bail!(
+                    "Failed to create a pipe: {}",
+                    std::io::Error::last_os_error()
+                );
+            }
+            let saved_fd = libc::fcntl(fd, libc::F_DUPFD_CLOEXEC, 0);
+            if saved_fd < 0 || libc::dup2(pipe[1], fd) < 0 {
+                let error = std::io::Error::last_os_error();
+                libc::close(pipe[0]);
+                libc::close(pipe[1]);
+                if saved_fd >= 0 {
+                    libc::close(saved_fd);
+                }
+                bail!("Failed to redirect output: {error}");
+            }
+            libc::fcntl(pipe[0], libc::F_SETFD, libc::FD_CLOEXEC);
+            libc::close(pipe[1]);
+            saved_fd
+        };
+
+        // SAFETY: the read end of the pipe is owned by this file from now on
+        let mut reader = unsafe { File::from_raw_fd(pipe[0]) };
+        let forwarder = thread::spawn(move || {
+            let mut buffer = vec![0u8; 64 * 1024];
+            // Keep reading until the pipe is closed, so writers never block on a full pipe
+            while let Ok(len) = reader.read(&mut buffer) {
+                if len == 0 {
+                    break;
+                }
+                sink(&buffer[..len]);
+            }
+        });
+
+        Ok(Redirect {
+            fd,
+            saved_fd,
+            forwarder,
+        })
+    }
+
+    /// Put the original descriptor back and wait until all output was passed on
+    pub fn restore(self) -> Result<()> {
+        flush_std_streams();
+        // SAFETY: both descriptors are open; the pipe's write end is closed by dup2,
+        // which ends the forwarder thread
+        unsafe {
+            let restored = libc::dup2(self.saved_fd, self.fd);
+            libc::close(self.saved_fd);
+            if restored < 0 {
+                bail!(
+                    "Failed to restore output: {}",
+                    std::io::Error::last_os_error()
+                );
+            }
+        }
+        let _ = self.forwarder.join();
+        Ok(())
+    }
+}
+
+pub fn flush_std_streams() {
+    let _ = std::io::stdout().flush();
+    let _ = std::io::stderr().flush();
+}
@@ -43,6 +43,10 @@ pub struct Args {
     #[arg(short = 'e', long = "exact")]
     pub exact: bool,
 
+    /// Tolerate typos in query terms by also matching words within a small edit distance
+    #[arg(long = "fuzzy")]
+    pub fuzzy: bool,
+
     /// Maximum number of results to return
     #[arg(long = "max-results")]
     pub max_results: Option<usize>,
@@ -63,6 +67,18 @@ pub struct Args {
     #[arg(long = "no-gitignore")]
     pub no_gitignore: bool,
 
+    /// Only search files changed between this git ref and the working tree
+    #[arg(long = "changed-since", value_name = "REF")]
+    pub changed_since: Option<String>,
+
+    /// Only search files staged in git, compared to --changed-since or HEAD
+    #[arg(long = "staged")]
+    pub staged: bool,
+
+    /// Search the files as they were at a git commit, branch or tag, without checking it out
+    #[arg(long = "rev", value_name = "REV")]
+    pub rev: Option<String>,
+
     /// Disable merging of adjacent code blocks after ranking (merging enabled by default)
     #[arg(long = "no-merge", default_value = "false")]
     pub no_merge: bool,
@@ -75,6 +91,10 @@ pub struct Args {
     #[arg(long = "dry-run")]
     pub dry_run: bool,
 
+    /// Print the parsed query, the search patterns and a score breakdown for every result
+    #[arg(long = "explain")]
+    pub explain: bool,
+
     /// Output format (default: color)
     /// Use 'json' or 'xml' for machine-readable output
     #[arg(short = 'o', long = "format", default_value = "color", value_parser = ["terminal", "markdown", "plain", "json", "xml", "color"])]
@@ -137,6 +157,10 @@ pub enum Commands {
         #[arg(short = 'e', long = "exact")]
         exact: bool,
 
+        /// Tolerate typos in query terms by also matching words within a small edit distance
+        #[arg(long = "fuzzy")]
+        fuzzy: bool,
+
         /// Programming language to limit search to specific file extensions
         #[arg(short = 'l', long = "language", value_parser = [
             "rust", "rs",
@@ -150,7 +174,13 @@ pub enum Commands {
             "ruby", "rb",
             "php",
             "swift",
-            "csharp", "cs"
+            "csharp", "cs",
+            "kotlin", "kt", "kts",
+            "scala", "sc",
+            "hcl", "terraform", "tf",
+            "markdown", "md", "mdx",
+            "rst",
+            "vue", "svelte", "astro", "html", "htm"
         ])]
         language: Option<String>,
 
@@ -174,6 +204,18 @@ pub enum Commands {
         #[arg(long = "no-gitignore")]
         no_gitignore: bool,
 
+        /// Only search files changed between this git ref and the working tree
+        #[arg(long = "changed-since", value_name = "REF")]
+        changed_since: Option<String>,
+
+        /// Only search files staged in git, compared to --changed-since or HEAD
+        #[arg(long = "staged")]
+        staged: bool,
+
+        /// Search the files as they were at a git commit, branch or tag, without checking it out
+        #[arg(long = "rev", value_name = "REV")]
+        rev: Option<String>,
+
         /// Disable merging of adjacent code blocks after ranking (merging enabled by default)
         #[arg(long = "no-merge", default_value = "false")]
         no_merge: bool,
@@ -186,6 +228,10 @@ pub enum Commands {
         #[arg(long = "dry-run")]
         dry_run: bool,
 
+        /// Print the parsed query, the search patterns and a score breakdown for every result
+        #[arg(long = "explain")]
+        explain: bool,
+
         /// Output format (default: color)
         /// Use 'json' or 'xml' for machine-readable output with structured data
         #[arg(short = 'o', long = "format", default_value = "color", value_parser = ["terminal", "markdown", "plain", "json", "xml", "color"])]
@@ -224,6 +270,18 @@ pub enum Commands {
         #[arg(long = "no-gitignore")]
         no_gitignore: bool,
 
+        /// Only extract from files changed between this git ref and the working tree
+        #[arg(long = "changed-since", value_name = "REF")]
+        changed_since: Option<String>,
+
+        /// Only extract from files staged in git, compared to --changed-since or HEAD
+        #[arg(long = "staged")]
+        staged: bool,
+
+        /// Extract from the files as they were at a git commit, branch or tag, without checking it out
+        #[arg(long = "rev", value_name = "REV")]
+        rev: Option<String>,
+
         /// Number of context lines to include before and after the extracted block
         #[arg(short = 'c', long = "context", default_value = "0")]
         context_lines: usize,
@@ -298,7 +356,9 @@ pub enum Commands {
             "ruby", "rb",
             "php",
             "swift",
-            "csharp", "cs"
+            "csharp", "cs",
+            "kotlin", "kt", "kts",
+            "scala", "sc"
         ])]
         language: Option<String>,
 
@@ -314,6 +374,18 @@ pub enum Commands {
         #[arg(long = "no-gitignore")]
         no_gitignore: bool,
 
+        /// Only query files changed between this git ref and the working tree
+        #[arg(long = "changed-since", value_name = "REF")]
+        changed_since: Option<String>,
+
+        /// Only query files staged in git, compared to --changed-since or HEAD
+        #[arg(long = "staged")]
+        staged: bool,
+
+        /// Query the files as they were at a git commit, branch or tag, without checking it out
+        #[arg(long = "rev", value_name = "REV")]
+        rev: Option<String>,
+
         /// Maximum number of results to return
         #[arg(long = "max-results")]
         max_results: Option<usize>,
@@ -324,6 +396,82 @@ pub enum Commands {
         format: String,
     },
 
+    /// Build or update the search index of a directory
+    ///
+    /// This command stores the words and the parsed code structure of every file
+    /// in .probe/index. Searches in the directory use the index to skip files that
+    /// can't match and to avoid parsing files again. Only files that changed since
+    /// the last run are re-parsed, and files that changed after indexing are
+    /// searched as usual.
+    Index {
+        /// Directory to index (defaults to current directory)
+        #[arg(value_name = "PATH", default_value = ".")]
+        path: PathBuf,
+
+        /// Custom patterns to ignore (in addition to .gitignore and common patterns)
+        #[arg(short, long)]
+        ignore: Vec<String>,
+
+        /// Index test code too, for searches with --allow-tests
+        #[arg(long = "allow-tests")]
+        allow_tests: bool,
+
+        /// Do not respect .gitignore files and patterns (gitignore is respected by default)
+        #[arg(long = "no-gitignore")]
+        no_gitignore: bool,
+    },
+
+    /// Keep probe running in the background to answer commands faster
+    ///
+    /// The daemon keeps file lists, parsers and parsed code in memory and serves
+    /// search, extract and query commands over a local Unix socket
+    /// (~/.cache/probe/daemon.sock, or PROBE_DAEMON_SOCKET). While it runs, these
+    /// commands are sent to it automatically; set PROBE_NO_DAEMON=1 to run them
+    /// in-process instead. Changes to the watched directory are picked up as they
+    /// happen.
+    Daemon {
+        /// Directory to watch for changes (defaults to current directory)
+        #[arg(value_name = "PATH", default_value = ".")]
+        path: PathBuf,
+    },
+
+    /// Run a Model Context Protocol server over stdio
+    ///
+    /// Exposes search, extract and query as tools for AI assistants. Add it to an
+    /// MCP client configuration with the command `probe` and the argument `mcp`.
+    /// Tools run in this process, so caches stay warm between calls.
+    Mcp {
+        /// Timeout for tool calls in seconds, unless a call sets its own (default: 30)
+        #[arg(short = 't', long = "timeout", default_value = "30")]
+        timeout: u64,
+    },
+
+    /// Run a Language Server Protocol server over stdio
+    ///
+    /// Lets editors search with probe: workspace symbols are the best matching
+    /// code blocks of a search, the custom probe/search request returns search
+    /// results as JSON, and go to definition finds symbols by name in the current
+    /// file and the rest of the workspace. Configure it as an additional language
+    /// server with the command `probe lsp`.
+    Lsp,
+
+    /// Run a local HTTP server with a JSON API
+    ///
+    /// Answers POST requests to /search, /extract and /query. Their JSON bodies
+    /// take the options of the commands by their snake_case names, such as
+    /// {"query": "auth", "path": "src", "max_results": 5}, and they return the
+    /// output of the command with --format json. Relative paths are resolved
+    /// against the directory the server was started in.
+    Serve {
+        /// Port to listen on (0 picks a free one)
+        #[arg(short = 'p', long = "port", default_value = "8080")]
+        port: u16,
+
+        /// Address to listen on; the API has no authentication, so keep it local
+        #[arg(long = "host", default_value = "127.0.0.1")]
+        host: String,
+    },
+
     /// Run performance benchmarks
     ///
     /// This command runs comprehensive performance benchmarks using the Criterion framework.
@@ -0,0 +1,89 @@
+//! Whether probe may use the console.
+//!
+//! Commands print progress to stdout, log to the console when `DEBUG=1` and
+//! exit the process when a search times out. Programs embedding probe through
+//! [`Probe`](crate::Probe) want none of that, so its calls run silenced, without
+//! changing what other calls in the process do.
+
+use std::cell::Cell;
+use std::marker::PhantomData;
+use std::sync::OnceLock;
+
+thread_local! {
+    /// Number of silenced calls in progress on this thread
+    static SILENCED: Cell<usize> = const { Cell::new(0) };
+}
+
+/// Keeps the console silenced for the current thread until dropped
+pub struct Silence(PhantomData<*const ()>);
+
+impl Drop for Silence {
+    fn drop(&mut self) {
+        SILENCED.with(|silenced| silenced.set(silenced.get() - 1));
+    }
+}
+
+/// Silence the console for the current thread until the returned guard is dropped
+pub fn silence() -> Silence {
+    SILENCED.with(|silenced| silenced.set(silenced.get() + 1));
+    Silence(PhantomData)
+}
+
+/// Run `f` with the console silenced. It runs on a thread pool of its own, whose
+/// threads are all silenced, so the work it spreads over threads with rayon is
+/// silenced too.
+pub fn silenced<T: Send>(f: impl FnOnce() -> T + Send) -> T {
+    static POOL: OnceLock<rayon::ThreadPool> = OnceLock::new();
+    let pool = POOL.get_or_init(|| {
+        rayon::ThreadPoolBuilder::new()
+            .thread_name(|i| format!("probe-silenced-{i}"))
+            .start_handler(|_| std::mem::forget(silence()))
+            .build()
+            .expect("Failed to start the threads of silenced calls")
+    });
+    pool.install(f)
+}
+
+/// Whether messages, environment variables meant for the command line and
+/// exiting on timeouts are off for the current thread
+pub fn is_silenced() -> bool {
+    SILENCED.with(|silenced| silenced.get() > 0)
+}
+
+/// Whether debug logging is enabled with `DEBUG=1`
+pub fn debug_mode() -> bool {
+    !is_silenced() && std::env::var("DEBUG").unwrap_or_default() == "1"
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rayon::prelude::*;
+
+    #[test]
+    fn test_silence_nests() {
+        let outer = silence();
+        let inner = silence();
+        assert!(is_silenced());
+        assert!(!debug_mode());
+        drop(inner);
+        assert!(is_silenced());
+        drop(outer);
+        assert!(!is_silenced());
+    }
+
+    #[test]
+    fn test_silence_is_scoped_to_the_call() {
+        let _silence = silence();
+        assert!(!std::thread::spawn(is_silenced).join().unwrap());
+
+        let workers = silenced(|| {
+            (0..64)
+                .into_par_iter()
+                .map(|_| is_silenced())
+                .collect::<Vec<_>>()
+        });
+        assert!(workers.into_iter().all(|silenced| silenced));
+        assert!(!(0..64).into_par_iter().any(|_| is_silenced()));
+    }
+}
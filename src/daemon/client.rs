@@ -0,0 +1,105 @@
+use std::io::{BufWriter, ErrorKind, Write};
+use std::os::unix::net::UnixStream;
+use std::time::Duration;
+
+use probe_code::daemon::protocol::{Frame, Request};
+use probe_code::daemon::socket_path;
+
+/// How much longer than the timeout of a command to wait for the daemon
+const RESPONSE_GRACE: Duration = Duration::from_secs(5);
+
+/// Environment variables that change how commands behave, sent along with requests
+pub(super) fn is_forwarded_env(name: &str) -> bool {
+    (name == "DEBUG" || name.starts_with("PROBE_"))
+        && name != "PROBE_DAEMON_SOCKET"
+        && name != "PROBE_NO_DAEMON"
+}
+
+/// Run a command on the running `probe daemon`, printing its output as if it ran
+/// in this process. `args` are the command line arguments without the program
+/// name, and `timeout` is the timeout of the command itself.
+///
+/// Returns the exit code of the command, or `None` when there is no daemon to run
+/// it (or it refused to, or stopped responding before any output) and the command
+/// should run in this process instead. Set `PROBE_NO_DAEMON=1` to never use the
+/// daemon.
+pub fn forward(args: Vec<String>, timeout: Duration) -> Option<i32> {
+    if std::env::var("PROBE_NO_DAEMON").unwrap_or_default() == "1" {
+        return None;
+    }
+    let debug_mode = std::env::var("DEBUG").unwrap_or_default() == "1";
+
+    let mut stream = UnixStream::connect(socket_path()).ok()?;
+    let request = Request {
+        version: probe_code::version::get_version().to_string(),
+        cwd: std::env::current_dir().ok()?,
+        args,
+        env: std::env::vars()
+            .filter(|(name, _)| is_forwarded_env(name))
+            .collect(),
+        color: colored::control::SHOULD_COLORIZE.should_colorize(),
+    };
+    if let Err(e) = request.write_to(&mut stream) {
+        if debug_mode {
+            eprintln!("DEBUG: Failed to send the command to the daemon: {e}");
+        }
+        return None;
+    }
+    // The daemon ends commands at their timeout, so only a daemon that stopped
+    // responding runs into this one
+    if stream
+        .set_read_timeout(Some(timeout + RESPONSE_GRACE))
+        .is_err()
+    {
+        return None;
+    }
+
+    let mut stdout = BufWriter::new(std::io::stdout().lock());
+    let mut received_output = false;
+    loop {
+        let frame = match Frame::read_from(&mut stream) {
+            Ok(frame) => frame,
+            Err(e) => {
+                let _ = stdout.flush();
+                let timed_out = e.downcast_ref::<std::io::Error>().is_some_and(|e| {
+                    matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut)
+                });
+                if !received_output {
+                    // Nothing was printed yet, so the command can still run here
+                    if debug_mode {
+                        eprintln!("DEBUG: Lost the connection to the daemon: {e}");
+                    }
+                    return None;
+                }
+                if timed_out {
+                    eprintln!("Error: The probe daemon stopped responding");
+                } else {
+                    eprintln!("Error: Lost the connection to the probe daemon: {e}");
+                }
+                return Some(1);
+            }
+        };
+
+        match frame {
+            Frame::Stdout(data) => {
+                received_output = true;
+                let _ = stdout.write_all(&data);
+            }
+            Frame::Stderr(data) => {
+                received_output = true;
+                let _ = stdout.flush();
+                let _ = std::io::stderr().write_all(&data);
+            }
+            Frame::Exit(code) => {
+                let _ = stdout.flush();
+                return Some(code);
+            }
+            Frame::Rejected(reason) => {
+                if debug_mode {
+                    eprintln!("DEBUG: The daemon didn't run the command: {reason}");
+                }
+                return None;
+            }
+        }
+    }
+}
@@ -0,0 +1,24 @@
+// Daemon module - keeps probe's in-memory caches warm between commands by serving
+// `search`, `extract` and `query` over a local Unix socket. The regular CLI
+// forwards these commands to a running daemon and falls back to running them
+// itself when there is none.
+
+mod client;
+mod protocol;
+mod server;
+mod watcher;
+
+use std::path::PathBuf;
+
+pub use client::forward;
+pub use server::run_daemon;
+
+/// Path of the daemon socket, `~/.cache/probe/daemon.sock` unless
+/// `PROBE_DAEMON_SOCKET` is set
+pub fn socket_path() -> PathBuf {
+    if let Some(path) = std::env::var_os("PROBE_DAEMON_SOCKET").filter(|path| !path.is_empty()) {
+        return PathBuf::from(path);
+    }
+    let home_dir = dirs::home_dir().unwrap_or_else(|| PathBuf::from("."));
+    home_dir.join(".cache").join("probe").join("daemon.sock")
+}
@@ -0,0 +1,135 @@
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+use std::io::{BufRead, Read, Write};
+use std::path::PathBuf;
+
+/// A command sent to the daemon, as a single line of JSON
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Request {
+    /// Version of the client; the daemon only runs commands of its own version
+    pub version: String,
+    /// Working directory of the client, relative paths are resolved against it
+    pub cwd: PathBuf,
+    /// Command line arguments, without the program name
+    pub args: Vec<String>,
+    /// `DEBUG` and `PROBE_*` environment variables of the client
+    pub env: Vec<(String, String)>,
+    /// Whether output should be colored, as decided for the client's terminal
+    pub color: bool,
+}
+
+impl Request {
+    pub fn write_to(&self, writer: &mut impl Write) -> Result<()> {
+        serde_json::to_writer(&mut *writer, self)?;
+        writer.write_all(b"\n")?;
+        writer.flush()?;
+        Ok(())
+    }
+
+    pub fn read_from(reader: &mut impl BufRead) -> Result<Self> {
+        let mut line = String::new();
+        if reader.read_line(&mut line)? == 0 {
+            bail!("Connection closed before a request was sent");
+        }
+        Ok(serde_json::from_str(&line)?)
+    }
+}
+
+/// What the daemon sends back while running a command
+#[derive(Debug, Clone, PartialEq)]
+pub enum Frame {
+    /// Output written to stdout
+    Stdout(Vec<u8>),
+    /// Output written to stderr
+    Stderr(Vec<u8>),
+    /// The command finished with this exit code
+    Exit(i32),
+    /// The command was not run, the client should run it itself
+    Rejected(String),
+}
+
+const STDOUT: u8 = 1;
+const STDERR: u8 = 2;
+const EXIT: u8 = 3;
+const REJECTED: u8 = 4;
+
+impl Frame {
+    /// Write the frame as its tag, the length of its payload and the payload
+    pub fn write_to(&self, writer: &mut impl Write) -> Result<()> {
+        let (tag, payload): (u8, &[u8]) = match self {
+            Frame::Stdout(data) => (STDOUT, data),
+            Frame::Stderr(data) => (STDERR, data),
+            Frame::Exit(code) => (EXIT, &code.to_be_bytes()),
+            Frame::Rejected(reason) => (REJECTED, reason.as_bytes()),
+        };
+        writer.write_all(&[tag])?;
+        writer.write_all(&(payload.len() as u32).to_be_bytes())?;
+        writer.write_all(payload)?;
+        writer.flush()?;
+        Ok(())
+    }
+
+    pub fn read_from(reader: &mut impl Read) -> Result<Self> {
+        let mut header = [0u8; 5];
+        reader.read_exact(&mut header)?;
+        let len = u32::from_be_bytes([header[1], header[2], header[3], header[4]]) as usize;
+        let mut payload = vec![0u8; len];
+        reader.read_exact(&mut payload)?;
+
+        Ok(match header[0] {
+            STDOUT => Frame::Stdout(payload),
+            STDERR => Frame::Stderr(payload),
+            EXIT => match <[u8; 4]>::try_from(payload.as_slice()) {
+                Ok(code) => Frame::Exit(i32::from_be_bytes(code)),
+                Err(_) => bail!("Invalid exit frame of {len} bytes"),
+            },
+            REJECTED => Frame::Rejected(String::from_utf8_lossy(&payload).into_owned()),
+            tag => bail!("Unknown frame type {tag}"),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufReader, Cursor};
+
+    #[test]
+    fn test_request_round_trip() {
+        let request = Request {
+            version: "1.0.0".to_string(),
+            cwd: PathBuf::from("/work/project"),
+            args: vec!["search".to_string(), "auth\ntoken".to_string()],
+            env: vec![("DEBUG".to_string(), "1".to_string())],
+            color: true,
+        };
+        let mut buffer = Vec::new();
+        request.write_to(&mut buffer).unwrap();
+        request.write_to(&mut buffer).unwrap();
+
+        let mut reader = BufReader::new(Cursor::new(buffer));
+        assert_eq!(Request::read_from(&mut reader).unwrap(), request);
+        assert_eq!(Request::read_from(&mut reader).unwrap(), request);
+        assert!(Request::read_from(&mut reader).is_err());
+    }
+
+    #[test]
+    fn test_frame_round_trip() {
+        let frames = vec![
+            Frame::Stdout(b"Found 2 results\n".to_vec()),
+            Frame::Stderr(Vec::new()),
+            Frame::Rejected("version mismatch".to_string()),
+            Frame::Exit(-1),
+        ];
+        let mut buffer = Vec::new();
+        for frame in &frames {
+            frame.write_to(&mut buffer).unwrap();
+        }
+
+        let mut reader = Cursor::new(buffer);
+        for frame in &frames {
+            assert_eq!(&Frame::read_from(&mut reader).unwrap(), frame);
+        }
+        assert!(Frame::read_from(&mut reader).is_err());
+    }
+}
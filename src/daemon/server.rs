@@ -0,0 +1,269 @@
+use anyhow::{bail, Context, Result};
+use colored::*;
+use std::fs;
+use std::io::BufReader;
+use std::os::unix::fs::PermissionsExt;
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::panic::{self, AssertUnwindSafe};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use probe_code::capture::Redirect;
+use probe_code::daemon::client::is_forwarded_env;
+use probe_code::daemon::protocol::{Frame, Request};
+use probe_code::daemon::socket_path;
+use probe_code::daemon::watcher::Watcher;
+use probe_code::language::parser_pool::{smart_warm_parser_pool_for_directory, warm_parser_pool};
+use probe_code::search::file_list_cache::retain_file_lists;
+use probe_code::search::search_runner::format_duration;
+use probe_code::search::timeout;
+
+/// Run the daemon for the directory `path` until the process is stopped.
+/// Commands received on the socket are run one at a time by `run`, which gets
+/// their command line arguments without the program name. Commands received
+/// while another one runs are rejected, so their clients run them instead of
+/// waiting for it.
+pub fn run_daemon(path: &Path, run: fn(Vec<String>) -> Result<()>) -> Result<()> {
+    let root = path
+        .canonicalize()
+        .with_context(|| format!("Failed to access {}", path.display()))?;
+    let socket = socket_path();
+    let listener = bind(&socket)?;
+
+    // A timeout must end the command, not the daemon
+    timeout::set_exit_on_timeout(false);
+
+    let start_time = Instant::now();
+    warm_parser_pool();
+    smart_warm_parser_pool_for_directory(&root);
+    let mut watcher = Watcher::new(&root);
+    if watcher.changes().is_none() {
+        println!(
+            "{}",
+            "File changes can't be watched, file lists will be rebuilt for every command".yellow()
+        );
+    }
+
+    println!(
+        "{} {} (watching {}, ready in {})",
+        "Probe daemon listening on".bold().green(),
+        socket.display(),
+        root.display(),
+        format_duration(start_time.elapsed())
+    );
+
+    let daemon_env: Vec<(String, String)> = std::env::vars()
+        .filter(|(name, _)| is_forwarded_env(name))
+        .collect();
+    let busy = Arc::new(AtomicBool::new(false));
+    let (commands, received) = mpsc::channel::<UnixStream>();
+    let worker_busy = Arc::clone(&busy);
+    thread::spawn(move || {
+        for stream in received {
+            if let Err(e) = handle_connection(stream, &mut watcher, &daemon_env, run) {
+                eprintln!("{} {e:#}", "Failed to handle a command:".red());
+            }
+            worker_busy.store(false, Ordering::SeqCst);
+        }
+    });
+
+    for stream in listener.incoming() {
+        let mut stream = match stream {
+            Ok(stream) => stream,
+            Err(e) => {
+                eprintln!("{} {e}", "Failed to accept a connection:".red());
+                continue;
+            }
+        };
+        if busy.swap(true, Ordering::SeqCst) {
+            let _ = Frame::Rejected("The daemon is running another command".to_string())
+                .write_to(&mut stream);
+            continue;
+        }
+        commands.send(stream)?;
+    }
+    Ok(())
+}
+
+/// Create the socket, replacing a socket left behind by a daemon that is gone
+fn bind(socket: &Path) -> Result<UnixListener> {
+    if socket.exists() {
+        if UnixStream::connect(socket).is_ok() {
+            bail!("A probe daemon is already running on {}", socket.display());
+        }
+        fs::remove_file(socket)
+            .with_context(|| format!("Failed to remove stale socket {}", socket.display()))?;
+    }
+    if let Some(parent) = socket.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let listener = UnixListener::bind(socket)
+        .with_context(|| format!("Failed to listen on {}", socket.display()))?;
+    // Only the current user may run commands
+    fs::set_permissions(socket, fs::Permissions::from_mode(0o600))?;
+    Ok(listener)
+}
+
+/// How long to wait for the request of a client that connected
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+fn handle_connection(
+    stream: UnixStream,
+    watcher: &mut Watcher,
+    daemon_env: &[(String, String)],
+    run: fn(Vec<String>) -> Result<()>,
+) -> Result<()> {
+    // A client that never sends its request must not keep others waiting
+    stream.set_read_timeout(Some(REQUEST_TIMEOUT))?;
+    let request = Request::read_from(&mut BufReader::new(&stream))?;
+    let mut writer = &stream;
+
+    let version = probe_code::version::get_version();
+    if request.version != version {
+        Frame::Rejected(format!(
+            "The daemon runs probe {version}, not {}",
+            request.version
+        ))
+        .write_to(&mut writer)?;
+        return Ok(());
+    }
+
+    // Forget the file lists of directories where files were added or removed
+    match watcher.changes() {
+        Some(changes) => retain_file_lists(|root| {
+            !changes
+                .iter()
+                .any(|path| path.starts_with(root) || root.starts_with(path))
+        }),
+        None => retain_file_lists(|_| false),
+    }
+
+    let start_time = Instant::now();
+    let command = request.args.join(" ");
+    let status = {
+        let _env = EnvGuard::set(daemon_env, &request.env);
+        let _cwd = CwdGuard::set(&request.cwd)?;
+        colored::control::set_override(request.color);
+        let status = run_captured(&stream, || run(request.args));
+        colored::control::unset_override();
+        status
+    };
+
+    // Only directories below the watched one are known to stay up to date
+    let root = watcher.root().to_path_buf();
+    retain_file_lists(|path| path.starts_with(&root));
+
+    let status = status?;
+    println!("{command} ({})", format_duration(start_time.elapsed()));
+
+    // The client may have given up waiting, which is not an error of the daemon
+    let _ = Frame::Exit(status).write_to(&mut writer);
+    Ok(())
+}
+
+/// Run `f` with stdout and stderr sent to the client as frames, and return its
+/// exit code
+fn run_captured(stream: &UnixStream, f: impl FnOnce() -> Result<()>) -> Result<i32> {
+    let writer = Arc::new(Mutex::new(stream.try_clone()?));
+    let stdout = Redirect::new(
+        libc::STDOUT_FILENO,
+        frame_sender(Frame::Stdout, Arc::clone(&writer)),
+    )?;
+    let stderr = match Redirect::new(libc::STDERR_FILENO, frame_sender(Frame::Stderr, writer)) {
+        Ok(stderr) => stderr,
+        Err(e) => {
+            stdout.restore()?;
+            return Err(e);
+        }
+    };
+
+    // Errors and panics are reported like the CLI reports them
+    let status = match panic::catch_unwind(AssertUnwindSafe(f)) {
+        Ok(Ok(())) => 0,
+        Ok(Err(e)) => {
+            eprintln!("Error: {e:?}");
+            1
+        }
+        Err(_) => 101,
+    };
+
+    stdout.restore()?;
+    stderr.restore()?;
+    Ok(status)
+}
+
+/// Sends output to the client as frames of the given type
+fn frame_sender(
+    frame: fn(Vec<u8>) -> Frame,
+    writer: Arc<Mutex<UnixStream>>,
+) -> impl FnMut(&[u8]) + Send + 'static {
+    // Output is dropped after the client is gone, so the command can still finish
+    let mut client_gone = false;
+    move |data| {
+        if !client_gone {
+            let mut stream = writer.lock().unwrap();
+            client_gone = frame(data.to_vec()).write_to(&mut *stream).is_err();
+        }
+    }
+}
+
+/// Sets the environment variables of a request and restores the daemon's own
+/// when dropped
+struct EnvGuard<'a> {
+    daemon_env: &'a [(String, String)],
+    request_env: Vec<String>,
+}
+
+impl<'a> EnvGuard<'a> {
+    fn set(daemon_env: &'a [(String, String)], request_env: &[(String, String)]) -> Self {
+        for (name, _) in daemon_env {
+            std::env::remove_var(name);
+        }
+        for (name, value) in request_env {
+            if is_forwarded_env(name) {
+                std::env::set_var(name, value);
+            }
+        }
+        EnvGuard {
+            daemon_env,
+            request_env: request_env.iter().map(|(name, _)| name.clone()).collect(),
+        }
+    }
+}
+
+impl Drop for EnvGuard<'_> {
+    fn drop(&mut self) {
+        for name in &self.request_env {
+            if is_forwarded_env(name) {
+                std::env::remove_var(name);
+            }
+        }
+        for (name, value) in self.daemon_env {
+            std::env::set_var(name, value);
+        }
+    }
+}
+
+/// Changes the working directory to the client's and back when dropped
+struct CwdGuard {
+    previous: PathBuf,
+}
+
+impl CwdGuard {
+    fn set(cwd: &Path) -> Result<Self> {
+        let previous = std::env::current_dir()?;
+        std::env::set_current_dir(cwd)
+            .with_context(|| format!("Failed to change directory to {}", cwd.display()))?;
+        Ok(CwdGuard { previous })
+    }
+}
+
+impl Drop for CwdGuard {
+    fn drop(&mut self) {
+        let _ = std::env::set_current_dir(&self.previous);
+    }
+}
@@ -0,0 +1,256 @@
+use std::path::{Path, PathBuf};
+
+/// Watches a directory tree for files being added, removed or renamed, so the
+/// daemon knows which cached file lists are out of date.
+///
+/// Events are queued by the kernel as the changes happen and collected with
+/// `changes` before each command, so a command always sees every change made
+/// before it was sent. Uses inotify on Linux; on other systems every directory
+/// is reported as changed before each command.
+pub struct Watcher {
+    #[cfg(target_os = "linux")]
+    inotify: inotify::Inotify,
+    root: PathBuf,
+}
+
+impl Watcher {
+    pub fn new(root: &Path) -> Self {
+        Watcher {
+            #[cfg(target_os = "linux")]
+            inotify: inotify::Inotify::new(root),
+            root: root.to_path_buf(),
+        }
+    }
+
+    /// Directory being watched
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Paths that were added, removed or renamed since the last call, or `None`
+    /// when it can't be known which paths changed
+    #[cfg(target_os = "linux")]
+    pub fn changes(&mut self) -> Option<Vec<PathBuf>> {
+        self.inotify.changes()
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    pub fn changes(&mut self) -> Option<Vec<PathBuf>> {
+        None
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod inotify {
+    use ignore::WalkBuilder;
+    use std::collections::HashMap;
+    use std::ffi::{CString, OsStr};
+    use std::io;
+    use std::os::unix::ffi::OsStrExt;
+    use std::path::{Path, PathBuf};
+
+    /// Events that change which files a directory contains. Writes are only
+    /// watched for ignore files, which change the file list as well.
+    const WATCH_MASK: u32 = libc::IN_CREATE
+        | libc::IN_DELETE
+        | libc::IN_MOVED_FROM
+        | libc::IN_MOVED_TO
+        | libc::IN_DELETE_SELF
+        | libc::IN_MOVE_SELF
+        | libc::IN_CLOSE_WRITE
+        | libc::IN_ONLYDIR;
+
+    const IGNORE_FILES: &[&str] = &[".gitignore", ".ignore", ".git"];
+
+    pub struct Inotify {
+        fd: i32,
+        /// Watched directory of each watch descriptor
+        watches: HashMap<i32, PathBuf>,
+        /// Set when a watch couldn't be added or events were lost, after which
+        /// changes can no longer be tracked
+        broken: bool,
+    }
+
+    impl Inotify {
+        pub fn new(root: &Path) -> Self {
+            // SAFETY: inotify_init1 has no memory safety requirements
+            let fd = unsafe { libc::inotify_init1(libc::IN_NONBLOCK | libc::IN_CLOEXEC) };
+            let mut inotify = Inotify {
+                fd,
+                watches: HashMap::new(),
+                broken: fd < 0,
+            };
+            inotify.watch_tree(root);
+            inotify
+        }
+
+        /// Watch a directory and every directory below it, including ignored ones,
+        /// since searches with `--no-gitignore` list their files too
+        fn watch_tree(&mut self, dir: &Path) {
+            if self.broken {
+                return;
+            }
+            let walker = WalkBuilder::new(dir)
+                .standard_filters(false)
+                .filter_entry(|entry| entry.file_name() != ".git")
+                .build();
+            for entry in walker.flatten() {
+                if entry.file_type().is_some_and(|t| t.is_dir())
+                    && self.watch(entry.path()).is_err()
+                {
+                    // Usually means the fs.inotify.max_user_watches limit was reached
+                    self.broken = true;
+                    return;
+                }
+            }
+        }
+
+        fn watch(&mut self, dir: &Path) -> io::Result<()> {
+            let path = CString::new(dir.as_os_str().as_bytes())?;
+            // SAFETY: the path is a valid NUL-terminated string
+            let wd = unsafe { libc::inotify_add_watch(self.fd, path.as_ptr(), WATCH_MASK) };
+            if wd < 0 {
+                return Err(io::Error::last_os_error());
+            }
+            self.watches.insert(wd, dir.to_path_buf());
+            Ok(())
+        }
+
+        pub fn changes(&mut self) -> Option<Vec<PathBuf>> {
+            let mut changes = Vec::new();
+            let mut new_dirs = Vec::new();
+            // Aligned for inotify_event, and large enough for any single event
+            let mut buffer = vec![0u64; 4096];
+
+            while !self.broken {
+                // SAFETY: the buffer is valid for writes of its whole length
+                let len = unsafe {
+                    libc::read(
+                        self.fd,
+                        buffer.as_mut_ptr().cast(),
+                        buffer.len() * std::mem::size_of::<u64>(),
+                    )
+                };
+                if len < 0 {
+                    if io::Error::last_os_error().kind() != io::ErrorKind::WouldBlock {
+                        self.broken = true;
+                    }
+                    break;
+                }
+                if len == 0 {
+                    break;
+                }
+
+                let bytes = as_bytes(&buffer, len as usize);
+                let mut offset = 0;
+                while offset + std::mem::size_of::<libc::inotify_event>() <= bytes.len() {
+                    // SAFETY: the kernel writes whole events, each starting with an
+                    // inotify_event header; read_unaligned doesn't need alignment
+                    let event: libc::inotify_event =
+                        unsafe { std::ptr::read_unaligned(bytes[offset..].as_ptr().cast()) };
+                    let name_start = offset + std::mem::size_of::<libc::inotify_event>();
+                    let name_end = name_start + event.len as usize;
+                    let name = bytes[name_start..name_end.min(bytes.len())]
+                        .split(|&b| b == 0)
+                        .next()
+                        .unwrap_or_default();
+                    offset = name_end;
+
+                    if event.mask & libc::IN_Q_OVERFLOW != 0 {
+                        self.broken = true;
+                        break;
+                    }
+                    let Some(dir) = self.watches.get(&event.wd) else {
+                        continue;
+                    };
+                    let path = if name.is_empty() {
+                        dir.clone()
+                    } else {
+                        dir.join(OsStr::from_bytes(name))
+                    };
+
+                    if event.mask & libc::IN_IGNORED != 0 {
+                        // The directory is gone and so is its watch
+                        self.watches.remove(&event.wd);
+                        continue;
+                    }
+                    if event.mask & libc::IN_CLOSE_WRITE != 0
+                        && !IGNORE_FILES.contains(&&*String::from_utf8_lossy(name))
+                    {
+                        continue;
+                    }
+                    if event.mask & libc::IN_ISDIR != 0
+                        && event.mask & (libc::IN_CREATE | libc::IN_MOVED_TO) != 0
+                    {
+                        new_dirs.push(path.clone());
+                    }
+                    changes.push(path);
+                }
+            }
+
+            for dir in new_dirs {
+                self.watch_tree(&dir);
+            }
+            changes.dedup();
+            if self.broken {
+                None
+            } else {
+                Some(changes)
+            }
+        }
+    }
+
+    /// The first `len` bytes of a buffer of u64s
+    fn as_bytes(buffer: &[u64], len: usize) -> &[u8] {
+        // SAFETY: any initialized memory is valid as bytes, and len is at most the
+        // size of the buffer since read never returns more than it was given
+        unsafe { std::slice::from_raw_parts(buffer.as_ptr().cast(), len) }
+    }
+
+    impl Drop for Inotify {
+        fn drop(&mut self) {
+            if self.fd >= 0 {
+                // SAFETY: the descriptor is owned by this struct
+                unsafe { libc::close(self.fd) };
+            }
+        }
+    }
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_watcher_reports_added_and_removed_files() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path().canonicalize().unwrap();
+        fs::create_dir(root.join("src")).unwrap();
+        fs::write(root.join("src/a.rs"), "fn a() {}").unwrap();
+
+        let mut watcher = Watcher::new(&root);
+        assert_eq!(watcher.changes(), Some(vec![]));
+
+        // Changing the content of a file doesn't change any file list
+        fs::write(root.join("src/a.rs"), "fn a() { b() }").unwrap();
+        assert_eq!(watcher.changes(), Some(vec![]));
+
+        fs::write(root.join("src/b.rs"), "fn b() {}").unwrap();
+        fs::remove_file(root.join("src/a.rs")).unwrap();
+        assert_eq!(
+            watcher.changes(),
+            Some(vec![root.join("src/b.rs"), root.join("src/a.rs")])
+        );
+
+        // New directories are watched too
+        fs::create_dir(root.join("lib")).unwrap();
+        assert_eq!(watcher.changes(), Some(vec![root.join("lib")]));
+        fs::write(root.join("lib/c.rs"), "fn c() {}").unwrap();
+        assert_eq!(watcher.changes(), Some(vec![root.join("lib/c.rs")]));
+
+        fs::write(root.join(".gitignore"), "lib/\n").unwrap();
+        assert_eq!(watcher.changes(), Some(vec![root.join(".gitignore")]));
+    }
+}
@@ -0,0 +1,237 @@
+//! Library API for programs embedding probe.
+
+use anyhow::Result;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use probe_code::console;
+use probe_code::extract::{perform_extract, ExtractOptions, ExtractResults};
+use probe_code::models::LimitedSearchResults;
+use probe_code::query::{perform_query, AstMatch, QueryOptions};
+use probe_code::search::{perform_probe, SearchOptions};
+use probe_code::source::Source;
+
+/// Searches, extracts and queries the code in a directory from another program.
+///
+/// ```no_run
+/// use probe_code::Probe;
+///
+/// let probe = Probe::new("./src").max_results(10).allow_tests(true);
+///
+/// let results = probe.search("session AND token")?;
+/// for result in &results.results {
+///     println!("{}:{}-{}", result.file, result.lines.0, result.lines.1);
+/// }
+///
+/// let blocks = probe.extract(&["auth.rs:42"])?;
+/// let functions = probe.language("rust").query("fn $NAME($$$PARAMS) $$$BODY")?;
+/// # Ok::<(), anyhow::Error>(())
+/// ```
+///
+/// Unlike the functions behind the commands, its calls print nothing, ignore the
+/// `DEBUG` and `PROBE_SESSION_ID` environment variables and never exit the
+/// process: a search that takes longer than its [`timeout`](Probe::timeout)
+/// returns an error instead. Other calls in the process aren't affected.
+#[derive(Debug, Clone)]
+pub struct Probe {
+    path: PathBuf,
+    custom_ignores: Vec<String>,
+    allow_tests: bool,
+    no_gitignore: bool,
+    language: Option<String>,
+    exact: bool,
+    reranker: String,
+    max_results: Option<usize>,
+    max_bytes: Option<usize>,
+    max_tokens: Option<usize>,
+    session: Option<String>,
+    context_lines: usize,
+    timeout: u64,
+    source: Option<Arc<dyn Source>>,
+}
+
+impl Probe {
+    /// Work on the code in `path`, with the defaults of the command line
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Probe {
+            path: path.into(),
+            custom_ignores: Vec::new(),
+            allow_tests: false,
+            no_gitignore: false,
+            language: None,
+            exact: false,
+            reranker: "bm25".to_string(),
+            max_results: None,
+            max_bytes: None,
+            max_tokens: None,
+            session: None,
+            context_lines: 0,
+            timeout: 30,
+            source: None,
+        }
+    }
+
+    /// Directory or file searched and queried, and relative files are extracted from
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Skip files matching a pattern, in addition to .gitignore
+    pub fn ignore(mut self, pattern: impl Into<String>) -> Self {
+        self.custom_ignores.push(pattern.into());
+        self
+    }
+
+    /// Include test files and test code
+    pub fn allow_tests(mut self, allow_tests: bool) -> Self {
+        self.allow_tests = allow_tests;
+        self
+    }
+
+    /// Don't respect .gitignore files
+    pub fn no_gitignore(mut self, no_gitignore: bool) -> Self {
+        self.no_gitignore = no_gitignore;
+        self
+    }
+
+    /// Only search files of this language, and match query patterns as this language
+    pub fn language(mut self, language: impl Into<String>) -> Self {
+        self.language = Some(language.into());
+        self
+    }
+
+    /// Match search terms exactly, without stemming or splitting
+    pub fn exact(mut self, exact: bool) -> Self {
+        self.exact = exact;
+        self
+    }
+
+    /// Ranking algorithm for search results, as for `--reranker`
+    pub fn reranker(mut self, reranker: impl Into<String>) -> Self {
+        self.reranker = reranker.into();
+        self
+    }
+
+    /// Most search results or query matches to return
+    pub fn max_results(mut self, max_results: usize) -> Self {
+        self.max_results = Some(max_results);
+        self
+    }
+
+    /// Most bytes of code in search results
+    pub fn max_bytes(mut self, max_bytes: usize) -> Self {
+        self.max_bytes = Some(max_bytes);
+        self
+    }
+
+    /// Most tokens of code in search results
+    pub fn max_tokens(mut self, max_tokens: usize) -> Self {
+        self.max_tokens = Some(max_tokens);
+        self
+    }
+
+    /// Session whose searches skip the results returned before
+    pub fn session(mut self, session: impl Into<String>) -> Self {
+        self.session = Some(session.into());
+        self
+    }
+
+    /// Lines of context around extracted lines that aren't in any code block
+    pub fn context_lines(mut self, context_lines: usize) -> Self {
+        self.context_lines = context_lines;
+        self
+    }
+
+    /// Seconds a search may take before it ends with an error (default: 30)
+    pub fn timeout(mut self, seconds: u64) -> Self {
+        self.timeout = seconds;
+        self
+    }
+
+    /// Read the files `source` holds from it instead of the disk, such as files
+    /// kept in memory with a [`MemorySource`](probe_code::source::MemorySource)
+    pub fn source(mut self, source: impl Source + 'static) -> Self {
+        self.source = Some(Arc::new(source));
+        self
+    }
+
+    /// Search with an Elasticsearch-like query, returning ranked code blocks
+    pub fn search(&self, query: &str) -> Result<LimitedSearchResults> {
+        let queries = vec![query.to_string()];
+        console::silenced(|| {
+            perform_probe(&SearchOptions {
+                path: &self.path,
+                queries: &queries,
+                files_only: false,
+                custom_ignores: &self.custom_ignores,
+                exclude_filenames: false,
+                reranker: &self.reranker,
+                frequency_search: true,
+                exact: self.exact,
+                fuzzy: false,
+                explain: false,
+                language: self.language.as_deref(),
+                max_results: self.max_results,
+                max_bytes: self.max_bytes,
+                max_tokens: self.max_tokens,
+                allow_tests: self.allow_tests,
+                no_merge: false,
+                merge_threshold: None,
+                dry_run: false,
+                session: self.session.as_deref(),
+                timeout: self.timeout,
+                question: None,
+                no_gitignore: self.no_gitignore,
+                file_filters: Default::default(),
+                source: self.source.clone(),
+            })
+        })
+    }
+
+    /// Extract code from files given like the arguments of `probe extract`, such
+    /// as `main.rs:42`, `main.rs:10-20` or `main.rs#main`
+    pub fn extract<S: AsRef<str>>(&self, files: &[S]) -> Result<ExtractResults> {
+        let files = files
+            .iter()
+            .map(|file| self.path.join(file.as_ref()).to_string_lossy().into_owned())
+            .collect();
+        console::silenced(|| {
+            perform_extract(&ExtractOptions {
+                files,
+                custom_ignores: self.custom_ignores.clone(),
+                context_lines: self.context_lines,
+                format: "json".to_string(),
+                from_clipboard: false,
+                input_file: None,
+                to_clipboard: false,
+                dry_run: false,
+                diff: false,
+                allow_tests: self.allow_tests,
+                keep_input: false,
+                prompt: None,
+                instructions: None,
+                no_gitignore: self.no_gitignore,
+                file_filters: Default::default(),
+                source: self.source.clone(),
+            })
+        })
+    }
+
+    /// Find code matching an ast-grep pattern
+    pub fn query(&self, pattern: &str) -> Result<Vec<AstMatch>> {
+        console::silenced(|| {
+            perform_query(&QueryOptions {
+                path: &self.path,
+                pattern,
+                language: self.language.as_deref(),
+                ignore: &self.custom_ignores,
+                allow_tests: self.allow_tests,
+                max_results: self.max_results,
+                format: "json",
+                no_gitignore: self.no_gitignore,
+                file_filters: Default::default(),
+                source: self.source.clone(),
+            })
+        })
+    }
+}
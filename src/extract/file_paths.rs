@@ -7,6 +7,7 @@ use glob::glob;
 use ignore::WalkBuilder;
 use probe_code::language::is_test_file;
 use probe_code::path_resolver::resolve_path;
+use probe_code::source::Sources;
 use regex::Regex;
 use std::collections::HashSet;
 use std::path::PathBuf;
@@ -98,7 +99,7 @@ pub fn extract_file_paths_from_git_diff(text: &str, allow_tests: bool) -> Vec<Fi
     let mut current_file_lines = HashSet::new();
 
     // Check if debug mode is enabled
-    let debug_mode = std::env::var("DEBUG").unwrap_or_default() == "1";
+    let debug_mode = probe_code::console::debug_mode();
 
     // Split the text into lines
     let lines: Vec<&str> = text.lines().collect();
@@ -273,11 +274,20 @@ pub fn extract_file_paths_from_git_diff(text: &str, allow_tests: bool) -> Vec<Fi
 ///
 /// If allow_tests is false, test files will be filtered out.
 pub fn extract_file_paths_from_text(text: &str, allow_tests: bool) -> Vec<FilePathInfo> {
+    extract_file_paths_from_text_in(&Sources::default(), text, allow_tests)
+}
+
+/// Like [`extract_file_paths_from_text`], checking whether files exist in `sources`
+pub fn extract_file_paths_from_text_in(
+    sources: &Sources,
+    text: &str,
+    allow_tests: bool,
+) -> Vec<FilePathInfo> {
     let mut results = Vec::new();
     let mut processed_paths = HashSet::new();
 
     // Check if debug mode is enabled
-    let debug_mode = std::env::var("DEBUG").unwrap_or_default() == "1";
+    let debug_mode = probe_code::console::debug_mode();
 
     // Preprocess the text to handle paths wrapped in backticks, quotes, and markdown formatting
     // This replaces backticks, single quotes, double quotes, and markdown bold/italic with spaces
@@ -665,7 +675,7 @@ pub fn extract_file_paths_from_text(text: &str, allow_tests: bool) -> Vec<FilePa
                             }
 
                             // Check if the file exists
-                            if !path.exists()
+                            if !sources.exists(&path)
                                 && !file_path.contains('/')
                                 && !file_path.contains('\\')
                             {
@@ -697,7 +707,7 @@ pub fn extract_file_paths_from_text(text: &str, allow_tests: bool) -> Vec<FilePa
                         if is_likely_file_path(file_path) {
                             let path = PathBuf::from(file_path);
                             // Only add if the file exists or matches common file patterns
-                            if path.exists()
+                            if sources.exists(&path)
                                 || (file_path.contains('/') || file_path.contains('\\'))
                             {
                                 let is_test = is_test_file(&path);
@@ -730,8 +740,17 @@ pub fn extract_file_paths_from_text(text: &str, allow_tests: bool) -> Vec<FilePa
 ///
 /// If allow_tests is false, test files will be filtered out.
 pub fn parse_file_with_line(input: &str, allow_tests: bool) -> Vec<FilePathInfo> {
+    parse_file_with_line_in(&Sources::default(), input, allow_tests)
+}
+
+/// Like [`parse_file_with_line`], checking whether files exist in `sources`
+pub fn parse_file_with_line_in(
+    sources: &Sources,
+    input: &str,
+    allow_tests: bool,
+) -> Vec<FilePathInfo> {
     let mut results = Vec::new();
-    let debug_mode = std::env::var("DEBUG").unwrap_or_default() == "1";
+    let debug_mode = probe_code::console::debug_mode();
 
     // Remove any surrounding backticks or quotes, but not apostrophes within words
     // First check if the input starts and ends with the same quote character
@@ -773,7 +792,7 @@ pub fn parse_file_with_line(input: &str, allow_tests: bool) -> Vec<FilePathInfo>
                 }
             }
             Err(err) => {
-                if std::env::var("DEBUG").unwrap_or_default() == "1" {
+                if probe_code::console::debug_mode() {
                     println!("DEBUG: Failed to resolve path '{file_part}': {err}");
                 }
 
@@ -781,7 +800,9 @@ pub fn parse_file_with_line(input: &str, allow_tests: bool) -> Vec<FilePathInfo>
                 if is_likely_file_path(file_part) {
                     let path = PathBuf::from(file_part);
                     // Only add if the file exists or matches common file patterns
-                    if path.exists() || (file_part.contains('/') || file_part.contains('\\')) {
+                    if sources.exists(&path)
+                        || (file_part.contains('/') || file_part.contains('\\'))
+                    {
                         let is_test = is_test_file(&path);
                         if allow_tests || !is_test {
                             // Symbol can be a simple name or a dot-separated path (e.g., "Class.method")
@@ -882,7 +903,7 @@ pub fn parse_file_with_line(input: &str, allow_tests: bool) -> Vec<FilePathInfo>
                             }
                         }
                         Err(err) => {
-                            if std::env::var("DEBUG").unwrap_or_default() == "1" {
+                            if probe_code::console::debug_mode() {
                                 println!("DEBUG: Failed to resolve path '{file_part}': {err}");
                             }
 
@@ -931,7 +952,7 @@ pub fn parse_file_with_line(input: &str, allow_tests: bool) -> Vec<FilePathInfo>
                             }
                         }
                         Err(err) => {
-                            if std::env::var("DEBUG").unwrap_or_default() == "1" {
+                            if probe_code::console::debug_mode() {
                                 println!("DEBUG: Failed to resolve path '{file_part}': {err}");
                             }
 
@@ -1010,7 +1031,7 @@ pub fn set_custom_ignores(patterns: &[String]) {
 /// Check if a file should be ignored according to .gitignore rules
 fn is_ignored_by_gitignore(path: &PathBuf) -> bool {
     // Check if debug mode is enabled
-    let debug_mode = std::env::var("DEBUG").unwrap_or_default() == "1";
+    let debug_mode = probe_code::console::debug_mode();
 
     // Simple check for common ignore patterns in the path
     let path_str = path.to_string_lossy();
@@ -6,6 +6,7 @@
 use anyhow::Result;
 use probe_code::models::SearchResult;
 use probe_code::search::search_tokens::sum_tokens_with_deduplication;
+use probe_code::source::CellRange;
 use serde::Serialize;
 use std::fmt::Write as FmtWrite;
 use std::path::Path;
@@ -42,6 +43,8 @@ fn format_extraction_internal(
                     file: &'a str,
                     #[serde(serialize_with = "serialize_lines_as_array")]
                     lines: (usize, usize),
+                    #[serde(skip_serializing_if = "Option::is_none")]
+                    cells: Option<CellRange>,
                     node_type: &'a str,
                 }
 
@@ -65,6 +68,7 @@ fn format_extraction_internal(
                     .map(|r| JsonDryRunResult {
                         file: &r.file,
                         lines: r.lines,
+                        cells: r.cells,
                         node_type: &r.node_type,
                     })
                     .collect();
@@ -100,6 +104,8 @@ fn format_extraction_internal(
                     file: &'a str,
                     #[serde(serialize_with = "serialize_lines_as_array")]
                     lines: (usize, usize),
+                    #[serde(skip_serializing_if = "Option::is_none")]
+                    cells: Option<CellRange>,
                     node_type: &'a str,
                     code: &'a str,
                     #[serde(skip_serializing_if = "Option::is_none")]
@@ -126,6 +132,7 @@ fn format_extraction_internal(
                     .map(|r| JsonResult {
                         file: &r.file,
                         lines: r.lines,
+                        cells: r.cells,
                         node_type: &r.node_type,
                         code: &r.code,
                         // We no longer put original_input per result. If you truly need it,
@@ -189,6 +196,9 @@ fn format_extraction_internal(
                         writeln!(output, "      <start>{}</start>", result.lines.0)?;
                         writeln!(output, "      <end>{}</end>", result.lines.1)?;
                         writeln!(output, "    </lines>")?;
+                        if let Some(cells) = result.cells {
+                            writeln!(output, "    <cells>{cells}</cells>")?;
+                        }
                     }
 
                     if result.node_type != "file" && result.node_type != "context" {
@@ -221,6 +231,9 @@ fn format_extraction_internal(
                         writeln!(output, "      <start>{}</start>", result.lines.0)?;
                         writeln!(output, "      <end>{}</end>", result.lines.1)?;
                         writeln!(output, "    </lines>")?;
+                        if let Some(cells) = result.cells {
+                            writeln!(output, "    <cells>{cells}</cells>")?;
+                        }
                     }
 
                     if result.node_type != "file" && result.node_type != "context" {
@@ -302,10 +315,22 @@ fn format_extraction_internal(
 
                     // Show lines if not a full file
                     if result.node_type != "file" {
+                        let cells = result
+                            .cells
+                            .map(|cells| format!(" ({cells})"))
+                            .unwrap_or_default();
                         if format == "markdown" {
-                            writeln!(output, "### Lines: {}-{}", result.lines.0, result.lines.1)?;
+                            writeln!(
+                                output,
+                                "### Lines: {}-{}{cells}",
+                                result.lines.0, result.lines.1
+                            )?;
                         } else {
-                            writeln!(output, "Lines: {}-{}", result.lines.0, result.lines.1)?;
+                            writeln!(
+                                output,
+                                "Lines: {}-{}{cells}",
+                                result.lines.0, result.lines.1
+                            )?;
                         }
                     }
 
@@ -520,7 +545,7 @@ fn escape_xml(s: &str) -> String {
 pub fn get_language_from_extension(extension: &str) -> &'static str {
     match extension {
         "rs" => "rust",
-        "py" => "python",
+        "py" | "ipynb" => "python",
         "js" => "javascript",
         "ts" => "typescript",
         "go" => "go",
@@ -530,16 +555,21 @@ pub fn get_language_from_extension(extension: &str) -> &'static str {
         "rb" => "ruby",
         "php" => "php",
         "sh" => "bash",
-        "md" => "markdown",
+        "md" | "markdown" | "mdx" => "markdown",
+        "rst" => "rst",
         "json" => "json",
         "yaml" | "yml" => "yaml",
-        "html" => "html",
+        "html" | "htm" => "html",
+        "vue" => "vue",
+        "svelte" => "svelte",
+        "astro" => "astro",
         "css" => "css",
         "sql" => "sql",
         "kt" | "kts" => "kotlin",
         "swift" => "swift",
         "cs" => "csharp",
-        "scala" => "scala",
+        "scala" | "sc" => "scala",
+        "tf" | "tfvars" | "hcl" => "hcl",
         "dart" => "dart",
         "ex" | "exs" => "elixir",
         "hs" => "haskell",
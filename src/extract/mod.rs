@@ -13,8 +13,9 @@ pub mod symbol_finder;
 // Re-export public functions
 #[allow(unused_imports)]
 pub use file_paths::{
-    extract_file_paths_from_git_diff, extract_file_paths_from_text, is_git_diff_format,
-    parse_file_with_line,
+    extract_file_paths_from_git_diff, extract_file_paths_from_text,
+    extract_file_paths_from_text_in, is_git_diff_format, parse_file_with_line,
+    parse_file_with_line_in,
 };
 #[allow(unused_imports)]
 pub use formatter::{
@@ -28,10 +29,13 @@ pub use prompts::PromptTemplate;
 use anyhow::Result;
 use probe_code::extract::file_paths::{set_custom_ignores, FilePathInfo};
 use probe_code::models::SearchResult;
+use probe_code::search::FileFilters;
+use probe_code::source::{Source, Sources};
 use std::collections::HashSet;
 use std::io::Read;
 #[allow(unused_imports)]
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
 /// Options for the extract command
 pub struct ExtractOptions {
@@ -63,6 +67,10 @@ pub struct ExtractOptions {
     pub instructions: Option<String>,
     /// Whether to ignore .gitignore files
     pub no_gitignore: bool,
+    /// Filters dropping the files to extract from, such as the files unchanged in git
+    pub file_filters: FileFilters,
+    /// Where the files it holds are read from, instead of the disk
+    pub source: Option<Arc<dyn Source>>,
 }
 
 /// Handle the extract command
@@ -76,7 +84,7 @@ pub fn handle_extract(options: ExtractOptions) -> Result<()> {
     }
 
     // Check if debug mode is enabled
-    let debug_mode = std::env::var("DEBUG").unwrap_or_default() == "1";
+    let debug_mode = probe_code::console::debug_mode();
 
     if debug_mode {
         eprintln!("\n[DEBUG] ===== Extract Command Started =====");
@@ -116,6 +124,7 @@ pub fn handle_extract(options: ExtractOptions) -> Result<()> {
 
     // Set custom ignore patterns
     set_custom_ignores(&options.custom_ignores);
+    let sources = Sources::new(options.source.clone());
 
     let mut file_paths: Vec<FilePathInfo> = Vec::new();
 
@@ -159,7 +168,8 @@ pub fn handle_extract(options: ExtractOptions) -> Result<()> {
             file_paths = extract_file_paths_from_git_diff(&buffer, options.allow_tests);
         } else {
             // Parse as regular text
-            file_paths = file_paths::extract_file_paths_from_text(&buffer, options.allow_tests);
+            file_paths =
+                file_paths::extract_file_paths_from_text_in(&sources, &buffer, options.allow_tests);
         }
 
         if debug_mode {
@@ -237,7 +247,8 @@ pub fn handle_extract(options: ExtractOptions) -> Result<()> {
             file_paths = extract_file_paths_from_git_diff(&buffer, options.allow_tests);
         } else {
             // Parse as regular text
-            file_paths = file_paths::extract_file_paths_from_text(&buffer, options.allow_tests);
+            file_paths =
+                file_paths::extract_file_paths_from_text_in(&sources, &buffer, options.allow_tests);
         }
 
         if debug_mode {
@@ -309,7 +320,11 @@ pub fn handle_extract(options: ExtractOptions) -> Result<()> {
                 file_paths = extract_file_paths_from_git_diff(&buffer, options.allow_tests);
             } else {
                 // Parse as regular text
-                file_paths = file_paths::extract_file_paths_from_text(&buffer, options.allow_tests);
+                file_paths = file_paths::extract_file_paths_from_text_in(
+                    &sources,
+                    &buffer,
+                    options.allow_tests,
+                );
             }
         } else {
             // No arguments and no stdin, show help
@@ -370,7 +385,7 @@ pub fn handle_extract(options: ExtractOptions) -> Result<()> {
                 eprintln!("[DEBUG] Parsing file argument: {file}");
             }
 
-            let paths = file_paths::parse_file_with_line(file, options.allow_tests);
+            let paths = file_paths::parse_file_with_line_in(&sources, file, options.allow_tests);
 
             if debug_mode {
                 eprintln!(
@@ -394,6 +409,8 @@ pub fn handle_extract(options: ExtractOptions) -> Result<()> {
         }
     }
 
+    file_paths.retain(|(path, ..)| options.file_filters.matches(&sources, Path::new(""), path));
+
     // Only print file information for non-JSON/XML formats
     if options.format != "json" && options.format != "xml" {
         println!("{text}", text = "Files to extract:".bold().green());
@@ -473,6 +490,171 @@ pub fn handle_extract(options: ExtractOptions) -> Result<()> {
         None
     };
 
+    // Files in archives, like `deps.zip!/src/lib.rs`, are read from the archives
+    let sources = sources.with_archives(file_paths.iter().map(|(path, ..)| path))?;
+    let (results, errors) = extract_code_blocks(&sources, file_paths, &options, debug_mode);
+
+    if debug_mode {
+        eprintln!("\n[DEBUG] ===== Extraction Summary =====");
+        eprintln!("[DEBUG] Total results: {}", results.len());
+        eprintln!("[DEBUG] Total errors: {}", errors.len());
+        eprintln!("[DEBUG] Output format: {}", options.format);
+        eprintln!("[DEBUG] Dry run: {}", options.dry_run);
+    }
+
+    // Format the results
+    let res = {
+        // Temporarily disable colors if writing to clipboard
+        let colors_enabled = if options.to_clipboard {
+            let was_enabled = colored::control::SHOULD_COLORIZE.should_colorize();
+            colored::control::set_override(false);
+            was_enabled
+        } else {
+            false
+        };
+
+        // Format the results
+        let result = if options.dry_run {
+            formatter::format_extraction_dry_run(
+                &results,
+                &options.format,
+                original_input.as_deref(),
+                system_prompt.as_deref(),
+                options.instructions.as_deref(),
+            )
+        } else {
+            formatter::format_extraction_results(
+                &results,
+                &options.format,
+                original_input.as_deref(),
+                system_prompt.as_deref(),
+                options.instructions.as_deref(),
+            )
+        };
+
+        // Restore color settings if they were changed
+        if options.to_clipboard && colors_enabled {
+            colored::control::set_override(true);
+        }
+
+        result
+    };
+    match res {
+        Ok(formatted_output) => {
+            if options.to_clipboard {
+                // Write to clipboard
+                let mut clipboard = Clipboard::new()?;
+                clipboard.set_text(&formatted_output)?;
+                println!("{}", "Results copied to clipboard.".green().bold());
+
+                if debug_mode {
+                    println!(
+                        "[DEBUG] Wrote {} bytes to clipboard",
+                        formatted_output.len()
+                    );
+                }
+            } else {
+                // Print to stdout
+                println!("{formatted_output}");
+            }
+        }
+        Err(e) => {
+            // Only print error messages for non-JSON/XML formats
+            if options.format != "json" && options.format != "xml" {
+                eprintln!("{}", format!("Error formatting results: {e}").red());
+            }
+            if debug_mode {
+                eprintln!("[DEBUG] Error formatting results: {e}");
+            }
+        }
+    }
+
+    // Print summary of errors if any (only for non-JSON/XML formats)
+    if !errors.is_empty() && options.format != "json" && options.format != "xml" {
+        println!();
+        println!(
+            "{} {} {}",
+            "Encountered".red().bold(),
+            errors.len(),
+            if errors.len() == 1 { "error" } else { "errors" }
+        );
+    }
+
+    if debug_mode {
+        eprintln!("[DEBUG] ===== Extract Command Completed =====");
+    }
+
+    Ok(())
+}
+
+/// Code blocks extracted from files
+pub struct ExtractResults {
+    /// Extracted blocks, without the blocks nested in others
+    pub results: Vec<SearchResult>,
+    /// Errors of the files that couldn't be extracted from
+    pub errors: Vec<String>,
+}
+
+/// Extract from the files of `options` without printing. Unlike `handle_extract`
+/// it never reads stdin or the clipboard, and ignores the output settings.
+pub fn perform_extract(options: &ExtractOptions) -> Result<ExtractResults> {
+    if options.files.is_empty() {
+        anyhow::bail!("No files to extract");
+    }
+    set_custom_ignores(&options.custom_ignores);
+    let sources = Sources::new(options.source.clone());
+
+    let file_paths: Vec<FilePathInfo> = options
+        .files
+        .iter()
+        .flat_map(|file| file_paths::parse_file_with_line_in(&sources, file, options.allow_tests))
+        .filter(|(path, ..)| options.file_filters.matches(&sources, Path::new(""), path))
+        .collect();
+    let sources = sources.with_archives(file_paths.iter().map(|(path, ..)| path))?;
+    let (results, errors) = extract_code_blocks(
+        &sources,
+        file_paths,
+        options,
+        probe_code::console::debug_mode(),
+    );
+    Ok(ExtractResults { results, errors })
+}
+
+/// Extract the files of `options` and return the document `--format json` prints
+pub fn extract_results_json(options: &ExtractOptions) -> Result<serde_json::Value> {
+    let results = perform_extract(options)?.results;
+
+    let original_input = options.keep_input.then(|| options.files.join(" "));
+    let system_prompt = options
+        .prompt
+        .as_ref()
+        .map(|prompt| prompt.get_content())
+        .transpose()?;
+    let format = if options.dry_run {
+        formatter::format_extraction_dry_run
+    } else {
+        formatter::format_extraction_results
+    };
+    let output = format(
+        &results,
+        "json",
+        original_input.as_deref(),
+        system_prompt.as_deref(),
+        options.instructions.as_deref(),
+    )?;
+    Ok(serde_json::from_str(&output)?)
+}
+
+/// Extract the code blocks of the given files in parallel, without the blocks
+/// nested in others. Returns the blocks and the errors of files that failed.
+fn extract_code_blocks(
+    sources: &Sources,
+    file_paths: Vec<FilePathInfo>,
+    options: &ExtractOptions,
+    debug_mode: bool,
+) -> (Vec<SearchResult>, Vec<String>) {
+    use colored::*;
+
     // Process files in parallel using Rayon
     use rayon::prelude::*;
     use std::sync::{Arc, Mutex};
@@ -492,13 +674,6 @@ pub fn handle_extract(options: ExtractOptions) -> Result<()> {
         context_lines: usize,
         debug_mode: bool,
         format: String,
-
-        #[allow(dead_code)]
-        original_input: Option<String>,
-        #[allow(dead_code)]
-        system_prompt: Option<String>,
-        #[allow(dead_code)]
-        user_instructions: Option<String>,
     }
 
     // Collect all file parameters
@@ -515,9 +690,6 @@ pub fn handle_extract(options: ExtractOptions) -> Result<()> {
                 context_lines: options.context_lines,
                 debug_mode,
                 format: options.format.clone(),
-                original_input: original_input.clone(),
-                system_prompt: system_prompt.clone(),
-                user_instructions: options.instructions.clone(),
             },
         )
         .collect();
@@ -535,7 +707,7 @@ pub fn handle_extract(options: ExtractOptions) -> Result<()> {
             );
 
             // Check if file exists
-            if params.path.exists() {
+            if sources.exists(&params.path) {
                 eprintln!("[DEBUG] File exists: Yes");
 
                 // Get file extension and language
@@ -565,6 +737,7 @@ pub fn handle_extract(options: ExtractOptions) -> Result<()> {
         }
 
         match processor::process_file_for_extraction(
+            sources,
             &params.path,
             params.start_line,
             params.end_line,
@@ -599,7 +772,10 @@ pub fn handle_extract(options: ExtractOptions) -> Result<()> {
                     eprintln!("[DEBUG] Error: {error_msg}");
                 }
                 // Only print error messages for non-JSON/XML formats
-                if params.format != "json" && params.format != "xml" {
+                if params.format != "json"
+                    && params.format != "xml"
+                    && !probe_code::console::is_silenced()
+                {
                     eprintln!("{}", error_msg.red());
                 }
                 // Thread-safe addition to errors
@@ -613,6 +789,8 @@ pub fn handle_extract(options: ExtractOptions) -> Result<()> {
         .expect("Failed to unwrap results mutex")
         .into_inner()
         .expect("Failed to get inner results");
+    // Blocks of notebooks are shown one cell at a time
+    results = sources.split_notebook_cells(results);
 
     let errors = Arc::try_unwrap(errors_mutex)
         .expect("Failed to unwrap errors mutex")
@@ -729,95 +907,5 @@ pub fn handle_extract(options: ExtractOptions) -> Result<()> {
         );
     }
 
-    if debug_mode {
-        eprintln!("\n[DEBUG] ===== Extraction Summary =====");
-        eprintln!("[DEBUG] Total results: {}", results.len());
-        eprintln!("[DEBUG] Total errors: {}", errors.len());
-        eprintln!("[DEBUG] Output format: {}", options.format);
-        eprintln!("[DEBUG] Dry run: {}", options.dry_run);
-    }
-
-    // Format the results
-    let res = {
-        // Temporarily disable colors if writing to clipboard
-        let colors_enabled = if options.to_clipboard {
-            let was_enabled = colored::control::SHOULD_COLORIZE.should_colorize();
-            colored::control::set_override(false);
-            was_enabled
-        } else {
-            false
-        };
-
-        // Format the results
-        let result = if options.dry_run {
-            formatter::format_extraction_dry_run(
-                &results,
-                &options.format,
-                original_input.as_deref(),
-                system_prompt.as_deref(),
-                options.instructions.as_deref(),
-            )
-        } else {
-            formatter::format_extraction_results(
-                &results,
-                &options.format,
-                original_input.as_deref(),
-                system_prompt.as_deref(),
-                options.instructions.as_deref(),
-            )
-        };
-
-        // Restore color settings if they were changed
-        if options.to_clipboard && colors_enabled {
-            colored::control::set_override(true);
-        }
-
-        result
-    };
-    match res {
-        Ok(formatted_output) => {
-            if options.to_clipboard {
-                // Write to clipboard
-                let mut clipboard = Clipboard::new()?;
-                clipboard.set_text(&formatted_output)?;
-                println!("{}", "Results copied to clipboard.".green().bold());
-
-                if debug_mode {
-                    println!(
-                        "[DEBUG] Wrote {} bytes to clipboard",
-                        formatted_output.len()
-                    );
-                }
-            } else {
-                // Print to stdout
-                println!("{formatted_output}");
-            }
-        }
-        Err(e) => {
-            // Only print error messages for non-JSON/XML formats
-            if options.format != "json" && options.format != "xml" {
-                eprintln!("{}", format!("Error formatting results: {e}").red());
-            }
-            if debug_mode {
-                eprintln!("[DEBUG] Error formatting results: {e}");
-            }
-        }
-    }
-
-    // Print summary of errors if any (only for non-JSON/XML formats)
-    if !errors.is_empty() && options.format != "json" && options.format != "xml" {
-        println!();
-        println!(
-            "{} {} {}",
-            "Encountered".red().bold(),
-            errors.len(),
-            if errors.len() == 1 { "error" } else { "errors" }
-        );
-    }
-
-    if debug_mode {
-        eprintln!("[DEBUG] ===== Extract Command Completed =====");
-    }
-
-    Ok(())
+    (results, errors)
 }
@@ -4,10 +4,10 @@
 //! based on file paths and optional line numbers.
 use anyhow::{Context, Result};
 use probe_code::extract::symbol_finder::find_symbol_in_file;
-use probe_code::language::parser::parse_file_for_code_blocks;
+use probe_code::language::parser::parse_file_for_code_blocks_with_tree;
 use probe_code::models::SearchResult;
+use probe_code::source::Sources;
 use std::collections::HashSet;
-use std::fs;
 use std::path::Path;
 
 /// Process a single file and extract code blocks
@@ -20,8 +20,10 @@ use std::path::Path;
 /// If no lines or symbol are specified, return the entire file.
 ///
 /// This function returns a single SearchResult that includes either the merged AST code
-/// or the literal lines as a fallback.
+/// or the literal lines as a fallback. The file is read from `sources`.
+#[allow(clippy::too_many_arguments)]
 pub fn process_file_for_extraction(
+    sources: &Sources,
     path: &Path,
     start_line: Option<usize>,
     end_line: Option<usize>,
@@ -31,7 +33,7 @@ pub fn process_file_for_extraction(
     specific_lines: Option<&HashSet<usize>>,
 ) -> Result<SearchResult> {
     // Check if debug mode is enabled
-    let debug_mode = std::env::var("DEBUG").unwrap_or_default() == "1";
+    let debug_mode = probe_code::console::debug_mode();
 
     if debug_mode {
         eprintln!("\n[DEBUG] ===== Processing File for Extraction =====");
@@ -45,7 +47,7 @@ pub fn process_file_for_extraction(
     }
 
     // Check if the file exists
-    if !path.exists() {
+    if !sources.exists(path) {
         if debug_mode {
             eprintln!("[DEBUG] Error: File does not exist");
         }
@@ -53,8 +55,11 @@ pub fn process_file_for_extraction(
     }
 
     // Read the file content
-    let content = fs::read_to_string(path).context(format!("Failed to read file: {path:?}"))?;
+    let content = sources
+        .read_to_string(path)
+        .context(format!("Failed to read file: {path:?}"))?;
     let lines: Vec<&str> = content.lines().collect();
+    let on_disk = sources.on_disk(path);
 
     if debug_mode {
         eprintln!("[DEBUG] File read successfully");
@@ -117,12 +122,14 @@ pub fn process_file_for_extraction(
             }
         }
 
-        let code_blocks_result = parse_file_for_code_blocks(
+        let code_blocks_result = parse_file_for_code_blocks_with_tree(
             &content,
             file_extension(path),
             &needed_lines,
             allow_tests,
             None,
+            None,
+            on_disk,
         );
 
         match code_blocks_result {
@@ -185,6 +192,7 @@ pub fn process_file_for_extraction(
                     block_id: None,
                     matched_keywords: None,
                     tokenized_content: Some(tokenized_content),
+                    cells: None,
                 })
             }
             _ => {
@@ -229,6 +237,7 @@ pub fn process_file_for_extraction(
                     block_id: None,
                     matched_keywords: None,
                     tokenized_content: Some(tokenized_content),
+                    cells: None,
                 })
             }
         }
@@ -261,12 +270,14 @@ pub fn process_file_for_extraction(
             }
         }
 
-        match parse_file_for_code_blocks(
+        match parse_file_for_code_blocks_with_tree(
             &content,
             file_extension(path),
             &needed_lines,
             allow_tests,
             None,
+            None,
+            on_disk,
         ) {
             Ok(blocks) if !blocks.is_empty() => {
                 // Merge them into a bounding block (in most cases it should only be one block,
@@ -326,6 +337,7 @@ pub fn process_file_for_extraction(
                     block_id: None,
                     matched_keywords: None,
                     tokenized_content: Some(tokenized_content),
+                    cells: None,
                 })
             }
             _ => {
@@ -382,6 +394,7 @@ pub fn process_file_for_extraction(
                     block_id: None,
                     matched_keywords: None,
                     tokenized_content: Some(tokenized_content),
+                    cells: None,
                 })
             }
         }
@@ -428,6 +441,7 @@ pub fn process_file_for_extraction(
                 block_id: None,
                 matched_keywords: None,
                 tokenized_content: Some(tokenized_content),
+                cells: None,
             });
         }
 
@@ -458,12 +472,14 @@ pub fn process_file_for_extraction(
         let lines_set = &clamped_lines;
 
         // Parse AST for all specified lines
-        let code_blocks_result = parse_file_for_code_blocks(
+        let code_blocks_result = parse_file_for_code_blocks_with_tree(
             &content,
             file_extension(path),
             lines_set,
             allow_tests,
             None,
+            None,
+            on_disk,
         );
 
         match code_blocks_result {
@@ -525,6 +541,7 @@ pub fn process_file_for_extraction(
                     block_id: None,
                     matched_keywords: None,
                     tokenized_content: Some(tokenized_content),
+                    cells: None,
                 })
             }
             _ => {
@@ -583,6 +600,7 @@ pub fn process_file_for_extraction(
                     block_id: None,
                     matched_keywords: None,
                     tokenized_content: Some(tokenized_content),
+                    cells: None,
                 })
             }
         }
@@ -623,6 +641,7 @@ pub fn process_file_for_extraction(
             block_id: None,
             matched_keywords: None,
             tokenized_content: Some(tokenized_content),
+            cells: None,
         })
     }
 }
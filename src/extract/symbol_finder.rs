@@ -31,7 +31,7 @@ pub fn find_symbol_in_file(
     _allow_tests: bool,
     context_lines: usize,
 ) -> Result<SearchResult> {
-    let debug_mode = std::env::var("DEBUG").unwrap_or_default() == "1";
+    let debug_mode = probe_code::console::debug_mode();
 
     // Check if the symbol contains a dot, indicating a nested symbol path
     let symbol_parts: Vec<&str> = symbol.split('.').collect();
@@ -69,6 +69,66 @@ pub fn find_symbol_in_file(
         println!("[DEBUG] File extension: {extension}");
     }
 
+    // Documents are looked up by heading, like `README.md#Installation`
+    if let Some(section) = crate::language::document::find_section(content, extension, symbol) {
+        if debug_mode {
+            println!(
+                "[DEBUG] Found section '{symbol}' at lines {}-{}",
+                section.start_row + 1,
+                section.end_row + 1
+            );
+        }
+        let code = content[section.start_byte..section.end_byte].to_string();
+        let filename = path
+            .file_name()
+            .map(|f| f.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let tokenized_content = crate::ranking::preprocess_text_with_filename(&code, &filename);
+
+        return Ok(SearchResult {
+            file: path.to_string_lossy().to_string(),
+            lines: (section.start_row + 1, section.end_row + 1),
+            node_type: section.node_type,
+            code,
+            matched_by_filename: None,
+            rank: None,
+            score: None,
+            tfidf_score: None,
+            bm25_score: None,
+            tfidf_rank: None,
+            bm25_rank: None,
+            new_score: None,
+            hybrid2_rank: None,
+            combined_score_rank: None,
+            file_unique_terms: None,
+            file_total_matches: None,
+            file_match_rank: None,
+            block_unique_terms: None,
+            block_total_matches: None,
+            parent_file_id: None,
+            block_id: None,
+            matched_keywords: None,
+            tokenized_content: Some(tokenized_content),
+            cells: None,
+        });
+    }
+
+    // Components are parsed on the scripts holding the symbol, with the rest
+    // blanked out so that nodes are where they are in the component
+    let symbol_name = symbol_parts.last().copied().unwrap_or(symbol);
+    let script =
+        crate::language::embedded::script_sources(content, extension).and_then(|sources| {
+            let first = sources.first().cloned();
+            sources
+                .into_iter()
+                .find(|(_, source)| source.contains(symbol_name))
+                .or(first)
+        });
+    let (extension, source) = match &script {
+        Some((script_extension, source)) => (*script_extension, source.as_str()),
+        None => (extension, content),
+    };
+
     // Get the language implementation for this extension
     // If unsupported, fall back to returning the full file
     let language_impl = match crate::language::factory::get_language_impl(extension) {
@@ -110,6 +170,7 @@ pub fn find_symbol_in_file(
                 block_id: None,
                 matched_keywords: None,
                 tokenized_content: Some(tokenized_content),
+                cells: None,
             });
         }
     };
@@ -124,7 +185,7 @@ pub fn find_symbol_in_file(
         .map_err(|e| anyhow::anyhow!("Failed to get pooled parser: {}", e))?;
 
     let tree = parser
-        .parse(content.as_bytes(), None)
+        .parse(source.as_bytes(), None)
         .ok_or_else(|| anyhow::anyhow!("Failed to parse file"))?;
 
     // Return parser to pool for reuse
@@ -445,14 +506,25 @@ pub fn find_symbol_in_file(
         None
     }
 
+    // Terraform blocks are named by their address, like `aws_s3_bucket.logs`,
+    // rather than by a single identifier
+    let block_node = match extension {
+        "tf" | "tfvars" | "hcl" => {
+            crate::language::hcl::find_block(root_node, symbol, content.as_bytes())
+        }
+        _ => None,
+    };
+
     // Search for the symbol in the AST
-    if let Some(found_node) = find_symbol_node(
-        root_node,
-        &symbol_parts,
-        language_impl.as_ref(),
-        content.as_bytes(),
-        debug_mode,
-    ) {
+    if let Some(found_node) = block_node.or_else(|| {
+        find_symbol_node(
+            root_node,
+            &symbol_parts,
+            language_impl.as_ref(),
+            content.as_bytes(),
+            debug_mode,
+        )
+    }) {
         let node_start_line = found_node.start_position().row + 1;
         let node_end_line = found_node.end_position().row + 1;
 
@@ -519,6 +591,7 @@ pub fn find_symbol_in_file(
             block_id: None,
             matched_keywords: None,
             tokenized_content: Some(tokenized_content),
+            cells: None,
         });
     }
 
@@ -636,6 +709,7 @@ pub fn find_symbol_in_file(
             block_id: None,
             matched_keywords: None,
             tokenized_content: Some(tokenized_content),
+            cells: None,
         });
     }
 
@@ -661,22 +735,22 @@ mod tests {
 
     #[test]
     fn test_find_symbol_unsupported_language_returns_full_file() {
-        // Create a temporary terraform file for testing
+        // Create a temporary TOML file for testing
         let temp_dir = std::env::temp_dir();
-        let test_file = temp_dir.join("test_symbol_terraform.tf");
-
-        let content = r#"resource "test" "example" {
-  name = "test"
-  
-  variable "nodes" {
-    type = map(number)
-  }
-}"#;
+        let test_file = temp_dir.join("test_symbol_config.toml");
+
+        let content = r#"[server]
+name = "test"
+
+[server.nodes]
+type = "map"
+count = 3
+"#;
 
         let mut file = fs::File::create(&test_file).unwrap();
         write!(file, "{content}").unwrap();
 
-        // Try to find a symbol in a .tf file (unsupported language)
+        // Try to find a symbol in a .toml file (unsupported language)
         let result = find_symbol_in_file(&test_file, "nodes", content, true, 0);
 
         // Should return Ok with the full file content
@@ -684,12 +758,43 @@ mod tests {
         let search_result = result.unwrap();
         assert_eq!(search_result.node_type, "file");
         assert_eq!(search_result.code, content);
-        assert_eq!(search_result.lines, (1, 7)); // 7 lines in the content
+        assert_eq!(search_result.lines, (1, 6)); // 6 lines in the content
 
         // Clean up
         let _ = fs::remove_file(&test_file);
     }
 
+    #[test]
+    fn test_find_symbol_in_terraform_file() {
+        let content = r#"variable "region" {
+  default = "us-east-1"
+}
+
+resource "aws_s3_bucket" "logs" {
+  bucket = "logs"
+}
+
+module "vpc" {
+  source = "./vpc"
+}
+"#;
+        let test_file = Path::new("main.tf");
+
+        // Resources are found by type and name, other blocks by their address
+        let result =
+            find_symbol_in_file(test_file, "aws_s3_bucket.logs", content, true, 0).unwrap();
+        assert_eq!(result.node_type, "block");
+        assert_eq!(result.lines, (5, 7));
+        assert!(result
+            .code
+            .starts_with(r#"resource "aws_s3_bucket" "logs""#));
+
+        let result = find_symbol_in_file(test_file, "module.vpc", content, true, 0).unwrap();
+        assert_eq!(result.lines, (9, 11));
+        let result = find_symbol_in_file(test_file, "var.region", content, true, 0).unwrap();
+        assert_eq!(result.lines, (1, 3));
+    }
+
     #[test]
     fn test_find_symbol_in_rust_file() {
         // Create a temporary rust file for testing
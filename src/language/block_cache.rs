@@ -0,0 +1,244 @@
+use anyhow::Result;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use once_cell::sync::Lazy;
+use std::fs;
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use probe_code::language::factory::get_language_impl;
+use probe_code::language::parser::{calculate_content_hash, PersistedLineMap};
+
+/// Extensions of the languages whose grammars decide block boundaries, one
+/// per grammar
+const GRAMMAR_EXTENSIONS: &[&str] = &[
+    "rs", "js", "ts", "tsx", "py", "go", "c", "cpp", "java", "rb", "php", "swift", "cs", "kt",
+    "scala", "tf",
+];
+
+/// Name of the subdirectory holding the entries of this version of probe and
+/// its language support, like `v0.6.0-1f2e3d4c5b6a7988`. Block boundaries depend
+/// on the grammars probe is built with, so entries written with other grammars
+/// are never used.
+static BUILD_DIR_NAME: Lazy<String> = Lazy::new(|| {
+    format!(
+        "v{}-{:016x}",
+        env!("CARGO_PKG_VERSION"),
+        language_support_hash()
+    )
+});
+
+/// Hash of the node kinds and fields of every grammar
+fn language_support_hash() -> u64 {
+    let mut description = String::new();
+    for extension in GRAMMAR_EXTENSIONS {
+        description.push_str(extension);
+        if let Some(language_impl) = get_language_impl(extension) {
+            let language = language_impl.get_tree_sitter_language();
+            for id in 0..language.node_kind_count() as u16 {
+                description.push(' ');
+                description.push_str(language.node_kind_for_id(id).unwrap_or_default());
+            }
+            for id in 1..=language.field_count() as u16 {
+                description.push(' ');
+                description.push_str(language.field_name_for_id(id).unwrap_or_default());
+            }
+        }
+        description.push('\n');
+    }
+    calculate_content_hash(&description)
+}
+
+/// Whether `name` is the name of a subdirectory holding entries, of any
+/// version of probe
+fn is_build_dir_name(name: &str) -> bool {
+    name.strip_prefix('v')
+        .and_then(|name| name.rsplit_once('-'))
+        .is_some_and(|(version, hash)| {
+            version.starts_with(|c: char| c.is_ascii_digit())
+                && hash.len() == 16
+                && hash.chars().all(|c| c.is_ascii_hexdigit())
+        })
+}
+
+/// Counter that keeps temporary file names unique within the process
+static TEMP_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+/// Directory of the on-disk block cache, `~/.cache/probe/blocks` by default.
+///
+/// The cache stores the code block boundaries derived from each parsed file,
+/// keyed by a hash of its content, so later runs don't need to parse unchanged
+/// files again. Set `PROBE_BLOCK_CACHE_DIR` to use another directory, or
+/// `PROBE_NO_BLOCK_CACHE=1` to disable the cache.
+pub fn cache_dir() -> Option<PathBuf> {
+    // Unit tests never read or write the cache of the user
+    if cfg!(test) || std::env::var("PROBE_NO_BLOCK_CACHE").unwrap_or_default() == "1" {
+        return None;
+    }
+    if let Some(dir) = std::env::var_os("PROBE_BLOCK_CACHE_DIR").filter(|dir| !dir.is_empty()) {
+        return Some(PathBuf::from(dir));
+    }
+    dirs::home_dir().map(|home| home.join(".cache").join("probe").join("blocks"))
+}
+
+/// Load the cached line map for a `line_map_cache_key`, if there is one
+pub fn load(key: &str) -> Option<PersistedLineMap> {
+    load_from(&cache_dir()?, key)
+}
+
+/// Store the line map for a `line_map_cache_key`. Failures only mean the file
+/// will be parsed again next time, so they are not reported.
+pub fn store(key: &str, line_map: &PersistedLineMap) {
+    if let Some(dir) = cache_dir() {
+        if let Err(e) = store_in(&dir, key, line_map) {
+            if probe_code::console::debug_mode() {
+                eprintln!("DEBUG: Failed to write block cache entry {key}: {e}");
+            }
+        }
+    }
+}
+
+fn load_from(dir: &Path, key: &str) -> Option<PersistedLineMap> {
+    let path = dir.join(BUILD_DIR_NAME.as_str()).join(file_name(key));
+    if !path.is_file() {
+        return None;
+    }
+    match read_line_map(&path) {
+        Ok(line_map) => Some(line_map),
+        Err(e) => {
+            if probe_code::console::debug_mode() {
+                eprintln!(
+                    "DEBUG: Ignoring unreadable block cache entry {}: {e}",
+                    path.display()
+                );
+            }
+            None
+        }
+    }
+}
+
+fn store_in(dir: &Path, key: &str, line_map: &PersistedLineMap) -> Result<()> {
+    // The first entry of a new build replaces the entries of older builds
+    let build_dir = dir.join(BUILD_DIR_NAME.as_str());
+    if !build_dir.is_dir() {
+        prune_other_builds(dir);
+        fs::create_dir_all(&build_dir)?;
+    }
+
+    // Write to a temporary file first, so concurrent runs never read a partial entry
+    let path = build_dir.join(file_name(key));
+    let temp_path = build_dir.join(format!(
+        "{}.{}.{}.tmp",
+        file_name(key),
+        std::process::id(),
+        TEMP_COUNTER.fetch_add(1, Ordering::Relaxed)
+    ));
+    if let Err(e) = write_line_map(&temp_path, line_map).and_then(|_| {
+        fs::rename(&temp_path, &path)?;
+        Ok(())
+    }) {
+        let _ = fs::remove_file(&temp_path);
+        return Err(e);
+    }
+    Ok(())
+}
+
+/// Remove the entries written by other versions of probe or its language
+/// support. Only files the cache writes are removed, from subdirectories named
+/// like its own, so nothing else in a `PROBE_BLOCK_CACHE_DIR` is touched.
+fn prune_other_builds(dir: &Path) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let name = entry.file_name();
+        let Some(name) = name.to_str() else {
+            continue;
+        };
+        if name == BUILD_DIR_NAME.as_str() || !is_build_dir_name(name) || !entry.path().is_dir() {
+            continue;
+        }
+        for file in fs::read_dir(entry.path()).into_iter().flatten().flatten() {
+            let file_name = file.file_name();
+            let file_name = file_name.to_string_lossy();
+            if file_name.ends_with(".json.gz") || file_name.ends_with(".tmp") {
+                let _ = fs::remove_file(file.path());
+            }
+        }
+        // Fails, and keeps the directory, if anything else is left in it
+        let _ = fs::remove_dir(entry.path());
+    }
+}
+
+fn file_name(key: &str) -> String {
+    format!("{key}.json.gz")
+}
+
+/// Write a line map as gzipped JSON
+pub fn write_line_map(path: &Path, line_map: &PersistedLineMap) -> Result<()> {
+    let mut encoder = GzEncoder::new(fs::File::create(path)?, Compression::fast());
+    serde_json::to_writer(&mut encoder, line_map)?;
+    encoder.finish()?;
+    Ok(())
+}
+
+/// Read a line map written by `write_line_map`
+pub fn read_line_map(path: &Path) -> Result<PersistedLineMap> {
+    let decoder = GzDecoder::new(BufReader::new(fs::File::open(path)?));
+    Ok(serde_json::from_reader(decoder)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use probe_code::language::parser::build_persisted_line_map;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_store_and_load() {
+        let temp_dir = TempDir::new().unwrap();
+        let dir = temp_dir.path().join("blocks");
+        let line_map = build_persisted_line_map("fn main() {\n    run();\n}\n", "rs", false)
+            .unwrap()
+            .unwrap();
+
+        let old_build = dir.join("v0.1.0-0123456789abcdef");
+        fs::create_dir_all(&old_build).unwrap();
+        fs::write(old_build.join(file_name("rs_1_false")), "old").unwrap();
+        let old_build_with_notes = dir.join("v0.1.0-fedcba9876543210");
+        fs::create_dir_all(&old_build_with_notes).unwrap();
+        fs::write(old_build_with_notes.join(file_name("rs_2_false")), "old").unwrap();
+        fs::write(old_build_with_notes.join("notes.txt"), "mine").unwrap();
+        let unrelated = dir.join("v1-backup");
+        fs::create_dir_all(&unrelated).unwrap();
+        fs::write(unrelated.join(file_name("rs_1_false")), "mine").unwrap();
+
+        assert_eq!(load_from(&dir, "rs_1_false"), None);
+        store_in(&dir, "rs_1_false", &line_map).unwrap();
+        assert_eq!(load_from(&dir, "rs_1_false"), Some(line_map));
+
+        // Entries of other builds are gone, anything else is left alone
+        let build_dir = dir.join(BUILD_DIR_NAME.as_str());
+        assert_eq!(fs::read_dir(&build_dir).unwrap().count(), 1);
+        assert!(!old_build.exists());
+        assert!(!old_build_with_notes.join(file_name("rs_2_false")).exists());
+        assert!(old_build_with_notes.join("notes.txt").exists());
+        assert!(unrelated.join(file_name("rs_1_false")).exists());
+
+        // Corrupt entries are treated as missing
+        fs::write(build_dir.join(file_name("rs_1_false")), "not gzip").unwrap();
+        assert_eq!(load_from(&dir, "rs_1_false"), None);
+    }
+
+    #[test]
+    fn test_build_dir_name() {
+        assert!(is_build_dir_name(BUILD_DIR_NAME.as_str()));
+        assert!(is_build_dir_name("v0.6.0-rc.1-0123456789abcdef"));
+        assert!(!is_build_dir_name("0.6.0-0123456789abcdef"));
+        assert!(!is_build_dir_name("v0.6.0-6630f2a1"));
+        assert!(!is_build_dir_name("vendor-0123456789abcdef"));
+        assert!(!is_build_dir_name("notes"));
+    }
+}
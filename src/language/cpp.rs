@@ -38,7 +38,7 @@ impl LanguageImpl for CppLanguage {
     }
 
     fn is_test_node(&self, node: &Node, source: &[u8]) -> bool {
-        let debug_mode = std::env::var("DEBUG").unwrap_or_default() == "1";
+        let debug_mode = probe_code::console::debug_mode();
         let node_type = node.kind();
 
         // C++: Check function_definition nodes with test in the name
@@ -0,0 +1,562 @@
+//! Markdown and reStructuredText documents, split into heading sections and
+//! code blocks so that searching and extracting them returns a section rather
+//! than a line or the whole file.
+//!
+//! Documents are outlined line by line rather than parsed with tree-sitter:
+//! the Markdown grammar only nests sections under `#` headings, and the
+//! reStructuredText one doesn't nest them at all.
+
+use std::collections::{BTreeSet, HashSet};
+
+use probe_code::models::CodeBlock;
+
+/// Characters reStructuredText titles can be underlined and overlined with
+const RST_ADORNMENTS: &str = "!\"#$%&'()*+,-./:;<=>?@[\\]^_`{|}~";
+
+/// reStructuredText directives holding code
+const RST_CODE_DIRECTIVES: &[&str] = &[".. code-block::", ".. code::", ".. sourcecode::"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Syntax {
+    Markdown,
+    ReStructuredText,
+}
+
+impl Syntax {
+    fn of(extension: &str) -> Option<Self> {
+        match extension {
+            "md" | "markdown" | "mdx" => Some(Syntax::Markdown),
+            "rst" => Some(Syntax::ReStructuredText),
+            _ => None,
+        }
+    }
+}
+
+/// A heading of a document
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Heading {
+    title: String,
+    /// 1 for the top level
+    level: usize,
+    /// First row of the heading, the overline of reStructuredText titles that have one
+    row: usize,
+}
+
+/// The headings and code blocks of a document
+#[derive(Debug)]
+struct Outline<'a> {
+    lines: Vec<&'a str>,
+    /// Byte offset of each line
+    offsets: Vec<usize>,
+    headings: Vec<Heading>,
+    /// First and last rows of each code block, fences and directives included
+    code_blocks: Vec<(usize, usize)>,
+}
+
+impl<'a> Outline<'a> {
+    fn new(content: &'a str, syntax: Syntax) -> Self {
+        let mut lines = Vec::new();
+        let mut offsets = Vec::new();
+        let mut offset = 0;
+        for line in content.split_inclusive('\n') {
+            offsets.push(offset);
+            offset += line.len();
+            lines.push(line.trim_end_matches(['\n', '\r']));
+        }
+
+        let mut outline = Outline {
+            lines,
+            offsets,
+            headings: Vec::new(),
+            code_blocks: Vec::new(),
+        };
+        match syntax {
+            Syntax::Markdown => outline.outline_markdown(),
+            Syntax::ReStructuredText => outline.outline_rst(),
+        }
+        outline
+    }
+
+    fn is_blank(&self, row: usize) -> bool {
+        self.lines[row].trim().is_empty()
+    }
+
+    fn outline_markdown(&mut self) {
+        let mut row = self.skip_frontmatter();
+        // The fence character and length of the open code block, and its first row
+        let mut fence: Option<(char, usize, usize)> = None;
+        let mut previous_is_text = false;
+
+        while row < self.lines.len() {
+            let line = self.lines[row];
+            let trimmed = line.trim_start();
+            let indented = line.len() - trimmed.len() > 3;
+
+            if let Some((fence_char, fence_len, start)) = fence {
+                let run = trimmed.chars().take_while(|&c| c == fence_char).count();
+                if !indented && run >= fence_len && trimmed[run..].trim().is_empty() {
+                    self.code_blocks.push((start, row));
+                    fence = None;
+                }
+                row += 1;
+                continue;
+            }
+
+            let fence_char = trimmed.chars().next().filter(|c| *c == '`' || *c == '~');
+            if let (Some(fence_char), false) = (fence_char, indented) {
+                let run = trimmed.chars().take_while(|&c| c == fence_char).count();
+                if run >= 3 && !(fence_char == '`' && trimmed[run..].contains('`')) {
+                    fence = Some((fence_char, run, row));
+                    previous_is_text = false;
+                    row += 1;
+                    continue;
+                }
+            }
+
+            let hashes = trimmed.chars().take_while(|&c| c == '#').count();
+            let after_hashes = &trimmed[hashes..];
+            if !indented
+                && (1..=6).contains(&hashes)
+                && (after_hashes.is_empty() || after_hashes.starts_with([' ', '\t']))
+            {
+                let title = after_hashes.trim().trim_end_matches('#').trim_end();
+                self.headings.push(Heading {
+                    title: title.to_string(),
+                    level: hashes,
+                    row,
+                });
+                previous_is_text = false;
+                row += 1;
+                continue;
+            }
+
+            // Setext headings, text underlined with `===` or `---`
+            let underline = trimmed.trim_end();
+            if !indented && previous_is_text && !underline.is_empty() {
+                let level = if underline.chars().all(|c| c == '=') {
+                    Some(1)
+                } else if underline.chars().all(|c| c == '-') {
+                    Some(2)
+                } else {
+                    None
+                };
+                if let Some(level) = level {
+                    self.headings.push(Heading {
+                        title: self.lines[row - 1].trim().to_string(),
+                        level,
+                        row: row - 1,
+                    });
+                    previous_is_text = false;
+                    row += 1;
+                    continue;
+                }
+            }
+
+            previous_is_text = !indented
+                && !trimmed.is_empty()
+                && !trimmed.starts_with(['>', '|', '<'])
+                && !["- ", "* ", "+ "]
+                    .iter()
+                    .any(|marker| trimmed.starts_with(marker));
+            row += 1;
+        }
+
+        // A code block left open runs to the end of the document
+        if let Some((_, _, start)) = fence {
+            self.code_blocks.push((start, self.lines.len() - 1));
+        }
+    }
+
+    /// The first row after YAML or TOML frontmatter, 0 without frontmatter
+    fn skip_frontmatter(&self) -> usize {
+        let Some(&marker) = self
+            .lines
+            .first()
+            .filter(|line| ["---", "+++"].contains(line))
+        else {
+            return 0;
+        };
+        self.lines
+            .iter()
+            .skip(1)
+            .position(|line| *line == marker)
+            .map_or(0, |end| end + 2)
+    }
+
+    fn outline_rst(&mut self) {
+        // Title styles, as adornment character and whether titles are overlined,
+        // in the order they first appear. The first one is level 1.
+        let mut styles: Vec<(char, bool)> = Vec::new();
+        let mut row = 0;
+
+        while row < self.lines.len() {
+            let line = self.lines[row];
+
+            // Titles are overlined and underlined, or only underlined
+            let overlined = rst_adornment(line)
+                .filter(|_| row + 2 < self.lines.len() && !self.is_blank(row + 1))
+                .filter(|&(c, len)| {
+                    rst_adornment(self.lines[row + 2]) == Some((c, len))
+                        && self.lines[row + 1].trim().chars().count() <= len
+                });
+            let underlined = rst_adornment(self.lines.get(row + 1).copied().unwrap_or("")).filter(
+                |&(_, len)| {
+                    !self.is_blank(row)
+                        && !line.starts_with([' ', '\t'])
+                        && rst_adornment(line).is_none()
+                        && line.trim_end().chars().count() <= len
+                        && (row == 0 || self.is_blank(row - 1))
+                },
+            );
+            let title = match (overlined, underlined) {
+                (Some((c, _)), _) => Some((self.lines[row + 1], (c, true), 3)),
+                (None, Some((c, _))) => Some((line, (c, false), 2)),
+                _ => None,
+            };
+            if let Some((title, style, rows)) = title {
+                let level = match styles.iter().position(|known| *known == style) {
+                    Some(index) => index + 1,
+                    None => {
+                        styles.push(style);
+                        styles.len()
+                    }
+                };
+                self.headings.push(Heading {
+                    title: title.trim().to_string(),
+                    level,
+                    row,
+                });
+                row += rows;
+                continue;
+            }
+
+            let trimmed = line.trim_start();
+            let indent = line.len() - trimmed.len();
+            if RST_CODE_DIRECTIVES
+                .iter()
+                .any(|directive| trimmed.starts_with(directive))
+            {
+                let end = self.indented_block_end(row + 1, indent).unwrap_or(row);
+                self.code_blocks.push((row, end));
+                row = end + 1;
+                continue;
+            }
+
+            // Literal blocks, the indented lines after a paragraph ending with `::`
+            if trimmed.trim_end().ends_with("::") && !trimmed.starts_with("..") {
+                if let Some(end) = self.indented_block_end(row + 1, indent) {
+                    let start = (row + 1..=end)
+                        .find(|&row| !self.is_blank(row))
+                        .unwrap_or(end);
+                    self.code_blocks.push((start, end));
+                    row = end + 1;
+                    continue;
+                }
+            }
+
+            row += 1;
+        }
+    }
+
+    /// The last row of the lines from `row` indented by more than `indent`,
+    /// blank lines in between included. `None` when there are no such lines.
+    fn indented_block_end(&self, row: usize, indent: usize) -> Option<usize> {
+        let mut end = None;
+        for row in row..self.lines.len() {
+            let line = self.lines[row];
+            if line.trim().is_empty() {
+                continue;
+            }
+            if line.len() - line.trim_start().len() <= indent {
+                break;
+            }
+            end = Some(row);
+        }
+        end
+    }
+
+    /// The last row of a block ending before `next_row`, without trailing blank lines
+    fn end_before(&self, start: usize, next_row: usize) -> usize {
+        (start..next_row)
+            .rev()
+            .find(|&row| !self.is_blank(row))
+            .unwrap_or(start)
+    }
+
+    /// Rows of the section of a heading, its subsections included
+    fn section(&self, index: usize) -> (usize, usize) {
+        let heading = &self.headings[index];
+        let next_row = self.headings[index + 1..]
+            .iter()
+            .find(|next| next.level <= heading.level)
+            .map_or(self.lines.len(), |next| next.row);
+        (heading.row, self.end_before(heading.row, next_row))
+    }
+
+    /// Rows of the text of a heading, up to the heading of the next section or
+    /// subsection
+    fn own_text(&self, index: usize) -> (usize, usize) {
+        let row = self.headings[index].row;
+        let next_row = self
+            .headings
+            .get(index + 1)
+            .map_or(self.lines.len(), |next| next.row);
+        (row, self.end_before(row, next_row))
+    }
+
+    /// The block holding `row`: its code block, the text of its heading, or
+    /// the text before the first heading
+    fn block_at(&self, row: usize) -> Option<(usize, usize, &'static str)> {
+        if let Some(&(start, end)) = self
+            .code_blocks
+            .iter()
+            .find(|(start, end)| (*start..=*end).contains(&row))
+        {
+            return Some((start, end, "code_block"));
+        }
+        match self.headings.iter().rposition(|heading| heading.row <= row) {
+            Some(index) => {
+                let (start, end) = self.own_text(index);
+                Some((start, end, "section"))
+            }
+            None => {
+                let next_row = self.headings.first().map_or(self.lines.len(), |h| h.row);
+                let start = (0..next_row).find(|&row| !self.is_blank(row))?;
+                Some((start, self.end_before(start, next_row), "preamble"))
+            }
+        }
+    }
+
+    fn code_block(&self, start: usize, end: usize, node_type: &str) -> CodeBlock {
+        CodeBlock {
+            start_row: start,
+            end_row: end,
+            start_byte: self.offsets[start],
+            end_byte: self.offsets[end] + self.lines[end].len(),
+            node_type: node_type.to_string(),
+            parent_node_type: None,
+            parent_start_row: None,
+            parent_end_row: None,
+        }
+    }
+}
+
+/// The adornment character and length of a reStructuredText title underline
+/// or overline like `=====`
+fn rst_adornment(line: &str) -> Option<(char, usize)> {
+    let line = line.trim_end();
+    let first = line
+        .chars()
+        .next()
+        .filter(|c| RST_ADORNMENTS.contains(*c))?;
+    let len = line.chars().count();
+    (len >= 2 && line.chars().all(|c| c == first)).then_some((first, len))
+}
+
+/// The anchor of a heading, like `getting-started` for `Getting Started`
+fn slug(title: &str) -> String {
+    title
+        .trim()
+        .to_lowercase()
+        .chars()
+        .filter(|c| c.is_alphanumeric() || matches!(c, ' ' | '-' | '_'))
+        .map(|c| if c == ' ' { '-' } else { c })
+        .collect()
+}
+
+/// The code blocks of a document holding the given lines (1-based): the code
+/// block or the text of the heading each line is in. Returns `None` for files
+/// that aren't documents.
+pub fn parse_document_blocks(
+    content: &str,
+    extension: &str,
+    line_numbers: &HashSet<usize>,
+) -> Option<Vec<CodeBlock>> {
+    let outline = Outline::new(content, Syntax::of(extension)?);
+    let blocks: BTreeSet<(usize, usize, &str)> = line_numbers
+        .iter()
+        .filter(|&&line| line > 0 && line <= outline.lines.len())
+        .filter_map(|&line| outline.block_at(line - 1))
+        .collect();
+
+    if probe_code::console::debug_mode() {
+        println!(
+            "DEBUG: Document outlined into {} headings and {} code blocks, {} blocks matched",
+            outline.headings.len(),
+            outline.code_blocks.len(),
+            blocks.len()
+        );
+    }
+
+    Some(
+        blocks
+            .into_iter()
+            .map(|(start, end, node_type)| outline.code_block(start, end, node_type))
+            .collect(),
+    )
+}
+
+/// The section of a document with the given heading, subsections included.
+/// Headings are matched by title, ignoring case, or by anchor, so both
+/// `README.md#Installation` and `README.md#getting-started` work. Returns
+/// `None` for files that aren't documents or have no such heading.
+pub fn find_section(content: &str, extension: &str, heading: &str) -> Option<CodeBlock> {
+    let outline = Outline::new(content, Syntax::of(extension)?);
+    let index = outline
+        .headings
+        .iter()
+        .position(|h| h.title == heading)
+        .or_else(|| {
+            outline
+                .headings
+                .iter()
+                .position(|h| h.title.eq_ignore_ascii_case(heading))
+        })
+        .or_else(|| {
+            let anchor = slug(heading);
+            outline
+                .headings
+                .iter()
+                .position(|h| slug(&h.title) == anchor)
+        })?;
+    let (start, end) = outline.section(index);
+    Some(outline.code_block(start, end, "section"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MARKDOWN: &str = r#"---
+title: Probe
+---
+
+# Probe
+
+Code search for people and agents.
+
+## Installation
+
+Install with cargo:
+
+```bash
+# not a heading
+cargo install probe-code
+```
+
+### From source
+
+Build it with `cargo build`.
+
+Getting Started
+---------------
+
+Run `probe search`.
+"#;
+
+    const RST: &str = r#"=====
+Probe
+=====
+
+Code search.
+
+Installation
+============
+
+Install with cargo::
+
+    cargo install probe-code
+
+From source
+-----------
+
+.. code-block:: bash
+
+   cargo build
+
+Usage
+=====
+
+Run ``probe search``.
+"#;
+
+    fn rows(blocks: &[CodeBlock]) -> Vec<(usize, usize, &str)> {
+        blocks
+            .iter()
+            .map(|block| (block.start_row, block.end_row, block.node_type.as_str()))
+            .collect()
+    }
+
+    #[test]
+    fn test_markdown_outline() {
+        let outline = Outline::new(MARKDOWN, Syntax::Markdown);
+        let headings: Vec<(&str, usize, usize)> = outline
+            .headings
+            .iter()
+            .map(|h| (h.title.as_str(), h.level, h.row))
+            .collect();
+        assert_eq!(
+            headings,
+            vec![
+                ("Probe", 1, 4),
+                ("Installation", 2, 8),
+                ("From source", 3, 17),
+                ("Getting Started", 2, 21),
+            ]
+        );
+        assert_eq!(outline.code_blocks, vec![(12, 15)]);
+    }
+
+    #[test]
+    fn test_parse_document_blocks() {
+        // A line of prose is in the text of its heading, a line of code in its code block
+        let blocks = parse_document_blocks(MARKDOWN, "md", &HashSet::from([11, 15, 19]));
+        assert_eq!(
+            rows(&blocks.unwrap()),
+            vec![
+                (8, 15, "section"),
+                (12, 15, "code_block"),
+                (17, 19, "section")
+            ]
+        );
+        let blocks = parse_document_blocks(MARKDOWN, "mdx", &HashSet::from([2])).unwrap();
+        assert_eq!(rows(&blocks), vec![(0, 2, "preamble")]);
+
+        let blocks = parse_document_blocks(RST, "rst", &HashSet::from([5, 12, 19, 24])).unwrap();
+        assert_eq!(
+            rows(&blocks),
+            vec![
+                (0, 4, "section"),
+                (11, 11, "code_block"),
+                (16, 18, "code_block"),
+                (20, 23, "section")
+            ]
+        );
+
+        assert!(parse_document_blocks(MARKDOWN, "rs", &HashSet::from([1])).is_none());
+    }
+
+    #[test]
+    fn test_find_section() {
+        let section = find_section(MARKDOWN, "md", "Installation").unwrap();
+        assert_eq!((section.start_row, section.end_row), (8, 19));
+        assert_eq!(
+            &MARKDOWN[section.start_byte..section.end_byte],
+            MARKDOWN
+                .lines()
+                .skip(8)
+                .take(12)
+                .collect::<Vec<_>>()
+                .join("\n")
+        );
+        let section = find_section(MARKDOWN, "md", "getting-started").unwrap();
+        assert_eq!((section.start_row, section.end_row), (21, 24));
+        assert!(find_section(MARKDOWN, "md", "not a heading").is_none());
+
+        // reStructuredText levels follow the order title styles first appear in
+        let section = find_section(RST, "rst", "installation").unwrap();
+        assert_eq!((section.start_row, section.end_row), (6, 18));
+        let section = find_section(RST, "rst", "Probe").unwrap();
+        assert_eq!((section.start_row, section.end_row), (0, 23));
+    }
+}
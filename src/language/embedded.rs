@@ -0,0 +1,417 @@
+//! Vue, Svelte and Astro components and HTML pages, split into their
+//! `<script>` and `<style>` regions. Scripts are parsed with the JavaScript or
+//! TypeScript implementation and styles are blocks of their own, while lines
+//! of markup are left to the context around them.
+//!
+//! Scripts are parsed on a copy of the file with everything else blanked out,
+//! keeping line breaks, so the rows and bytes of their nodes are those of the
+//! file itself.
+
+use std::collections::{BTreeSet, HashSet};
+use std::ops::Range;
+
+use anyhow::Result;
+use probe_code::language::parser::parse_file_for_code_blocks;
+use probe_code::models::CodeBlock;
+
+/// What a region of a component holds
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    /// A script, with the extension of its language
+    Script(&'static str),
+    Style,
+}
+
+/// A `<script>` or `<style>` element of a component, or the frontmatter of an
+/// Astro component
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Region {
+    kind: Kind,
+    /// Bytes of the script or style, between the tags
+    content: Range<usize>,
+    /// Bytes of the element, tags included
+    element: Range<usize>,
+}
+
+/// The regions of a component, `None` for files that aren't components
+fn regions(content: &str, extension: &str) -> Option<Vec<Region>> {
+    if !matches!(extension, "vue" | "svelte" | "astro" | "html" | "htm") {
+        return None;
+    }
+    // Same bytes, for finding tags whatever their case
+    let lower = content.to_ascii_lowercase();
+    let mut regions = Vec::new();
+    let mut position = 0;
+
+    // The frontmatter of Astro components is TypeScript
+    if extension == "astro" {
+        if let Some(frontmatter) = astro_frontmatter(content) {
+            position = frontmatter.element.end;
+            regions.push(frontmatter);
+        }
+    }
+
+    while let Some(found) = lower[position..].find('<') {
+        let start = position + found;
+        let rest = &lower[start..];
+        if rest.starts_with("<!--") {
+            position = rest.find("-->").map_or(lower.len(), |end| start + end + 3);
+            continue;
+        }
+        let Some(tag) = ["script", "style"].into_iter().find(|tag| {
+            rest[1..].starts_with(tag)
+                && rest[1 + tag.len()..]
+                    .starts_with(|c: char| c.is_ascii_whitespace() || c == '>' || c == '/')
+        }) else {
+            position = start + 1;
+            continue;
+        };
+        let Some(open_end) = tag_end(&lower, start) else {
+            break;
+        };
+
+        let (content_end, element_end) = if lower[..open_end].ends_with("/>") {
+            (open_end, open_end)
+        } else {
+            match lower[open_end..].find(&format!("</{tag}")) {
+                Some(close) => {
+                    let close = open_end + close;
+                    let element_end = tag_end(&lower, close).unwrap_or(lower.len());
+                    (close, element_end)
+                }
+                // An element left open runs to the end of the file
+                None => (lower.len(), lower.len()),
+            }
+        };
+        let kind = match tag {
+            "style" => Some(Kind::Style),
+            _ => script_language(&lower[start..open_end]).map(Kind::Script),
+        };
+        if let Some(kind) = kind {
+            regions.push(Region {
+                kind,
+                content: open_end..content_end,
+                element: start..element_end,
+            });
+        }
+        position = element_end;
+    }
+
+    Some(regions)
+}
+
+/// The TypeScript between the `---` fences at the top of an Astro component
+fn astro_frontmatter(content: &str) -> Option<Region> {
+    let start = content.len() - content.trim_start().len();
+    let mut lines = content[start..].split_inclusive('\n');
+    let fence = lines.next()?;
+    if fence.trim_end() != "---" {
+        return None;
+    }
+
+    let body = start + fence.len();
+    let mut offset = body;
+    for line in lines {
+        if line.trim_end() == "---" {
+            return Some(Region {
+                kind: Kind::Script("ts"),
+                content: body..offset,
+                element: start..offset + line.len(),
+            });
+        }
+        offset += line.len();
+    }
+    None
+}
+
+/// The byte after the `>` ending the tag that starts at `start`, skipping
+/// quoted attribute values
+fn tag_end(content: &str, start: usize) -> Option<usize> {
+    let mut quote = None;
+    for (index, c) in content[start..].char_indices() {
+        match (quote, c) {
+            (None, '"' | '\'') => quote = Some(c),
+            (Some(open), c) if c == open => quote = None,
+            (None, '>') => return Some(start + index + 1),
+            _ => {}
+        }
+    }
+    None
+}
+
+/// The language of a lowercase `<script>` tag, from its `lang` or `type`
+/// attribute. `None` for scripts that aren't JavaScript or TypeScript, like
+/// JSON data or templates.
+fn script_language(tag: &str) -> Option<&'static str> {
+    if let Some(lang) = attribute(tag, "lang") {
+        return match lang {
+            "js" | "javascript" => Some("js"),
+            "jsx" => Some("jsx"),
+            "ts" | "typescript" => Some("ts"),
+            "tsx" => Some("tsx"),
+            _ => None,
+        };
+    }
+    match attribute(tag, "type") {
+        None | Some("" | "module" | "text/javascript" | "application/javascript") => Some("js"),
+        Some("text/babel" | "text/jsx") => Some("jsx"),
+        Some("text/typescript" | "application/typescript") => Some("ts"),
+        _ => None,
+    }
+}
+
+/// The value of an attribute of a tag, without quotes
+fn attribute<'a>(tag: &'a str, name: &str) -> Option<&'a str> {
+    let mut rest = tag;
+    while let Some(index) = rest.find(name) {
+        let follows_space = rest[..index].ends_with(|c: char| c.is_ascii_whitespace());
+        let after = &rest[index + name.len()..];
+        rest = after;
+        let Some(value) = after.trim_start().strip_prefix('=') else {
+            continue;
+        };
+        if !follows_space {
+            continue;
+        }
+        let value = value.trim_start();
+        return match value.chars().next() {
+            Some(quote @ ('"' | '\'')) => value[1..].split(quote).next(),
+            _ => value
+                .split(|c: char| c.is_ascii_whitespace() || c == '>')
+                .next(),
+        };
+    }
+    None
+}
+
+/// Row of the byte at `offset`, from 0
+fn row_of(content: &str, offset: usize) -> usize {
+    content[..offset].matches('\n').count()
+}
+
+/// First and last rows of a range of bytes
+fn rows(content: &str, range: &Range<usize>) -> (usize, usize) {
+    let start = row_of(content, range.start);
+    let end = row_of(content, range.end.max(range.start + 1) - 1).max(start);
+    (start, end)
+}
+
+/// `content` with everything but its scripts in `language` blanked out
+fn script_source(content: &str, regions: &[Region], language: &'static str) -> String {
+    let mut source: Vec<u8> = content
+        .bytes()
+        .map(|b| if b == b'\n' || b == b'\r' { b } else { b' ' })
+        .collect();
+    for region in regions.iter().filter(|r| r.kind == Kind::Script(language)) {
+        source[region.content.clone()].copy_from_slice(&content.as_bytes()[region.content.clone()]);
+    }
+    // Regions start and end next to ASCII tags or line breaks, and all other
+    // bytes are now ASCII
+    String::from_utf8(source).expect("blanked out component is valid UTF-8")
+}
+
+/// The languages of the scripts of a component, in order of appearance
+fn script_languages(regions: &[Region]) -> Vec<&'static str> {
+    let mut languages = Vec::new();
+    for region in regions {
+        if let Kind::Script(language) = region.kind {
+            if !languages.contains(&language) {
+                languages.push(language);
+            }
+        }
+    }
+    languages
+}
+
+/// The scripts of a component, by language: the extension of the language
+/// and the component blanked out but for its scripts in that language.
+/// Returns `None` for files that aren't components.
+pub fn script_sources(content: &str, extension: &str) -> Option<Vec<(&'static str, String)>> {
+    let regions = regions(content, extension)?;
+    Some(
+        script_languages(&regions)
+            .into_iter()
+            .map(|language| (language, script_source(content, &regions, language)))
+            .collect(),
+    )
+}
+
+/// The code blocks of a component holding the given lines (1-based): nodes
+/// of its scripts, as the JavaScript or TypeScript implementation finds them,
+/// and its style elements. Lines of markup get no block. Returns `None` for
+/// files that aren't components.
+pub fn parse_embedded_blocks(
+    content: &str,
+    extension: &str,
+    line_numbers: &HashSet<usize>,
+    allow_tests: bool,
+) -> Option<Result<Vec<CodeBlock>>> {
+    let regions = regions(content, extension)?;
+    Some(parse_regions(content, &regions, line_numbers, allow_tests))
+}
+
+fn parse_regions(
+    content: &str,
+    regions: &[Region],
+    line_numbers: &HashSet<usize>,
+    allow_tests: bool,
+) -> Result<Vec<CodeBlock>> {
+    let in_region = |region: &Region, line: usize| {
+        let (start, end) = rows(content, &region.content);
+        line > start && line <= end + 1
+    };
+
+    let mut blocks = Vec::new();
+    for language in script_languages(regions) {
+        let lines: HashSet<usize> = line_numbers
+            .iter()
+            .copied()
+            .filter(|&line| {
+                regions
+                    .iter()
+                    .any(|r| r.kind == Kind::Script(language) && in_region(r, line))
+            })
+            .collect();
+        if lines.is_empty() {
+            continue;
+        }
+        let source = script_source(content, regions, language);
+        blocks.extend(parse_file_for_code_blocks(
+            &source,
+            language,
+            &lines,
+            allow_tests,
+            None,
+        )?);
+    }
+
+    let styles: BTreeSet<(usize, usize, usize, usize)> = regions
+        .iter()
+        .filter(|r| r.kind == Kind::Style)
+        .filter(|r| line_numbers.iter().any(|&line| in_region(r, line)))
+        .map(|r| {
+            let (start, end) = rows(content, &r.element);
+            (start, end, r.element.start, r.element.end)
+        })
+        .collect();
+    blocks.extend(
+        styles
+            .into_iter()
+            .map(|(start, end, start_byte, end_byte)| CodeBlock {
+                start_row: start,
+                end_row: end,
+                start_byte,
+                end_byte,
+                node_type: "style_element".to_string(),
+                parent_node_type: None,
+                parent_start_row: None,
+                parent_end_row: None,
+            }),
+    );
+
+    if probe_code::console::debug_mode() {
+        println!(
+            "DEBUG: Component split into {} regions, {} blocks matched",
+            regions.len(),
+            blocks.len()
+        );
+    }
+
+    blocks.sort_by_key(|block| (block.start_row, block.end_row));
+    Ok(blocks)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const VUE: &str = r#"<template>
+  <button @click="increment">{{ count }}</button>
+</template>
+
+<script setup lang="ts">
+import { ref } from 'vue'
+
+const count = ref<number>(0)
+
+function increment(): void {
+  count.value++
+}
+</script>
+
+<style scoped>
+button {
+  color: red;
+}
+</style>
+"#;
+
+    const ASTRO: &str = r#"---
+interface Props {
+  title: string
+}
+const { title } = Astro.props
+---
+<h1>{title}</h1>
+<script type="application/ld+json">{"@type": "WebPage"}</script>
+<script>
+  document.querySelector('h1')
+</script>
+"#;
+
+    #[test]
+    fn test_regions() {
+        let found = regions(VUE, "vue").unwrap();
+        let kinds: Vec<(Kind, (usize, usize))> = found
+            .iter()
+            .map(|r| (r.kind, rows(VUE, &r.element)))
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![(Kind::Script("ts"), (4, 12)), (Kind::Style, (14, 18))]
+        );
+
+        // JSON data isn't a script
+        let found = regions(ASTRO, "astro").unwrap();
+        let kinds: Vec<(Kind, (usize, usize))> = found
+            .iter()
+            .map(|r| (r.kind, rows(ASTRO, &r.content)))
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![(Kind::Script("ts"), (1, 4)), (Kind::Script("js"), (8, 9))]
+        );
+
+        assert_eq!(regions("<p>", "md"), None);
+    }
+
+    #[test]
+    fn test_script_source() {
+        let sources = script_sources(VUE, "vue").unwrap();
+        assert_eq!(sources.len(), 1);
+        let (language, source) = &sources[0];
+        assert_eq!(*language, "ts");
+        assert_eq!(source.len(), VUE.len());
+        assert_eq!(source.lines().count(), VUE.lines().count());
+        assert!(source.contains("function increment(): void {"));
+        assert!(!source.contains("template"));
+        assert!(!source.contains("color"));
+    }
+
+    #[test]
+    fn test_parse_embedded_blocks() {
+        let lines = HashSet::from([2, 11, 17]);
+        let blocks = parse_embedded_blocks(VUE, "vue", &lines, false)
+            .unwrap()
+            .unwrap();
+        let found: Vec<(usize, usize, &str)> = blocks
+            .iter()
+            .map(|b| (b.start_row, b.end_row, b.node_type.as_str()))
+            .collect();
+        assert_eq!(
+            found,
+            vec![(9, 11, "function_declaration"), (14, 18, "style_element")]
+        );
+        assert!(parse_embedded_blocks("fn main() {}", "rs", &lines, false).is_none());
+    }
+}
@@ -2,13 +2,16 @@ use probe_code::language::c::CLanguage;
 use probe_code::language::cpp::CppLanguage;
 use probe_code::language::csharp::CSharpLanguage;
 use probe_code::language::go::GoLanguage;
+use probe_code::language::hcl::HclLanguage;
 use probe_code::language::java::JavaLanguage;
 use probe_code::language::javascript::JavaScriptLanguage;
+use probe_code::language::kotlin::KotlinLanguage;
 use probe_code::language::language_trait::LanguageImpl;
 use probe_code::language::php::PhpLanguage;
 use probe_code::language::python::PythonLanguage;
 use probe_code::language::ruby::RubyLanguage;
 use probe_code::language::rust::RustLanguage;
+use probe_code::language::scala::ScalaLanguage;
 use probe_code::language::swift::SwiftLanguage;
 use probe_code::language::typescript::TypeScriptLanguage;
 
@@ -19,7 +22,8 @@ pub fn get_language_impl(extension: &str) -> Option<Box<dyn LanguageImpl>> {
         "js" | "jsx" => Some(Box::new(JavaScriptLanguage::new())),
         "ts" => Some(Box::new(TypeScriptLanguage::new_typescript())),
         "tsx" => Some(Box::new(TypeScriptLanguage::new_tsx())),
-        "py" => Some(Box::new(PythonLanguage::new())),
+        // Notebooks are read as a script of their cells
+        "py" | "ipynb" => Some(Box::new(PythonLanguage::new())),
         "go" => Some(Box::new(GoLanguage::new())),
         "c" | "h" => Some(Box::new(CLanguage::new())),
         "cpp" | "cc" | "cxx" | "hpp" | "hxx" => Some(Box::new(CppLanguage::new())),
@@ -28,6 +32,9 @@ pub fn get_language_impl(extension: &str) -> Option<Box<dyn LanguageImpl>> {
         "php" => Some(Box::new(PhpLanguage::new())),
         "swift" => Some(Box::new(SwiftLanguage::new())),
         "cs" => Some(Box::new(CSharpLanguage::new())),
+        "kt" | "kts" => Some(Box::new(KotlinLanguage::new())),
+        "scala" | "sc" => Some(Box::new(ScalaLanguage::new())),
+        "tf" | "tfvars" | "hcl" => Some(Box::new(HclLanguage::new())),
         _ => None,
     }
 }
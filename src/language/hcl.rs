@@ -0,0 +1,112 @@
+use super::language_trait::LanguageImpl;
+use tree_sitter::{Language as TSLanguage, Node};
+
+/// Implementation of LanguageImpl for HCL, as written in Terraform configurations
+pub struct HclLanguage;
+
+impl Default for HclLanguage {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl HclLanguage {
+    pub fn new() -> Self {
+        HclLanguage
+    }
+}
+
+/// Whether `node` is a block at the top of a file, like a `resource`, `module`,
+/// `variable`, `data` or `locals` block. Nested blocks such as `lifecycle` stay
+/// part of the block they're in.
+fn is_top_level_block(node: &Node) -> bool {
+    node.kind() == "block"
+        && node
+            .parent()
+            .filter(|body| body.kind() == "body")
+            .and_then(|body| body.parent())
+            .is_some_and(|file| file.kind() == "config_file")
+}
+
+/// The type and labels of a block, `["resource", "aws_s3_bucket", "logs"]` for
+/// `resource "aws_s3_bucket" "logs" { ... }`
+fn block_names<'a>(node: &Node, source: &'a [u8]) -> Vec<&'a str> {
+    let mut cursor = node.walk();
+    node.children(&mut cursor)
+        .take_while(|child| child.kind() != "block_start")
+        .filter_map(|child| match child.kind() {
+            "identifier" => child.utf8_text(source).ok(),
+            // Quoted labels, without the quotes
+            "string_lit" => Some(
+                child
+                    .utf8_text(source)
+                    .ok()?
+                    .trim_start_matches('"')
+                    .trim_end_matches('"'),
+            ),
+            _ => None,
+        })
+        .collect()
+}
+
+/// The addresses a top-level block is referred to by, as in Terraform:
+/// `aws_s3_bucket.logs` for a resource, `data.aws_iam_policy_document.read`,
+/// `module.vpc`, `var.region` or `variable.region`, and `locals`
+pub fn block_addresses(node: &Node, source: &[u8]) -> Vec<String> {
+    if !is_top_level_block(node) {
+        return Vec::new();
+    }
+    let names = block_names(node, source);
+    let Some((block_type, labels)) = names.split_first() else {
+        return Vec::new();
+    };
+
+    let mut addresses = vec![names.join(".")];
+    match *block_type {
+        "resource" if !labels.is_empty() => addresses.push(labels.join(".")),
+        "variable" if !labels.is_empty() => addresses.push(format!("var.{}", labels.join("."))),
+        _ => {}
+    }
+    addresses
+}
+
+/// Find the top-level block with the given address, see [`block_addresses`]
+pub fn find_block<'a>(root: Node<'a>, address: &str, source: &[u8]) -> Option<Node<'a>> {
+    let mut cursor = root.walk();
+    let body = root
+        .children(&mut cursor)
+        .find(|child| child.kind() == "body")?;
+    let mut cursor = body.walk();
+    let block = body.children(&mut cursor).find(|block| {
+        block_addresses(block, source)
+            .iter()
+            .any(|block_address| block_address == address)
+    });
+    block
+}
+
+impl LanguageImpl for HclLanguage {
+    fn get_tree_sitter_language(&self) -> TSLanguage {
+        tree_sitter_hcl::LANGUAGE.into()
+    }
+
+    fn get_extension(&self) -> &'static str {
+        "tf"
+    }
+
+    fn is_acceptable_parent(&self, node: &Node) -> bool {
+        is_top_level_block(node)
+    }
+
+    fn is_test_node(&self, node: &Node, source: &[u8]) -> bool {
+        // `run` blocks of `terraform test` files, like `main.tftest.hcl`
+        if is_top_level_block(node) && block_names(node, source).first() == Some(&"run") {
+            if probe_code::console::debug_mode() {
+                println!("DEBUG: Test node detected (HCL): run block");
+            }
+            return true;
+        }
+
+        false
+    }
+}
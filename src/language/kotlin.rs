@@ -0,0 +1,111 @@
+use super::language_trait::LanguageImpl;
+use tree_sitter::{Language as TSLanguage, Node};
+
+/// Base classes of Kotest specs
+const KOTEST_SPECS: &[&str] = &[
+    "AnnotationSpec",
+    "BehaviorSpec",
+    "DescribeSpec",
+    "ExpectSpec",
+    "FeatureSpec",
+    "FreeSpec",
+    "FunSpec",
+    "ShouldSpec",
+    "StringSpec",
+    "WordSpec",
+];
+
+/// Implementation of LanguageImpl for Kotlin
+pub struct KotlinLanguage;
+
+impl Default for KotlinLanguage {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl KotlinLanguage {
+    pub fn new() -> Self {
+        KotlinLanguage
+    }
+}
+
+impl LanguageImpl for KotlinLanguage {
+    fn get_tree_sitter_language(&self) -> TSLanguage {
+        tree_sitter_kotlin_sg::LANGUAGE.into()
+    }
+
+    fn get_extension(&self) -> &'static str {
+        "kt"
+    }
+
+    fn is_acceptable_parent(&self, node: &Node) -> bool {
+        matches!(
+            node.kind(),
+            "function_declaration"
+                | "class_declaration"
+                | "object_declaration"
+                | "companion_object"
+                | "secondary_constructor"
+                | "anonymous_initializer"
+                | "property_declaration"
+                | "type_alias"
+        )
+    }
+
+    fn is_test_node(&self, node: &Node, source: &[u8]) -> bool {
+        let debug_mode = probe_code::console::debug_mode();
+        let mut cursor = node.walk();
+
+        match node.kind() {
+            // JUnit: functions annotated with @Test
+            "function_declaration" => {
+                for child in node.children(&mut cursor) {
+                    if child.kind() == "modifiers" {
+                        let mut subcursor = child.walk();
+                        for annotation in child.children(&mut subcursor) {
+                            if annotation.kind() == "annotation"
+                                && annotation.utf8_text(source).unwrap_or("") == "@Test"
+                            {
+                                if debug_mode {
+                                    println!("DEBUG: Test node detected (Kotlin): @Test function");
+                                }
+                                return true;
+                            }
+                        }
+                    }
+                }
+            }
+            // Kotest: classes extending a spec, like `class AuthSpec : FunSpec({ ... })`
+            "class_declaration" => {
+                for child in node.children(&mut cursor) {
+                    if child.kind() == "delegation_specifier" {
+                        let specifier = child.utf8_text(source).unwrap_or("");
+                        if KOTEST_SPECS.iter().any(|spec| specifier.starts_with(spec)) {
+                            if debug_mode {
+                                println!("DEBUG: Test node detected (Kotlin): Kotest spec");
+                            }
+                            return true;
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+
+        false
+    }
+
+    fn find_parent_function<'a>(&self, node: Node<'a>) -> Option<Node<'a>> {
+        let mut current = node;
+
+        while let Some(parent) = current.parent() {
+            if parent.kind() == "function_declaration" {
+                return Some(parent);
+            }
+            current = parent;
+        }
+
+        None
+    }
+}
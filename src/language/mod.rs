@@ -2,8 +2,11 @@
 // using tree-sitter and extracting code blocks.
 
 // Import submodules
+pub mod block_cache;
 pub mod block_handling;
 pub mod common;
+pub mod document;
+pub mod embedded;
 pub mod factory;
 pub mod language_trait;
 pub mod parser;
@@ -16,12 +19,15 @@ pub mod c;
 pub mod cpp;
 pub mod csharp;
 pub mod go;
+pub mod hcl;
 pub mod java;
 pub mod javascript;
+pub mod kotlin;
 pub mod php;
 pub mod python;
 pub mod ruby;
 pub mod rust;
+pub mod scala;
 pub mod swift;
 pub mod typescript;
 
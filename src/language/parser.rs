@@ -1,12 +1,15 @@
 use anyhow::{Context, Result};
 use dashmap::DashMap;
 use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet};
 use tree_sitter::{Node, Parser as TSParser};
 
+use probe_code::language::block_cache;
 use probe_code::language::factory::get_language_impl;
 use probe_code::language::language_trait::LanguageImpl;
 use probe_code::language::tree_cache;
+use probe_code::language::{document, embedded};
 use probe_code::models::CodeBlock;
 
 /// Node type priority for deterministic selection when multiple important types match same content
@@ -90,13 +93,46 @@ impl SparseLineMap {
     }
 
     /// Check if a line is within any populated range
-    #[allow(dead_code)]
     fn is_populated(&self, line: usize) -> bool {
         self.populated_ranges
             .iter()
             .any(|&(start, end)| line >= start && end >= line)
     }
 
+    /// Check if every requested (1-based) line is within a populated range
+    fn covers(&self, line_numbers: &HashSet<usize>) -> bool {
+        line_numbers
+            .iter()
+            .all(|&line| self.is_populated(line.saturating_sub(1)))
+    }
+
+    /// Add the populated lines of an older map of the same file, so the map
+    /// keeps covering the lines earlier queries needed
+    fn merge(&mut self, older: SparseLineMap) {
+        for (line, info) in older.mappings {
+            if older
+                .populated_ranges
+                .iter()
+                .any(|&(s, e)| line >= s && e >= line)
+                && !self.is_populated(line)
+            {
+                self.mappings.insert(line, info);
+            }
+        }
+        self.populated_ranges.extend(older.populated_ranges);
+
+        // Coalesce overlapping and adjacent ranges
+        self.populated_ranges.sort_unstable();
+        let mut ranges: Vec<(usize, usize)> = Vec::with_capacity(self.populated_ranges.len());
+        for &(start, end) in &self.populated_ranges {
+            match ranges.last_mut() {
+                Some((_, last_end)) if start <= *last_end + 1 => *last_end = (*last_end).max(end),
+                _ => ranges.push((start, end)),
+            }
+        }
+        self.populated_ranges = ranges;
+    }
+
     /// Get total number of mappings stored
     fn len(&self) -> usize {
         self.mappings.len()
@@ -111,7 +147,7 @@ impl SparseLineMap {
 ///
 /// The hash function is based on FNV-1a algorithm which is fast and provides
 /// good distribution while being deterministic.
-fn calculate_content_hash(content: &str) -> u64 {
+pub fn calculate_content_hash(content: &str) -> u64 {
     // FNV-1a hash algorithm - fast and deterministic
     // Constants for 64-bit FNV-1a
     const FNV_OFFSET_BASIS: u64 = 14695981039346656037;
@@ -126,7 +162,7 @@ fn calculate_content_hash(content: &str) -> u64 {
 }
 
 /// A version of NodeInfo without lifetimes for caching
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
 struct CachedNodeInfo {
     // Original node info
     start_byte: usize,
@@ -325,7 +361,7 @@ fn find_acceptable_child<'a>(node: Node<'a>, language_impl: &dyn LanguageImpl) -
 
 /// Finds the immediate next node that follows a given node in the AST
 fn find_immediate_next_node(node: Node<'_>) -> Option<Node<'_>> {
-    let debug_mode = std::env::var("DEBUG").unwrap_or_default() == "1";
+    let debug_mode = probe_code::console::debug_mode();
 
     // First try direct next sibling
     if let Some(next) = node.next_sibling() {
@@ -1819,10 +1855,14 @@ pub fn parse_file_for_code_blocks(
         allow_tests,
         _term_matches,
         None,
+        true,
     )
 }
 
-/// Function to parse a file and extract code blocks with an optional pre-parsed tree
+/// Function to parse a file and extract code blocks with an optional pre-parsed tree.
+/// `on_disk` tells whether the file was read from the disk: what is derived from
+/// files kept in memory, like unsaved editor buffers, or read from archives and
+/// git revisions must not end up in the block cache on the disk.
 pub fn parse_file_for_code_blocks_with_tree(
     content: &str,
     extension: &str,
@@ -1830,9 +1870,22 @@ pub fn parse_file_for_code_blocks_with_tree(
     allow_tests: bool,
     _term_matches: Option<&HashMap<usize, HashSet<usize>>>, // Query index to line numbers
     pre_parsed_tree: Option<tree_sitter::Tree>,
+    on_disk: bool,
 ) -> Result<Vec<CodeBlock>> {
     // Check for debug mode
-    let debug_mode = std::env::var("DEBUG").unwrap_or_default() == "1";
+    let debug_mode = probe_code::console::debug_mode();
+
+    // Documents are split into heading sections and code blocks instead
+    if let Some(blocks) = document::parse_document_blocks(content, extension, line_numbers) {
+        return Ok(blocks);
+    }
+
+    // Components are split into scripts, parsed on their own, and styles
+    if let Some(blocks) =
+        embedded::parse_embedded_blocks(content, extension, line_numbers, allow_tests)
+    {
+        return blocks;
+    }
 
     // Get the appropriate language implementation
     let language_impl = match get_language_impl(extension) {
@@ -1848,23 +1901,27 @@ pub fn parse_file_for_code_blocks_with_tree(
 
     // Calculate content hash for cache key
     let content_hash = calculate_content_hash(content);
-    let cache_key = format!("{extension}_{content_hash}_{allow_tests}");
+    let cache_key = line_map_cache_key(extension, content_hash, allow_tests);
 
-    // Check if we have a cached sparse line map
-    if let Some(cached_entry) = LINE_MAP_CACHE.get(&cache_key) {
-        if debug_mode {
-            eprintln!("DEBUG: Sparse cache hit for line_map key: {cache_key}");
-        }
+    // Check if we have a cached sparse line map that covers the requested lines.
+    // The map only holds the lines it was built for, so a map built for another
+    // query may be missing some of them.
+    if load_cached_line_map(&cache_key, line_numbers, on_disk, debug_mode) {
+        if let Some(cached_entry) = LINE_MAP_CACHE.get(&cache_key) {
+            if debug_mode {
+                eprintln!("DEBUG: Sparse cache hit for line_map key: {cache_key}");
+            }
 
-        // Process the sparse cached line map
-        return process_sparse_line_map(
-            cached_entry.value(),
-            line_numbers,
-            language_impl.as_ref(),
-            content,
-            allow_tests,
-            debug_mode,
-        );
+            // Process the sparse cached line map
+            return process_sparse_line_map(
+                cached_entry.value(),
+                line_numbers,
+                language_impl.as_ref(),
+                content,
+                allow_tests,
+                debug_mode,
+            );
+        }
     }
 
     if debug_mode {
@@ -1904,7 +1961,7 @@ pub fn parse_file_for_code_blocks_with_tree(
     }
 
     // OPTIMIZATION: Build sparse line map that only processes intersecting nodes
-    let sparse_line_map = build_sparse_line_map(
+    let mut sparse_line_map = build_sparse_line_map(
         root_node,
         line_numbers,
         language_impl.as_ref(),
@@ -1929,7 +1986,21 @@ pub fn parse_file_for_code_blocks_with_tree(
         allow_tests,
         debug_mode,
     )?;
-    // Store the sparse line map in cache for future requests
+    // Store the sparse line map in cache for future requests, in memory and on
+    // disk so the next run doesn't need to parse the file either
+    for &line in line_numbers {
+        let line_idx = line.saturating_sub(1);
+        sparse_line_map.mark_populated(line_idx, line_idx);
+    }
+    if let Some((_, older)) = LINE_MAP_CACHE.remove(&cache_key) {
+        sparse_line_map.merge(older);
+    }
+    if on_disk {
+        block_cache::store(
+            &cache_key,
+            &PersistedLineMap::from_line_map(&sparse_line_map),
+        );
+    }
     LINE_MAP_CACHE.insert(cache_key.clone(), sparse_line_map);
     if debug_mode {
         eprintln!("DEBUG: SPARSE OPTIMIZATION - Stored sparse line map in cache key: {cache_key}");
@@ -1938,6 +2009,321 @@ pub fn parse_file_for_code_blocks_with_tree(
     Ok(code_blocks)
 }
 
+/// Key of the line map cache for a file with the given content hash
+pub fn line_map_cache_key(extension: &str, content_hash: u64, allow_tests: bool) -> String {
+    format!("{extension}_{content_hash}_{allow_tests}")
+}
+
+/// Make sure the in-memory line map cache holds a map covering the requested
+/// lines, loading it from the block cache on disk when needed and `on_disk`.
+/// Returns whether such a map is now cached.
+fn load_cached_line_map(
+    key: &str,
+    line_numbers: &HashSet<usize>,
+    on_disk: bool,
+    debug_mode: bool,
+) -> bool {
+    if LINE_MAP_CACHE
+        .get(key)
+        .is_some_and(|entry| entry.covers(line_numbers))
+    {
+        return true;
+    }
+
+    let Some(line_map) = on_disk.then(|| block_cache::load(key)).flatten() else {
+        return false;
+    };
+    if debug_mode {
+        eprintln!("DEBUG: Loaded line map for key {key} from the block cache");
+    }
+    let mut line_map = line_map.to_line_map();
+    if let Some((_, older)) = LINE_MAP_CACHE.remove(key) {
+        line_map.merge(older);
+    }
+    let covered = line_map.covers(line_numbers);
+    LINE_MAP_CACHE.insert(key.to_string(), line_map);
+    covered
+}
+
+/// Line map of a file, in a form that can be stored on disk.
+/// Lines mapped to the same node are stored as a single run.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PersistedLineMap {
+    /// `(first line, last line)` ranges the map is complete for, 0-based and inclusive
+    populated: Vec<(usize, usize)>,
+    /// Distinct node infos referenced by `runs`
+    nodes: Vec<CachedNodeInfo>,
+    /// `(first line, last line, index into nodes)`, 0-based and inclusive
+    runs: Vec<(usize, usize, usize)>,
+}
+
+impl PersistedLineMap {
+    fn from_line_map(map: &SparseLineMap) -> Self {
+        let mut nodes: Vec<CachedNodeInfo> = Vec::new();
+        let mut node_ids: HashMap<&CachedNodeInfo, usize> = HashMap::new();
+        let mut runs: Vec<(usize, usize, usize)> = Vec::new();
+
+        let mut lines: Vec<usize> = map.mappings.keys().copied().collect();
+        lines.sort_unstable();
+        for line in lines {
+            let Some(info) = map.get(line) else {
+                continue;
+            };
+            let id = *node_ids.entry(info).or_insert_with(|| {
+                nodes.push(info.clone());
+                nodes.len() - 1
+            });
+            match runs.last_mut() {
+                Some((_, end, last_id)) if *end + 1 == line && *last_id == id => *end = line,
+                _ => runs.push((line, line, id)),
+            }
+        }
+
+        PersistedLineMap {
+            populated: map.populated_ranges.clone(),
+            nodes,
+            runs,
+        }
+    }
+
+    fn to_line_map(&self) -> SparseLineMap {
+        let mut map = SparseLineMap::new(0);
+        for &(start, end, id) in &self.runs {
+            if let Some(info) = self.nodes.get(id) {
+                for line in start..=end {
+                    map.insert(line, info.clone());
+                }
+            }
+        }
+        for &(start, end) in &self.populated {
+            map.mark_populated(start, end);
+        }
+        map
+    }
+}
+
+/// Build the line map of a whole file, for persisting it with `seed_line_map_cache`.
+/// Returns `None` when the language isn't supported.
+pub fn build_persisted_line_map(
+    content: &str,
+    extension: &str,
+    allow_tests: bool,
+) -> Result<Option<PersistedLineMap>> {
+    let Some(language_impl) = get_language_impl(extension) else {
+        return Ok(None);
+    };
+
+    let mut parser = TSParser::new();
+    parser.set_language(&language_impl.get_tree_sitter_language())?;
+    let tree = parser
+        .parse(content, None)
+        .context("Failed to parse the file")?;
+
+    let lines = content.lines().count();
+    let mut map = SparseLineMap::new(0);
+    process_node_sparse(
+        tree.root_node(),
+        &mut map,
+        language_impl.as_ref(),
+        content.as_bytes(),
+        allow_tests,
+        false,
+        None,
+        &[(0, lines)],
+    );
+    map.mark_populated(0, lines);
+
+    Ok(Some(PersistedLineMap::from_line_map(&map)))
+}
+
+/// Make a persisted line map available to `parse_file_for_code_blocks`, so files
+/// with the matching content are not parsed again. `key` comes from
+/// `line_map_cache_key`.
+pub fn seed_line_map_cache(key: String, line_map: &PersistedLineMap) {
+    LINE_MAP_CACHE.insert(key, line_map.to_line_map());
+}
+
+/// Whether code blocks for the given lines can be built from a cached line map,
+/// in memory or, for files read from the disk, in the block cache on disk,
+/// without parsing the file
+pub fn has_cached_line_map(
+    content: &str,
+    extension: &str,
+    line_numbers: &HashSet<usize>,
+    allow_tests: bool,
+    on_disk: bool,
+) -> bool {
+    let key = line_map_cache_key(extension, calculate_content_hash(content), allow_tests);
+    let debug_mode = probe_code::console::debug_mode();
+    load_cached_line_map(&key, line_numbers, on_disk, debug_mode)
+}
+
+/// Structural role of source text inside a code block.
+/// Used by field-scoped query terms such as `fn:parse` or `comment:todo`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BlockField {
+    /// Names of function and method declarations
+    Function,
+    /// Names of class, struct, interface, enum, trait and type declarations
+    Class,
+    /// Any declaration name (includes function and class names)
+    Name,
+    /// Comments and docstrings
+    Comment,
+    /// String and character literals
+    StringLiteral,
+    /// Everything else
+    Code,
+}
+
+/// Check whether a node kind represents a comment
+fn is_comment_kind(kind: &str) -> bool {
+    kind.contains("comment") || kind == "//"
+}
+
+/// Check whether a node kind represents a string or character literal
+fn is_string_literal_kind(kind: &str) -> bool {
+    kind.contains("string")
+        || kind == "char_literal"
+        || kind == "character_literal"
+        || kind == "rune_literal"
+        || kind == "heredoc_body"
+}
+
+/// Check whether a node is a docstring, i.e. a string literal used as a statement
+/// (Python module, class and function docstrings)
+fn is_docstring(node: Node) -> bool {
+    node.parent().is_some_and(|parent| {
+        parent.kind() == "expression_statement" && parent.named_child_count() == 1
+    })
+}
+
+/// Classify a node that names a declaration, returning `None` if the node is not
+/// a declaration name at all.
+fn classify_declaration_name(node: Node) -> Option<BlockField> {
+    let parent = node.parent()?;
+    let parent_kind = parent.kind();
+
+    // C and C++ declare function names through a function_declarator
+    if parent_kind == "function_declarator" {
+        let is_declarator = parent
+            .child_by_field_name("declarator")
+            .is_some_and(|n| n.id() == node.id());
+        return is_declarator.then_some(BlockField::Function);
+    }
+
+    let is_name = parent
+        .child_by_field_name("name")
+        .is_some_and(|n| n.id() == node.id());
+    if !is_name {
+        return None;
+    }
+
+    if parent_kind.contains("function")
+        || parent_kind.contains("method")
+        || parent_kind.contains("constructor")
+    {
+        Some(BlockField::Function)
+    } else if [
+        "class",
+        "struct",
+        "interface",
+        "enum",
+        "trait",
+        "type",
+        "module",
+        "mod_item",
+        "record",
+        "protocol",
+        "union",
+        "object",
+    ]
+    .iter()
+    .any(|k| parent_kind.contains(k))
+    {
+        Some(BlockField::Class)
+    } else if [
+        "declaration",
+        "declarator",
+        "definition",
+        "_item",
+        "_spec",
+        "signature",
+    ]
+    .iter()
+    .any(|k| parent_kind.contains(k))
+    {
+        Some(BlockField::Name)
+    } else {
+        None
+    }
+}
+
+/// Collect the source text of a block grouped by structural role.
+///
+/// Walks every node of `tree` that intersects rows `start_row..=end_row` (0-based)
+/// and assigns its text to exactly one of comment, string literal, declaration name
+/// or code. Function and class names are also added to `BlockField::Name`.
+pub fn collect_block_field_text(
+    tree: &tree_sitter::Tree,
+    content: &[u8],
+    start_row: usize,
+    end_row: usize,
+) -> HashMap<BlockField, String> {
+    let mut fields: HashMap<BlockField, String> = HashMap::new();
+    let mut push = |field: BlockField, text: &str| {
+        let entry = fields.entry(field).or_default();
+        if !entry.is_empty() {
+            entry.push('\n');
+        }
+        entry.push_str(text);
+    };
+
+    let mut stack = vec![tree.root_node()];
+    while let Some(node) = stack.pop() {
+        if node.end_position().row < start_row || node.start_position().row > end_row {
+            continue;
+        }
+
+        let kind = node.kind();
+        let text = node.utf8_text(content).unwrap_or("");
+
+        if is_comment_kind(kind) {
+            push(BlockField::Comment, text);
+            continue;
+        }
+
+        if is_string_literal_kind(kind) {
+            if is_docstring(node) {
+                push(BlockField::Comment, text);
+            } else {
+                push(BlockField::StringLiteral, text);
+            }
+            continue;
+        }
+
+        if let Some(field) = classify_declaration_name(node) {
+            push(field, text);
+            if field != BlockField::Name {
+                push(BlockField::Name, text);
+            }
+            continue;
+        }
+
+        if node.child_count() == 0 {
+            push(BlockField::Code, text);
+            continue;
+        }
+
+        let mut cursor = node.walk();
+        let children: Vec<Node> = node.children(&mut cursor).collect();
+        // Push in reverse so that text is collected in source order
+        stack.extend(children.into_iter().rev());
+    }
+
+    fields
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1947,20 +2333,19 @@ mod tests {
     fn test_parse_file_unsupported_language_returns_empty() {
         // Test that parsing an unsupported language returns empty blocks instead of error
         let content = r#"
-        # Terraform configuration
-        resource "aws_instance" "example" {
-          ami           = "ami-0c55b159cbfafe1f0"
-          instance_type = "t2.micro"
-        }
+        # Cargo manifest
+        [package]
+        name = "example"
+        version = "0.1.0"
         "#;
 
         let mut line_numbers = HashSet::new();
-        line_numbers.insert(3); // Line with 'resource'
+        line_numbers.insert(3); // Line with 'package'
 
-        // .tf extension is not supported by tree-sitter
+        // .toml extension is not supported by tree-sitter
         let result = parse_file_for_code_blocks(
             content,
-            "tf", // Terraform extension, not supported
+            "toml", // TOML extension, not supported
             &line_numbers,
             false, // allow_tests
             None,  // term_matches
@@ -2011,4 +2396,132 @@ fn test_function() {
             .any(|b| b.node_type == "function_item" || b.node_type == "function");
         assert!(has_function, "Should find a function block");
     }
+
+    #[test]
+    fn test_collect_block_field_text_classifies_rust_nodes() {
+        let content = r#"/// Cache documentation
+fn load_cache() {
+    let label = "cache miss";
+    refresh(label);
+}
+
+struct CacheEntry {
+    value: u32,
+}
+"#;
+        let mut parser = TSParser::new();
+        parser
+            .set_language(&tree_sitter_rust::LANGUAGE.into())
+            .unwrap();
+        let tree = parser.parse(content, None).unwrap();
+
+        let fields = collect_block_field_text(&tree, content.as_bytes(), 0, 9);
+        let text = |field: BlockField| fields.get(&field).cloned().unwrap_or_default();
+
+        assert!(text(BlockField::Comment).contains("Cache documentation"));
+        assert!(text(BlockField::StringLiteral).contains("\"cache miss\""));
+        assert_eq!(text(BlockField::Function), "load_cache");
+        assert_eq!(text(BlockField::Class), "CacheEntry");
+        assert!(text(BlockField::Name).contains("load_cache"));
+        assert!(text(BlockField::Name).contains("CacheEntry"));
+        assert!(text(BlockField::Code).contains("refresh"));
+        assert!(!text(BlockField::Code).contains("load_cache"));
+        assert!(!text(BlockField::Code).contains("cache miss"));
+
+        // Restricting the row range only collects intersecting nodes
+        let fields = collect_block_field_text(&tree, content.as_bytes(), 6, 8);
+        assert!(!fields.contains_key(&BlockField::Function));
+        assert_eq!(
+            fields.get(&BlockField::Class).map(String::as_str),
+            Some("CacheEntry")
+        );
+    }
+
+    #[test]
+    fn test_cached_line_map_covers_requested_lines() {
+        let content: String = (0..20)
+            .map(|i| format!("fn cover_target_{i}() {{\n    step({i});\n}}\n"))
+            .collect();
+
+        // The second call hits the map cached by the first, which was only
+        // built for the lines of the first call
+        let first: HashSet<usize> = [2].into();
+        let second: HashSet<usize> = [56].into();
+        let first_blocks = parse_file_for_code_blocks(&content, "rs", &first, false, None).unwrap();
+        let second_blocks =
+            parse_file_for_code_blocks(&content, "rs", &second, false, None).unwrap();
+        assert_eq!(first_blocks[0].start_row, 0);
+        assert_eq!(second_blocks.len(), 1);
+        assert_eq!(second_blocks[0].start_row, 54);
+    }
+
+    #[test]
+    fn test_persisted_line_map_gives_same_blocks() {
+        let content = r#"
+/// Load the configuration
+fn load_config(path: &str) -> Config {
+    let text = read(path);
+    parse(text)
+}
+
+struct Config {
+    name: String,
+}
+
+fn unrelated() {
+    // nothing to see here
+}
+"#;
+        let lines: HashSet<usize> = (1..=content.lines().count()).collect();
+        let expected = parse_file_for_code_blocks(content, "rs", &lines, false, None).unwrap();
+        assert!(!expected.is_empty());
+
+        let line_map = build_persisted_line_map(content, "rs", false)
+            .unwrap()
+            .unwrap();
+        let json = serde_json::to_string(&line_map).unwrap();
+        let line_map: PersistedLineMap = serde_json::from_str(&json).unwrap();
+
+        let key = line_map_cache_key("rs", calculate_content_hash(content), false);
+        LINE_MAP_CACHE.remove(&key);
+        seed_line_map_cache(key, &line_map);
+        assert!(has_cached_line_map(content, "rs", &lines, false, true));
+
+        let blocks = parse_file_for_code_blocks(content, "rs", &lines, false, None).unwrap();
+        assert_eq!(blocks, expected);
+
+        assert!(build_persisted_line_map(content, "unknown", false)
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn test_sparse_line_maps_are_merged() {
+        let content: String = (0..20)
+            .map(|i| format!("fn merge_target_{i}() {{\n    step({i});\n}}\n"))
+            .collect();
+        let key = line_map_cache_key("rs", calculate_content_hash(&content), false);
+        LINE_MAP_CACHE.remove(&key);
+
+        let first: HashSet<usize> = [2].into();
+        let second: HashSet<usize> = [56].into();
+        let first_blocks = parse_file_for_code_blocks(&content, "rs", &first, false, None).unwrap();
+        let second_blocks =
+            parse_file_for_code_blocks(&content, "rs", &second, false, None).unwrap();
+        assert_eq!(first_blocks[0].start_row, 0);
+        assert_eq!(second_blocks[0].start_row, 54);
+
+        // The map now covers the lines of both queries, and so does its persisted form
+        let line_map = PersistedLineMap::from_line_map(&LINE_MAP_CACHE.get(&key).unwrap());
+        let restored = line_map.to_line_map();
+        assert!(restored.covers(&first) && restored.covers(&second));
+        assert!(!restored.covers(&[30].into()));
+
+        LINE_MAP_CACHE.remove(&key);
+        seed_line_map_cache(key, &line_map);
+        for (lines, expected) in [(&first, &first_blocks), (&second, &second_blocks)] {
+            let blocks = parse_file_for_code_blocks(&content, "rs", lines, false, None).unwrap();
+            assert_eq!(&blocks, expected);
+        }
+    }
 }
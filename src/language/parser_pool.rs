@@ -6,6 +6,7 @@ use tree_sitter::Parser;
 
 use crate::language::factory;
 use crate::search::file_list_cache;
+use crate::source::Sources;
 
 // PHASE 4 OPTIMIZATION: Dynamic pool sizing based on CPU cores
 const DEFAULT_MAX_PARSERS_PER_LANGUAGE: usize = 4;
@@ -43,7 +44,7 @@ lazy_static::lazy_static! {
             let common_languages = ["cpp", "c", "jsx", "tsx", "rb", "php", "cs"];
 
             // Tier 3: Specialized languages - warm last
-            let specialized_languages = ["swift", "h", "cc", "cxx", "hpp", "hxx"];
+            let specialized_languages = ["swift", "kt", "scala", "tf", "h", "cc", "cxx", "hpp", "hxx"];
 
             // Create a single parser per language to initialize the pool
             // This reduces startup latency for the first file of each type
@@ -72,10 +73,12 @@ fn detect_languages_in_directory(path: &Path) -> HashSet<String> {
 
     // Use the existing file discovery system
     if let Ok(file_list) = file_list_cache::get_file_list(
+        &Sources::default(),
         path,
         true,  // Include tests for complete detection
         &[],   // No custom ignores
         false, // Respect gitignore
+        &file_list_cache::FileFilters::default(),
     ) {
         for file in &file_list.files {
             if let Some(extension) = file.extension() {
@@ -112,7 +115,7 @@ pub fn smart_warm_parser_pool_for_directory(path: &Path) {
         return;
     }
 
-    let debug_mode = std::env::var("DEBUG").unwrap_or_default() == "1";
+    let debug_mode = probe_code::console::debug_mode();
     if debug_mode {
         println!("[DEBUG] Smart pre-warming detected languages: {detected_languages:?}");
     }
@@ -121,7 +124,7 @@ pub fn smart_warm_parser_pool_for_directory(path: &Path) {
     let priority_order = [
         "rs", "js", "ts", "py", "go", "java", // Tier 1: Critical
         "cpp", "c", "jsx", "tsx", "rb", "php", "cs", // Tier 2: Common
-        "swift", "h", "cc", "cxx", "hpp", "hxx", // Tier 3: Specialized
+        "swift", "kt", "scala", "tf", "h", "cc", "cxx", "hpp", "hxx", // Tier 3: Specialized
     ];
 
     // Warm detected languages in priority order
@@ -173,7 +176,7 @@ pub fn smart_warm_parser_pool_for_directory(path: &Path) {
 /// return_pooled_parser("rs", parser);
 /// ```
 pub fn get_pooled_parser(extension: &str) -> Result<Parser> {
-    let debug_mode = std::env::var("DEBUG").unwrap_or_default() == "1";
+    let debug_mode = probe_code::console::debug_mode();
 
     // First, try to get a parser from the pool
     {
@@ -224,7 +227,7 @@ pub fn get_pooled_parser(extension: &str) -> Result<Parser> {
 /// The pool will maintain a reasonable number of parsers per language to balance
 /// performance with memory usage.
 pub fn return_pooled_parser(extension: &str, parser: Parser) {
-    let debug_mode = std::env::var("DEBUG").unwrap_or_default() == "1";
+    let debug_mode = probe_code::console::debug_mode();
 
     let mut pool = PARSER_POOL
         .lock()
@@ -276,7 +279,7 @@ pub fn clear_parser_pool() {
         .lock()
         .unwrap_or_else(|poisoned| poisoned.into_inner());
 
-    let debug_mode = std::env::var("DEBUG").unwrap_or_default() == "1";
+    let debug_mode = probe_code::console::debug_mode();
 
     if debug_mode {
         let total_parsers: usize = pool.values().map(|v| v.len()).sum();
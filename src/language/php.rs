@@ -37,7 +37,7 @@ impl LanguageImpl for PhpLanguage {
     }
 
     fn is_test_node(&self, node: &Node, source: &[u8]) -> bool {
-        let debug_mode = std::env::var("DEBUG").unwrap_or_default() == "1";
+        let debug_mode = probe_code::console::debug_mode();
         let node_type = node.kind();
 
         // PHP: Check method_declaration nodes with test prefix or PHPUnit annotations
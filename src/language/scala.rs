@@ -0,0 +1,140 @@
+use super::language_trait::LanguageImpl;
+use tree_sitter::{Language as TSLanguage, Node};
+
+/// Base classes of test suites: ScalaTest styles (`AnyFunSuite`, `AnyFlatSpec`,
+/// their async and fixture variants and the pre-3.1 names), specs2
+/// specifications, MUnit, utest and ZIO Test suites
+const TEST_SUITE_BASES: &[&str] = &[
+    "AnyFunSuite",
+    "AnyFlatSpec",
+    "AnyFunSpec",
+    "AnyWordSpec",
+    "AnyFreeSpec",
+    "AnyPropSpec",
+    "AnyFeatureSpec",
+    "AsyncFunSuite",
+    "AsyncFlatSpec",
+    "AsyncFunSpec",
+    "AsyncWordSpec",
+    "AsyncFreeSpec",
+    "AsyncFeatureSpec",
+    "FixtureAnyFunSuite",
+    "FixtureAnyFlatSpec",
+    "FixtureAnyFunSpec",
+    "FixtureAnyWordSpec",
+    "FixtureAnyFreeSpec",
+    "FunSuite",
+    "FlatSpec",
+    "FunSpec",
+    "WordSpec",
+    "FreeSpec",
+    "PropSpec",
+    "FeatureSpec",
+    "Specification",
+    "SpecificationWithJUnit",
+    "SpecWithJUnit",
+    "ScalaCheckSuite",
+    "CatsEffectSuite",
+    "TestSuite",
+    "ZIOSpecDefault",
+    "ZIOSpec",
+];
+
+/// Implementation of LanguageImpl for Scala
+pub struct ScalaLanguage;
+
+impl Default for ScalaLanguage {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ScalaLanguage {
+    pub fn new() -> Self {
+        ScalaLanguage
+    }
+}
+
+impl LanguageImpl for ScalaLanguage {
+    fn get_tree_sitter_language(&self) -> TSLanguage {
+        tree_sitter_scala::LANGUAGE.into()
+    }
+
+    fn get_extension(&self) -> &'static str {
+        "scala"
+    }
+
+    fn is_acceptable_parent(&self, node: &Node) -> bool {
+        matches!(
+            node.kind(),
+            "function_definition"
+                | "class_definition"
+                | "object_definition"
+                | "trait_definition"
+                | "enum_definition"
+                | "extension_definition"
+                | "given_definition"
+                | "type_definition"
+                | "val_definition"
+                | "var_definition"
+        )
+    }
+
+    fn is_test_node(&self, node: &Node, source: &[u8]) -> bool {
+        let debug_mode = probe_code::console::debug_mode();
+        let mut cursor = node.walk();
+
+        match node.kind() {
+            // JUnit: methods annotated with @Test
+            "function_definition" => {
+                for child in node.children(&mut cursor) {
+                    if child.kind() == "annotation"
+                        && child.utf8_text(source).unwrap_or("") == "@Test"
+                    {
+                        if debug_mode {
+                            println!("DEBUG: Test node detected (Scala): @Test method");
+                        }
+                        return true;
+                    }
+                }
+            }
+            // Suites extending the class of a test framework, like
+            // `class AuthSuite extends AnyFunSuite with Matchers` or
+            // `class AuthSuite extends munit.FunSuite`
+            "class_definition" | "object_definition" => {
+                for child in node.children(&mut cursor) {
+                    if child.kind() == "extends_clause" {
+                        let mut subcursor = child.walk();
+                        for base in child.children(&mut subcursor) {
+                            let name = base.utf8_text(source).unwrap_or("");
+                            let name = name.split(['[', '(']).next().unwrap_or(name);
+                            let name = name.rsplit('.').next().unwrap_or(name).trim();
+                            if TEST_SUITE_BASES.contains(&name) {
+                                if debug_mode {
+                                    println!("DEBUG: Test node detected (Scala): test suite");
+                                }
+                                return true;
+                            }
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+
+        false
+    }
+
+    fn find_parent_function<'a>(&self, node: Node<'a>) -> Option<Node<'a>> {
+        let mut current = node;
+
+        while let Some(parent) = current.parent() {
+            if parent.kind() == "function_definition" {
+                return Some(parent);
+            }
+            current = parent;
+        }
+
+        None
+    }
+}
@@ -2,7 +2,7 @@ use std::path::Path;
 
 /// Function to determine if a file is a test file based on common naming conventions and directory patterns
 pub fn is_test_file(path: &Path) -> bool {
-    let _debug_mode = std::env::var("DEBUG").unwrap_or_default() == "1";
+    let _debug_mode = probe_code::console::debug_mode();
 
     // Check file name patterns
     if let Some(file_name) = path.file_name().and_then(|f| f.to_str()) {
@@ -102,6 +102,30 @@ pub fn is_test_file(path: &Path) -> bool {
             }
             return true;
         }
+
+        // Kotlin: *Test.kt, *Tests.kt (JUnit), *Spec.kt (Kotest)
+        if let Some(stem) = file_name
+            .strip_suffix(".kt")
+            .or_else(|| file_name.strip_suffix(".kts"))
+        {
+            if stem.ends_with("Test") || stem.ends_with("Tests") || stem.ends_with("Spec") {
+                if _debug_mode {
+                    println!("DEBUG: Test file detected (Kotlin pattern): {file_name}");
+                }
+                return true;
+            }
+        }
+
+        // Scala files are no test files by their name, as `*Spec.scala` may well
+        // hold an API spec: suites are in test directories or found by their class
+
+        // Terraform: *.tftest.hcl, *.tftest.json
+        if file_name.ends_with(".tftest.hcl") || file_name.ends_with(".tftest.json") {
+            if _debug_mode {
+                println!("DEBUG: Test file detected (Terraform pattern): {file_name}");
+            }
+            return true;
+        }
     }
 
     // Check directory patterns
@@ -7,12 +7,15 @@ extern crate tree_sitter_c;
 extern crate tree_sitter_c_sharp;
 extern crate tree_sitter_cpp;
 extern crate tree_sitter_go;
+extern crate tree_sitter_hcl;
 extern crate tree_sitter_java;
 extern crate tree_sitter_javascript;
+extern crate tree_sitter_kotlin_sg;
 extern crate tree_sitter_php;
 extern crate tree_sitter_python;
 extern crate tree_sitter_ruby;
 extern crate tree_sitter_rust;
+extern crate tree_sitter_scala;
 extern crate tree_sitter_swift;
 extern crate tree_sitter_typescript;
 
@@ -31,6 +34,9 @@ fn get_language(extension: &str) -> Option<Language> {
         "rb" => Some(tree_sitter_ruby::LANGUAGE.into()),
         "swift" => Some(tree_sitter_swift::LANGUAGE.into()),
         "cs" => Some(tree_sitter_c_sharp::LANGUAGE.into()),
+        "kt" | "kts" => Some(tree_sitter_kotlin_sg::LANGUAGE.into()),
+        "scala" | "sc" => Some(tree_sitter_scala::LANGUAGE.into()),
+        "tf" | "tfvars" | "hcl" => Some(tree_sitter_hcl::LANGUAGE.into()),
         // It seems tree_sitter_php::LANGUAGE doesn't exist, so we'll return None for PHP
         "php" => None,
         _ => None,
@@ -55,6 +61,9 @@ fn test_get_language() {
     assert!(get_language("rb").is_some()); // Ruby
     assert!(get_language("swift").is_some()); // Swift
     assert!(get_language("cs").is_some()); // C#
+    assert!(get_language("kt").is_some()); // Kotlin
+    assert!(get_language("scala").is_some()); // Scala
+    assert!(get_language("tf").is_some()); // Terraform/HCL
     assert!(get_language("php").is_none()); // PHP (not supported in current tree-sitter version)
 
     // Test unsupported language
@@ -785,3 +794,208 @@ fn print_ast_structure(node: tree_sitter::Node, depth: usize) {
 // Include tree cache tests
 #[path = "tree_cache_tests.rs"]
 mod tree_cache_tests;
+
+#[test]
+fn test_kotlin_code_blocks() {
+    let kotlin_code = r#"package auth
+
+class SessionManager(private val store: Store) {
+    fun refreshToken(session: Session): Token {
+        return session.token.refresh()
+    }
+}
+
+class SessionManagerTest {
+    @Test
+    fun refreshesToken() {
+        assertEquals(1, 1)
+    }
+}
+
+class SessionSpec : FunSpec({
+    test("refresh") { }
+})
+"#;
+
+    assert!(get_language_impl("kt").is_some());
+    assert!(get_language_impl("kts").is_some());
+
+    let line_numbers = HashSet::from([5]);
+    let blocks = parse_file_for_code_blocks(kotlin_code, "kt", &line_numbers, false, None).unwrap();
+    assert_eq!(blocks.len(), 1);
+    assert_eq!(blocks[0].node_type, "function_declaration");
+    assert_eq!((blocks[0].start_row, blocks[0].end_row), (3, 5));
+
+    // JUnit tests and Kotest specs are left out unless tests are allowed
+    let line_numbers = HashSet::from([12, 17]);
+    let blocks = parse_file_for_code_blocks(kotlin_code, "kt", &line_numbers, false, None).unwrap();
+    assert!(blocks.is_empty(), "{blocks:?}");
+    let blocks = parse_file_for_code_blocks(kotlin_code, "kt", &line_numbers, true, None).unwrap();
+    assert!(!blocks.is_empty());
+}
+
+#[test]
+fn test_scala_code_blocks() {
+    let scala_code = r#"package auth
+
+class SessionManager(store: Store) extends Manager {
+  def refreshToken(session: Session): String = {
+    session.token.reverse
+  }
+}
+
+class SessionSuite extends munit.FunSuite {
+  test("refresh") { assert(true) }
+}
+
+class SessionJUnit {
+  @Test def refreshesToken(): Unit = assertEquals(1, 1)
+}
+
+class SessionSpec extends AnyFlatSpec with Matchers {
+  "A session" should "refresh" in { succeed }
+}
+
+class PaymentSuite extends ApiSuite {
+  def charge(amount: Int): Int = amount
+}
+"#;
+
+    assert!(get_language_impl("scala").is_some());
+    assert!(get_language_impl("sc").is_some());
+
+    let line_numbers = HashSet::from([5]);
+    let blocks =
+        parse_file_for_code_blocks(scala_code, "scala", &line_numbers, false, None).unwrap();
+    assert_eq!(blocks.len(), 1);
+    assert_eq!(blocks[0].node_type, "function_definition");
+    assert_eq!((blocks[0].start_row, blocks[0].end_row), (3, 5));
+
+    // ScalaTest/MUnit suites and JUnit tests are left out unless tests are allowed
+    let line_numbers = HashSet::from([10, 14]);
+    let blocks =
+        parse_file_for_code_blocks(scala_code, "scala", &line_numbers, false, None).unwrap();
+    assert!(blocks.is_empty(), "{blocks:?}");
+    let blocks =
+        parse_file_for_code_blocks(scala_code, "scala", &line_numbers, true, None).unwrap();
+    assert!(!blocks.is_empty());
+    let line_numbers = HashSet::from([18]);
+    let blocks =
+        parse_file_for_code_blocks(scala_code, "scala", &line_numbers, false, None).unwrap();
+    assert!(blocks.is_empty(), "{blocks:?}");
+
+    // Extending a class named like a suite doesn't make a test suite
+    let line_numbers = HashSet::from([21]);
+    let blocks =
+        parse_file_for_code_blocks(scala_code, "scala", &line_numbers, false, None).unwrap();
+    assert_eq!(blocks.len(), 1, "{blocks:?}");
+}
+
+#[test]
+fn test_hcl_code_blocks() {
+    let terraform_code = r#"locals {
+  bucket_prefix = "logs"
+}
+
+resource "aws_s3_bucket" "logs" {
+  bucket = "${local.bucket_prefix}-bucket"
+  lifecycle {
+    prevent_destroy = true
+  }
+}
+
+run "creates_bucket" {
+  assert {
+    condition = aws_s3_bucket.logs.bucket != ""
+  }
+}
+"#;
+
+    assert!(get_language_impl("tf").is_some());
+    assert!(get_language_impl("tfvars").is_some());
+    assert!(get_language_impl("hcl").is_some());
+
+    // Nested blocks like `lifecycle` stay in the top-level block they're in
+    let line_numbers = HashSet::from([8]);
+    let blocks =
+        parse_file_for_code_blocks(terraform_code, "tf", &line_numbers, false, None).unwrap();
+    assert_eq!(blocks.len(), 1);
+    assert_eq!(blocks[0].node_type, "block");
+    assert_eq!((blocks[0].start_row, blocks[0].end_row), (4, 9));
+
+    // `terraform test` run blocks are left out unless tests are allowed
+    let line_numbers = HashSet::from([14]);
+    let blocks =
+        parse_file_for_code_blocks(terraform_code, "hcl", &line_numbers, false, None).unwrap();
+    assert!(blocks.is_empty(), "{blocks:?}");
+    let blocks =
+        parse_file_for_code_blocks(terraform_code, "hcl", &line_numbers, true, None).unwrap();
+    assert!(!blocks.is_empty());
+}
+
+#[test]
+fn test_hcl_block_addresses() {
+    use probe_code::language::hcl::{block_addresses, find_block};
+
+    let terraform_code = r#"variable "region" {}
+
+data "aws_iam_policy_document" "read" {}
+
+resource "aws_s3_bucket" "logs" {
+  tags = {}
+}
+"#;
+    let mut parser = tree_sitter::Parser::new();
+    parser
+        .set_language(&tree_sitter_hcl::LANGUAGE.into())
+        .unwrap();
+    let tree = parser.parse(terraform_code, None).unwrap();
+    let root = tree.root_node();
+    let source = terraform_code.as_bytes();
+
+    let block = find_block(root, "aws_s3_bucket.logs", source).unwrap();
+    assert_eq!(block.start_position().row, 4);
+    assert_eq!(
+        block_addresses(&block, source),
+        vec!["resource.aws_s3_bucket.logs", "aws_s3_bucket.logs"]
+    );
+    assert_eq!(
+        find_block(root, "var.region", source).map(|block| block.start_position().row),
+        Some(0)
+    );
+    assert_eq!(
+        find_block(root, "data.aws_iam_policy_document.read", source)
+            .map(|block| block.start_position().row),
+        Some(2)
+    );
+    assert!(find_block(root, "aws_iam_policy_document.read", source).is_none());
+}
+
+#[test]
+fn test_terraform_test_files() {
+    use probe_code::language::test_detection::is_test_file;
+    use std::path::Path;
+
+    assert!(is_test_file(Path::new("modules/bucket/main.tftest.hcl")));
+    assert!(!is_test_file(Path::new("modules/bucket/main.tf")));
+    assert!(!is_test_file(Path::new("modules/bucket/terraform.tfvars")));
+}
+
+#[test]
+fn test_kotlin_and_scala_test_files() {
+    use probe_code::language::test_detection::is_test_file;
+    use std::path::Path;
+
+    assert!(is_test_file(Path::new("app/SessionManagerTest.kt")));
+    assert!(is_test_file(Path::new("app/SessionManagerTests.kt")));
+    assert!(is_test_file(Path::new("app/SessionSpec.kt")));
+    assert!(!is_test_file(Path::new("app/SessionManager.kt")));
+    assert!(is_test_file(Path::new(
+        "core/src/test/scala/SessionSuite.scala"
+    )));
+    assert!(is_test_file(Path::new("core/test/src/SessionSpec.scala")));
+    assert!(!is_test_file(Path::new(
+        "core/src/main/scala/ApiSpec.scala"
+    )));
+    assert!(!is_test_file(Path::new("core/Session.scala")));
+}
@@ -14,6 +14,32 @@
 //!
 //! ## Examples
 //!
+//! ### Embedding probe
+//!
+//! [`Probe`] runs searches, extractions and queries and returns their results,
+//! without printing anything or reading the environment variables meant for the
+//! command line:
+//!
+//! ```no_run
+//! use probe_code::Probe;
+//!
+//! let probe = Probe::new(".").max_results(10);
+//! let results = probe.search("function search").unwrap();
+//! println!("Found {} results", results.results.len());
+//! ```
+//!
+//! Files can also come from elsewhere than the disk, such as unsaved buffers
+//! held in memory:
+//!
+//! ```no_run
+//! use probe_code::source::MemorySource;
+//! use probe_code::Probe;
+//!
+//! let buffers = MemorySource::new("/project").file("src/lib.rs", "fn unsaved() {}");
+//! let probe = Probe::new("/project").source(buffers);
+//! let results = probe.search("unsaved").unwrap();
+//! ```
+//!
 //! ### Searching for code
 //!
 //! ```no_run
@@ -30,6 +56,8 @@
 //!     reranker: "bm25",
 //!     frequency_search: true,
 //!     exact: false,
+//!     fuzzy: false,
+//!     explain: false,
 //!     language: None,
 //!     max_results: Some(10),
 //!     max_bytes: None,
@@ -40,6 +68,10 @@
 //!     dry_run: false,
 //!     session: None,
 //!     timeout: 30,
+//!     question: None,
+//!     no_gitignore: false,
+//!     file_filters: Default::default(),
+//!     source: None,
 //! };
 //!
 //! let results = perform_probe(&options).unwrap();
@@ -65,6 +97,9 @@
 //!     keep_input: false,
 //!     prompt: None,
 //!     instructions: None,
+//!     no_gitignore: false,
+//!     file_filters: Default::default(),
+//!     source: None,
 //! };
 //!
 //! handle_extract(options).unwrap();
@@ -80,11 +115,14 @@
 //! let options = QueryOptions {
 //!     path: Path::new("."),
 //!     pattern: "fn $NAME($$$PARAMS) { $$$BODY }",
-//!     language: Some("rust".to_string()),
+//!     language: Some("rust"),
 //!     ignore: &[],
 //!     allow_tests: false,
 //!     max_results: None,
-//!     format: "text".to_string(),
+//!     format: "text",
+//!     no_gitignore: false,
+//!     file_filters: Default::default(),
+//!     source: None,
 //! };
 //!
 //! let matches = perform_query(&options).unwrap();
@@ -95,21 +133,32 @@
 extern crate self as probe_code;
 
 pub mod bert_reranker;
+#[cfg(unix)]
+pub mod capture;
+pub mod console;
+#[cfg(unix)]
+pub mod daemon;
+pub mod embed;
 pub mod extract;
 pub mod language;
+pub mod lsp;
+pub mod mcp;
 pub mod models;
 pub mod path_resolver;
 pub mod query;
 pub mod ranking;
 pub mod search;
+pub mod serve;
 pub mod simd_ranking;
 pub mod simd_test;
+pub mod source;
 pub mod version;
 
 // Re-export commonly used types for convenience
+pub use embed::Probe;
 pub use extract::{
-    format_and_print_extraction_results, handle_extract, process_file_for_extraction,
-    ExtractOptions,
+    format_and_print_extraction_results, handle_extract, perform_extract,
+    process_file_for_extraction, ExtractOptions, ExtractResults,
 };
 pub use models::{CodeBlock, LimitedSearchResults, SearchLimits, SearchResult};
 pub use path_resolver::resolve_path;
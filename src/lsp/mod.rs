@@ -0,0 +1,303 @@
+//! Language Server Protocol server, run by `probe lsp`.
+//!
+//! Gives editors probe's search through `workspace/symbol` and a custom
+//! `probe/search` request, and answers `textDocument/definition` by looking the
+//! symbol up in the current file and then across the workspace. Meant to run
+//! next to the language's own server, as a fallback for what it can't resolve.
+
+mod symbols;
+mod transport;
+
+use anyhow::{bail, Result};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use url::Url;
+
+use probe_code::console;
+use probe_code::search::timeout;
+use transport::{read_message, write_message};
+
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+const SERVER_NOT_INITIALIZED: i64 = -32002;
+const REQUEST_FAILED: i64 = -32803;
+
+/// Run the language server on stdio until the client tells it to exit
+pub fn run_lsp_server() -> Result<()> {
+    // Stdout carries the protocol, so nothing else may print to it
+    let _silence = console::silence();
+    let mut protocol = std::io::stdout();
+    colored::control::set_override(false);
+    // A slow search must fail its request, not end the server
+    timeout::set_exit_on_timeout(false);
+
+    let mut server = LspServer::default();
+    let debug_mode = std::env::var("DEBUG").unwrap_or_default() == "1";
+    let mut input = std::io::stdin().lock();
+    while let Some(message) = read_message(&mut input)? {
+        if debug_mode {
+            eprintln!("DEBUG: LSP message: {message}");
+        }
+        if let Some(response) = server.handle(&message) {
+            write_message(&mut protocol, &response)?;
+        }
+        if server.exit_requested {
+            break;
+        }
+    }
+
+    if !server.shutdown_requested {
+        bail!("The client exited without shutting the language server down");
+    }
+    Ok(())
+}
+
+#[derive(Default)]
+struct LspServer {
+    /// Workspace to search, set by `initialize`
+    root: Option<PathBuf>,
+    /// Content of the documents open in the editor, by URI
+    documents: HashMap<String, String>,
+    shutdown_requested: bool,
+    exit_requested: bool,
+}
+
+impl LspServer {
+    /// Handle one message, returning the response to send if it needs one
+    fn handle(&mut self, message: &Value) -> Option<Value> {
+        let method = message.get("method").and_then(Value::as_str)?;
+        let params = message.get("params").cloned().unwrap_or(Value::Null);
+        let Some(id) = message.get("id") else {
+            self.handle_notification(method, &params);
+            return None;
+        };
+
+        let result = if self.shutdown_requested {
+            Err((INVALID_REQUEST, "The server is shutting down".to_string()))
+        } else if self.root.is_none() && method != "initialize" {
+            Err((SERVER_NOT_INITIALIZED, "Not initialized".to_string()))
+        } else {
+            self.handle_request(method, &params)
+        };
+        Some(match result {
+            Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+            Err((code, message)) => json!({
+                "jsonrpc": "2.0",
+                "id": id,
+                "error": { "code": code, "message": message },
+            }),
+        })
+    }
+
+    fn handle_request(&mut self, method: &str, params: &Value) -> Result<Value, (i64, String)> {
+        let failed = |e: anyhow::Error| (REQUEST_FAILED, format!("{e:#}"));
+        match method {
+            "initialize" => Ok(self.initialize(params)),
+            "shutdown" => {
+                self.shutdown_requested = true;
+                Ok(Value::Null)
+            }
+            "workspace/symbol" => {
+                let query = params.get("query").and_then(Value::as_str).unwrap_or("");
+                symbols::workspace_symbols(self.root(), query).map_err(failed)
+            }
+            "probe/search" => {
+                let params = serde_json::from_value(params.clone())
+                    .map_err(|e| (INVALID_PARAMS, format!("Invalid parameters: {e}")))?;
+                symbols::search(self.root(), params).map_err(failed)
+            }
+            "textDocument/definition" => {
+                let (uri, line, character) = text_document_position(params).ok_or_else(|| {
+                    (INVALID_PARAMS, "Missing text document position".to_string())
+                })?;
+                let path = Url::parse(uri)
+                    .ok()
+                    .and_then(|url| url.to_file_path().ok())
+                    .ok_or_else(|| (INVALID_PARAMS, format!("Not a file: {uri}")))?;
+                let content = match self.documents.get(uri) {
+                    Some(content) => content.clone(),
+                    None => std::fs::read_to_string(&path)
+                        .map_err(|e| failed(anyhow::anyhow!("Failed to read {uri}: {e}")))?,
+                };
+                symbols::definition(self.root(), &path, &content, line, character).map_err(failed)
+            }
+            _ => Err((METHOD_NOT_FOUND, format!("Method not found: {method}"))),
+        }
+    }
+
+    fn handle_notification(&mut self, method: &str, params: &Value) {
+        let uri = params
+            .pointer("/textDocument/uri")
+            .and_then(Value::as_str)
+            .map(str::to_string);
+        match (method, uri) {
+            ("textDocument/didOpen", Some(uri)) => {
+                if let Some(text) = params.pointer("/textDocument/text").and_then(Value::as_str) {
+                    self.documents.insert(uri, text.to_string());
+                }
+            }
+            ("textDocument/didChange", Some(uri)) => {
+                // Full sync: the last change holds the whole document
+                let text = params
+                    .get("contentChanges")
+                    .and_then(Value::as_array)
+                    .and_then(|changes| changes.last())
+                    .and_then(|change| change.get("text"))
+                    .and_then(Value::as_str);
+                if let Some(text) = text {
+                    self.documents.insert(uri, text.to_string());
+                }
+            }
+            ("textDocument/didClose", Some(uri)) => {
+                self.documents.remove(&uri);
+            }
+            ("exit", _) => self.exit_requested = true,
+            _ => {}
+        }
+    }
+
+    fn initialize(&mut self, params: &Value) -> Value {
+        let root_uri = params.get("rootUri").and_then(Value::as_str).or_else(|| {
+            params
+                .pointer("/workspaceFolders/0/uri")
+                .and_then(Value::as_str)
+        });
+        let root = root_uri
+            .and_then(|uri| Url::parse(uri).ok())
+            .and_then(|url| url.to_file_path().ok())
+            .or_else(|| {
+                params
+                    .get("rootPath")
+                    .and_then(Value::as_str)
+                    .map(PathBuf::from)
+            })
+            .or_else(|| std::env::current_dir().ok())
+            .unwrap_or_else(|| PathBuf::from("."));
+        self.root = Some(root);
+
+        json!({
+            "capabilities": {
+                // Full document sync
+                "textDocumentSync": 1,
+                "workspaceSymbolProvider": true,
+                "definitionProvider": true,
+            },
+            "serverInfo": {
+                "name": "probe",
+                "version": probe_code::version::get_version(),
+            },
+        })
+    }
+
+    fn root(&self) -> &std::path::Path {
+        self.root.as_deref().unwrap_or(std::path::Path::new("."))
+    }
+}
+
+/// The document URI and zero-based line and UTF-16 column of a position request
+fn text_document_position(params: &Value) -> Option<(&str, usize, usize)> {
+    let uri = params.pointer("/textDocument/uri")?.as_str()?;
+    let line = params.pointer("/position/line")?.as_u64()?;
+    let character = params.pointer("/position/character")?.as_u64()?;
+    Some((uri, line as usize, character as usize))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(id: u64, method: &str, params: Value) -> Value {
+        json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params })
+    }
+
+    fn notification(method: &str, params: Value) -> Value {
+        json!({ "jsonrpc": "2.0", "method": method, "params": params })
+    }
+
+    #[test]
+    fn test_lifecycle() {
+        let mut server = LspServer::default();
+        let response = server
+            .handle(&request(1, "workspace/symbol", json!({ "query": "auth" })))
+            .unwrap();
+        assert_eq!(response["error"]["code"], SERVER_NOT_INITIALIZED);
+
+        let response = server
+            .handle(&request(
+                2,
+                "initialize",
+                json!({ "rootUri": "file:///work/project" }),
+            ))
+            .unwrap();
+        let capabilities = &response["result"]["capabilities"];
+        assert_eq!(capabilities["workspaceSymbolProvider"], true);
+        assert_eq!(capabilities["definitionProvider"], true);
+        assert_eq!(server.root, Some(PathBuf::from("/work/project")));
+        assert_eq!(server.handle(&notification("initialized", json!({}))), None);
+
+        let response = server
+            .handle(&request(3, "textDocument/hover", json!({})))
+            .unwrap();
+        assert_eq!(response["error"]["code"], METHOD_NOT_FOUND);
+
+        let response = server.handle(&request(4, "shutdown", Value::Null)).unwrap();
+        assert_eq!(response["result"], Value::Null);
+        assert!(!server.exit_requested);
+        assert_eq!(server.handle(&notification("exit", Value::Null)), None);
+        assert!(server.exit_requested);
+    }
+
+    #[test]
+    fn test_definition_in_open_document() {
+        let mut server = LspServer::default();
+        server.handle(&request(
+            1,
+            "initialize",
+            json!({ "rootUri": "file:///work/project" }),
+        ));
+
+        let uri = "file:///work/project/src/lib.rs";
+        let text = "fn main() {\n    let total = add(1, 2);\n}\n\n/// Adds two numbers\nfn add(a: i32, b: i32) -> i32 {\n    a + b\n}\n";
+        server.handle(&notification(
+            "textDocument/didOpen",
+            json!({ "textDocument": { "uri": uri, "languageId": "rust", "version": 1, "text": text } }),
+        ));
+
+        let response = server
+            .handle(&request(
+                2,
+                "textDocument/definition",
+                json!({ "textDocument": { "uri": uri }, "position": { "line": 1, "character": 17 } }),
+            ))
+            .unwrap();
+        assert_eq!(
+            response["result"],
+            json!([{
+                "uri": uri,
+                "range": {
+                    "start": { "line": 5, "character": 3 },
+                    "end": { "line": 5, "character": 6 },
+                },
+            }])
+        );
+
+        // Edits that aren't saved yet are used too
+        server.handle(&notification(
+            "textDocument/didChange",
+            json!({
+                "textDocument": { "uri": uri, "version": 2 },
+                "contentChanges": [{ "text": text.replace("fn add(", "fn  add(") }],
+            }),
+        ));
+        let response = server
+            .handle(&request(
+                3,
+                "textDocument/definition",
+                json!({ "textDocument": { "uri": uri }, "position": { "line": 1, "character": 18 } }),
+            ))
+            .unwrap();
+        assert_eq!(response["result"][0]["range"]["start"]["character"], 4);
+    }
+}
@@ -0,0 +1,340 @@
+use anyhow::Result;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use url::Url;
+
+use probe_code::console;
+use probe_code::extract::symbol_finder::find_symbol_in_file;
+use probe_code::models::SearchResult;
+use probe_code::search::{perform_probe, search_results_json, SearchOptions};
+
+/// Most symbols returned for a workspace/symbol request
+const MAX_SYMBOLS: usize = 100;
+/// Most files mentioning a symbol that are looked at for its definition
+const MAX_DEFINITION_FILES: usize = 50;
+
+/// Parameters of a `probe/search` request
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchParams {
+    query: String,
+    /// Directory or file to search, relative to the workspace (default: the workspace)
+    path: Option<PathBuf>,
+    #[serde(default)]
+    files_only: bool,
+    #[serde(default)]
+    exact: bool,
+    #[serde(default)]
+    allow_tests: bool,
+    language: Option<String>,
+    max_results: Option<usize>,
+    max_tokens: Option<usize>,
+    session: Option<String>,
+}
+
+/// Search options of the server, with the settings of the CLI's defaults
+fn search_options<'a>(path: &'a Path, queries: &'a [String]) -> SearchOptions<'a> {
+    SearchOptions {
+        path,
+        queries,
+        files_only: false,
+        custom_ignores: &[],
+        exclude_filenames: false,
+        reranker: "bm25",
+        frequency_search: true,
+        exact: false,
+        fuzzy: false,
+        explain: false,
+        language: None,
+        max_results: None,
+        max_bytes: None,
+        max_tokens: None,
+        allow_tests: false,
+        no_merge: false,
+        merge_threshold: None,
+        dry_run: false,
+        session: None,
+        timeout: 30,
+        question: None,
+        no_gitignore: std::env::var("PROBE_NO_GITIGNORE").unwrap_or_default() == "1",
+        file_filters: Default::default(),
+        source: None,
+    }
+}
+
+fn run_search(options: &SearchOptions) -> Result<Vec<SearchResult>> {
+    // Searches print progress meant for the terminal
+    Ok(console::silenced(|| perform_probe(options))?.results)
+}
+
+/// `probe/search`: ranked code blocks, as printed by `probe search --format json`
+pub fn search(root: &Path, params: SearchParams) -> Result<Value> {
+    let path = match &params.path {
+        Some(path) => root.join(path),
+        None => root.to_path_buf(),
+    };
+    let queries = vec![params.query];
+    let options = SearchOptions {
+        files_only: params.files_only,
+        exact: params.exact,
+        allow_tests: params.allow_tests,
+        language: params.language.as_deref(),
+        max_results: params.max_results,
+        max_tokens: params.max_tokens,
+        session: params.session.as_deref(),
+        ..search_options(&path, &queries)
+    };
+    Ok(search_results_json(&run_search(&options)?))
+}
+
+/// `workspace/symbol`: the best matching code blocks of a search, named after
+/// their first line of code
+pub fn workspace_symbols(root: &Path, query: &str) -> Result<Value> {
+    if query.trim().is_empty() {
+        return Ok(json!([]));
+    }
+    let queries = vec![query.to_string()];
+    let options = SearchOptions {
+        max_results: Some(MAX_SYMBOLS),
+        ..search_options(root, &queries)
+    };
+
+    let symbols: Vec<Value> = run_search(&options)?
+        .iter()
+        .filter(|result| !result.file.is_empty())
+        .filter_map(|result| {
+            let path = Path::new(&result.file);
+            let uri = Url::from_file_path(root.join(path)).ok()?;
+            let (offset, signature) = signature(&result.code)?;
+            let line = result.lines.0.saturating_sub(1) + offset;
+            Some(json!({
+                "name": signature,
+                "kind": symbol_kind(&result.node_type),
+                "location": {
+                    "uri": uri.as_str(),
+                    "range": {
+                        "start": { "line": line, "character": 0 },
+                        "end": { "line": result.lines.1.saturating_sub(1), "character": 0 },
+                    },
+                },
+                "containerName": path.strip_prefix(root).unwrap_or(path).to_string_lossy(),
+            }))
+        })
+        .collect();
+    Ok(Value::Array(symbols))
+}
+
+/// `textDocument/definition`: where the identifier at the position is defined,
+/// looked up in the document itself and then in the files of the workspace that
+/// mention it
+pub fn definition(
+    root: &Path,
+    path: &Path,
+    content: &str,
+    line: usize,
+    character: usize,
+) -> Result<Value> {
+    let Some(symbol) = identifier_at(content, line, character) else {
+        return Ok(Value::Null);
+    };
+
+    if let Some(location) = find_definition(path, content, &symbol) {
+        return Ok(json!([location]));
+    }
+
+    let queries = vec![symbol.clone()];
+    let options = SearchOptions {
+        files_only: true,
+        exact: true,
+        allow_tests: true,
+        ..search_options(root, &queries)
+    };
+    let mut seen = HashSet::new();
+    let mut locations = Vec::new();
+    for result in run_search(&options)? {
+        let file = root.join(&result.file);
+        if file == path || !seen.insert(file.clone()) {
+            continue;
+        }
+        if seen.len() > MAX_DEFINITION_FILES {
+            break;
+        }
+        let Ok(content) = std::fs::read_to_string(&file) else {
+            continue;
+        };
+        locations.extend(find_definition(&file, &content, &symbol));
+    }
+    Ok(Value::Array(locations))
+}
+
+/// Location of the name of a symbol defined in a file
+fn find_definition(path: &Path, content: &str, symbol: &str) -> Option<Value> {
+    let result = find_symbol_in_file(path, symbol, content, true, 0).ok()?;
+    // Whole files and plain text matches are no definitions
+    if result.node_type == "file" || result.node_type == "text_search" {
+        return None;
+    }
+
+    let lines: Vec<&str> = content.lines().collect();
+    let start = result.lines.0.saturating_sub(1);
+    let end = result.lines.1.min(lines.len());
+    let (line, first, last) = (start..end)
+        .find_map(|i| {
+            find_identifier(lines[i], symbol).map(|first| (i, first, first + symbol.len()))
+        })
+        .unwrap_or((start, 0, 0));
+    let line_text = lines.get(line).copied().unwrap_or("");
+
+    let uri = Url::from_file_path(path).ok()?;
+    Some(json!({
+        "uri": uri.as_str(),
+        "range": {
+            "start": { "line": line, "character": utf16_column(line_text, first) },
+            "end": { "line": line, "character": utf16_column(line_text, last) },
+        },
+    }))
+}
+
+fn is_identifier_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '$'
+}
+
+/// The identifier at a zero-based line and UTF-16 column, including one that
+/// ends right before the column
+fn identifier_at(content: &str, line: usize, character: usize) -> Option<String> {
+    let text = content.lines().nth(line)?;
+    let offset = byte_offset(text, character);
+
+    let start = text[..offset]
+        .char_indices()
+        .rev()
+        .take_while(|(_, c)| is_identifier_char(*c))
+        .last()
+        .map_or(offset, |(i, _)| i);
+    let end = text[offset..]
+        .char_indices()
+        .find(|(_, c)| !is_identifier_char(*c))
+        .map_or(text.len(), |(i, _)| offset + i);
+
+    let identifier = &text[start..end];
+    if identifier.is_empty() || identifier.starts_with(|c: char| c.is_ascii_digit()) {
+        return None;
+    }
+    Some(identifier.to_string())
+}
+
+/// Byte offset of the first occurrence of `identifier` in `line` as a whole word
+fn find_identifier(line: &str, identifier: &str) -> Option<usize> {
+    line.match_indices(identifier).map(|(i, _)| i).find(|&i| {
+        let before = line[..i].chars().next_back();
+        let after = line[i + identifier.len()..].chars().next();
+        !before.is_some_and(is_identifier_char) && !after.is_some_and(is_identifier_char)
+    })
+}
+
+/// Byte offset of a UTF-16 column, clamped to the line
+fn byte_offset(line: &str, character: usize) -> usize {
+    let mut units = 0;
+    for (i, c) in line.char_indices() {
+        if units >= character {
+            return i;
+        }
+        units += c.len_utf16();
+    }
+    line.len()
+}
+
+/// UTF-16 column of a byte offset
+fn utf16_column(line: &str, offset: usize) -> usize {
+    line[..offset].chars().map(char::len_utf16).sum()
+}
+
+/// The first line of a code block that isn't a comment, attribute or decorator,
+/// with its offset in the block
+fn signature(code: &str) -> Option<(usize, String)> {
+    const SKIPPED: &[&str] = &["//", "/*", "*", "#", "@", "--", "\"\"\"", "'''"];
+    code.lines().enumerate().find_map(|(offset, line)| {
+        let line = line.trim();
+        if line.is_empty() || SKIPPED.iter().any(|prefix| line.starts_with(prefix)) {
+            return None;
+        }
+        let line = line.trim_end_matches(['{', ':', ' ']);
+        let signature: String = line.chars().take(120).collect();
+        Some((offset, signature))
+    })
+}
+
+/// LSP symbol kind of a tree-sitter node type
+fn symbol_kind(node_type: &str) -> u32 {
+    const KINDS: &[(&str, u32)] = &[
+        ("method", 6),
+        ("constructor", 9),
+        ("function", 12),
+        ("class", 5),
+        ("impl", 5),
+        ("struct", 23),
+        ("interface", 11),
+        ("trait", 11),
+        ("protocol", 11),
+        ("enum", 10),
+        ("mod", 2),
+        ("namespace", 3),
+        ("package", 4),
+        ("const", 14),
+        ("field", 8),
+        ("property", 7),
+        ("variable", 13),
+        ("type", 26),
+    ];
+    KINDS
+        .iter()
+        .find(|(name, _)| node_type.contains(name))
+        .map_or(19, |(_, kind)| *kind)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_identifier_at() {
+        let content = "let total = add(1, 2);\nlet émoji = \"😀\" + naïve_sum;";
+        assert_eq!(identifier_at(content, 0, 13).as_deref(), Some("add"));
+        assert_eq!(identifier_at(content, 0, 12).as_deref(), Some("add"));
+        // Right after the identifier
+        assert_eq!(identifier_at(content, 0, 15).as_deref(), Some("add"));
+        // Numbers aren't identifiers
+        assert_eq!(identifier_at(content, 0, 16), None);
+        assert_eq!(identifier_at(content, 0, 11), None);
+        // The emoji is two UTF-16 code units
+        assert_eq!(identifier_at(content, 1, 21).as_deref(), Some("naïve_sum"));
+        assert_eq!(identifier_at(content, 1, 5).as_deref(), Some("émoji"));
+        assert_eq!(identifier_at(content, 5, 0), None);
+    }
+
+    #[test]
+    fn test_find_identifier() {
+        assert_eq!(
+            find_identifier("fn add_all(add: i32) -> add", "add"),
+            Some(11)
+        );
+        assert_eq!(find_identifier("fn adder()", "add"), None);
+        assert_eq!(utf16_column("let 😀 = add", 11), 9);
+    }
+
+    #[test]
+    fn test_signature() {
+        let code = "/// Adds numbers\n#[inline]\npub fn add(a: i32) -> i32 {\n    a\n}";
+        assert_eq!(
+            signature(code),
+            Some((2, "pub fn add(a: i32) -> i32".to_string()))
+        );
+        assert_eq!(signature("// only a comment"), None);
+        assert_eq!(symbol_kind("function_item"), 12);
+        assert_eq!(symbol_kind("method_declaration"), 6);
+        assert_eq!(symbol_kind("class_definition"), 5);
+        assert_eq!(symbol_kind("comment"), 19);
+    }
+}
@@ -1,7 +1,8 @@
 use anyhow::Result;
 use clap::{CommandFactory, Parser as ClapParser};
 use colored::*;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 use std::time::Instant;
 
 mod cli;
@@ -9,7 +10,14 @@ mod cli;
 use cli::{Args, Commands};
 use probe_code::{
     extract::{handle_extract, ExtractOptions},
-    search::{format_and_print_search_results, perform_probe, SearchOptions},
+    search::{
+        format_and_print_search_results,
+        git_changes::changed_file_filters,
+        perform_probe,
+        search_index::{handle_index, IndexOptions},
+        SearchOptions,
+    },
+    source,
 };
 
 struct SearchParams {
@@ -21,6 +29,7 @@ struct SearchParams {
     reranker: String,
     frequency_search: bool,
     exact: bool,
+    fuzzy: bool,
     language: Option<String>,
     max_results: Option<usize>,
     max_bytes: Option<usize>,
@@ -29,11 +38,15 @@ struct SearchParams {
     no_merge: bool,
     merge_threshold: Option<usize>,
     dry_run: bool,
+    explain: bool,
     format: String,
     session: Option<String>,
     timeout: u64,
     question: Option<String>,
     no_gitignore: bool,
+    changed_since: Option<String>,
+    staged: bool,
+    rev: Option<String>,
 }
 
 struct BenchmarkParams {
@@ -80,6 +93,9 @@ fn handle_search(params: SearchParams) -> Result<()> {
     if !use_frequency {
         advanced_options.push("Frequency search disabled".to_string());
     }
+    if params.fuzzy {
+        advanced_options.push("Fuzzy matching".to_string());
+    }
     if let Some(lang) = &params.language {
         advanced_options.push(format!("Language: {lang}"));
     }
@@ -98,9 +114,21 @@ fn handle_search(params: SearchParams) -> Result<()> {
     if params.dry_run {
         advanced_options.push("Dry run (file names and lines only)".to_string());
     }
+    if params.explain {
+        advanced_options.push("Explain".to_string());
+    }
     if let Some(session) = &params.session {
         advanced_options.push(format!("Session: {session}"));
     }
+    if let Some(changed_since) = &params.changed_since {
+        advanced_options.push(format!("Changed since: {changed_since}"));
+    }
+    if params.staged {
+        advanced_options.push("Staged files only".to_string());
+    }
+    if let Some(rev) = &params.rev {
+        advanced_options.push(format!("Revision: {rev}"));
+    }
 
     // Show timeout if it's not the default value of 30 seconds
     if params.timeout != 30 {
@@ -120,6 +148,14 @@ fn handle_search(params: SearchParams) -> Result<()> {
     // Create a vector with the pattern
     let query = vec![params.pattern.clone()];
 
+    let source = revision_source(params.paths.first().unwrap(), params.rev.as_deref())?;
+
+    let file_filters = changed_file_filters(
+        params.paths.first().unwrap(),
+        params.changed_since.as_deref(),
+        params.staged,
+    )?;
+
     let search_options = SearchOptions {
         path: params.paths.first().unwrap(),
         queries: &query,
@@ -129,6 +165,8 @@ fn handle_search(params: SearchParams) -> Result<()> {
         reranker: &params.reranker,
         frequency_search: use_frequency,
         exact: params.exact,
+        fuzzy: params.fuzzy,
+        explain: params.explain,
         language: params.language.as_deref(),
         max_results: params.max_results,
         max_bytes: params.max_bytes,
@@ -141,6 +179,8 @@ fn handle_search(params: SearchParams) -> Result<()> {
         timeout: params.timeout,
         question: params.question.as_deref(),
         no_gitignore: params.no_gitignore,
+        file_filters,
+        source,
     };
 
     let limited_results = perform_probe(&search_options)?;
@@ -340,10 +380,79 @@ fn handle_benchmark(params: BenchmarkParams) -> Result<()> {
     Ok(())
 }
 
+/// Where to read files from the git revision `rev` instead of the disk, with `--rev`
+fn revision_source(path: &Path, rev: Option<&str>) -> Result<Option<Arc<dyn source::Source>>> {
+    rev.map(|rev| {
+        let revision = source::GitRevision::open(path, rev)?;
+        Ok(Arc::new(revision) as Arc<dyn source::Source>)
+    })
+    .transpose()
+}
+
+/// How long a command forwarded to `probe daemon` may take, or `None` if the
+/// daemon can't run it. Search has its own timeout; extract and query, which
+/// have none, get as long as a search does by default.
+#[cfg(unix)]
+fn daemon_timeout(args: &Args) -> Option<u64> {
+    const DEFAULT_TIMEOUT: u64 = 30;
+    match &args.command {
+        None if args.pattern.as_ref().is_some_and(|p| !p.is_empty()) => Some(args.timeout),
+        Some(Commands::Search { timeout, .. }) => Some(*timeout),
+        Some(Commands::Extract {
+            files,
+            input_file,
+            from_clipboard,
+            to_clipboard,
+            ..
+        }) => {
+            // Stdin and the clipboard can only be read by this process
+            if (files.is_empty() && input_file.is_none()) || *from_clipboard || *to_clipboard {
+                return None;
+            }
+            Some(DEFAULT_TIMEOUT)
+        }
+        Some(Commands::Query { .. }) => Some(DEFAULT_TIMEOUT),
+        _ => None,
+    }
+}
+
+/// Send the command to a running `probe daemon`, if it is one the daemon can
+/// run. Returns the exit code of the command when the daemon ran it.
+#[cfg(unix)]
+fn forward_to_daemon(args: &Args) -> Option<i32> {
+    let timeout = daemon_timeout(args)?;
+    let args = std::env::args_os()
+        .skip(1)
+        .map(|arg| arg.into_string())
+        .collect::<Result<Vec<_>, _>>()
+        .ok()?;
+    probe_code::daemon::forward(args, std::time::Duration::from_secs(timeout))
+}
+
+/// Run a command sent to `probe daemon`. Only the commands clients forward are
+/// run: the daemon runs in another directory and environment than its clients.
+#[cfg(unix)]
+fn run_forwarded(args: Vec<String>) -> Result<()> {
+    let args = Args::try_parse_from(std::iter::once("probe".to_string()).chain(args))?;
+    if daemon_timeout(&args).is_none() {
+        anyhow::bail!("probe daemon only runs search, extract and query commands");
+    }
+    run(args)
+}
+
 #[tokio::main]
 async fn main() -> Result<()> {
     let args = Args::parse();
 
+    #[cfg(unix)]
+    if let Some(code) = forward_to_daemon(&args) {
+        std::process::exit(code);
+    }
+
+    run(args)
+}
+
+fn run(args: Args) -> Result<()> {
     match args.command {
         // When no subcommand provided and no pattern, show help
         None if args.pattern.is_none() || args.pattern.as_ref().unwrap().is_empty() => {
@@ -371,6 +480,7 @@ async fn main() -> Result<()> {
                 reranker: args.reranker,
                 frequency_search: args.frequency_search,
                 exact: args.exact,
+                fuzzy: args.fuzzy,
                 language: None, // Default to None for the no-subcommand case
                 max_results: args.max_results,
                 max_bytes: args.max_bytes,
@@ -379,12 +489,16 @@ async fn main() -> Result<()> {
                 no_merge: args.no_merge,
                 merge_threshold: args.merge_threshold,
                 dry_run: args.dry_run,
+                explain: args.explain,
                 format: args.format,
                 session: args.session,
                 timeout: args.timeout,
                 question: args.question,
                 no_gitignore: args.no_gitignore
                     || std::env::var("PROBE_NO_GITIGNORE").unwrap_or_default() == "1",
+                changed_since: args.changed_since,
+                staged: args.staged,
+                rev: args.rev,
             })?
         }
         Some(Commands::Search {
@@ -396,6 +510,7 @@ async fn main() -> Result<()> {
             reranker,
             frequency_search,
             exact,
+            fuzzy,
             language,
             max_results,
             max_bytes,
@@ -404,11 +519,15 @@ async fn main() -> Result<()> {
             no_merge,
             merge_threshold,
             dry_run,
+            explain,
             format,
             session,
             timeout,
             question,
             no_gitignore,
+            changed_since,
+            staged,
+            rev,
         }) => handle_search(SearchParams {
             pattern,
             paths,
@@ -418,6 +537,7 @@ async fn main() -> Result<()> {
             reranker,
             frequency_search,
             exact,
+            fuzzy,
             language,
             max_results,
             max_bytes,
@@ -426,12 +546,16 @@ async fn main() -> Result<()> {
             no_merge,
             merge_threshold,
             dry_run,
+            explain,
             format,
             session,
             timeout,
             question,
             no_gitignore: no_gitignore
                 || std::env::var("PROBE_NO_GITIGNORE").unwrap_or_default() == "1",
+            changed_since,
+            staged,
+            rev,
         })?,
         Some(Commands::Extract {
             files,
@@ -448,6 +572,9 @@ async fn main() -> Result<()> {
             prompt,
             instructions,
             no_gitignore,
+            changed_since,
+            staged,
+            rev,
         }) => handle_extract(ExtractOptions {
             files,
             custom_ignores: ignore,
@@ -469,6 +596,8 @@ async fn main() -> Result<()> {
             instructions,
             no_gitignore: no_gitignore
                 || std::env::var("PROBE_NO_GITIGNORE").unwrap_or_default() == "1",
+            file_filters: changed_file_filters(Path::new("."), changed_since.as_deref(), staged)?,
+            source: revision_source(Path::new("."), rev.as_deref())?,
         })?,
         Some(Commands::Query {
             pattern,
@@ -479,29 +608,59 @@ async fn main() -> Result<()> {
             max_results,
             format,
             no_gitignore,
-        }) => probe_code::query::handle_query(
-            &pattern,
-            &path,
-            language.as_deref().map(|lang| {
-                // Normalize language aliases
-                match lang.to_lowercase().as_str() {
-                    "rs" => "rust",
-                    "js" | "jsx" => "javascript",
-                    "ts" | "tsx" => "typescript",
-                    "py" => "python",
-                    "h" => "c",
-                    "cc" | "cxx" | "hpp" | "hxx" => "cpp",
-                    "rb" => "ruby",
-                    "cs" => "csharp",
-                    _ => lang, // Return the original language if no alias is found
-                }
-            }),
-            &ignore,
+            changed_since,
+            staged,
+            rev,
+        }) => {
+            probe_code::query::handle_query(
+                &pattern,
+                &path,
+                language.as_deref().map(|lang| {
+                    // Normalize language aliases
+                    match lang.to_lowercase().as_str() {
+                        "rs" => "rust",
+                        "js" | "jsx" => "javascript",
+                        "ts" | "tsx" => "typescript",
+                        "py" => "python",
+                        "h" => "c",
+                        "cc" | "cxx" | "hpp" | "hxx" => "cpp",
+                        "rb" => "ruby",
+                        "cs" => "csharp",
+                        "kt" | "kts" => "kotlin",
+                        "sc" => "scala",
+                        _ => lang, // Return the original language if no alias is found
+                    }
+                }),
+                &ignore,
+                allow_tests,
+                max_results,
+                &format,
+                no_gitignore || std::env::var("PROBE_NO_GITIGNORE").unwrap_or_default() == "1",
+                changed_file_filters(&path, changed_since.as_deref(), staged)?,
+                revision_source(&path, rev.as_deref())?,
+            )?
+        }
+        Some(Commands::Index {
+            path,
+            ignore,
             allow_tests,
-            max_results,
-            &format,
-            no_gitignore || std::env::var("PROBE_NO_GITIGNORE").unwrap_or_default() == "1",
-        )?,
+            no_gitignore,
+        }) => handle_index(&IndexOptions {
+            path: &path,
+            custom_ignores: &ignore,
+            allow_tests,
+            no_gitignore: no_gitignore
+                || std::env::var("PROBE_NO_GITIGNORE").unwrap_or_default() == "1",
+        })?,
+        #[cfg(unix)]
+        Some(Commands::Daemon { path }) => probe_code::daemon::run_daemon(&path, run_forwarded)?,
+        #[cfg(not(unix))]
+        Some(Commands::Daemon { .. }) => {
+            anyhow::bail!("probe daemon is only supported on Unix systems")
+        }
+        Some(Commands::Mcp { timeout }) => probe_code::mcp::run_mcp_server(timeout)?,
+        Some(Commands::Lsp) => probe_code::lsp::run_lsp_server()?,
+        Some(Commands::Serve { port, host }) => probe_code::serve::run_server(&host, port)?,
         Some(Commands::Benchmark {
             bench,
             sample_size,
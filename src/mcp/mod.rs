@@ -0,0 +1,230 @@
+//! Model Context Protocol server, run by `probe mcp`.
+//!
+//! Speaks JSON-RPC 2.0 over stdio, one message per line, and exposes search,
+//! extract and query as tools. Tools run in this process, so parsers and caches
+//! stay warm between calls.
+
+mod tools;
+
+use anyhow::Result;
+use serde_json::{json, Value};
+use std::io::{BufRead, Write};
+use std::time::Duration;
+
+use probe_code::search::timeout;
+
+/// Protocol versions this server can speak, newest first
+const PROTOCOL_VERSIONS: &[&str] = &["2025-06-18", "2025-03-26", "2024-11-05"];
+
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+
+/// Run the MCP server until stdin is closed. `default_timeout` is the time in
+/// seconds a tool call may take when it doesn't set its own timeout.
+pub fn run_mcp_server(default_timeout: u64) -> Result<()> {
+    // Stdout carries the protocol; tools run silenced and return their output
+    let mut protocol = std::io::stdout();
+    colored::control::set_override(false);
+    // A timeout must fail the tool call, not end the server
+    timeout::set_exit_on_timeout(false);
+
+    let server = McpServer { default_timeout };
+    let debug_mode = std::env::var("DEBUG").unwrap_or_default() == "1";
+    for line in std::io::stdin().lock().lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        if debug_mode {
+            eprintln!("DEBUG: MCP request: {line}");
+        }
+        if let Some(response) = server.handle_line(&line) {
+            serde_json::to_writer(&mut protocol, &response)?;
+            protocol.write_all(b"\n")?;
+            protocol.flush()?;
+        }
+    }
+    Ok(())
+}
+
+struct McpServer {
+    default_timeout: u64,
+}
+
+impl McpServer {
+    /// Handle one message, returning the response to send if it needs one
+    fn handle_line(&self, line: &str) -> Option<Value> {
+        match serde_json::from_str::<Value>(line) {
+            Ok(message) => self.handle_message(&message),
+            Err(e) => Some(error_response(
+                &Value::Null,
+                PARSE_ERROR,
+                &format!("Parse error: {e}"),
+            )),
+        }
+    }
+
+    fn handle_message(&self, message: &Value) -> Option<Value> {
+        let id = message.get("id");
+        let Some(method) = message.get("method").and_then(Value::as_str) else {
+            // Responses to requests of the server, which it doesn't send
+            return id
+                .filter(|_| message.get("result").is_none() && message.get("error").is_none())
+                .map(|id| error_response(id, INVALID_REQUEST, "Invalid request"));
+        };
+        // Notifications, such as notifications/initialized, need no response
+        let id = id?;
+        let params = message.get("params").cloned().unwrap_or(Value::Null);
+
+        let result = match method {
+            "initialize" => Ok(initialize(&params)),
+            "ping" => Ok(json!({})),
+            "tools/list" => Ok(json!({ "tools": tools::definitions() })),
+            "tools/call" => self.call_tool(&params),
+            _ => Err((METHOD_NOT_FOUND, format!("Method not found: {method}"))),
+        };
+        Some(match result {
+            Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+            Err((code, message)) => error_response(id, code, &message),
+        })
+    }
+
+    fn call_tool(&self, params: &Value) -> Result<Value, (i64, String)> {
+        let name = params
+            .get("name")
+            .and_then(Value::as_str)
+            .ok_or_else(|| (INVALID_PARAMS, "Missing tool name".to_string()))?;
+        let tool = tools::Tool::from_name(name)
+            .ok_or_else(|| (INVALID_PARAMS, format!("Unknown tool: {name}")))?;
+        let arguments = params.get("arguments").cloned().unwrap_or(json!({}));
+
+        // Failures of the tool are results the model gets to see
+        let default_timeout = Duration::from_secs(self.default_timeout);
+        Ok(match tools::call(tool, arguments, default_timeout) {
+            Ok(text) => json!({
+                "content": [{ "type": "text", "text": text }],
+                "isError": false,
+            }),
+            Err(e) => json!({
+                "content": [{ "type": "text", "text": format!("Error: {e:#}") }],
+                "isError": true,
+            }),
+        })
+    }
+}
+
+fn initialize(params: &Value) -> Value {
+    let requested = params.get("protocolVersion").and_then(Value::as_str);
+    let version = requested
+        .filter(|version| PROTOCOL_VERSIONS.contains(version))
+        .unwrap_or(PROTOCOL_VERSIONS[0]);
+    json!({
+        "protocolVersion": version,
+        "capabilities": { "tools": {} },
+        "serverInfo": {
+            "name": "probe",
+            "version": probe_code::version::get_version(),
+        },
+        "instructions": "Use search to find code related to a question, extract to read \
+            whole functions, classes or files, and query to find code by its structure.",
+    })
+}
+
+fn error_response(id: &Value, code: i64, message: &str) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": { "code": code, "message": message },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn server() -> McpServer {
+        McpServer {
+            default_timeout: 30,
+        }
+    }
+
+    fn request(method: &str, params: Value) -> String {
+        json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params }).to_string()
+    }
+
+    #[test]
+    fn test_initialize() {
+        let response = server()
+            .handle_line(&request(
+                "initialize",
+                json!({ "protocolVersion": "2024-11-05", "capabilities": {} }),
+            ))
+            .unwrap();
+        assert_eq!(response["id"], 1);
+        assert_eq!(response["result"]["protocolVersion"], "2024-11-05");
+        assert_eq!(response["result"]["serverInfo"]["name"], "probe");
+        assert!(response["result"]["capabilities"]["tools"].is_object());
+
+        // Unknown versions get the newest one the server speaks
+        let response = server()
+            .handle_line(&request(
+                "initialize",
+                json!({ "protocolVersion": "1999-01-01" }),
+            ))
+            .unwrap();
+        assert_eq!(response["result"]["protocolVersion"], PROTOCOL_VERSIONS[0]);
+    }
+
+    #[test]
+    fn test_notifications_get_no_response() {
+        let notification = json!({ "jsonrpc": "2.0", "method": "notifications/initialized" });
+        assert_eq!(server().handle_line(&notification.to_string()), None);
+    }
+
+    #[test]
+    fn test_tools_list() {
+        let response = server()
+            .handle_line(&request("tools/list", Value::Null))
+            .unwrap();
+        let names: Vec<&str> = response["result"]["tools"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|tool| tool["name"].as_str().unwrap())
+            .collect();
+        assert_eq!(names, ["search", "extract", "query"]);
+        for tool in response["result"]["tools"].as_array().unwrap() {
+            assert_eq!(tool["inputSchema"]["type"], "object");
+        }
+    }
+
+    #[test]
+    fn test_errors() {
+        let response = server().handle_line("{not json").unwrap();
+        assert_eq!(response["error"]["code"], PARSE_ERROR);
+        assert_eq!(response["id"], Value::Null);
+
+        let response = server()
+            .handle_line(&request("resources/list", Value::Null))
+            .unwrap();
+        assert_eq!(response["error"]["code"], METHOD_NOT_FOUND);
+
+        let response = server()
+            .handle_line(&request("tools/call", json!({ "name": "grep" })))
+            .unwrap();
+        assert_eq!(response["error"]["code"], INVALID_PARAMS);
+
+        // Bad arguments are reported to the model as a failed tool call
+        let response = server()
+            .handle_line(&request(
+                "tools/call",
+                json!({ "name": "search", "arguments": { "path": "." } }),
+            ))
+            .unwrap();
+        assert_eq!(response["result"]["isError"], true);
+        let text = response["result"]["content"][0]["text"].as_str().unwrap();
+        assert!(text.contains("query"), "{text}");
+    }
+}
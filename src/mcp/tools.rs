@@ -0,0 +1,426 @@
+use anyhow::{anyhow, bail, Context, Result};
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::fmt::Write;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::PathBuf;
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+
+use probe_code::console;
+use probe_code::extract::{format_extraction_results, perform_extract, ExtractOptions};
+use probe_code::query::{format_query_results, perform_query, QueryOptions};
+use probe_code::search::query::create_query_plan;
+use probe_code::search::{format_search_results, perform_probe, SearchOptions};
+
+/// How much longer than its timeout to wait for a search, which ends itself when
+/// the timeout is reached
+const TIMEOUT_GRACE: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Tool {
+    Search,
+    Extract,
+    Query,
+}
+
+impl Tool {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "search" => Some(Tool::Search),
+            "extract" => Some(Tool::Extract),
+            "query" => Some(Tool::Query),
+            _ => None,
+        }
+    }
+}
+
+/// Descriptions and input schemas of the tools, for tools/list
+pub fn definitions() -> Value {
+    let format = json!({
+        "type": "string",
+        "enum": ["markdown", "plain", "json", "xml", "color"],
+        "description": "Output format (default: color, printed without colors)",
+    });
+    let timeout = json!({
+        "type": "number",
+        "description": "Timeout in seconds (default: the --timeout of probe mcp)",
+    });
+    let no_gitignore = json!({
+        "type": "boolean",
+        "description": "Don't respect .gitignore files (default: PROBE_NO_GITIGNORE=1)",
+    });
+    json!([
+        {
+            "name": "search",
+            "description": "Search code in the repository with an Elasticsearch-like query, \
+                returning whole functions, classes and other code blocks ranked by relevance. \
+                Use this tool first for any code-related question.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "path": { "type": "string", "description": "Absolute path of the directory to search in" },
+                    "query": {
+                        "type": "string",
+                        "description": "Search query. Supports AND, OR, NOT, grouping with parentheses \
+                            and quotes for exact matches, e.g. \"(auth OR login) AND token\"",
+                    },
+                    "filesOnly": { "type": "boolean", "description": "Only list the matching files" },
+                    "ignore": {
+                        "type": "array",
+                        "items": { "type": "string" },
+                        "description": "Patterns to ignore, in addition to .gitignore",
+                    },
+                    "excludeFilenames": { "type": "boolean", "description": "Don't match file names" },
+                    "exact": { "type": "boolean", "description": "Match terms exactly, without stemming or splitting" },
+                    "language": { "type": "string", "description": "Only search files of this language" },
+                    "maxResults": { "type": "number", "description": "Maximum number of results" },
+                    "maxTokens": { "type": "number", "description": "Maximum number of tokens to return" },
+                    "allowTests": { "type": "boolean", "description": "Include test files and test code" },
+                    "session": {
+                        "type": "string",
+                        "description": "Session ID to skip results returned before. Use \"new\" for a new \
+                            session and reuse the ID it returns for follow-up searches",
+                    },
+                    "format": format,
+                    "timeout": timeout,
+                    "noGitignore": no_gitignore,
+                },
+                "required": ["path", "query"],
+            },
+        },
+        {
+            "name": "extract",
+            "description": "Extract code from files: the block around a line, a symbol, a range of lines \
+                or the whole file.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "path": { "type": "string", "description": "Directory that relative file paths are resolved against" },
+                    "files": {
+                        "type": "array",
+                        "items": { "type": "string" },
+                        "description": "Files to extract from, e.g. /project/src/main.rs, \
+                            /project/src/main.rs:42, /project/src/main.rs:10-20 or /project/src/main.rs#main",
+                    },
+                    "contextLines": {
+                        "type": "number",
+                        "description": "Lines of context around a line that isn't in any code block",
+                    },
+                    "allowTests": { "type": "boolean", "description": "Include test files and test code" },
+                    "format": format,
+                    "timeout": timeout,
+                    "noGitignore": no_gitignore,
+                },
+                "required": ["files"],
+            },
+        },
+        {
+            "name": "query",
+            "description": "Find code by its structure with an ast-grep pattern, such as all functions \
+                taking a certain argument or all calls of a method.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "path": { "type": "string", "description": "Absolute path of the directory to search in" },
+                    "pattern": {
+                        "type": "string",
+                        "description": "ast-grep pattern, e.g. \"fn $NAME($$$PARAMS) $$$BODY\" for Rust functions",
+                    },
+                    "language": {
+                        "type": "string",
+                        "description": "Language of the pattern: rust, javascript, typescript, python, go, c, \
+                            cpp, java, ruby, php, swift, csharp, kotlin or scala (default: inferred from the files)",
+                    },
+                    "ignore": {
+                        "type": "array",
+                        "items": { "type": "string" },
+                        "description": "Patterns to ignore, in addition to .gitignore",
+                    },
+                    "maxResults": { "type": "number", "description": "Maximum number of results" },
+                    "allowTests": { "type": "boolean", "description": "Include test files and test code" },
+                    "format": format,
+                    "timeout": timeout,
+                    "noGitignore": no_gitignore,
+                },
+                "required": ["path", "pattern"],
+            },
+        },
+    ])
+}
+
+/// Arguments of a tool call. Fields other than the required ones are optional.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SearchArgs {
+    path: PathBuf,
+    query: String,
+    #[serde(default)]
+    files_only: bool,
+    #[serde(default)]
+    ignore: Vec<String>,
+    #[serde(default)]
+    exclude_filenames: bool,
+    #[serde(default)]
+    exact: bool,
+    language: Option<String>,
+    max_results: Option<usize>,
+    max_tokens: Option<usize>,
+    #[serde(default)]
+    allow_tests: bool,
+    session: Option<String>,
+    format: Option<String>,
+    timeout: Option<u64>,
+    no_gitignore: Option<bool>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ExtractArgs {
+    path: Option<PathBuf>,
+    files: Vec<String>,
+    #[serde(default)]
+    context_lines: usize,
+    #[serde(default)]
+    allow_tests: bool,
+    format: Option<String>,
+    timeout: Option<u64>,
+    no_gitignore: Option<bool>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct QueryArgs {
+    path: PathBuf,
+    pattern: String,
+    language: Option<String>,
+    #[serde(default)]
+    ignore: Vec<String>,
+    max_results: Option<usize>,
+    #[serde(default)]
+    allow_tests: bool,
+    format: Option<String>,
+    timeout: Option<u64>,
+    no_gitignore: Option<bool>,
+}
+
+enum Call {
+    Search(SearchArgs),
+    Extract(ExtractArgs),
+    Query(QueryArgs),
+}
+
+impl Call {
+    fn parse(tool: Tool, arguments: Value) -> Result<Self> {
+        Ok(match tool {
+            Tool::Search => Call::Search(parse_arguments(arguments, "search")?),
+            Tool::Extract => Call::Extract(parse_arguments(arguments, "extract")?),
+            Tool::Query => Call::Query(parse_arguments(arguments, "query")?),
+        })
+    }
+
+    fn timeout(&self) -> Option<u64> {
+        match self {
+            Call::Search(args) => args.timeout,
+            Call::Extract(args) => args.timeout,
+            Call::Query(args) => args.timeout,
+        }
+    }
+
+    /// Run the call, which a search ends after `timeout` seconds, and return its output
+    fn run(self, timeout: u64) -> Result<String> {
+        match self {
+            Call::Search(args) => search(args, timeout),
+            Call::Extract(args) => extract(args),
+            Call::Query(args) => query(args),
+        }
+    }
+}
+
+/// Run a tool and return its output. A search ends when the timeout is reached.
+/// Extract and query can't be stopped: one that times out finishes in the
+/// background.
+pub fn call(tool: Tool, arguments: Value, default_timeout: Duration) -> Result<String> {
+    let call = Call::parse(tool, arguments)?;
+    let timeout = call
+        .timeout()
+        .map(Duration::from_secs)
+        .unwrap_or(default_timeout);
+
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        let seconds = timeout.as_secs_f64().ceil() as u64;
+        let result = console::silenced(|| {
+            panic::catch_unwind(AssertUnwindSafe(|| call.run(seconds)))
+                .unwrap_or_else(|_| Err(anyhow!("probe panicked, see its stderr for details")))
+        });
+        let _ = sender.send(result);
+    });
+
+    match receiver.recv_timeout(timeout + TIMEOUT_GRACE) {
+        Ok(result) => result,
+        Err(_) => bail!("Timed out after {} seconds", timeout.as_secs()),
+    }
+}
+
+fn parse_arguments<T: DeserializeOwned>(arguments: Value, tool: &str) -> Result<T> {
+    serde_json::from_value(arguments).with_context(|| format!("Invalid arguments for {tool}"))
+}
+
+/// `noGitignore`, or the PROBE_NO_GITIGNORE setting the CLI uses when it isn't given
+fn no_gitignore(no_gitignore: Option<bool>) -> bool {
+    no_gitignore.unwrap_or_else(|| std::env::var("PROBE_NO_GITIGNORE").unwrap_or_default() == "1")
+}
+
+fn search(args: SearchArgs, timeout: u64) -> Result<String> {
+    let format = args.format.as_deref().unwrap_or("color");
+    let queries = vec![args.query];
+
+    let options = SearchOptions {
+        path: &args.path,
+        queries: &queries,
+        files_only: args.files_only,
+        custom_ignores: &args.ignore,
+        exclude_filenames: args.exclude_filenames,
+        reranker: "bm25",
+        frequency_search: true,
+        exact: args.exact,
+        fuzzy: false,
+        explain: false,
+        language: args.language.as_deref(),
+        max_results: args.max_results,
+        max_bytes: None,
+        max_tokens: args.max_tokens,
+        allow_tests: args.allow_tests,
+        no_merge: false,
+        merge_threshold: None,
+        dry_run: false,
+        session: args.session.as_deref(),
+        timeout,
+        question: None,
+        no_gitignore: no_gitignore(args.no_gitignore),
+        file_filters: Default::default(),
+        source: None,
+    };
+    let limited_results = perform_probe(&options)?;
+
+    let structured = format == "json" || format == "xml";
+    if limited_results.results.is_empty() && !structured {
+        return Ok("No results found.\n".to_string());
+    }
+    let query_plan = create_query_plan(&queries[0], false).ok();
+    let mut output =
+        format_search_results(&limited_results.results, false, format, query_plan.as_ref())?;
+
+    let skipped = limited_results.skipped_files.len()
+        + limited_results.files_skipped_early_termination.unwrap_or(0);
+    if skipped > 0 && !structured {
+        writeln!(output)?;
+        writeln!(output, "Skipped files due to limits: {skipped}")?;
+        writeln!(
+            output,
+            "To get more results, repeat the search with the same session ID"
+        )?;
+    }
+    Ok(output)
+}
+
+fn extract(args: ExtractArgs) -> Result<String> {
+    if args.files.is_empty() {
+        bail!("No files to extract");
+    }
+    let files = match &args.path {
+        Some(path) => args
+            .files
+            .iter()
+            .map(|file| path.join(file).to_string_lossy().into_owned())
+            .collect(),
+        None => args.files,
+    };
+
+    let format = args.format.unwrap_or_else(|| "color".to_string());
+    let extracted = perform_extract(&ExtractOptions {
+        files,
+        custom_ignores: Vec::new(),
+        context_lines: args.context_lines,
+        format: format.clone(),
+        from_clipboard: false,
+        input_file: None,
+        to_clipboard: false,
+        dry_run: false,
+        diff: false,
+        allow_tests: args.allow_tests,
+        keep_input: false,
+        prompt: None,
+        instructions: None,
+        no_gitignore: no_gitignore(args.no_gitignore),
+        file_filters: Default::default(),
+        source: None,
+    })?;
+
+    let mut output = format_extraction_results(&extracted.results, &format, None, None, None)?;
+    if !extracted.errors.is_empty() && format != "json" && format != "xml" {
+        writeln!(output)?;
+        for error in &extracted.errors {
+            writeln!(output, "{error}")?;
+        }
+    }
+    Ok(output)
+}
+
+fn query(args: QueryArgs) -> Result<String> {
+    let format = args.format.as_deref().unwrap_or("color");
+
+    let matches = perform_query(&QueryOptions {
+        path: &args.path,
+        pattern: &args.pattern,
+        language: args.language.as_deref(),
+        ignore: &args.ignore,
+        allow_tests: args.allow_tests,
+        max_results: args.max_results,
+        format,
+        no_gitignore: no_gitignore(args.no_gitignore),
+        file_filters: Default::default(),
+        source: None,
+    })?;
+
+    if matches.is_empty() && format != "json" && format != "xml" {
+        return Ok("No results found.\n".to_string());
+    }
+    format_query_results(&matches, format)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_calls_return_their_output() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let file = dir.path().join("checkpoint.rs");
+        fs::write(&file, "fn write_checkpoint() {}\n").unwrap();
+        let timeout = Duration::from_secs(30);
+
+        let arguments = json!({ "files": [file], "format": "plain" });
+        let text = call(Tool::Extract, arguments, timeout).unwrap();
+        assert!(text.contains("fn write_checkpoint() {}"), "{text}");
+
+        let arguments = json!({
+            "path": dir.path(),
+            "pattern": "fn $NAME() {}",
+            "language": "rust",
+            "format": "plain",
+        });
+        let text = call(Tool::Query, arguments, timeout).unwrap();
+        assert!(text.contains("checkpoint.rs:1:1"), "{text}");
+
+        // Nothing else is mixed into structured output
+        let arguments = json!({ "path": dir.path(), "query": "checkpoint", "format": "json" });
+        let text = call(Tool::Search, arguments, timeout).unwrap();
+        let output: Value = serde_json::from_str(&text).unwrap();
+        assert_eq!(output["results"].as_array().unwrap().len(), 1);
+    }
+}
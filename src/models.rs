@@ -1,3 +1,5 @@
+use probe_code::source::CellRange;
+
 // Structure to hold both limited search results and skipped files
 #[derive(Debug)]
 pub struct LimitedSearchResults {
@@ -68,6 +70,8 @@ pub struct SearchResult {
     /// Tokenized version of the code block with filename prepended
     #[allow(dead_code)]
     pub tokenized_content: Option<Vec<String>>,
+    /// Where the lines are in the cells of a notebook
+    pub cells: Option<CellRange>,
 }
 
 // Structure to hold node information for merging
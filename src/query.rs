@@ -4,9 +4,13 @@ use ast_grep_language::SupportLang;
 use colored::*;
 use ignore::WalkBuilder;
 use probe_code::path_resolver::resolve_path;
+use probe_code::search::FileFilters;
+use probe_code::source::{Source, Sources};
 use rayon::prelude::*; // Added import
-use std::fs;
+use std::fmt::Write;
+use std::panic::AssertUnwindSafe;
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
 use std::time::Instant;
 
 /// Represents a match found by ast-grep
@@ -30,6 +34,10 @@ pub struct QueryOptions<'a> {
     #[allow(dead_code)]
     pub format: &'a str,
     pub no_gitignore: bool,
+    /// Path, extension, language and git change filters applied to the searched files
+    pub file_filters: FileFilters,
+    /// Where the files it holds are read from, instead of the disk
+    pub source: Option<Arc<dyn Source>>,
 }
 
 /// Convert a language string to the corresponding SupportLang
@@ -47,6 +55,8 @@ fn get_language(lang: &str) -> Option<SupportLang> {
         "php" => Some(SupportLang::Php),
         "swift" => Some(SupportLang::Swift),
         "csharp" => Some(SupportLang::CSharp),
+        "kotlin" => Some(SupportLang::Kotlin),
+        "scala" => Some(SupportLang::Scala),
         _ => None,
     }
 }
@@ -57,7 +67,7 @@ fn get_file_extension(lang: &str) -> Vec<&str> {
         "rust" => vec![".rs"],
         "javascript" => vec![".js", ".jsx", ".mjs"],
         "typescript" => vec![".ts", ".tsx"],
-        "python" => vec![".py"],
+        "python" => vec![".py", ".ipynb"],
         "go" => vec![".go"],
         "c" => vec![".c", ".h"],
         "cpp" => vec![".cpp", ".hpp", ".cc", ".hh", ".cxx", ".hxx"],
@@ -66,6 +76,8 @@ fn get_file_extension(lang: &str) -> Vec<&str> {
         "php" => vec![".php"],
         "swift" => vec![".swift"],
         "csharp" => vec![".cs"],
+        "kotlin" => vec![".kt", ".kts"],
+        "scala" => vec![".scala", ".sc"],
         _ => vec![],
     }
 }
@@ -97,7 +109,11 @@ fn should_ignore_file(file_path: &Path, options: &QueryOptions) -> bool {
 }
 
 /// Perform an ast-grep query on a single file
-fn query_file(file_path: &Path, options: &QueryOptions) -> Result<Vec<AstMatch>> {
+fn query_file(
+    sources: &Sources,
+    file_path: &Path,
+    options: &QueryOptions,
+) -> Result<Vec<AstMatch>> {
     // Get the file extension
     let file_ext = file_path.extension().and_then(|e| e.to_str()).unwrap_or("");
 
@@ -114,7 +130,8 @@ fn query_file(file_path: &Path, options: &QueryOptions) -> Result<Vec<AstMatch>>
     }
 
     // Read the file content
-    let content = fs::read_to_string(file_path)
+    let content = sources
+        .read_to_string(file_path)
         .with_context(|| format!("Failed to read file: {}", file_path.display()))?;
 
     // Get the language for ast-grep
@@ -130,7 +147,7 @@ fn query_file(file_path: &Path, options: &QueryOptions) -> Result<Vec<AstMatch>>
             "rs" => Some(SupportLang::Rust),
             "js" | "jsx" | "mjs" => Some(SupportLang::JavaScript),
             "ts" | "tsx" => Some(SupportLang::TypeScript),
-            "py" => Some(SupportLang::Python),
+            "py" | "ipynb" => Some(SupportLang::Python),
             "go" => Some(SupportLang::Go),
             "c" | "h" => Some(SupportLang::C),
             "cpp" | "hpp" | "cc" | "hh" | "cxx" | "hxx" => Some(SupportLang::Cpp),
@@ -139,6 +156,8 @@ fn query_file(file_path: &Path, options: &QueryOptions) -> Result<Vec<AstMatch>>
             "php" => Some(SupportLang::Php),
             "swift" => Some(SupportLang::Swift),
             "cs" => Some(SupportLang::CSharp),
+            "kt" | "kts" => Some(SupportLang::Kotlin),
+            "scala" | "sc" => Some(SupportLang::Scala),
             _ => None, // Unsupported extension
         };
 
@@ -152,14 +171,14 @@ fn query_file(file_path: &Path, options: &QueryOptions) -> Result<Vec<AstMatch>>
     let grep = AstGrep::new(&content, lang);
 
     // Create the pattern and find all matches
-    let matches = match std::panic::catch_unwind(|| {
+    let matches = match std::panic::catch_unwind(AssertUnwindSafe(|| {
         grep.root().find_all(options.pattern).collect::<Vec<_>>()
-    }) {
+    })) {
         Ok(matches) => matches,
         Err(_) => {
             // Only print error if language is explicitly specified
             // This suppresses errors during auto-detection
-            if options.language.is_some() {
+            if options.language.is_some() && !probe_code::console::is_silenced() {
                 eprintln!(
                     "Error parsing pattern: '{}' is not a valid ast-grep pattern",
                     options.pattern
@@ -216,6 +235,9 @@ fn query_file(file_path: &Path, options: &QueryOptions) -> Result<Vec<AstMatch>>
 }
 
 pub fn perform_query(options: &QueryOptions) -> Result<Vec<AstMatch>> {
+    // The files of an archive, like `deps.zip`, are read from the archive
+    let sources = Sources::new(options.source.clone()).with_archives([options.path])?;
+
     // Suppress panic output if language is not specified
     let suppress_output = options.language.is_none();
 
@@ -234,7 +256,7 @@ pub fn perform_query(options: &QueryOptions) -> Result<Vec<AstMatch>> {
     let resolved_path = if let Some(path_str) = options.path.to_str() {
         match resolve_path(path_str) {
             Ok(resolved_path) => {
-                if std::env::var("DEBUG").unwrap_or_default() == "1" {
+                if probe_code::console::debug_mode() {
                     println!(
                         "DEBUG: Resolved path '{}' to '{}'",
                         path_str,
@@ -244,7 +266,7 @@ pub fn perform_query(options: &QueryOptions) -> Result<Vec<AstMatch>> {
                 resolved_path
             }
             Err(err) => {
-                if std::env::var("DEBUG").unwrap_or_default() == "1" {
+                if probe_code::console::debug_mode() {
                     println!("DEBUG: Failed to resolve path '{path_str}': {err}");
                 }
                 // Fall back to the original path
@@ -270,19 +292,28 @@ pub fn perform_query(options: &QueryOptions) -> Result<Vec<AstMatch>> {
         builder.git_exclude(false);
     }
 
-    let file_paths: Vec<PathBuf> = builder
-        .build()
-        .filter_map(|entry| entry.ok())
-        .filter(|entry| entry.file_type().is_some_and(|ft| ft.is_file()))
-        .filter(|entry| !should_ignore_file(entry.path(), options))
-        .map(|entry| entry.path().to_path_buf())
+    // Files not on the disk are listed by their source instead of walking it
+    let file_paths: Vec<PathBuf> = if sources.on_disk(&resolved_path) {
+        builder
+            .build()
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.file_type().is_some_and(|ft| ft.is_file()))
+            .map(|entry| entry.into_path())
+            .collect()
+    } else {
+        sources.list_files(&resolved_path)
+    };
+    let file_paths: Vec<PathBuf> = file_paths
+        .into_iter()
+        .filter(|path| !should_ignore_file(path, options))
+        .filter(|path| options.file_filters.matches(&sources, &resolved_path, path))
         .collect();
 
     // Process files in parallel
     let all_matches: Vec<AstMatch> = file_paths
         .par_iter()
         .flat_map(|path| {
-            std::panic::catch_unwind(|| query_file(path, options))
+            std::panic::catch_unwind(AssertUnwindSafe(|| query_file(&sources, path, options)))
                 .unwrap_or_else(|_| {
                     // Panic was caught, return empty results
                     Ok(vec![])
@@ -317,12 +348,55 @@ fn escape_xml(s: &str) -> String {
         .replace("'", "&apos;")
 }
 
+/// Query results as the JSON document printed by `--format json`
+pub fn query_results_json(matches: &[AstMatch]) -> serde_json::Value {
+    // BATCH TOKENIZATION WITH DEDUPLICATION OPTIMIZATION for query JSON output:
+    // Process all matched text in batch to leverage content deduplication
+    use probe_code::search::search_tokens::sum_tokens_with_deduplication;
+    let matched_texts: Vec<&str> = matches.iter().map(|m| m.matched_text.as_str()).collect();
+    let total_tokens = sum_tokens_with_deduplication(&matched_texts);
+
+    // Create standardized results
+    let json_matches_standardized: Vec<_> = matches
+        .iter()
+        .map(|m| {
+            serde_json::json!({
+                "file": m.file_path.to_string_lossy(),
+                "lines": [m.line_start, m.line_end],
+                "node_type": "match",
+                "content": m.matched_text,
+                "column_start": m.column_start,
+                "column_end": m.column_end
+            })
+        })
+        .collect();
+
+    // Create the wrapper object
+    serde_json::json!({
+        "results": json_matches_standardized,
+        "summary": {
+            "count": matches.len(),
+            "total_bytes": matches.iter().map(|m| m.matched_text.len()).sum::<usize>(),
+            "total_tokens": total_tokens
+        },
+        "version": probe_code::version::get_version()
+    })
+}
+
 /// Format and print the query results
 pub fn format_and_print_query_results(matches: &[AstMatch], format: &str) -> Result<()> {
+    print!("{}", format_query_results(matches, format)?);
+    Ok(())
+}
+
+/// Format the query results, as `format_and_print_query_results` prints them
+pub fn format_query_results(matches: &[AstMatch], format: &str) -> Result<String> {
+    let mut output = String::new();
     match format {
         "color" | "terminal" => {
             for m in matches {
-                println!(
+                writeln!(
+                    output,
                     "{}",
                     format!(
                         "{}:{}:{}",
@@ -331,31 +405,33 @@ pub fn format_and_print_query_results(matches: &[AstMatch], format: &str) -> Res
                         m.column_start
                     )
                     .cyan()
-                );
-                println!("{}", m.matched_text.trim());
-                println!();
+                )?;
+                writeln!(output, "{}", m.matched_text.trim())?;
+                writeln!(output)?;
             }
         }
         "plain" => {
             for m in matches {
-                println!(
+                writeln!(
+                    output,
                     "{}:{}:{}",
                     m.file_path.display(),
                     m.line_start,
                     m.column_start
-                );
-                println!("{}", m.matched_text.trim());
-                println!();
+                )?;
+                writeln!(output, "{}", m.matched_text.trim())?;
+                writeln!(output)?;
             }
         }
         "markdown" => {
             for m in matches {
-                println!(
+                writeln!(
+                    output,
                     "**{}:{}:{}**",
                     m.file_path.display(),
                     m.line_start,
                     m.column_start
-                );
+                )?;
 
                 // Determine language for code block
                 let lang = m
@@ -364,73 +440,51 @@ pub fn format_and_print_query_results(matches: &[AstMatch], format: &str) -> Res
                     .and_then(|e| e.to_str())
                     .unwrap_or("");
 
-                println!("```{lang}");
-                println!("{}", m.matched_text.trim());
-                println!("```");
-                println!();
+                writeln!(output, "```{lang}")?;
+                writeln!(output, "{}", m.matched_text.trim())?;
+                writeln!(output, "```")?;
+                writeln!(output)?;
             }
         }
         "json" => {
-            // BATCH TOKENIZATION WITH DEDUPLICATION OPTIMIZATION for query JSON output:
-            // Process all matched text in batch to leverage content deduplication
-            use probe_code::search::search_tokens::sum_tokens_with_deduplication;
-            let matched_texts: Vec<&str> =
-                matches.iter().map(|m| m.matched_text.as_str()).collect();
-            let total_tokens = sum_tokens_with_deduplication(&matched_texts);
-
-            // Create standardized results
-            let json_matches_standardized: Vec<_> = matches
-                .iter()
-                .map(|m| {
-                    serde_json::json!({
-                        "file": m.file_path.to_string_lossy(),
-                        "lines": [m.line_start, m.line_end],
-                        "node_type": "match",
-                        "content": m.matched_text,
-                        "column_start": m.column_start,
-                        "column_end": m.column_end
-                    })
-                })
-                .collect();
-
-            // Create the wrapper object
-            let wrapper = serde_json::json!({
-                "results": json_matches_standardized,
-                "summary": {
-                    "count": matches.len(),
-                    "total_bytes": matches.iter().map(|m| m.matched_text.len()).sum::<usize>(),
-                    "total_tokens": total_tokens
-                },
-                "version": probe_code::version::get_version()
-            });
-
-            println!("{}", serde_json::to_string_pretty(&wrapper)?);
+            let wrapper = query_results_json(matches);
+            writeln!(output, "{}", serde_json::to_string_pretty(&wrapper)?)?;
         }
         "xml" => {
-            println!("<?xml version=\"1.0\" encoding=\"UTF-8\"?>");
-            println!("<probe_results>");
+            writeln!(output, "<?xml version=\"1.0\" encoding=\"UTF-8\"?>")?;
+            writeln!(output, "<probe_results>")?;
 
             for m in matches {
-                println!("  <result>");
-                println!(
+                writeln!(output, "  <result>")?;
+                writeln!(
+                    output,
                     "    <file>{}</file>",
                     escape_xml(&m.file_path.to_string_lossy())
-                );
-                println!("    <lines>{}-{}</lines>", m.line_start, m.line_end);
-                println!("    <node_type>match</node_type>");
-                println!("    <column_start>{}</column_start>", m.column_start);
-                println!("    <column_end>{}</column_end>", m.column_end);
-                println!("    <code><![CDATA[{}]]></code>", m.matched_text.trim());
-                println!("  </result>");
+                )?;
+                writeln!(output, "    <lines>{}-{}</lines>", m.line_start, m.line_end)?;
+                writeln!(output, "    <node_type>match</node_type>")?;
+                writeln!(
+                    output,
+                    "    <column_start>{}</column_start>",
+                    m.column_start
+                )?;
+                writeln!(output, "    <column_end>{}</column_end>", m.column_end)?;
+                writeln!(
+                    output,
+                    "    <code><![CDATA[{}]]></code>",
+                    m.matched_text.trim()
+                )?;
+                writeln!(output, "  </result>")?;
             }
 
             // Add summary section
-            println!("  <summary>");
-            println!("    <count>{}</count>", matches.len());
-            println!(
+            writeln!(output, "  <summary>")?;
+            writeln!(output, "    <count>{}</count>", matches.len())?;
+            writeln!(
+                output,
                 "    <total_bytes>{}",
                 matches.iter().map(|m| m.matched_text.len()).sum::<usize>()
-            );
+            )?;
 
             // BATCH TOKENIZATION WITH DEDUPLICATION OPTIMIZATION for query XML output:
             // Process all matched text in batch to leverage content deduplication
@@ -439,23 +493,24 @@ pub fn format_and_print_query_results(matches: &[AstMatch], format: &str) -> Res
                 matches.iter().map(|m| m.matched_text.as_str()).collect();
             let total_tokens = sum_tokens_with_deduplication(&matched_texts);
 
-            println!("    <total_tokens>{total_tokens}");
-            println!("  </summary>");
+            writeln!(output, "    <total_tokens>{total_tokens}")?;
+            writeln!(output, "  </summary>")?;
 
-            println!(
+            writeln!(
+                output,
                 "  <version>{}</version>",
                 probe_code::version::get_version()
-            );
+            )?;
 
-            println!("</probe_results>");
+            writeln!(output, "</probe_results>")?;
         }
         _ => {
             // Default to color format
-            format_and_print_query_results(matches, "color")?;
+            return format_query_results(matches, "color");
         }
     }
 
-    Ok(())
+    Ok(output)
 }
 
 /// Handle the query command
@@ -469,6 +524,8 @@ pub fn handle_query(
     max_results: Option<usize>,
     format: &str,
     no_gitignore: bool,
+    file_filters: FileFilters,
+    source: Option<Arc<dyn Source>>,
 ) -> Result<()> {
     // Print version at the start for text-based formats
     if format != "json" && format != "xml" {
@@ -519,6 +576,8 @@ pub fn handle_query(
         max_results,
         format,
         no_gitignore,
+        file_filters,
+        source,
     };
 
     let matches = perform_query(&options)?;
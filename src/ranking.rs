@@ -1,6 +1,6 @@
 use crate::simd_ranking::{SimdBm25Params, SparseDocumentMatrix};
 use ahash::{AHashMap, AHashSet};
-use probe_code::search::elastic_query::Expr;
+use probe_code::search::elastic_query::{min_proximity_gap, Expr};
 use probe_code::search::tokenization;
 use rayon::prelude::*;
 use rust_stemmers::{Algorithm, Stemmer};
@@ -13,6 +13,14 @@ type HashSet<T> = AHashSet<T>;
 /// Maps unique query tokens (Strings) to unique u8 indices
 pub type QueryTokenMap = HashMap<String, u8>;
 
+// BM25 parameters
+// The standard defaults established in academic literature are k1=1.2 and b=0.75:
+// k1 controls term frequency saturation (higher values give more weight to term frequency)
+// b controls document length normalization (higher values give more penalty to longer documents)
+// See: Robertson, S. E., & Zaragoza, H. (2009). The Probabilistic Relevance Framework: BM25 and Beyond
+const BM25_K1: f64 = 1.5; // EXPERIMENT: Slightly increased from 1.2 for balanced term frequency weight
+const BM25_B: f64 = 0.5; // EXPERIMENT: Moderately reduced from 0.75 for balanced length normalization
+
 /// Represents the result of term frequency and document frequency computation
 pub struct TfDfResult {
     /// Term frequencies for each document, using u8 index for query tokens
@@ -87,6 +95,8 @@ pub struct PrecomputedBm25Params<'a> {
     pub idfs: &'a HashMap<String, f64>,
     /// Map from query term string to u8 index
     pub query_token_map: &'a QueryTokenMap,
+    /// Document token positions of query terms using u8 indices
+    pub term_positions: &'a HashMap<u8, Vec<usize>>,
     /// BM25 k1 parameter
     pub k1: f64,
     /// BM25 b parameter
@@ -106,18 +116,53 @@ pub fn extract_query_terms(expr: &Expr) -> HashSet<String> {
             terms.extend(extract_query_terms(left));
             terms.extend(extract_query_terms(right));
         }
+        Near(inner, _) | Required(inner) | Excluded(inner) | Boost(inner, _) => {
+            terms.extend(extract_query_terms(inner));
+        }
     }
 
     terms
 }
 
-/// Precomputes IDF values for a set of terms
+/// Collects the boost of every boosted keyword in a query expression, e.g.
+/// `retry^3` => `{"retry": 3.0}`. Nested boosts multiply; a keyword that is
+/// boosted in several places keeps the largest boost.
+pub fn extract_term_boosts(expr: &Expr) -> HashMap<String, f64> {
+    fn collect(expr: &Expr, boost: f64, boosts: &mut HashMap<String, f64>) {
+        use Expr::*;
+        match expr {
+            Term { keywords, .. } => {
+                if boost != 1.0 {
+                    for keyword in keywords {
+                        let entry = boosts.entry(keyword.clone()).or_insert(boost);
+                        *entry = entry.max(boost);
+                    }
+                }
+            }
+            And(left, right) | Or(left, right) => {
+                collect(left, boost, boosts);
+                collect(right, boost, boosts);
+            }
+            Near(inner, _) | Required(inner) | Excluded(inner) => collect(inner, boost, boosts),
+            Boost(inner, factor) => collect(inner, boost * factor, boosts),
+        }
+    }
+
+    let mut boosts = HashMap::new();
+    collect(expr, 1.0, &mut boosts);
+    boosts
+}
+
+/// Precomputes IDF values for a set of terms.
+/// The IDF of a boosted term (see `extract_term_boosts`) is multiplied by its boost,
+/// so every BM25 scorer built on these values weights the term accordingly.
 pub fn precompute_idfs(
     terms: &HashSet<String>,
     dfs: &HashMap<String, usize>,
     n_docs: usize,
+    boosts: &HashMap<String, f64>,
 ) -> HashMap<String, f64> {
-    let debug_mode = std::env::var("DEBUG").unwrap_or_default() == "1";
+    let debug_mode = probe_code::console::debug_mode();
 
     if debug_mode {
         println!(
@@ -134,7 +179,7 @@ pub fn precompute_idfs(
                 let numerator = (n_docs as f64 - df as f64) + 0.5;
                 let denominator = df as f64 + 0.5;
                 let idf = (1.0 + (numerator / denominator)).ln();
-                Some((term.as_str(), idf))
+                Some((term.as_str(), idf * boosts.get(term).unwrap_or(&1.0)))
             } else {
                 None
             }
@@ -270,7 +315,122 @@ pub fn score_expr_bm25_optimized(expr: &Expr, params: &PrecomputedBm25Params) ->
                 (Some(ls), Some(rs)) => Some(ls + rs),
             }
         }
+        Near(inner, distance) => {
+            let Term {
+                keywords,
+                required,
+                excluded,
+                ..
+            } = inner.as_ref()
+            else {
+                return score_expr_bm25_optimized(inner, params);
+            };
+
+            let gap = proximity_gap(keywords, params).filter(|gap| gap <= distance);
+            let score = score_term_bm25_optimized(keywords, params);
+
+            match gap {
+                // must_not => doc out only if the keywords occur close together
+                Some(_) if *excluded => None,
+                None if *excluded => Some(0.0),
+                Some(gap) => Some(score * proximity_factor(gap)),
+                None if *required => None,
+                None => Some(0.0),
+            }
+        }
+        Required(inner) => {
+            // must => doc out unless the group scores
+            score_expr_bm25_optimized(inner, params).filter(|score| *score > 0.0)
+        }
+        Excluded(inner) => {
+            // must_not => doc out if the group scores
+            match score_expr_bm25_optimized(inner, params) {
+                Some(score) if score > 0.0 => None,
+                _ => Some(0.0),
+            }
+        }
+        // The boost is already part of the precomputed IDF values
+        Boost(inner, _) => score_expr_bm25_optimized(inner, params),
+    }
+}
+
+/// Token positions of query terms in each document, keyed by u8 query token index.
+/// Only proximity queries need them; other queries get empty maps.
+fn compute_term_positions(
+    expr: &Expr,
+    documents: &[&str],
+    query_token_map: &QueryTokenMap,
+) -> Vec<HashMap<u8, Vec<usize>>> {
+    if !expr.has_proximity() {
+        return vec![HashMap::new(); documents.len()];
     }
+
+    documents
+        .par_iter()
+        .map(|doc| {
+            let mut positions: HashMap<u8, Vec<usize>> = HashMap::new();
+            for (pos, token) in tokenization::tokenize_sequence(doc).iter().enumerate() {
+                if let Some(&token_index) = query_token_map.get(token) {
+                    positions.entry(token_index).or_default().push(pos);
+                }
+            }
+            positions
+        })
+        .collect()
+}
+
+/// Reward for closer co-occurrence of a proximity group's keywords: adjacent
+/// keywords double the score
+fn proximity_factor(gap: usize) -> f64 {
+    1.0 + 1.0 / (1.0 + gap as f64)
+}
+
+/// Factor that each proximity group applies to the scores of its keywords in a
+/// document, keyed by u8 query token index: the closeness reward, or 0 when the
+/// keywords aren't close enough to score. Other keywords are left out.
+fn proximity_factors(expr: &Expr, params: &PrecomputedBm25Params, factors: &mut HashMap<u8, f64>) {
+    use Expr::*;
+    match expr {
+        Term { .. } => {}
+        And(left, right) | Or(left, right) => {
+            proximity_factors(left, params, factors);
+            proximity_factors(right, params, factors);
+        }
+        Near(inner, distance) => {
+            let Term {
+                keywords, excluded, ..
+            } = inner.as_ref()
+            else {
+                return proximity_factors(inner, params, factors);
+            };
+            let factor = match proximity_gap(keywords, params).filter(|gap| gap <= distance) {
+                Some(gap) if !*excluded => proximity_factor(gap),
+                _ => 0.0,
+            };
+            for kw in keywords {
+                if let Some(&token_index) = params.query_token_map.get(kw) {
+                    factors.insert(token_index, factor);
+                }
+            }
+        }
+        Required(inner) | Excluded(inner) | Boost(inner, _) => {
+            proximity_factors(inner, params, factors)
+        }
+    }
+}
+
+/// Smallest number of tokens separating all keywords of a proximity group in
+/// the document, or `None` if some keyword doesn't occur.
+fn proximity_gap(keywords: &[String], params: &PrecomputedBm25Params) -> Option<usize> {
+    let mut lists: Vec<&[usize]> = Vec::with_capacity(keywords.len());
+    let mut seen: HashSet<u8> = HashSet::new();
+    for kw in keywords {
+        let &token_index = params.query_token_map.get(kw)?;
+        if seen.insert(token_index) {
+            lists.push(params.term_positions.get(&token_index)?);
+        }
+    }
+    min_proximity_gap(&lists)
 }
 
 // -------------------------------------------------------------------------
@@ -279,7 +439,7 @@ pub fn score_expr_bm25_optimized(expr: &Expr, params: &PrecomputedBm25Params) ->
 pub fn rank_documents(params: &RankingParams) -> Vec<(usize, f64)> {
     use std::cmp::Ordering;
 
-    let debug_mode = std::env::var("DEBUG").unwrap_or_default() == "1";
+    let debug_mode = probe_code::console::debug_mode();
 
     // 1) Parse the user query into an AST (Expr)
     //    If your code uses parse_query(...) from `elastic_query.rs`, do:
@@ -291,7 +451,9 @@ pub fn rank_documents(params: &RankingParams) -> Vec<(usize, f64)> {
             }
             // Instead of silently returning empty results, log a warning even in non-debug mode
             // to ensure errors are visible and can be addressed
-            eprintln!("WARNING: Query parsing failed: {e:?}. Returning empty results.");
+            if !probe_code::console::is_silenced() {
+                eprintln!("WARNING: Query parsing failed: {e:?}. Returning empty results.");
+            }
             // In a future version, consider changing the return type to Result<Vec<(usize, f64)>, QueryError>
             // to properly propagate errors to the caller
             return vec![];
@@ -308,7 +470,9 @@ pub fn rank_documents(params: &RankingParams) -> Vec<(usize, f64)> {
             if debug_mode {
                 eprintln!("DEBUG: Failed to generate query token map: {e}");
             }
-            eprintln!("WARNING: {e}");
+            if !probe_code::console::is_silenced() {
+                eprintln!("WARNING: {e}");
+            }
             return vec![];
         }
     };
@@ -321,7 +485,7 @@ pub fn rank_documents(params: &RankingParams) -> Vec<(usize, f64)> {
     }
 
     // 2) Precompute TF/DF for docs
-    let tf_df_result = if let Some(pre_tokenized) = &params.pre_tokenized {
+    let mut tf_df_result = if let Some(pre_tokenized) = &params.pre_tokenized {
         // Use pre-tokenized content if available
         if debug_mode {
             println!("DEBUG: Using pre-tokenized content for ranking");
@@ -338,13 +502,21 @@ pub fn rank_documents(params: &RankingParams) -> Vec<(usize, f64)> {
         compute_tf_df_from_tokenized(&tokenized_docs, &query_token_map)
     };
 
+    add_regex_term_frequencies(&mut tf_df_result, params.documents, &query_token_map);
+
     let n_docs = params.documents.len();
     let avgdl = compute_avgdl(&tf_df_result.document_lengths);
+    let term_positions = compute_term_positions(&parsed_expr, params.documents, &query_token_map);
 
     // Precompute IDF values
 
-    let precomputed_idfs =
-        precompute_idfs(&query_terms, &tf_df_result.document_frequencies, n_docs);
+    let term_boosts = extract_term_boosts(&parsed_expr);
+    let precomputed_idfs = precompute_idfs(
+        &query_terms,
+        &tf_df_result.document_frequencies,
+        n_docs,
+        &term_boosts,
+    );
 
     if debug_mode {
         println!(
@@ -354,12 +526,8 @@ pub fn rank_documents(params: &RankingParams) -> Vec<(usize, f64)> {
     }
 
     // 4) BM25 parameters
-    // These values are standard defaults for BM25 as established in academic literature:
-    // k1=1.2 controls term frequency saturation (higher values give more weight to term frequency)
-    // b=0.75 controls document length normalization (higher values give more penalty to longer documents)
-    // See: Robertson, S. E., & Zaragoza, H. (2009). The Probabilistic Relevance Framework: BM25 and Beyond
-    let k1 = 1.5; // EXPERIMENT: Slightly increased from 1.2 for balanced term frequency weight
-    let b = 0.5; // EXPERIMENT: Moderately reduced from 0.75 for balanced length normalization
+    let k1 = BM25_K1;
+    let b = BM25_B;
 
     if debug_mode {
         println!("DEBUG: Starting parallel document scoring for {n_docs} documents");
@@ -381,6 +549,7 @@ pub fn rank_documents(params: &RankingParams) -> Vec<(usize, f64)> {
                 avgdl,
                 idfs: &precomputed_idfs,
                 query_token_map: &query_token_map,
+                term_positions: &term_positions[i],
                 k1,
                 b,
             };
@@ -432,7 +601,7 @@ pub fn rank_documents(params: &RankingParams) -> Vec<(usize, f64)> {
 pub fn rank_documents_simd(params: &RankingParams) -> Vec<(usize, f64)> {
     use std::cmp::Ordering;
 
-    let debug_mode = std::env::var("DEBUG").unwrap_or_default() == "1";
+    let debug_mode = probe_code::console::debug_mode();
 
     if debug_mode {
         println!("DEBUG: Using SIMD-optimized ranking");
@@ -445,7 +614,9 @@ pub fn rank_documents_simd(params: &RankingParams) -> Vec<(usize, f64)> {
             if debug_mode {
                 eprintln!("DEBUG: parse_query failed: {e:?}");
             }
-            eprintln!("WARNING: Query parsing failed: {e:?}. Returning empty results.");
+            if !probe_code::console::is_silenced() {
+                eprintln!("WARNING: Query parsing failed: {e:?}. Returning empty results.");
+            }
             return vec![];
         }
     };
@@ -460,7 +631,9 @@ pub fn rank_documents_simd(params: &RankingParams) -> Vec<(usize, f64)> {
             if debug_mode {
                 eprintln!("DEBUG: Failed to generate query token map: {e}");
             }
-            eprintln!("WARNING: {e}");
+            if !probe_code::console::is_silenced() {
+                eprintln!("WARNING: {e}");
+            }
             return vec![];
         }
     };
@@ -473,7 +646,7 @@ pub fn rank_documents_simd(params: &RankingParams) -> Vec<(usize, f64)> {
     }
 
     // 3) Precompute TF/DF for docs
-    let tf_df_result = if let Some(pre_tokenized) = &params.pre_tokenized {
+    let mut tf_df_result = if let Some(pre_tokenized) = &params.pre_tokenized {
         if debug_mode {
             println!("DEBUG: Using pre-tokenized content for SIMD ranking");
         }
@@ -487,12 +660,20 @@ pub fn rank_documents_simd(params: &RankingParams) -> Vec<(usize, f64)> {
         compute_tf_df_from_tokenized(&tokenized_docs, &query_token_map)
     };
 
+    add_regex_term_frequencies(&mut tf_df_result, params.documents, &query_token_map);
+
     let n_docs = params.documents.len();
     let avgdl = compute_avgdl(&tf_df_result.document_lengths);
+    let term_positions = compute_term_positions(&parsed_expr, params.documents, &query_token_map);
 
     // 4) Precompute IDF values
-    let precomputed_idfs =
-        precompute_idfs(&query_terms, &tf_df_result.document_frequencies, n_docs);
+    let term_boosts = extract_term_boosts(&parsed_expr);
+    let precomputed_idfs = precompute_idfs(
+        &query_terms,
+        &tf_df_result.document_frequencies,
+        n_docs,
+        &term_boosts,
+    );
 
     if debug_mode {
         println!(
@@ -552,8 +733,9 @@ pub fn rank_documents_simd(params: &RankingParams) -> Vec<(usize, f64)> {
                 avgdl,
                 idfs: &precomputed_idfs,
                 query_token_map: &query_token_map,
-                k1: 1.5, // EXPERIMENT: Slightly increased from 1.2
-                b: 0.5,  // EXPERIMENT: Moderately reduced from 0.75
+                term_positions: &term_positions[i],
+                k1: BM25_K1,
+                b: BM25_B,
             };
 
             // Apply boolean query logic - this filters out documents that don't match requirements
@@ -590,7 +772,7 @@ pub fn rank_documents_simd(params: &RankingParams) -> Vec<(usize, f64)> {
 pub fn rank_documents_simd_simple(params: &RankingParams) -> Vec<(usize, f64)> {
     use std::cmp::Ordering;
 
-    let debug_mode = std::env::var("DEBUG").unwrap_or_default() == "1";
+    let debug_mode = probe_code::console::debug_mode();
 
     if debug_mode {
         println!("DEBUG: Using simple SIMD-only ranking (no boolean logic)");
@@ -628,8 +810,13 @@ pub fn rank_documents_simd_simple(params: &RankingParams) -> Vec<(usize, f64)> {
     let avgdl = compute_avgdl(&tf_df_result.document_lengths);
 
     // 3) Precompute IDF values
-    let precomputed_idfs =
-        precompute_idfs(&query_terms, &tf_df_result.document_frequencies, n_docs);
+    // Plain tokenized queries carry no boosts
+    let precomputed_idfs = precompute_idfs(
+        &query_terms,
+        &tf_df_result.document_frequencies,
+        n_docs,
+        &HashMap::new(),
+    );
 
     // 4) Create sparse document matrix for pure SIMD operations
     let sparse_matrix = SparseDocumentMatrix::from_ranking_data(
@@ -683,6 +870,114 @@ pub fn rank_documents_simd_simple(params: &RankingParams) -> Vec<(usize, f64)> {
     filtered_docs
 }
 
+/// BM25 contribution of a single query term to the score of a document,
+/// as reported by `explain_bm25_scores`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TermContribution {
+    /// Query term (stemmed keyword or regex term)
+    pub term: String,
+    /// Occurrences of the term in the document
+    pub tf: usize,
+    /// IDF of the term, including its boost
+    pub idf: f64,
+    /// BM25 contribution of the term
+    pub score: f64,
+    /// Part of `score` owed to occurrences in the document's filename
+    pub filename_score: f64,
+}
+
+/// Breaks the BM25 score of every document down into per-term contributions,
+/// using the same term statistics and positions as `rank_documents`, so the
+/// contributions of proximity keywords include their closeness reward. `filename_tokens` holds
+/// the tokens of each document's filename, to tell how much of a term's
+/// contribution comes from the filename; it may be empty. Terms that don't
+/// occur in a document are left out, the rest are sorted by contribution.
+pub fn explain_bm25_scores(
+    params: &RankingParams,
+    filename_tokens: &[Vec<String>],
+) -> Vec<Vec<TermContribution>> {
+    let Ok(parsed_expr) = crate::search::elastic_query::parse_query(params.query, false) else {
+        return vec![Vec::new(); params.documents.len()];
+    };
+    let query_terms = extract_query_terms(&parsed_expr);
+    let Ok(query_token_map) = generate_query_token_map(&query_terms) else {
+        return vec![Vec::new(); params.documents.len()];
+    };
+
+    let mut tf_df_result = if let Some(pre_tokenized) = &params.pre_tokenized {
+        compute_tf_df_from_tokenized(pre_tokenized, &query_token_map)
+    } else {
+        let tokenized_docs: Vec<Vec<String>> =
+            params.documents.iter().map(|doc| tokenize(doc)).collect();
+        compute_tf_df_from_tokenized(&tokenized_docs, &query_token_map)
+    };
+    add_regex_term_frequencies(&mut tf_df_result, params.documents, &query_token_map);
+
+    let avgdl = compute_avgdl(&tf_df_result.document_lengths);
+    let precomputed_idfs = precompute_idfs(
+        &query_terms,
+        &tf_df_result.document_frequencies,
+        params.documents.len(),
+        &extract_term_boosts(&parsed_expr),
+    );
+    let term_positions = compute_term_positions(&parsed_expr, params.documents, &query_token_map);
+
+    (0..params.documents.len())
+        .map(|i| {
+            let doc_tf = &tf_df_result.term_frequencies[i];
+            let bm25_params = PrecomputedBm25Params {
+                doc_tf,
+                doc_len: tf_df_result.document_lengths[i],
+                avgdl,
+                idfs: &precomputed_idfs,
+                query_token_map: &query_token_map,
+                term_positions: &term_positions[i],
+                k1: BM25_K1,
+                b: BM25_B,
+            };
+            let mut factors = HashMap::new();
+            proximity_factors(&parsed_expr, &bm25_params, &mut factors);
+            let filename_tokens = filename_tokens.get(i).map_or(&[][..], |t| t.as_slice());
+
+            let mut contributions: Vec<TermContribution> = query_token_map
+                .iter()
+                .filter_map(|(term, token_index)| {
+                    let tf = *doc_tf.get(token_index)?;
+                    let factor = factors.get(token_index).copied().unwrap_or(1.0);
+                    let score = bm25_single_token_optimized(term, &bm25_params) * factor;
+
+                    // Score the term again without its filename occurrences
+                    let filename_tf = filename_tokens.iter().filter(|t| *t == term).count();
+                    let mut content_tf = doc_tf.clone();
+                    content_tf.insert(*token_index, tf.saturating_sub(filename_tf));
+                    let content_score = bm25_single_token_optimized(
+                        term,
+                        &PrecomputedBm25Params {
+                            doc_tf: &content_tf,
+                            ..bm25_params
+                        },
+                    ) * factor;
+
+                    Some(TermContribution {
+                        term: term.clone(),
+                        tf,
+                        idf: *precomputed_idfs.get(term).unwrap_or(&0.0),
+                        score,
+                        filename_score: score - content_score,
+                    })
+                })
+                .collect();
+            contributions.sort_by(|a, b| {
+                b.score
+                    .partial_cmp(&a.score)
+                    .unwrap_or(std::cmp::Ordering::Equal)
+                    .then_with(|| a.term.cmp(&b.term))
+            });
+            contributions
+        })
+        .collect()
+}
+
 /// Computes term frequencies (TF) for each document, document frequencies (DF) for each term,
 /// and document lengths from pre-tokenized content.
 ///
@@ -691,7 +986,7 @@ pub fn compute_tf_df_from_tokenized(
     tokenized_docs: &[Vec<String>],
     query_token_map: &QueryTokenMap,
 ) -> TfDfResult {
-    let debug_mode = std::env::var("DEBUG").unwrap_or_default() == "1";
+    let debug_mode = probe_code::console::debug_mode();
 
     if debug_mode {
         println!("DEBUG: Starting parallel TF-DF computation from pre-tokenized content for {docs_len} documents", docs_len = tokenized_docs.len());
@@ -790,6 +1085,39 @@ pub fn compute_tf_df_from_tokenized(
     }
 }
 
+/// Adds the matches of regex terms (`/pattern/`) to the term and document
+/// frequencies. Regex terms never appear among the tokens of a document, so
+/// they are matched against the raw document text instead.
+fn add_regex_term_frequencies(
+    tf_df_result: &mut TfDfResult,
+    documents: &[&str],
+    query_token_map: &QueryTokenMap,
+) {
+    for (term, &token_index) in query_token_map {
+        let Some(regex) = crate::search::elastic_query::compile_regex_term(term) else {
+            continue;
+        };
+        let counts: Vec<usize> = documents
+            .par_iter()
+            .map(|doc| regex.find_iter(doc).count())
+            .collect();
+
+        let mut matching_docs = 0;
+        for (doc_tf, count) in tf_df_result.term_frequencies.iter_mut().zip(counts) {
+            if count > 0 {
+                *doc_tf.entry(token_index).or_insert(0) += count;
+                matching_docs += 1;
+            }
+        }
+        if matching_docs > 0 {
+            *tf_df_result
+                .document_frequencies
+                .entry(term.clone())
+                .or_insert(0) += matching_docs;
+        }
+    }
+}
+
 // -------------------------------------------------------------------------
 // Unit tests (optional). Adapt or remove as you wish.
 // -------------------------------------------------------------------------
@@ -1055,6 +1383,7 @@ mod tests {
             avgdl: 3.0,
             idfs: &idfs,
             query_token_map: &query_token_map,
+            term_positions: &HashMap::new(),
             k1: 1.2,
             b: 0.75,
         };
@@ -1081,4 +1410,165 @@ mod tests {
         // The score should be the sum of individual scores
         assert_eq!(term_score, apple_score + banana_score);
     }
+
+    #[test]
+    fn test_proximity_rewards_closer_terms() {
+        let documents = [
+            "cache entries are dropped before we invalidate them",
+            "cache invalidate",
+            "cache one two three four five six seven eight nine ten invalidate",
+        ];
+        let params = RankingParams {
+            documents: &documents,
+            query: "\"cache invalidate\"~5",
+            pre_tokenized: None,
+        };
+
+        let ranked = rank_documents(&params);
+        let order: Vec<usize> = ranked.iter().map(|(i, _)| *i).collect();
+
+        // Adjacent terms rank first; terms further apart than the distance score nothing
+        assert_eq!(order, vec![1, 0, 2]);
+        assert!(ranked[0].1 > ranked[1].1);
+        assert_eq!(ranked[2].1, 0.0);
+    }
+
+    #[test]
+    fn test_extract_term_boosts() {
+        let expr = crate::search::elastic_query::parse_query_test(
+            "+payment retry^3 (backoff OR jitter^2)^1.5",
+        )
+        .unwrap();
+        let boosts = extract_term_boosts(&expr);
+
+        assert_eq!(boosts.get("retri"), Some(&3.0));
+        assert_eq!(boosts.get("backoff"), Some(&1.5));
+        assert_eq!(boosts.get("jitter"), Some(&3.0));
+        assert_eq!(boosts.get("payment"), None);
+    }
+
+    #[test]
+    fn test_boost_changes_ranking() {
+        let documents = ["payment backoff backoff backoff", "payment retry"];
+        let rank = |query| {
+            let params = RankingParams {
+                documents: &documents,
+                query,
+                pre_tokenized: None,
+            };
+            rank_documents(&params)
+                .iter()
+                .map(|(i, _)| *i)
+                .collect::<Vec<usize>>()
+        };
+
+        assert_eq!(rank("+payment retry backoff"), vec![0, 1]);
+        assert_eq!(rank("+payment retry^3 backoff"), vec![1, 0]);
+
+        // Boosts are part of the IDF values used by the SIMD matrix as well
+        let mut dfs = HashMap::new();
+        dfs.insert("retry".to_string(), 1);
+        let terms: HashSet<String> = ["retry".to_string()].into_iter().collect();
+        let plain = precompute_idfs(&terms, &dfs, 2, &HashMap::new());
+        let boosted = precompute_idfs(
+            &terms,
+            &dfs,
+            2,
+            &[("retry".to_string(), 3.0)].into_iter().collect(),
+        );
+        assert!((boosted["retry"] - 3.0 * plain["retry"]).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_regex_terms_count_as_matches() {
+        let documents = [
+            "session token // todo later",
+            "session token // TODO(alice) TODO(bob)",
+        ];
+        let rank = |query| {
+            let params = RankingParams {
+                documents: &documents,
+                query,
+                pre_tokenized: None,
+            };
+            rank_documents(&params)
+                .iter()
+                .map(|(i, _)| *i)
+                .collect::<Vec<usize>>()
+        };
+
+        assert_eq!(rank("session"), vec![0, 1]);
+        // Regex matches are counted like any other term
+        assert_eq!(rank(r"session /TODO\(\w+\)/"), vec![1, 0]);
+        assert_eq!(rank("session -/TODO/"), vec![0]);
+    }
+
+    #[test]
+    fn test_explain_bm25_scores_matches_ranking() {
+        let files = [
+            ("token.rs", "fn refresh() { let token = session(); }"),
+            ("lock.rs", "fn acquire() { let lock = session(); }"),
+            ("other.rs", "fn unrelated() {}"),
+        ];
+        let documents: Vec<&str> = files.iter().map(|(_, code)| *code).collect();
+        let pre_tokenized: Vec<Vec<String>> = files
+            .iter()
+            .map(|(file, code)| preprocess_text_with_filename(code, file))
+            .collect();
+        let filename_tokens: Vec<Vec<String>> =
+            files.iter().map(|(file, _)| tokenize(file)).collect();
+        let params = RankingParams {
+            documents: &documents,
+            query: "token session",
+            pre_tokenized: Some(&pre_tokenized),
+        };
+
+        let ranked: std::collections::HashMap<usize, f64> =
+            rank_documents(&params).into_iter().collect();
+        let explained = explain_bm25_scores(&params, &filename_tokens);
+        assert_eq!(explained.len(), documents.len());
+
+        // Term contributions add up to the ranking score
+        for (i, score) in &ranked {
+            let total: f64 = explained[*i].iter().map(|t| t.score).sum();
+            assert!((total - score).abs() < 1e-9, "doc {i}: {total} != {score}");
+        }
+
+        // Highest contribution first, with the filename share of "token"
+        let first = &explained[0];
+        assert_eq!(first[0].term, "token");
+        assert_eq!(first[0].tf, 2);
+        assert!(first[0].filename_score > 0.0);
+        assert!(first[0].filename_score < first[0].score);
+        assert_eq!(first[1].term, "session");
+        assert_eq!(first[1].filename_score, 0.0);
+
+        assert!(explained[1].iter().all(|t| t.term == "session"));
+        assert!(explained[2].is_empty());
+    }
+
+    #[test]
+    fn test_explain_bm25_scores_matches_proximity_ranking() {
+        let documents = [
+            "cache entries are dropped before we invalidate them",
+            "cache invalidate",
+            "cache one two three four five six seven eight nine ten invalidate",
+        ];
+        let params = RankingParams {
+            documents: &documents,
+            query: "cache NEAR/5 invalidate",
+            pre_tokenized: None,
+        };
+
+        let ranked = rank_documents(&params);
+        let explained = explain_bm25_scores(&params, &[]);
+        assert_eq!(ranked.len(), documents.len());
+
+        // Term contributions add up to the ranking score, closeness reward included
+        for (i, score) in &ranked {
+            let total: f64 = explained[*i].iter().map(|t| t.score).sum();
+            assert!((total - score).abs() < 1e-9, "doc {i}: {total} != {score}");
+        }
+        assert!(explained[2].iter().all(|t| t.score == 0.0));
+    }
 }
@@ -1,7 +1,6 @@
 use probe_code::models::SearchResult;
+use probe_code::source::Sources;
 use std::collections::BTreeMap;
-use std::fs::File;
-use std::io::{BufRead, BufReader};
 use std::path::{Path, PathBuf};
 
 /// Merges ranked search results that are adjacent or overlapping
@@ -10,16 +9,18 @@ use std::path::{Path, PathBuf};
 /// that come from the same file and are adjacent or overlapping.
 ///
 /// # Arguments
+/// * `sources` - Where the files of the results are read from, to fill gaps between blocks
 /// * `results` - A vector of already ranked and limited SearchResult objects
 /// * `threshold` - Maximum number of lines between blocks to consider them adjacent (default: 5)
 ///
 /// # Returns
 /// A new vector of SearchResult objects with adjacent blocks merged
 pub fn merge_ranked_blocks(
+    sources: &Sources,
     results: Vec<SearchResult>,
     threshold: Option<usize>,
 ) -> Vec<SearchResult> {
-    let debug_mode = std::env::var("DEBUG").unwrap_or_default() == "1";
+    let debug_mode = probe_code::console::debug_mode();
     let threshold = threshold.unwrap_or(5); // Default to 5 lines if not specified
 
     if results.is_empty() {
@@ -113,7 +114,7 @@ pub fn merge_ranked_blocks(
                         // Merge the blocks
                         let merged_start = current_block.lines.0.min(next_block.lines.0);
                         let merged_end = current_block.lines.1.max(next_block.lines.1);
-                        let merged_code = merge_block_content(&current_block, next_block);
+                        let merged_code = merge_block_content(sources, &current_block, next_block);
 
                         // Use node type from the highest-ranked block
                         let merged_node_type = if current_block.rank.unwrap_or(usize::MAX)
@@ -176,7 +177,7 @@ pub fn merge_ranked_blocks(
 /// # Returns
 /// `true` if blocks should be merged, `false` otherwise
 pub fn should_merge_blocks(block1: &SearchResult, block2: &SearchResult, threshold: usize) -> bool {
-    let debug_mode = std::env::var("DEBUG").unwrap_or_default() == "1";
+    let debug_mode = probe_code::console::debug_mode();
 
     // Check if both blocks have parent_file_id, and if they match
     if let (Some(file_id1), Some(file_id2)) = (&block1.parent_file_id, &block2.parent_file_id) {
@@ -252,7 +253,7 @@ fn is_function_like(node_type: &str) -> bool {
 ///
 /// # Returns
 /// The merged code content
-fn merge_block_content(block1: &SearchResult, block2: &SearchResult) -> String {
+fn merge_block_content(sources: &Sources, block1: &SearchResult, block2: &SearchResult) -> String {
     // Extract line ranges
     let (start1, end1) = block1.lines;
     let (start2, end2) = block2.lines;
@@ -296,13 +297,11 @@ fn merge_block_content(block1: &SearchResult, block2: &SearchResult) -> String {
     // Build the merged content from the line map
     let mut merged_lines = Vec::new();
     let mut current_line = merged_start;
-    let debug_mode = std::env::var("DEBUG").unwrap_or_default() == "1";
+    let debug_mode = probe_code::console::debug_mode();
 
-    // Try to open the file to fill small gaps
+    // Try to read the file to fill small gaps
     let file_path = Path::new(&block1.file);
-    let file_result = File::open(file_path);
-    let file_content_available = file_result.is_ok();
-    let _reader = file_result.map(BufReader::new).ok(); // Used for debugging purposes only
+    let file_content = sources.read_to_string(file_path).ok();
 
     if debug_mode {
         println!(
@@ -316,7 +315,7 @@ fn merge_block_content(block1: &SearchResult, block2: &SearchResult) -> String {
                 .unwrap_or_else(|_| PathBuf::from(file_path))
         );
         println!("DEBUG: File exists: {}", file_path.exists());
-        println!("DEBUG: File can be opened: {file_content_available}");
+        println!("DEBUG: File can be read: {}", file_content.is_some());
     }
 
     while current_line <= merged_end {
@@ -337,7 +336,7 @@ fn merge_block_content(block1: &SearchResult, block2: &SearchResult) -> String {
 
             // For small gaps (less than 10 lines), try to read the actual content
             if gap_size < 10 {
-                if file_content_available {
+                if let Some(content) = &file_content {
                     if debug_mode {
                         println!(
                             "DEBUG: Attempting to fill small gap from line {} to {} from file {}",
@@ -345,50 +344,26 @@ fn merge_block_content(block1: &SearchResult, block2: &SearchResult) -> String {
                         );
                     }
 
-                    // Read the file content directly instead of using a reader clone
-                    // which might have its position already moved forward
-                    let file_result = File::open(Path::new(&block1.file));
-
-                    if let Ok(file) = file_result {
-                        let reader = BufReader::new(file);
+                    let lines_read: Vec<String> = content
+                        .lines()
+                        .skip(gap_start - 1)
+                        .take(gap_size)
+                        .map(String::from)
+                        .collect();
 
+                    // Add the actual content for the gap
+                    if !lines_read.is_empty() {
                         if debug_mode {
-                            println!("DEBUG: Created fresh file reader for gap");
-                        }
-
-                        // Read the file line by line
-                        let mut lines_read = Vec::new();
-                        let mut current_line_in_file = 1;
-
-                        for line_content in reader.lines().map_while(Result::ok) {
-                            if current_line_in_file >= gap_start && current_line_in_file <= gap_end
-                            {
-                                lines_read.push(line_content);
-                            }
-
-                            current_line_in_file += 1;
-
-                            if current_line_in_file > gap_end {
-                                break;
-                            }
-                        }
-
-                        // Add the actual content for the gap
-                        if !lines_read.is_empty() {
-                            if debug_mode {
-                                println!(
-                                    "DEBUG: Successfully read {} lines for gap",
-                                    lines_read.len()
-                                );
-                            }
-                            merged_lines.extend(lines_read);
-                            current_line = gap_end + 1;
-                            continue;
-                        } else if debug_mode {
-                            println!("DEBUG: No lines were read for the gap (empty lines)");
+                            println!(
+                                "DEBUG: Successfully read {} lines for gap",
+                                lines_read.len()
+                            );
                         }
+                        merged_lines.extend(lines_read);
+                        current_line = gap_end + 1;
+                        continue;
                     } else if debug_mode {
-                        println!("DEBUG: Could not create fresh file reader");
+                        println!("DEBUG: No lines were read for the gap (empty lines)");
                     }
                 } else if debug_mode {
                     println!("DEBUG: File content not available for {}", block1.file);
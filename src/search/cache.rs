@@ -7,6 +7,7 @@ use std::io::{Read, Write};
 use std::path::{Path, PathBuf};
 
 use probe_code::models::SearchResult;
+use probe_code::source::Sources;
 
 /// Generate a deterministic hash for a query string
 /// This is used to create a unique identifier for each query
@@ -42,10 +43,8 @@ pub struct SessionCache {
 }
 
 /// Calculate MD5 hash of a file's contents
-pub fn calculate_file_md5(file_path: &Path) -> Result<String> {
-    let mut file = File::open(file_path)?;
-    let mut contents = Vec::new();
-    file.read_to_end(&mut contents)?;
+pub fn calculate_file_md5(sources: &Sources, file_path: &Path) -> Result<String> {
+    let contents = sources.read(file_path)?;
 
     let digest = md5::compute(&contents);
     Ok(format!("{digest:x}"))
@@ -64,9 +63,10 @@ impl SessionCache {
         }
     }
 
-    /// Load a session cache from disk and validate file MD5 hashes
-    pub fn load(session_id: &str, query_hash: &str) -> Result<Self> {
-        let debug_mode = std::env::var("DEBUG").unwrap_or_default() == "1";
+    /// Load a session cache from disk and validate file MD5 hashes, reading the
+    /// files from `sources`
+    pub fn load(sources: &Sources, session_id: &str, query_hash: &str) -> Result<Self> {
+        let debug_mode = probe_code::console::debug_mode();
         let cache_path = Self::get_cache_path(session_id, query_hash);
 
         // If the cache file doesn't exist, create a new empty cache
@@ -120,14 +120,14 @@ impl SessionCache {
         }
 
         // Validate and invalidate cache entries based on file MD5 changes
-        cache.validate_and_invalidate_cache(debug_mode)?;
+        cache.validate_and_invalidate_cache(sources, debug_mode)?;
 
         Ok(cache)
     }
 
     /// Save the session cache to disk
     pub fn save(&self) -> Result<()> {
-        let debug_mode = std::env::var("DEBUG").unwrap_or_default() == "1";
+        let debug_mode = probe_code::console::debug_mode();
         let cache_path = Self::get_cache_path(&self.session_id, &self.query_hash);
 
         if debug_mode {
@@ -195,7 +195,11 @@ impl SessionCache {
     }
 
     /// Validate cache entries against current file MD5 hashes and invalidate if changed
-    pub fn validate_and_invalidate_cache(&mut self, debug_mode: bool) -> Result<()> {
+    pub fn validate_and_invalidate_cache(
+        &mut self,
+        sources: &Sources,
+        debug_mode: bool,
+    ) -> Result<()> {
         let mut invalidated_files = HashSet::new();
         let mut blocks_to_remove = HashSet::new();
 
@@ -204,7 +208,7 @@ impl SessionCache {
             let current_path = Path::new(file_path);
 
             // Skip if file no longer exists
-            if !current_path.exists() {
+            if !sources.exists(current_path) {
                 if debug_mode {
                     println!("DEBUG: File no longer exists, invalidating cache: {file_path}");
                 }
@@ -213,7 +217,7 @@ impl SessionCache {
             }
 
             // Calculate current MD5
-            match calculate_file_md5(current_path) {
+            match calculate_file_md5(sources, current_path) {
                 Ok(current_md5) => {
                     if &current_md5 != cached_md5 {
                         if debug_mode {
@@ -272,12 +276,12 @@ impl SessionCache {
     }
 
     /// Update the MD5 hash for a file
-    pub fn update_file_md5(&mut self, file_path: &str) -> Result<()> {
+    pub fn update_file_md5(&mut self, sources: &Sources, file_path: &str) -> Result<()> {
         let normalized_path = normalize_path(file_path);
         let path = Path::new(&normalized_path);
 
-        if path.exists() {
-            let md5_hash = calculate_file_md5(path)?;
+        if sources.exists(path) {
+            let md5_hash = calculate_file_md5(sources, path)?;
             self.file_md5_hashes.insert(normalized_path, md5_hash);
         }
 
@@ -316,12 +320,13 @@ pub fn generate_cache_key(result: &SearchResult) -> String {
 
 /// Filter search results using the cache without adding to the cache
 pub fn filter_results_with_cache(
+    sources: &Sources,
     results: &[SearchResult],
     session_id: &str,
     query: &str,
 ) -> Result<(Vec<SearchResult>, usize)> {
     let query_hash = hash_query(query);
-    let debug_mode = std::env::var("DEBUG").unwrap_or_default() == "1";
+    let debug_mode = probe_code::console::debug_mode();
 
     // Check if this is a new session by looking for the cache file
     let cache_path = SessionCache::get_cache_path(session_id, &query_hash);
@@ -337,7 +342,7 @@ pub fn filter_results_with_cache(
     }
 
     // Load the cache
-    let cache = SessionCache::load(session_id, &query_hash)?;
+    let cache = SessionCache::load(sources, session_id, &query_hash)?;
 
     // If the cache is empty, don't skip any results
     if cache.block_identifiers.is_empty() {
@@ -392,12 +397,13 @@ pub fn filter_results_with_cache(
 /// Filter matched lines using the cache to skip already cached blocks
 /// This is applied early in the search process, right after ripgrep results
 pub fn filter_matched_lines_with_cache(
+    sources: &Sources,
     file_term_map: &mut HashMap<PathBuf, HashMap<usize, HashSet<usize>>>,
     session_id: &str,
     query: &str,
 ) -> Result<usize> {
     let query_hash = hash_query(query);
-    let debug_mode = std::env::var("DEBUG").unwrap_or_default() == "1";
+    let debug_mode = probe_code::console::debug_mode();
 
     // Check if this is a new session by looking for the cache file
     let cache_path = SessionCache::get_cache_path(session_id, &query_hash);
@@ -412,7 +418,7 @@ pub fn filter_matched_lines_with_cache(
     }
 
     // Load the cache
-    let cache = SessionCache::load(session_id, &query_hash)?;
+    let cache = SessionCache::load(sources, session_id, &query_hash)?;
 
     // If the cache is empty, don't skip any lines
     if cache.block_identifiers.is_empty() {
@@ -541,12 +547,17 @@ pub fn filter_matched_lines_with_cache(
 }
 
 /// Add search results to the cache
-pub fn add_results_to_cache(results: &[SearchResult], session_id: &str, query: &str) -> Result<()> {
-    let debug_mode = std::env::var("DEBUG").unwrap_or_default() == "1";
+pub fn add_results_to_cache(
+    sources: &Sources,
+    results: &[SearchResult],
+    session_id: &str,
+    query: &str,
+) -> Result<()> {
+    let debug_mode = probe_code::console::debug_mode();
     let query_hash = hash_query(query);
 
     // Load or create the cache
-    let mut cache = SessionCache::load(session_id, &query_hash)?;
+    let mut cache = SessionCache::load(sources, session_id, &query_hash)?;
 
     if debug_mode {
         println!(
@@ -581,7 +592,7 @@ pub fn add_results_to_cache(results: &[SearchResult], session_id: &str, query: &
 
     // Update MD5 hashes for all files in this batch
     for file_path in &unique_files {
-        if let Err(e) = cache.update_file_md5(file_path) {
+        if let Err(e) = cache.update_file_md5(sources, file_path) {
             if debug_mode {
                 println!("DEBUG: Failed to update MD5 for {file_path}: {e}");
             }
@@ -605,14 +616,14 @@ pub fn add_results_to_cache(results: &[SearchResult], session_id: &str, query: &
 }
 
 /// Debug function to print cache contents (only used when DEBUG=1)
-pub fn debug_print_cache(session_id: &str, query: &str) -> Result<()> {
-    let debug_mode = std::env::var("DEBUG").unwrap_or_default() == "1";
+pub fn debug_print_cache(sources: &Sources, session_id: &str, query: &str) -> Result<()> {
+    let debug_mode = probe_code::console::debug_mode();
     if !debug_mode {
         return Ok(());
     }
 
     let query_hash = hash_query(query);
-    let cache = SessionCache::load(session_id, &query_hash)?;
+    let cache = SessionCache::load(sources, session_id, &query_hash)?;
 
     println!("DEBUG: Cache for session {session_id} with query hash {query_hash}");
     println!(
@@ -635,7 +646,7 @@ pub fn debug_print_cache(session_id: &str, query: &str) -> Result<()> {
 /// Generate a unique 4-character alphanumeric session ID
 /// Returns a tuple of (session_id, is_new) where is_new indicates if this is a newly generated ID
 pub fn generate_session_id() -> Result<(&'static str, bool)> {
-    let debug_mode = std::env::var("DEBUG").unwrap_or_default() == "1";
+    let debug_mode = probe_code::console::debug_mode();
 
     // Generate a single session ID instead of looping
     if (0..10).next().is_some() {
@@ -731,6 +742,7 @@ mod tests {
             block_id: None,
             matched_keywords: None,
             tokenized_content: None,
+            cells: None,
         };
 
         let result2 = SearchResult {
@@ -757,6 +769,7 @@ mod tests {
             block_id: None,
             matched_keywords: None,
             tokenized_content: None,
+            cells: None,
         };
 
         // Generate cache keys for both results
@@ -3,6 +3,158 @@ use std::collections::HashSet;
 use std::hash::{Hash, Hasher};
 use std::iter::Peekable;
 use std::str::Chars;
+use std::sync::Mutex;
+
+use lazy_static::lazy_static;
+use regex::{Regex, RegexBuilder};
+
+use probe_code::language::parser::BlockField;
+use probe_code::search::file_list_cache::FileFilters;
+use probe_code::search::synonyms::{Synonyms, SYNONYM_BOOST};
+
+/// Matched term indices per structural field of a block, used to evaluate
+/// field-scoped terms such as `fn:parse` or `comment:todo`.
+pub type FieldMatches = HashMap<BlockField, HashSet<usize>>;
+
+/// Token positions of each matched term index within a block, used to
+/// evaluate proximity groups such as `"token refresh"~5`.
+pub type TermPositions = HashMap<usize, Vec<usize>>;
+
+/// Map a query field specifier to the structural block field it restricts to.
+/// Returns `None` for fields without structural meaning (e.g. `title:`), which
+/// are matched anywhere in the block.
+pub fn structural_field(field: &str) -> Option<BlockField> {
+    match field.to_ascii_lowercase().as_str() {
+        "fn" | "func" | "function" | "method" => Some(BlockField::Function),
+        "class" | "struct" | "type" => Some(BlockField::Class),
+        "name" => Some(BlockField::Name),
+        "comment" | "doc" => Some(BlockField::Comment),
+        "string" | "str" => Some(BlockField::StringLiteral),
+        "code" => Some(BlockField::Code),
+        _ => None,
+    }
+}
+
+/// Split a regex keyword such as `/TODO\(\w+\)/` or `/todo/i` into its pattern
+/// and whether it is case-insensitive. Returns `None` for ordinary keywords.
+pub fn regex_term(keyword: &str) -> Option<(&str, bool)> {
+    let rest = keyword.strip_prefix('/')?;
+    let (pattern, case_insensitive) = match rest.strip_suffix('/') {
+        Some(pattern) => (pattern, false),
+        None => (rest.strip_suffix("/i")?, true),
+    };
+    // The closing slash must not be escaped
+    let trailing_backslashes = pattern.chars().rev().take_while(|&c| c == '\\').count();
+    (!pattern.is_empty() && trailing_backslashes % 2 == 0).then_some((pattern, case_insensitive))
+}
+
+lazy_static! {
+    static ref REGEX_TERM_CACHE: Mutex<HashMap<String, Option<Regex>>> = Mutex::new(HashMap::new());
+}
+
+/// Compile a regex keyword (see `regex_term`). Compiled regexes are cached, so
+/// this is cheap to call for every block. Returns `None` for ordinary keywords.
+pub fn compile_regex_term(keyword: &str) -> Option<Regex> {
+    let (pattern, case_insensitive) = regex_term(keyword)?;
+    let mut cache = REGEX_TERM_CACHE.lock().unwrap();
+    cache
+        .entry(keyword.to_string())
+        .or_insert_with(|| {
+            RegexBuilder::new(pattern)
+                .case_insensitive(case_insensitive)
+                .build()
+                .ok()
+        })
+        .clone()
+}
+
+/// Check whether a keyword is present, honoring the term's structural field
+/// when per-field matches are available.
+fn keyword_matched(
+    keyword: &str,
+    field: &Option<String>,
+    matched_terms: &HashSet<usize>,
+    term_indices: &HashMap<String, usize>,
+    field_matches: Option<&FieldMatches>,
+) -> bool {
+    let Some(idx) = term_indices.get(keyword) else {
+        return false;
+    };
+    match (field_matches, field.as_deref().and_then(structural_field)) {
+        (Some(field_matches), Some(block_field)) => field_matches
+            .get(&block_field)
+            .is_some_and(|indices| indices.contains(idx)),
+        _ => matched_terms.contains(idx),
+    }
+}
+
+/// Return the smallest number of other tokens separating one occurrence of
+/// every term, given the sorted token positions of each term. `Some(0)` means
+/// the terms occur next to each other; `None` means some term never occurs.
+pub fn min_proximity_gap(positions: &[&[usize]]) -> Option<usize> {
+    if positions.is_empty() || positions.iter().any(|p| p.is_empty()) {
+        return None;
+    }
+
+    // Slide a window over the position lists, always advancing the list
+    // holding the leftmost position.
+    let mut cursors = vec![0usize; positions.len()];
+    let mut best: Option<usize> = None;
+    loop {
+        let mut min_list = 0;
+        let mut min_pos = usize::MAX;
+        let mut max_pos = 0;
+        for (i, list) in positions.iter().enumerate() {
+            let pos = list[cursors[i]];
+            if pos < min_pos {
+                min_pos = pos;
+                min_list = i;
+            }
+            max_pos = max_pos.max(pos);
+        }
+
+        let gap = (max_pos - min_pos + 1).saturating_sub(positions.len());
+        best = Some(best.map_or(gap, |b| b.min(gap)));
+
+        cursors[min_list] += 1;
+        if cursors[min_list] >= positions[min_list].len() {
+            return best;
+        }
+    }
+}
+
+/// Check whether the keywords of a proximity group occur within `distance`
+/// tokens of each other. Always `true` when token positions are unknown.
+fn keywords_within_distance(
+    keywords: &[String],
+    distance: usize,
+    term_indices: &HashMap<String, usize>,
+    positions: Option<&TermPositions>,
+) -> bool {
+    let Some(positions) = positions else {
+        return true;
+    };
+
+    let mut indices: Vec<usize> = Vec::with_capacity(keywords.len());
+    for kw in keywords {
+        let Some(&idx) = term_indices.get(kw) else {
+            return false;
+        };
+        if !indices.contains(&idx) {
+            indices.push(idx);
+        }
+    }
+
+    let mut lists: Vec<&[usize]> = Vec::with_capacity(indices.len());
+    for idx in indices {
+        match positions.get(&idx) {
+            Some(list) => lists.push(list),
+            None => return false,
+        }
+    }
+
+    min_proximity_gap(&lists).is_some_and(|gap| gap <= distance)
+}
 
 // PHASE 3C OPTIMIZATION: Compute hash key for evaluation cache
 fn compute_evaluation_key(matched_terms: &HashSet<usize>) -> u64 {
@@ -36,6 +188,25 @@ pub enum Expr {
 
     /// Logical OR of two sub-expressions.
     Or(Box<Expr>, Box<Expr>),
+
+    /// Proximity group, e.g. `"token refresh"~5` or `token NEAR/5 refresh`.
+    /// Wraps a `Term` whose keywords must all occur within the given number
+    /// of tokens of each other. Without token positions it behaves like the
+    /// wrapped term.
+    Near(Box<Expr>, usize),
+
+    /// A parenthesized group with a leading `+`, e.g. `+(parse OR lex)`:
+    /// the group must match for the whole query to match.
+    Required(Box<Expr>),
+
+    /// A parenthesized group with a leading `-`, e.g. `-(legacy OR deprecated)`:
+    /// blocks matching the group are excluded.
+    Excluded(Box<Expr>),
+
+    /// A term or group with a ranking weight, e.g. `retry^3` or `(retry OR backoff)^2`.
+    /// Matching is unaffected; the BM25 contribution of the wrapped keywords is
+    /// multiplied by the boost.
+    Boost(Box<Expr>, f64),
 }
 
 impl Expr {
@@ -75,6 +246,10 @@ impl Expr {
                 left.collect_terms(required, optional);
                 right.collect_terms(required, optional);
             }
+            Expr::Near(inner, _) | Expr::Required(inner) | Expr::Boost(inner, _) => {
+                inner.collect_terms(required, optional)
+            }
+            Expr::Excluded(_) => {}
         }
     }
 
@@ -85,6 +260,9 @@ impl Expr {
             Expr::And(left, right) | Expr::Or(left, right) => {
                 left.has_required_term() || right.has_required_term()
             }
+            Expr::Near(inner, _) | Expr::Boost(inner, _) => inner.has_required_term(),
+            Expr::Required(_) => true,
+            Expr::Excluded(_) => false,
         }
     }
 
@@ -99,6 +277,9 @@ impl Expr {
             Expr::Or(left, right) => {
                 left.is_only_excluded_terms() && right.is_only_excluded_terms()
             }
+            Expr::Near(inner, _) | Expr::Boost(inner, _) => inner.is_only_excluded_terms(),
+            Expr::Required(_) => false,
+            Expr::Excluded(_) => true,
         }
     }
 
@@ -108,10 +289,13 @@ impl Expr {
         &self,
         matched_terms: &HashSet<usize>,
         term_indices: &HashMap<String, usize>,
+        field_matches: Option<&FieldMatches>,
+        positions: Option<&TermPositions>,
     ) -> bool {
         match self {
             Expr::Term {
                 keywords,
+                field,
                 required,
                 excluded,
                 ..
@@ -119,27 +303,60 @@ impl Expr {
                 if *required && !*excluded {
                     // All keywords in this required term must be present
                     keywords.iter().all(|kw| {
-                        term_indices
-                            .get(kw)
-                            .map(|idx| matched_terms.contains(idx))
-                            .unwrap_or(false)
+                        keyword_matched(kw, field, matched_terms, term_indices, field_matches)
                     })
                 } else {
                     // Not a required term, so it doesn't affect required term checking
                     true
                 }
             }
-            Expr::And(left, right) => {
+            Expr::And(left, right) | Expr::Or(left, right) => {
                 // For AND: both sides must have their required terms satisfied
-                left.check_all_required_terms_present(matched_terms, term_indices)
-                    && right.check_all_required_terms_present(matched_terms, term_indices)
-            }
-            Expr::Or(left, right) => {
-                // For OR: both sides must have their required terms satisfied
+                // For OR: both sides must have their required terms satisfied too.
                 // This is crucial - even in OR, required terms must be present
-                left.check_all_required_terms_present(matched_terms, term_indices)
-                    && right.check_all_required_terms_present(matched_terms, term_indices)
+                left.check_all_required_terms_present(
+                    matched_terms,
+                    term_indices,
+                    field_matches,
+                    positions,
+                ) && right.check_all_required_terms_present(
+                    matched_terms,
+                    term_indices,
+                    field_matches,
+                    positions,
+                )
+            }
+            Expr::Near(inner, distance) => {
+                if !inner.check_all_required_terms_present(
+                    matched_terms,
+                    term_indices,
+                    field_matches,
+                    positions,
+                ) {
+                    return false;
+                }
+                // A required proximity group also needs its keywords close together
+                match inner.as_ref() {
+                    Expr::Term {
+                        keywords,
+                        required: true,
+                        excluded: false,
+                        ..
+                    } => keywords_within_distance(keywords, *distance, term_indices, positions),
+                    _ => true,
+                }
+            }
+            // A required group must match on its own
+            Expr::Required(inner) => {
+                inner.evaluate_group(matched_terms, term_indices, field_matches, positions, false)
             }
+            Expr::Excluded(_) => true,
+            Expr::Boost(inner, _) => inner.check_all_required_terms_present(
+                matched_terms,
+                term_indices,
+                field_matches,
+                positions,
+            ),
         }
     }
 
@@ -149,6 +366,8 @@ impl Expr {
         &self,
         matched_terms: &HashSet<usize>,
         term_indices: &HashMap<String, usize>,
+        field_matches: Option<&FieldMatches>,
+        positions: Option<&TermPositions>,
         ignore_negatives: bool,
         has_required_anywhere: bool,
     ) -> bool {
@@ -158,13 +377,17 @@ impl Expr {
             return false;
         }
 
-        let debug_mode = std::env::var("DEBUG").unwrap_or_default() == "1";
+        let debug_mode = probe_code::console::debug_mode();
 
         // CRITICAL FIX: Check required terms FIRST before any other evaluation
         // In Lucene semantics, if ANY required term is missing, the entire query fails
         if has_required_anywhere && !ignore_negatives {
-            let all_required_terms_present =
-                self.check_all_required_terms_present(matched_terms, term_indices);
+            let all_required_terms_present = self.check_all_required_terms_present(
+                matched_terms,
+                term_indices,
+                field_matches,
+                positions,
+            );
             if !all_required_terms_present {
                 if debug_mode {
                     println!("DEBUG: Query failed - required terms missing");
@@ -176,6 +399,7 @@ impl Expr {
         match self {
             Expr::Term {
                 keywords,
+                field,
                 required,
                 excluded,
                 ..
@@ -184,13 +408,11 @@ impl Expr {
                     // Empty term => if excluded, trivially true, otherwise false
                     return *excluded;
                 }
+                let is_present = |kw: &String| {
+                    keyword_matched(kw, field, matched_terms, term_indices, field_matches)
+                };
                 // Are all keywords present?
-                let all_present = keywords.iter().all(|kw| {
-                    term_indices
-                        .get(kw)
-                        .map(|idx| matched_terms.contains(idx))
-                        .unwrap_or(false)
-                });
+                let all_present = keywords.iter().all(is_present);
 
                 if *excluded {
                     if ignore_negatives {
@@ -198,12 +420,7 @@ impl Expr {
                         true
                     } else {
                         // Excluded => none should be present
-                        !keywords.iter().any(|kw| {
-                            term_indices
-                                .get(kw)
-                                .map(|idx| matched_terms.contains(idx))
-                                .unwrap_or(false)
-                        })
+                        !keywords.iter().any(is_present)
                     }
                 } else if *required && ignore_negatives {
                     // If ignoring negatives, we've already enforced required terms up front.
@@ -223,12 +440,7 @@ impl Expr {
                         // ["jwt", "middleware"], both parts must be present.
 
                         // Check if any keywords are present
-                        let any_present = keywords.iter().any(|kw| {
-                            term_indices
-                                .get(kw)
-                                .map(|idx| matched_terms.contains(idx))
-                                .unwrap_or(false)
-                        });
+                        let any_present = keywords.iter().any(is_present);
 
                         // If no keywords are present, the term doesn't match
                         if !any_present {
@@ -245,12 +457,16 @@ impl Expr {
                 let lval = left.evaluate_with_has_required(
                     matched_terms,
                     term_indices,
+                    field_matches,
+                    positions,
                     ignore_negatives,
                     has_required_anywhere,
                 );
                 let rval = right.evaluate_with_has_required(
                     matched_terms,
                     term_indices,
+                    field_matches,
+                    positions,
                     ignore_negatives,
                     has_required_anywhere,
                 );
@@ -268,12 +484,16 @@ impl Expr {
                 let lval = left.evaluate_with_has_required(
                     matched_terms,
                     term_indices,
+                    field_matches,
+                    positions,
                     ignore_negatives,
                     has_required_anywhere,
                 );
                 let rval = right.evaluate_with_has_required(
                     matched_terms,
                     term_indices,
+                    field_matches,
+                    positions,
                     ignore_negatives,
                     has_required_anywhere,
                 );
@@ -288,9 +508,95 @@ impl Expr {
                 }
                 lval || rval
             }
+            Expr::Near(inner, distance) => {
+                let Expr::Term {
+                    keywords,
+                    required,
+                    excluded,
+                    ..
+                } = inner.as_ref()
+                else {
+                    return inner.evaluate_with_has_required(
+                        matched_terms,
+                        term_indices,
+                        field_matches,
+                        positions,
+                        ignore_negatives,
+                        has_required_anywhere,
+                    );
+                };
+                let close = !keywords.is_empty()
+                    && keywords.iter().all(|kw| {
+                        keyword_matched(kw, &None, matched_terms, term_indices, field_matches)
+                    })
+                    && keywords_within_distance(keywords, *distance, term_indices, positions);
+
+                let result = if *excluded {
+                    // An excluded group only rules out blocks where its keywords occur close together
+                    ignore_negatives || !close
+                } else if !*required && has_required_anywhere {
+                    // Optional groups don't gate the match when other terms are required
+                    true
+                } else {
+                    close
+                };
+
+                if debug_mode {
+                    println!("DEBUG: NEAR/{distance} {keywords:?} => result={result}");
+                }
+                result
+            }
+            Expr::Required(inner) => inner.evaluate_group(
+                matched_terms,
+                term_indices,
+                field_matches,
+                positions,
+                ignore_negatives,
+            ),
+            Expr::Excluded(inner) => {
+                ignore_negatives
+                    || !inner.evaluate_group(
+                        matched_terms,
+                        term_indices,
+                        field_matches,
+                        positions,
+                        false,
+                    )
+            }
+            Expr::Boost(inner, _) => inner.evaluate_with_has_required(
+                matched_terms,
+                term_indices,
+                field_matches,
+                positions,
+                ignore_negatives,
+                has_required_anywhere,
+            ),
         }
     }
 
+    /// Evaluate a `+(...)` / `-(...)` group as if it were a query of its own,
+    /// so the required terms outside the group don't make its terms optional.
+    fn evaluate_group(
+        &self,
+        matched_terms: &HashSet<usize>,
+        term_indices: &HashMap<String, usize>,
+        field_matches: Option<&FieldMatches>,
+        positions: Option<&TermPositions>,
+        ignore_negatives: bool,
+    ) -> bool {
+        if matched_terms.is_empty() && !self.is_only_excluded_terms() {
+            return false;
+        }
+        self.evaluate_with_has_required(
+            matched_terms,
+            term_indices,
+            field_matches,
+            positions,
+            ignore_negatives,
+            self.has_required_term(),
+        )
+    }
+
     // PHASE 3C OPTIMIZATION: Fast-path evaluation for simple queries
     pub fn evaluate_fast_path(
         &self,
@@ -361,33 +667,117 @@ impl Expr {
     /// - AND => both sides must match.
     /// - OR => at least one side must match.
     /// - `ignore_negatives` => if true, excluded terms are basically ignored (they don't exclude).
-    /// - Field is **ignored** in evaluation, per request.
+    /// - Field is **ignored** in evaluation; use `evaluate_with_fields` to honor
+    ///   structural fields such as `fn:` or `comment:`.
     pub fn evaluate(
         &self,
         matched_terms: &HashSet<usize>,
         term_indices: &HashMap<String, usize>,
         ignore_negatives: bool,
+    ) -> bool {
+        self.evaluate_scoped(matched_terms, term_indices, None, None, ignore_negatives)
+    }
+
+    /// Like `evaluate`, but terms with a structural field (`fn:`, `class:`, `name:`,
+    /// `comment:`, `string:`, `code:`) only count as present when their keyword was
+    /// matched in that part of the block, as given by `field_matches`.
+    /// Terms without a structural field still use `matched_terms`.
+    pub fn evaluate_with_fields(
+        &self,
+        matched_terms: &HashSet<usize>,
+        field_matches: &FieldMatches,
+        term_indices: &HashMap<String, usize>,
+        ignore_negatives: bool,
+    ) -> bool {
+        self.evaluate_scoped(
+            matched_terms,
+            term_indices,
+            Some(field_matches),
+            None,
+            ignore_negatives,
+        )
+    }
+
+    /// Evaluate against everything known about a block: structural field matches
+    /// (see `evaluate_with_fields`) and the token positions of each matched term,
+    /// which proximity groups need to check how far apart their keywords are.
+    pub fn evaluate_in_block(
+        &self,
+        matched_terms: &HashSet<usize>,
+        field_matches: Option<&FieldMatches>,
+        positions: Option<&TermPositions>,
+        term_indices: &HashMap<String, usize>,
+        ignore_negatives: bool,
+    ) -> bool {
+        self.evaluate_scoped(
+            matched_terms,
+            term_indices,
+            field_matches,
+            positions,
+            ignore_negatives,
+        )
+    }
+
+    /// Returns `true` if any term in this expression uses a structural field.
+    pub fn has_structural_field(&self) -> bool {
+        match self {
+            Expr::Term { field, .. } => field.as_deref().and_then(structural_field).is_some(),
+            Expr::And(left, right) | Expr::Or(left, right) => {
+                left.has_structural_field() || right.has_structural_field()
+            }
+            Expr::Near(inner, _)
+            | Expr::Required(inner)
+            | Expr::Excluded(inner)
+            | Expr::Boost(inner, _) => inner.has_structural_field(),
+        }
+    }
+
+    /// Returns `true` if this expression contains a proximity group.
+    pub fn has_proximity(&self) -> bool {
+        match self {
+            Expr::Term { .. } => false,
+            Expr::And(left, right) | Expr::Or(left, right) => {
+                left.has_proximity() || right.has_proximity()
+            }
+            Expr::Near(..) => true,
+            Expr::Required(inner) | Expr::Excluded(inner) | Expr::Boost(inner, _) => {
+                inner.has_proximity()
+            }
+        }
+    }
+
+    fn evaluate_scoped(
+        &self,
+        matched_terms: &HashSet<usize>,
+        term_indices: &HashMap<String, usize>,
+        field_matches: Option<&FieldMatches>,
+        positions: Option<&TermPositions>,
+        ignore_negatives: bool,
     ) -> bool {
         // Early termination optimization
         if matched_terms.is_empty() && !self.is_only_excluded_terms() {
             return false;
         }
 
-        let debug_mode = std::env::var("DEBUG").unwrap_or_default() == "1";
+        let debug_mode = probe_code::console::debug_mode();
 
         // If ignoring negatives, let's ensure that all required terms are present up front.
         // (We skip enforcing them again for each subtree.)
         if ignore_negatives {
-            fn collect_required(expr: &Expr) -> Vec<String> {
+            fn collect_required(expr: &Expr) -> Vec<(String, Option<String>)> {
                 match expr {
                     Expr::Term {
                         keywords,
+                        field,
                         required,
                         excluded,
                         ..
                     } => {
                         if *required && !*excluded {
-                            keywords.clone()
+                            keywords
+                                .iter()
+                                .map(|kw| (kw.clone(), field.clone()))
+                                .collect()
                         } else {
                             vec![]
                         }
@@ -402,22 +792,21 @@ impl Expr {
                         out.extend(collect_required(right));
                         out
                     }
+                    Expr::Near(inner, _) | Expr::Boost(inner, _) => collect_required(inner),
+                    // Groups are checked as a whole during evaluation
+                    Expr::Required(_) | Expr::Excluded(_) => vec![],
                 }
             }
             let required_terms = collect_required(self);
             if debug_mode && !required_terms.is_empty() {
                 println!("DEBUG: Required terms (ignoring negatives): {required_terms:?}");
             }
-            for term in &required_terms {
-                if let Some(&idx) = term_indices.get(term) {
-                    if !matched_terms.contains(&idx) {
-                        if debug_mode {
-                            println!("DEBUG: Missing required term '{term}' (idx={idx})");
-                        }
-                        return false;
+            for (term, field) in &required_terms {
+                // Also fails immediately if we can't find that required term at all
+                if !keyword_matched(term, field, matched_terms, term_indices, field_matches) {
+                    if debug_mode {
+                        println!("DEBUG: Missing required term '{term}' (field={field:?})");
                     }
-                } else {
-                    // If we can't find that required term at all, fail immediately
                     return false;
                 }
             }
@@ -437,6 +826,8 @@ impl Expr {
         self.evaluate_with_has_required(
             matched_terms,
             term_indices,
+            field_matches,
+            positions,
             ignore_negatives,
             has_required_anywhere,
         )
@@ -465,9 +856,12 @@ impl std::fmt::Display for Expr {
                 } else {
                     String::new()
                 };
+                // A regex keyword is shown as written
                 // If there's exactly one keyword and it's exact => show it quoted
                 // If multiple or not exact => "quoted" with joined keywords
-                if keywords.len() == 1 && *exact {
+                if keywords.len() == 1 && regex_term(&keywords[0]).is_some() {
+                    write!(f, "{}{}{}", prefix, field_prefix, keywords[0])
+                } else if keywords.len() == 1 && *exact {
                     write!(f, "{}{}\"{}\"", prefix, field_prefix, keywords[0])
                 } else if keywords.len() == 1 {
                     write!(f, "{}{}{}", prefix, field_prefix, keywords[0])
@@ -477,6 +871,27 @@ impl std::fmt::Display for Expr {
             }
             Expr::And(left, right) => write!(f, "({left} AND {right})"),
             Expr::Or(left, right) => write!(f, "({left} OR {right})"),
+            Expr::Near(inner, distance) => match inner.as_ref() {
+                Expr::Term {
+                    keywords,
+                    required,
+                    excluded,
+                    ..
+                } => {
+                    let prefix = if *required {
+                        "+"
+                    } else if *excluded {
+                        "-"
+                    } else {
+                        ""
+                    };
+                    write!(f, "{}\"{}\"~{}", prefix, keywords.join(" "), distance)
+                }
+                other => write!(f, "({other})~{distance}"),
+            },
+            Expr::Required(inner) => write!(f, "+{inner}"),
+            Expr::Excluded(inner) => write!(f, "-{inner}"),
+            Expr::Boost(inner, boost) => write!(f, "{inner}^{boost}"),
         }
     }
 }
@@ -492,8 +907,15 @@ pub enum Token {
     Colon,                // ':'
     Ident(String),        // alphanumeric / underscore / dot
     QuotedString(String), // raw string inside quotes
+    Tilde(Option<usize>), // '~' with an optional distance, e.g. '~5'
+    Near(usize),          // 'NEAR/n'
+    Caret(f64),           // '^' with a boost, e.g. '^3' or '^0.5'
+    Regex(String),        // '/pattern/' or '/pattern/i', kept as written
 }
 
+/// Distance used by a bare `~` after a quoted phrase.
+const DEFAULT_PROXIMITY_DISTANCE: usize = 5;
+
 /// A simple error type for parsing/tokenizing.
 #[derive(Debug)]
 pub enum ParseError {
@@ -501,6 +923,9 @@ pub enum ParseError {
     UnexpectedChar(char),
     UnexpectedEndOfInput,
     UnexpectedToken(Token),
+    InvalidRegex(String),
+    /// A `~`, `NEAR/n` or `^` operator used where it can't apply
+    InvalidOperator(String),
     Generic(String),
 }
 impl std::fmt::Display for ParseError {
@@ -509,6 +934,8 @@ impl std::fmt::Display for ParseError {
             ParseError::UnexpectedChar(c) => write!(f, "Unexpected character '{c}'"),
             ParseError::UnexpectedEndOfInput => write!(f, "Unexpected end of input"),
             ParseError::UnexpectedToken(t) => write!(f, "Unexpected token '{t:?}'"),
+            ParseError::InvalidRegex(s) => write!(f, "Invalid regular expression {s}"),
+            ParseError::InvalidOperator(s) => write!(f, "Invalid use of {s}"),
             ParseError::Generic(s) => write!(f, "{s}"),
         }
     }
@@ -520,7 +947,7 @@ impl std::error::Error for ParseError {}
 fn tokenize(input: &str) -> Result<Vec<Token>, ParseError> {
     let mut chars = input.chars().peekable();
     let mut tokens = Vec::new();
-    let debug_mode = std::env::var("DEBUG").unwrap_or_default() == "1";
+    let debug_mode = probe_code::console::debug_mode();
 
     while let Some(&ch) = chars.peek() {
         match ch {
@@ -552,6 +979,37 @@ fn tokenize(input: &str) -> Result<Vec<Token>, ParseError> {
                 let quoted_string = lex_quoted_string(&mut chars)?;
                 tokens.push(Token::QuotedString(quoted_string));
             }
+            '~' => {
+                chars.next(); // consume '~'
+                tokens.push(Token::Tilde(lex_number(&mut chars)));
+            }
+            '/' => {
+                // A slash at the start of a word opens a regex term; otherwise skip it
+                let remaining: usize = chars.clone().map(char::len_utf8).sum();
+                let previous = input[..input.len() - remaining].chars().next_back();
+                let at_word_start = previous
+                    .is_none_or(|c| c.is_whitespace() || matches!(c, '(' | '+' | '-' | ':'));
+                match at_word_start.then(|| lex_regex(&mut chars)).transpose()? {
+                    Some(Some(regex)) => tokens.push(Token::Regex(regex)),
+                    _ => {
+                        if debug_mode {
+                            println!("DEBUG: Skipping '/' outside of a regex term");
+                        }
+                        chars.next();
+                    }
+                }
+            }
+            '^' => {
+                chars.next(); // consume '^'
+                match lex_decimal(&mut chars) {
+                    Some(boost) => tokens.push(Token::Caret(boost)),
+                    None => {
+                        return Err(ParseError::InvalidOperator(
+                            "'^': it takes a boost, like retry^2".to_string(),
+                        ))
+                    }
+                }
+            }
             _ => {
                 // If it starts with alphanumeric, underscore, or dot => parse identifier
                 if ch.is_alphanumeric() || ch == '_' || ch == '.' {
@@ -561,6 +1019,12 @@ fn tokenize(input: &str) -> Result<Vec<Token>, ParseError> {
                         tokens.push(Token::And);
                     } else if ident_upper == "OR" {
                         tokens.push(Token::Or);
+                    } else if ident_upper == "NEAR" && chars.peek() == Some(&'/') {
+                        chars.next(); // consume '/'
+                        match lex_number(&mut chars) {
+                            Some(distance) => tokens.push(Token::Near(distance)),
+                            None => tokens.push(Token::Ident(ident)),
+                        }
                     } else {
                         tokens.push(Token::Ident(ident));
                     }
@@ -583,6 +1047,54 @@ fn tokenize(input: &str) -> Result<Vec<Token>, ParseError> {
     Ok(tokens)
 }
 
+/// Lex a `/pattern/` regex term with an optional `i` flag, starting at the
+/// opening slash. Returns `None` and leaves `chars` untouched when there is no
+/// closing slash at the end of a word, so paths like `/usr/lib` are not regexes.
+fn lex_regex(chars: &mut Peekable<Chars>) -> Result<Option<String>, ParseError> {
+    let mut lookahead = chars.clone();
+    let mut regex = String::from(lookahead.next().unwrap_or('/'));
+    let mut escaped = false;
+
+    loop {
+        match lookahead.next() {
+            None => return Ok(None),
+            Some(c) if escaped => {
+                regex.push(c);
+                escaped = false;
+            }
+            Some('\\') => {
+                regex.push('\\');
+                escaped = true;
+            }
+            Some('/') => {
+                regex.push('/');
+                break;
+            }
+            Some(c) => regex.push(c),
+        }
+    }
+    if lookahead.peek() == Some(&'i') {
+        lookahead.next();
+        regex.push('i');
+    }
+    if !lookahead
+        .peek()
+        .is_none_or(|&c| c.is_whitespace() || matches!(c, ')' | '^' | '~'))
+    {
+        return Ok(None);
+    }
+
+    let Some((pattern, _)) = regex_term(&regex) else {
+        return Ok(None);
+    };
+    if let Err(e) = Regex::new(pattern) {
+        return Err(ParseError::InvalidRegex(format!("{regex}: {e}")));
+    }
+
+    *chars = lookahead;
+    Ok(Some(regex))
+}
+
 /// Lex a quoted string, allowing `\"` to escape quotes
 fn lex_quoted_string(chars: &mut Peekable<Chars>) -> Result<String, ParseError> {
     let mut buf = String::new();
@@ -608,6 +1120,36 @@ fn lex_quoted_string(chars: &mut Peekable<Chars>) -> Result<String, ParseError>
     Err(ParseError::UnexpectedEndOfInput)
 }
 
+/// Lex a run of ASCII digits, e.g. the distance in `~5` or `NEAR/5`
+fn lex_number(chars: &mut Peekable<Chars>) -> Option<usize> {
+    let mut buf = String::new();
+    while let Some(&ch) = chars.peek() {
+        if ch.is_ascii_digit() {
+            buf.push(ch);
+            chars.next();
+        } else {
+            break;
+        }
+    }
+    buf.parse().ok()
+}
+
+/// Lex a decimal number such as `3` or `0.5`, e.g. the boost in `^3`
+fn lex_decimal(chars: &mut Peekable<Chars>) -> Option<f64> {
+    let mut buf = String::new();
+    let mut seen_dot = false;
+    while let Some(&ch) = chars.peek() {
+        if ch.is_ascii_digit() || (ch == '.' && !seen_dot) {
+            seen_dot |= ch == '.';
+            buf.push(ch);
+            chars.next();
+        } else {
+            break;
+        }
+    }
+    buf.parse().ok()
+}
+
 fn lex_identifier(chars: &mut Peekable<Chars>) -> String {
     let mut buf = String::new();
     while let Some(&ch) = chars.peek() {
@@ -659,7 +1201,7 @@ impl Parser {
     }
 
     fn parse_or_expr(&mut self) -> Result<Expr, ParseError> {
-        let debug_mode = std::env::var("DEBUG").unwrap_or_default() == "1";
+        let debug_mode = probe_code::console::debug_mode();
         if debug_mode {
             println!("DEBUG: parse_or_expr => pos={pos}", pos = self.pos);
         }
@@ -678,7 +1220,7 @@ impl Parser {
     }
 
     fn parse_and_expr(&mut self) -> Result<Expr, ParseError> {
-        let debug_mode = std::env::var("DEBUG").unwrap_or_default() == "1";
+        let debug_mode = probe_code::console::debug_mode();
         if debug_mode {
             println!("DEBUG: parse_and_expr => pos={pos}", pos = self.pos);
         }
@@ -700,6 +1242,10 @@ impl Parser {
                 Token::Or => {
                     break;
                 }
+                // A proximity or boost suffix that doesn't follow a term
+                Token::Tilde(_) | Token::Near(_) | Token::Caret(_) => {
+                    return Err(dangling_operator(token));
+                }
                 // If next token is a plus or minus, interpret as an AND
                 Token::Plus | Token::Minus => {
                     let right = self.parse_factor()?;
@@ -708,8 +1254,8 @@ impl Parser {
                         println!("DEBUG: forced AND => {left:?}");
                     }
                 }
-                // Otherwise (Ident, QuotedString, LParen) => implicit combos
-                Token::Ident(_) | Token::QuotedString(_) | Token::LParen => {
+                // Otherwise (Ident, QuotedString, Regex, LParen) => implicit combos
+                Token::Ident(_) | Token::QuotedString(_) | Token::Regex(_) | Token::LParen => {
                     let right = self.parse_factor()?;
                     // True Lucene/Elasticsearch semantics: implicit combinations are always OR
                     // The + and - operators only affect individual terms, not the combination logic
@@ -726,21 +1272,43 @@ impl Parser {
     }
 
     fn parse_factor(&mut self) -> Result<Expr, ParseError> {
-        match self.peek() {
+        let expr = match self.peek() {
             Some(Token::LParen) => {
                 self.next(); // consume '('
                 let expr = self.parse_expr()?;
                 self.expect(&Token::RParen)?;
-                Ok(expr)
+                expr
             }
-            _ => self.parse_prefixed_term(),
+            _ => self.parse_prefixed_term()?,
+        };
+        Ok(self.parse_boost(expr))
+    }
+
+    /// Wrap `expr` in a boost if it is followed by `^n`, e.g. `retry^3`.
+    /// Repeated boosts multiply; a zero boost is ignored.
+    fn parse_boost(&mut self, expr: Expr) -> Expr {
+        let mut boost = 1.0;
+        while let Some(Token::Caret(factor)) = self.peek() {
+            if *factor > 0.0 {
+                boost *= *factor;
+            }
+            self.next();
+        }
+
+        if boost == 1.0 {
+            expr
+        } else {
+            if probe_code::console::debug_mode() {
+                println!("DEBUG: parse_boost => boost={boost}, expr={expr:?}");
+            }
+            Expr::Boost(Box::new(expr), boost)
         }
     }
 
     fn parse_prefixed_term(&mut self) -> Result<Expr, ParseError> {
         let mut required = false;
         let mut excluded = false;
-        let debug_mode = std::env::var("DEBUG").unwrap_or_default() == "1";
+        let debug_mode = probe_code::console::debug_mode();
 
         match self.peek() {
             Some(Token::Plus) => {
@@ -754,7 +1322,47 @@ impl Parser {
             _ => {}
         }
 
+        // `+(...)` / `-(...)` => the prefix applies to the whole group
+        if (required || excluded) && self.peek() == Some(&Token::LParen) {
+            let group = self.parse_factor()?;
+            if debug_mode {
+                println!("DEBUG: parse_prefixed_term => group required={required}, excluded={excluded}, group={group:?}");
+            }
+            return Ok(match group {
+                Expr::And(..) | Expr::Or(..) if required => Expr::Required(Box::new(group)),
+                Expr::And(..) | Expr::Or(..) => Expr::Excluded(Box::new(group)),
+                // A single term in parentheses takes the prefix itself
+                single => apply_prefix(single, required, excluded),
+            });
+        }
+
         let primary_expr = self.parse_primary()?;
+
+        // `"a b"~n` or `a NEAR/n b` => proximity group over all keywords
+        if let Some((keywords, distance)) = self.parse_proximity(&primary_expr)? {
+            let mut expanded = Vec::new();
+            for kw in &keywords {
+                // Positions are compared on block tokens, so always tokenize
+                expanded.extend(custom_tokenize(kw).into_iter().filter(|s| !s.is_empty()));
+            }
+            expanded.dedup();
+
+            if debug_mode {
+                println!("DEBUG: parse_prefixed_term => NEAR/{distance}, required={required}, excluded={excluded}, keywords={expanded:?}");
+            }
+
+            return Ok(Expr::Near(
+                Box::new(Expr::Term {
+                    keywords: expanded,
+                    field: None,
+                    required,
+                    excluded,
+                    exact: false,
+                }),
+                distance,
+            ));
+        }
+
         // If it's a Term => update its required/excluded
         if let Expr::Term {
             keywords,
@@ -767,7 +1375,7 @@ impl Parser {
             // If exact or excluded => skip further tokenization
             let final_keywords = if exact || excluded {
                 // Mark them special (no splitting)
-                for kw in &keywords {
+                for kw in keywords.iter().filter(|kw| regex_term(kw).is_none()) {
                     add_special_term(kw);
                 }
                 keywords
@@ -801,8 +1409,70 @@ impl Parser {
         }
     }
 
+    /// Collect a proximity group starting at `first`: a `~n` suffix on a quoted
+    /// phrase and/or a chain of `NEAR/n` operands. Returns the raw keywords and
+    /// the distance, or `None` if `first` is not part of a proximity group.
+    /// A `~n` after a plain term is a fuzzy suffix, expanded before parsing, and
+    /// is skipped. Operands other than words and quoted phrases are an error.
+    fn parse_proximity(
+        &mut self,
+        first: &Expr,
+    ) -> Result<Option<(Vec<String>, usize)>, ParseError> {
+        let Expr::Term {
+            keywords, exact, ..
+        } = first
+        else {
+            return Ok(None);
+        };
+
+        let mut group: Option<(Vec<String>, usize)> = None;
+        if let Some(Token::Tilde(distance)) = self.peek() {
+            let distance = *distance;
+            self.next();
+            if *exact && !is_proximity_operand(first) {
+                return Err(dangling_operator(&Token::Tilde(distance)));
+            }
+            if *exact {
+                group = Some((
+                    keywords.clone(),
+                    distance.unwrap_or(DEFAULT_PROXIMITY_DISTANCE),
+                ));
+            }
+        }
+
+        while let Some(Token::Near(distance)) = self.peek() {
+            let distance = *distance;
+            self.next();
+            let next = match self.peek() {
+                Some(Token::Ident(_) | Token::QuotedString(_)) => Some(self.parse_primary()?),
+                _ => None,
+            };
+            let next_keywords = match next {
+                Some(Expr::Term {
+                    keywords: next_keywords,
+                    ..
+                }) if is_proximity_operand(first)
+                    && is_proximity_operand(next.as_ref().unwrap()) =>
+                {
+                    next_keywords
+                }
+                _ => return Err(dangling_operator(&Token::Near(distance))),
+            };
+            if let Some(Token::Tilde(_)) = self.peek() {
+                self.next();
+            }
+            let (group_keywords, group_distance) =
+                group.get_or_insert_with(|| (keywords.clone(), distance));
+            group_keywords.extend(next_keywords);
+            // Chained operators share one window; the widest distance wins
+            *group_distance = (*group_distance).max(distance);
+        }
+
+        Ok(group)
+    }
+
     fn parse_primary(&mut self) -> Result<Expr, ParseError> {
-        let debug_mode = std::env::var("DEBUG").unwrap_or_default() == "1";
+        let debug_mode = probe_code::console::debug_mode();
 
         match self.peek() {
             // Quoted => exact
@@ -820,6 +1490,21 @@ impl Parser {
                     exact: true,
                 })
             }
+            // Regex => exact term keeping the slashes, matched against the raw text
+            Some(Token::Regex(r)) => {
+                let val = r.clone();
+                self.next();
+                if debug_mode {
+                    println!("DEBUG: Regex => {val}");
+                }
+                Ok(Expr::Term {
+                    keywords: vec![val],
+                    field: None,
+                    required: false,
+                    excluded: false,
+                    exact: true,
+                })
+            }
             // Possibly field:term
             Some(Token::Ident(_)) => {
                 let Token::Ident(first) = self.next().unwrap() else {
@@ -844,7 +1529,7 @@ impl Parser {
                                 exact: false,
                             })
                         }
-                        Some(Token::QuotedString(qs)) => {
+                        Some(Token::QuotedString(qs)) | Some(Token::Regex(qs)) => {
                             let qval = qs.clone();
                             self.next();
                             Ok(Expr::Term {
@@ -875,15 +1560,67 @@ impl Parser {
                     })
                 }
             }
+            Some(t @ (Token::Tilde(_) | Token::Near(_) | Token::Caret(_))) => {
+                Err(dangling_operator(t))
+            }
             Some(t) => Err(ParseError::UnexpectedToken(t.clone())),
             None => Err(ParseError::UnexpectedEndOfInput),
         }
     }
 }
 
+/// Whether `expr` can be grouped with `NEAR/n` or `~n`: a word or a quoted
+/// phrase, not a field-scoped or regex term
+fn is_proximity_operand(expr: &Expr) -> bool {
+    matches!(expr, Expr::Term { keywords, field: None, .. }
+        if keywords.iter().all(|keyword| regex_term(keyword).is_none()))
+}
+
+/// The error for a proximity or boost operator without the terms it applies
+/// to, like `NEAR/3 retry`, `retry NEAR/3 (backoff OR jitter)` or `(retry)~2`
+fn dangling_operator(token: &Token) -> ParseError {
+    ParseError::InvalidOperator(match token {
+        Token::Tilde(Some(distance)) => {
+            format!("'~{distance}': it follows quoted phrases and words")
+        }
+        Token::Tilde(None) => "'~': it follows quoted phrases and words".to_string(),
+        Token::Near(distance) => {
+            format!("NEAR/{distance}: it goes between words or quoted phrases")
+        }
+        Token::Caret(boost) => format!("'^{boost}': it follows a term or a group"),
+        other => format!("{other:?}"),
+    })
+}
+
+/// Apply a `+`/`-` prefix written in front of a parenthesized single term.
+fn apply_prefix(expr: Expr, required: bool, excluded: bool) -> Expr {
+    match expr {
+        Expr::Term {
+            keywords,
+            field,
+            exact,
+            ..
+        } => Expr::Term {
+            keywords,
+            field,
+            required,
+            excluded,
+            exact,
+        },
+        Expr::Near(inner, distance) => {
+            Expr::Near(Box::new(apply_prefix(*inner, required, excluded)), distance)
+        }
+        Expr::Boost(inner, boost) => {
+            Expr::Boost(Box::new(apply_prefix(*inner, required, excluded)), boost)
+        }
+        other if required => Expr::Required(Box::new(other)),
+        other => Expr::Excluded(Box::new(other)),
+    }
+}
+
 /// Parse the query string into an AST
 pub fn parse_query(input: &str, exact: bool) -> Result<Expr, ParseError> {
-    let debug_mode = std::env::var("DEBUG").unwrap_or_default() == "1";
+    let debug_mode = probe_code::console::debug_mode();
 
     if debug_mode {
         println!("DEBUG: parse_query('{input}', exact={exact})");
@@ -909,9 +1646,10 @@ pub fn parse_query(input: &str, exact: bool) -> Result<Expr, ParseError> {
         println!("DEBUG: Tokens => {tokens_result:?}");
     }
 
-    // If tokenization fails => fallback
+    // If tokenization fails => fallback, unless a regex term is broken
     let tokens = match tokens_result {
         Ok(ts) => ts,
+        Err(e @ (ParseError::InvalidRegex(_) | ParseError::InvalidOperator(_))) => return Err(e),
         Err(_) => {
             let cleaned_input = input
                 .chars()
@@ -936,7 +1674,10 @@ pub fn parse_query(input: &str, exact: bool) -> Result<Expr, ParseError> {
 
     // Parse into AST
     let mut parser = Parser::new(tokens);
-    let parsed = parser.parse_expr();
+    let parsed = match parser.parse_expr() {
+        Err(e @ ParseError::InvalidOperator(_)) => return Err(e),
+        parsed => parsed,
+    };
 
     if parsed.is_err() {
         // If parse fails => fallback to any Ident tokens
@@ -966,6 +1707,454 @@ pub fn parse_query(input: &str, exact: bool) -> Result<Expr, ParseError> {
     Ok(parsed.unwrap())
 }
 
+/// Plain words of a query as written, before tokenization and stemming, e.g.
+/// `+getUser OR fn:"a b" refresh` => `["getUser", "refresh"]`. Field names,
+/// operators, quoted phrases and regex terms are left out.
+pub fn query_words(input: &str) -> Vec<String> {
+    let tokens = tokenize(input).unwrap_or_default();
+    tokens
+        .iter()
+        .enumerate()
+        .filter_map(|(i, token)| match token {
+            Token::Ident(word) if tokens.get(i + 1) != Some(&Token::Colon) => Some(word.clone()),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Split a query into whitespace-separated chunks, keeping quoted strings and
+/// regex terms intact
+fn split_query_chunks(input: &str) -> Vec<String> {
+    let mut chunks = Vec::new();
+    let mut current = String::new();
+    let mut in_quotes = false;
+    let mut in_regex = false;
+    let mut escaped = false;
+
+    for ch in input.chars() {
+        if escaped {
+            current.push(ch);
+            escaped = false;
+        } else if ch == '\\' && (in_quotes || in_regex) {
+            current.push(ch);
+            escaped = true;
+        } else if ch == '"' && !in_regex {
+            current.push(ch);
+            in_quotes = !in_quotes;
+        } else if ch == '/' && !in_quotes {
+            in_regex = !in_regex && (current.is_empty() || current.ends_with(['(', '+', '-', ':']));
+            current.push(ch);
+        } else if ch.is_whitespace() && !in_quotes && !in_regex {
+            if !current.is_empty() {
+                chunks.push(std::mem::take(&mut current));
+            }
+        } else {
+            current.push(ch);
+        }
+    }
+    if !current.is_empty() {
+        chunks.push(current);
+    }
+    chunks
+}
+
+/// Try to interpret a chunk as an inline file filter (`path:`, `ext:`, `lang:`),
+/// recording it in `filters`. Returns `false` if the chunk is a regular query term.
+fn apply_inline_filter(chunk: &str, filters: &mut FileFilters) -> bool {
+    let (excluded, rest) = match chunk.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, chunk.strip_prefix('+').unwrap_or(chunk)),
+    };
+    let Some((field, value)) = rest.split_once(':') else {
+        return false;
+    };
+    let value = value.trim_matches('"');
+    if value.is_empty() {
+        return false;
+    }
+    let values = value.split(',').filter(|v| !v.is_empty()).map(String::from);
+
+    match field.to_ascii_lowercase().as_str() {
+        "path" | "dir" if excluded => filters.exclude_paths.extend(values),
+        "path" | "dir" => filters.paths.extend(values),
+        "ext" => {
+            let values = values.map(|v| v.trim_start_matches('.').to_string());
+            if excluded {
+                filters.exclude_extensions.extend(values)
+            } else {
+                filters.extensions.extend(values)
+            }
+        }
+        "lang" | "language" if excluded => filters.exclude_languages.extend(values),
+        "lang" | "language" => filters.languages.extend(values),
+        _ => return false,
+    }
+    true
+}
+
+/// Extract inline file filters (`path:src/auth ext:rs lang:go -path:vendor`) from a
+/// query string. Returns the remaining query, with the filters and any boolean
+/// operators or parentheses left dangling by their removal stripped out, along with
+/// the collected filters.
+pub fn extract_inline_filters(input: &str) -> (String, FileFilters) {
+    let mut filters = FileFilters::default();
+    let mut kept: Vec<String> = Vec::new();
+
+    for chunk in split_query_chunks(input) {
+        // Keep grouping parentheses around a filter, e.g. `(path:src` or `ext:rs)`
+        let open = chunk.len() - chunk.trim_start_matches('(').len();
+        let close = chunk.len() - chunk.trim_end_matches(')').len();
+        if open + close >= chunk.len() || chunk.starts_with('"') {
+            kept.push(chunk);
+            continue;
+        }
+        let core = &chunk[open..chunk.len() - close];
+        if apply_inline_filter(core, &mut filters) {
+            kept.extend(std::iter::repeat_n("(".to_string(), open));
+            kept.extend(std::iter::repeat_n(")".to_string(), close));
+        } else {
+            kept.push(chunk);
+        }
+    }
+
+    if filters.is_empty() {
+        return (input.to_string(), filters);
+    }
+
+    // Drop boolean operators and empty groups left behind by removed filters
+    let is_operator = |c: &str| c.eq_ignore_ascii_case("AND") || c.eq_ignore_ascii_case("OR");
+    loop {
+        let mut changed = false;
+        let mut cleaned: Vec<String> = Vec::with_capacity(kept.len());
+        for (i, chunk) in kept.iter().enumerate() {
+            if is_operator(chunk) {
+                let prev = cleaned.last().map(String::as_str);
+                let next = kept.get(i + 1).map(String::as_str);
+                let dangling_prev = prev.is_none_or(|p| is_operator(p) || p.ends_with('('));
+                let dangling_next = next.is_none_or(|n| is_operator(n) || n.starts_with(')'));
+                if dangling_prev || dangling_next {
+                    changed = true;
+                    continue;
+                }
+            }
+            if chunk == ")" && cleaned.last().is_some_and(|p| p == "(") {
+                cleaned.pop();
+                changed = true;
+                continue;
+            }
+            cleaned.push(chunk.clone());
+        }
+        kept = cleaned;
+        if !changed {
+            break;
+        }
+    }
+
+    (kept.join(" "), filters)
+}
+
+/// Returns `true` if a query word is a wildcard pattern such as `auth*`, `*Handler`
+/// or `get?ser`. A trailing `?` on its own is treated as punctuation, not a wildcard.
+pub fn is_wildcard(word: &str) -> bool {
+    (word.contains('*') || word.trim_end_matches('?').contains('?'))
+        && word.chars().any(|c| c.is_alphanumeric())
+}
+
+/// A wildcard term compiled for matching against identifiers found in the code.
+/// `*` matches any run of identifier characters and `?` exactly one; matching
+/// is case-insensitive and covers the whole identifier.
+#[derive(Debug, Clone)]
+pub struct WildcardPattern {
+    pattern: String,
+    regex: regex::Regex,
+    fragments: Vec<String>,
+}
+
+impl WildcardPattern {
+    /// Compile a wildcard term. Returns `None` if `pattern` is not a wildcard.
+    pub fn new(pattern: &str) -> Option<Self> {
+        if !is_wildcard(pattern) {
+            return None;
+        }
+
+        let mut regex = String::from("(?i)^");
+        for ch in pattern.chars() {
+            match ch {
+                '*' => regex.push_str("[A-Za-z0-9_]*"),
+                '?' => regex.push_str("[A-Za-z0-9_]"),
+                _ => regex.push_str(&regex::escape(&ch.to_string())),
+            }
+        }
+        regex.push('$');
+
+        let fragments = pattern
+            .split(['*', '?'])
+            .filter(|f| !f.is_empty())
+            .map(|f| f.to_lowercase())
+            .collect();
+
+        Some(WildcardPattern {
+            pattern: pattern.to_string(),
+            regex: regex::Regex::new(&regex).ok()?,
+            fragments,
+        })
+    }
+
+    /// The wildcard term as written in the query.
+    pub fn pattern(&self) -> &str {
+        &self.pattern
+    }
+
+    /// Check whether an identifier matches the pattern.
+    pub fn matches(&self, identifier: &str) -> bool {
+        self.regex.is_match(identifier)
+    }
+
+    /// Cheap pre-check: can lowercased text contain a match at all?
+    pub fn may_occur_in(&self, lowercase_text: &str) -> bool {
+        self.fragments
+            .iter()
+            .all(|f| lowercase_text.contains(f.as_str()))
+    }
+}
+
+/// Split a query chunk into its grouping parentheses, `+`/`-` prefix, field
+/// prefix, the bare word and what follows it (boost and closing parentheses),
+/// e.g. `(+fn:auth*^2` => `("(", "+", "fn:", "auth*", "^2")`.
+fn split_term_chunk(chunk: &str) -> Option<(&str, &str, &str, &str, &str)> {
+    if chunk.starts_with('"') {
+        return None;
+    }
+    let open = chunk.len() - chunk.trim_start_matches('(').len();
+    let close = chunk.len() - chunk.trim_end_matches(')').len();
+    if open + close >= chunk.len() {
+        return None;
+    }
+    let mut core = &chunk[open..chunk.len() - close];
+    // A boost (`retry^3`) stays attached to whatever the word is replaced with
+    let mut suffix_start = chunk.len() - close;
+    if let Some((word, boost)) = core.rsplit_once('^') {
+        if !boost.is_empty() && boost.chars().all(|c| c.is_ascii_digit() || c == '.') {
+            suffix_start = open + word.len();
+            core = word;
+        }
+    }
+    let prefix_len = usize::from(core.starts_with(['+', '-']));
+    let (prefix, rest) = core.split_at(prefix_len);
+    let (field, word) = match rest.split_once(':') {
+        Some((field, word)) => (&rest[..field.len() + 1], word),
+        None => ("", rest),
+    };
+    // Quoted phrases and regex terms are never rewritten
+    if word.is_empty() || word.starts_with(['"', '/']) {
+        return None;
+    }
+    Some((&chunk[..open], prefix, field, word, &chunk[suffix_start..]))
+}
+
+/// Replace terms of a query string with the alternatives returned by `expand`.
+/// A term with several alternatives becomes an OR group that keeps the term's
+/// `+`/`-` prefix and field, e.g. `+auth*` => `+(authenticate OR authorize)`.
+/// Terms for which `expand` returns `None` are left as they are. `expand` also
+/// gets whether the term is excluded, by its own `-` or by a `-(...)` group it
+/// is in.
+fn expand_terms(input: &str, mut expand: impl FnMut(&str, bool) -> Option<Vec<String>>) -> String {
+    let mut changed = false;
+    let mut out: Vec<String> = Vec::new();
+    // Whether each group the chunk is in is excluded, innermost last
+    let mut groups: Vec<bool> = Vec::new();
+
+    for chunk in split_query_chunks(input) {
+        let mut excluded = false;
+        for c in chunk.chars().take_while(|c| matches!(c, '(' | '+' | '-')) {
+            match c {
+                '-' => excluded = true,
+                '(' => {
+                    groups.push(excluded || groups.last().copied().unwrap_or(false));
+                    excluded = false;
+                }
+                _ => {}
+            }
+        }
+        let excluded = excluded || groups.last().copied().unwrap_or(false);
+
+        let expanded = split_term_chunk(&chunk).and_then(|(open, prefix, field, word, close)| {
+            let alternatives = expand(word, excluded)
+                .filter(|alternatives| !alternatives.is_empty())?
+                .iter()
+                .map(|alternative| format!("{field}{alternative}"))
+                .collect::<Vec<_>>();
+            Some(if alternatives.len() == 1 {
+                format!("{open}{prefix}{}{close}", alternatives[0])
+            } else {
+                format!("{open}{prefix}({}){close}", alternatives.join(" OR "))
+            })
+        });
+
+        // Closing parentheses come before a boost, e.g. `cfg)^2`
+        let end = chunk.trim_end_matches(|c: char| c.is_ascii_digit() || c == '.');
+        let end = end.strip_suffix('^').unwrap_or(&chunk);
+        let closed = end.len() - end.trim_end_matches(')').len();
+        groups.truncate(groups.len().saturating_sub(closed));
+
+        match expanded {
+            Some(expanded) => {
+                changed = true;
+                out.push(expanded);
+            }
+            None => out.push(chunk),
+        }
+    }
+
+    if changed {
+        out.join(" ")
+    } else {
+        input.to_string()
+    }
+}
+
+/// Collect the wildcard terms (`auth*`, `*Handler`, `get?ser`) of a query string.
+pub fn wildcard_terms(input: &str) -> Vec<String> {
+    let mut terms: Vec<String> = Vec::new();
+    for chunk in split_query_chunks(input) {
+        if let Some((_, _, _, word, _)) = split_term_chunk(&chunk) {
+            if is_wildcard(word) && !terms.iter().any(|t| t == word) {
+                terms.push(word.to_string());
+            }
+        }
+    }
+    terms
+}
+
+/// Replace wildcard terms with the identifiers they expand to, keyed by the term
+/// as written. A term with several expansions becomes an OR group that keeps the
+/// term's `+`/`-` prefix and field, e.g. `+auth*` => `+(authenticate OR authorize)`.
+/// Terms without expansions are left as they are.
+pub fn expand_wildcards(input: &str, expansions: &HashMap<String, Vec<String>>) -> String {
+    expand_terms(input, |word, _| {
+        if is_wildcard(word) {
+            expansions.get(word).cloned()
+        } else {
+            None
+        }
+    })
+}
+
+/// Largest edit distance accepted by `term~N`
+pub const MAX_FUZZY_DISTANCE: usize = 2;
+
+/// A query term to be matched with a tolerance for typos, written as `term~N`
+/// (or any plain term when fuzzy matching is enabled for the whole query).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct FuzzyTerm {
+    /// The term without its `~N` suffix
+    pub term: String,
+    /// Maximum number of edits
+    pub distance: usize,
+}
+
+/// Edit distance used for `term~` and `--fuzzy`, based on the term length:
+/// exact for up to 2 characters, 1 edit for up to 5, and 2 edits above that.
+pub fn auto_fuzzy_distance(term: &str) -> usize {
+    match term.chars().count() {
+        0..=2 => 0,
+        3..=5 => 1,
+        _ => 2,
+    }
+}
+
+/// Interpret a bare query word as a fuzzy term. `recieve~1` always is; a plain
+/// word like `recieve` only when `fuzzy_all` is set.
+fn parse_fuzzy_word(word: &str, fuzzy_all: bool) -> Option<FuzzyTerm> {
+    let (term, distance) = match word.rsplit_once('~') {
+        Some((term, "")) => (term, auto_fuzzy_distance(term)),
+        Some((term, distance)) => (term, distance.parse::<usize>().ok()?),
+        None if fuzzy_all => {
+            let lowercase = word.to_lowercase();
+            if matches!(lowercase.as_str(), "and" | "or" | "not")
+                || crate::search::tokenization::is_stop_word(&lowercase)
+            {
+                return None;
+            }
+            (word, auto_fuzzy_distance(word))
+        }
+        None => return None,
+    };
+
+    if term.is_empty() || !term.chars().all(|c| c.is_alphanumeric() || c == '_') {
+        return None;
+    }
+
+    Some(FuzzyTerm {
+        term: term.to_string(),
+        distance: distance.min(MAX_FUZZY_DISTANCE),
+    })
+}
+
+/// Collect the fuzzy terms (`recieve~1`, `Serilaizer~2`) of a query string.
+/// With `fuzzy_all`, every plain word is treated as fuzzy too.
+pub fn fuzzy_terms(input: &str, fuzzy_all: bool) -> Vec<FuzzyTerm> {
+    let mut terms: Vec<FuzzyTerm> = Vec::new();
+    for chunk in split_query_chunks(input) {
+        if let Some((_, _, _, word, _)) = split_term_chunk(&chunk) {
+            if let Some(term) = parse_fuzzy_word(word, fuzzy_all) {
+                if term.distance > 0 && !terms.contains(&term) {
+                    terms.push(term);
+                }
+            }
+        }
+    }
+    terms
+}
+
+/// Replace fuzzy terms with the words they expand to. The `~N` suffix is always
+/// removed, so a fuzzy term without expansions is searched as written.
+pub fn expand_fuzzy_terms(
+    input: &str,
+    expansions: &HashMap<FuzzyTerm, Vec<String>>,
+    fuzzy_all: bool,
+) -> String {
+    expand_terms(input, |word, _| {
+        let term = parse_fuzzy_word(word, fuzzy_all)?;
+        match expansions.get(&term) {
+            Some(words) if !words.is_empty() => Some(words.clone()),
+            _ if word.contains('~') => Some(vec![term.term]),
+            _ => None,
+        }
+    })
+}
+
+/// Add the synonyms of every plain term as alternatives weighted with
+/// `SYNONYM_BOOST`, e.g. `+auth` => `+(auth OR authentication^0.5 OR login^0.5)`.
+/// Synonyms that are words of the query themselves are left out, so they keep
+/// their own weight. Excluded terms only exclude themselves, not their synonyms.
+pub fn expand_synonyms(input: &str, synonyms: &Synonyms) -> String {
+    let query_words: HashSet<String> = query_words(input)
+        .iter()
+        .map(|word| word.to_lowercase())
+        .collect();
+
+    expand_terms(input, |word, excluded| {
+        if excluded {
+            return None;
+        }
+        let alternatives: Vec<String> = synonyms
+            .get(word)?
+            .iter()
+            .filter(|synonym| !query_words.contains(&synonym.to_lowercase()))
+            .map(|synonym| format!("{synonym}^{SYNONYM_BOOST}"))
+            .collect();
+        if alternatives.is_empty() {
+            return None;
+        }
+        Some(
+            std::iter::once(word.to_string())
+                .chain(alternatives)
+                .collect(),
+        )
+    })
+}
+
 /// Backward compatibility wrapper for parse_query
 #[allow(dead_code)]
 pub fn parse_query_compat(input: &str) -> Result<Expr, ParseError> {
@@ -1,4 +1,5 @@
 use super::Expr;
+use probe_code::language::parser::BlockField;
 use std::collections::{HashMap, HashSet};
 
 // Helper functions to create common expressions
@@ -481,4 +482,181 @@ fn test_required_term_in_or_bug() {
     
     println!("Test 4 - No terms present: matched_terms={matched_terms:?}, result={result}");
     assert!(!result, "Should return false when no terms are present");
-}
\ No newline at end of file
+}
+
+fn create_field_term(field: &str, keyword: &str, required: bool, excluded: bool) -> Expr {
+    Expr::Term {
+        keywords: vec![keyword.to_string()],
+        field: Some(field.to_string()),
+        required,
+        excluded,
+        exact: false,
+    }
+}
+
+fn create_field_matches(entries: &[(BlockField, &[usize])]) -> super::FieldMatches {
+    entries
+        .iter()
+        .map(|(field, indices)| (*field, indices.iter().cloned().collect()))
+        .collect()
+}
+
+#[test]
+fn test_structural_field_mapping() {
+    assert_eq!(super::structural_field("fn"), Some(BlockField::Function));
+    assert_eq!(super::structural_field("class"), Some(BlockField::Class));
+    assert_eq!(super::structural_field("name"), Some(BlockField::Name));
+    assert_eq!(
+        super::structural_field("comment"),
+        Some(BlockField::Comment)
+    );
+    assert_eq!(
+        super::structural_field("string"),
+        Some(BlockField::StringLiteral)
+    );
+    assert_eq!(super::structural_field("code"), Some(BlockField::Code));
+    assert_eq!(super::structural_field("title"), None);
+}
+
+#[test]
+fn test_evaluate_with_fields_restricts_term_to_field() {
+    let expr = create_field_term("fn", "cache", false, false);
+    let term_indices = create_term_indices(&["cache"]);
+    let matched_terms = create_matched_terms(&[0]);
+
+    // "cache" only appears in a comment => fn:cache must not match
+    let comment_only = create_field_matches(&[(BlockField::Comment, &[0])]);
+    assert!(!expr.evaluate_with_fields(&matched_terms, &comment_only, &term_indices, false));
+
+    // "cache" appears in a function name => fn:cache matches
+    let in_name = create_field_matches(&[(BlockField::Function, &[0]), (BlockField::Name, &[0])]);
+    assert!(expr.evaluate_with_fields(&matched_terms, &in_name, &term_indices, false));
+
+    // Plain evaluation still ignores the field
+    assert!(expr.evaluate(&matched_terms, &term_indices, false));
+}
+
+#[test]
+fn test_evaluate_with_fields_excluded_and_required() {
+    // +upload -comment:deprecated
+    let expr = Expr::And(
+        Box::new(create_required_term("upload")),
+        Box::new(create_field_term("comment", "deprecated", false, true)),
+    );
+    let term_indices = create_term_indices(&["upload", "deprecated"]);
+    let matched_terms = create_matched_terms(&[0, 1]);
+
+    // "deprecated" appears only in code => the exclusion does not apply
+    let in_code = create_field_matches(&[(BlockField::Code, &[0, 1])]);
+    assert!(expr.evaluate_with_fields(&matched_terms, &in_code, &term_indices, false));
+
+    // "deprecated" appears in a comment => the block is excluded
+    let in_comment = create_field_matches(&[(BlockField::Code, &[0]), (BlockField::Comment, &[1])]);
+    assert!(!expr.evaluate_with_fields(&matched_terms, &in_comment, &term_indices, false));
+
+    // Required field-scoped term missing from its field => no match
+    let required = create_field_term("string", "upload", true, false);
+    assert!(!required.evaluate_with_fields(&matched_terms, &in_code, &term_indices, false));
+}
+
+#[test]
+fn test_has_structural_field() {
+    assert!(!create_term("cache").has_structural_field());
+    assert!(!create_field_term("title", "cache", false, false).has_structural_field());
+    let expr = Expr::Or(
+        Box::new(create_term("cache")),
+        Box::new(create_field_term("name", "cache", false, false)),
+    );
+    assert!(expr.has_structural_field());
+}
+
+fn create_near(keywords: &[&str], required: bool, excluded: bool, distance: usize) -> Expr {
+    Expr::Near(
+        Box::new(Expr::Term {
+            keywords: keywords.iter().map(|k| k.to_string()).collect(),
+            field: None,
+            required,
+            excluded,
+            exact: false,
+        }),
+        distance,
+    )
+}
+
+fn create_positions(positions: &[(usize, &[usize])]) -> super::TermPositions {
+    positions
+        .iter()
+        .map(|(idx, pos)| (*idx, pos.to_vec()))
+        .collect()
+}
+
+#[test]
+fn test_evaluate_proximity() {
+    let expr = create_near(&["cache", "invalidate"], false, false, 3);
+    let term_indices = create_term_indices(&["cache", "invalidate"]);
+    let matched_terms = create_matched_terms(&[0, 1]);
+
+    let close = create_positions(&[(0, &[10]), (1, &[13])]);
+    let far = create_positions(&[(0, &[10]), (1, &[300])]);
+    assert!(expr.evaluate_in_block(&matched_terms, None, Some(&close), &term_indices, false));
+    assert!(!expr.evaluate_in_block(&matched_terms, None, Some(&far), &term_indices, false));
+
+    // Without positions the group only needs every keyword
+    assert!(expr.evaluate(&matched_terms, &term_indices, false));
+    assert!(!expr.evaluate(&create_matched_terms(&[0]), &term_indices, false));
+}
+
+#[test]
+fn test_evaluate_proximity_required_and_excluded() {
+    let term_indices = create_term_indices(&["cache", "invalidate", "token"]);
+    let matched_terms = create_matched_terms(&[0, 1, 2]);
+    let close = create_positions(&[(0, &[1]), (1, &[2]), (2, &[40])]);
+    let far = create_positions(&[(0, &[1]), (1, &[30]), (2, &[40])]);
+
+    // token +"cache invalidate"~2
+    let required = Expr::Or(
+        Box::new(create_term("token")),
+        Box::new(create_near(&["cache", "invalidate"], true, false, 2)),
+    );
+    assert!(required.evaluate_in_block(&matched_terms, None, Some(&close), &term_indices, false));
+    assert!(!required.evaluate_in_block(&matched_terms, None, Some(&far), &term_indices, false));
+
+    // token -"cache invalidate"~2
+    let excluded = Expr::And(
+        Box::new(create_term("token")),
+        Box::new(create_near(&["cache", "invalidate"], false, true, 2)),
+    );
+    assert!(!excluded.evaluate_in_block(&matched_terms, None, Some(&close), &term_indices, false));
+    assert!(excluded.evaluate_in_block(&matched_terms, None, Some(&far), &term_indices, false));
+}
+
+#[test]
+fn test_evaluate_required_and_excluded_groups() {
+    let term_indices = create_term_indices(&["token", "session", "refresh"]);
+
+    // +(token OR session) refresh
+    let required = Expr::And(
+        Box::new(Expr::Required(Box::new(Expr::Or(
+            Box::new(create_term("token")),
+            Box::new(create_term("session")),
+        )))),
+        Box::new(create_term("refresh")),
+    );
+    assert!(required.evaluate(&create_matched_terms(&[0]), &term_indices, false));
+    assert!(required.evaluate(&create_matched_terms(&[1, 2]), &term_indices, false));
+    assert!(!required.evaluate(&create_matched_terms(&[2]), &term_indices, false));
+
+    // refresh -(token OR session)
+    let excluded = Expr::And(
+        Box::new(create_term("refresh")),
+        Box::new(Expr::Excluded(Box::new(Expr::Or(
+            Box::new(create_term("token")),
+            Box::new(create_term("session")),
+        )))),
+    );
+    assert!(excluded.evaluate(&create_matched_terms(&[2]), &term_indices, false));
+    assert!(!excluded.evaluate(&create_matched_terms(&[0, 2]), &term_indices, false));
+    assert!(!excluded.evaluate(&create_matched_terms(&[1, 2]), &term_indices, false));
+    // Negatives are ignored when asked to
+    assert!(excluded.evaluate(&create_matched_terms(&[1, 2]), &term_indices, true));
+}
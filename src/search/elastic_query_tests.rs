@@ -702,3 +702,425 @@ fn test_quoted_strings() {
         )
     );
 }
+
+#[test]
+fn test_extract_inline_filters() {
+    let (query, filters) = extract_inline_filters("path:src/auth ext:rs lang:go -path:vendor retry timeout");
+    assert_eq!(query, "retry timeout");
+    assert_eq!(filters.paths, vec!["src/auth".to_string()]);
+    assert_eq!(filters.exclude_paths, vec!["vendor".to_string()]);
+    assert_eq!(filters.extensions, vec!["rs".to_string()]);
+    assert_eq!(filters.languages, vec!["go".to_string()]);
+
+    // Negated extension and language filters
+    let (query, filters) = extract_inline_filters("retry -ext:md -lang:go");
+    assert_eq!(query, "retry");
+    assert_eq!(filters.exclude_extensions, vec!["md".to_string()]);
+    assert_eq!(filters.exclude_languages, vec!["go".to_string()]);
+
+    // Comma separated values and a leading dot on extensions
+    let (query, filters) = extract_inline_filters("ext:.ts,tsx handler");
+    assert_eq!(query, "handler");
+    assert_eq!(filters.extensions, vec!["ts".to_string(), "tsx".to_string()]);
+
+    // Queries without filters are returned unchanged
+    let (query, filters) = extract_inline_filters("title:foo AND \"path:bar\"");
+    assert_eq!(query, "title:foo AND \"path:bar\"");
+    assert!(filters.is_empty());
+}
+
+#[test]
+fn test_extract_inline_filters_cleans_up_operators() {
+    let (query, _) = extract_inline_filters("retry AND path:src");
+    assert_eq!(query, "retry");
+
+    let (query, _) = extract_inline_filters("(ext:rs OR ext:go) AND (retry OR timeout)");
+    assert_eq!(query, "(retry OR timeout)");
+    assert!(parse_query_test(&query).is_ok());
+
+    let (query, filters) = extract_inline_filters("(path:src AND retry)");
+    assert_eq!(query, "( retry)");
+    assert_eq!(filters.paths, vec!["src".to_string()]);
+    assert!(parse_query_test(&query).is_ok());
+
+    let (query, _) = extract_inline_filters("path:src");
+    assert!(query.is_empty());
+}
+
+#[test]
+fn test_parse_proximity_operators() {
+    let near = |keywords: &[&str], excluded: bool, distance: usize| {
+        Expr::Near(
+            Box::new(Expr::Term {
+                keywords: keywords.iter().map(|k| k.to_string()).collect(),
+                field: None,
+                required: false,
+                excluded,
+                exact: false,
+            }),
+            distance,
+        )
+    };
+
+    assert_parse_eq("\"token refresh\"~5", near(&["token", "refresh"], false, 5));
+    assert_parse_eq("token NEAR/3 refresh", near(&["token", "refresh"], false, 3));
+    assert_parse_eq("-\"token refresh\"~2", near(&["token", "refresh"], true, 2));
+
+    // Chained NEAR shares one group using the widest distance
+    assert_parse_eq(
+        "token NEAR/2 refresh near/4 session",
+        near(&["token", "refresh", "session"], false, 4),
+    );
+
+    // A bare tilde falls back to the default distance
+    assert_parse_eq(
+        "\"token refresh\"~",
+        near(&["token", "refresh"], false, DEFAULT_PROXIMITY_DISTANCE),
+    );
+
+    // "near" without a distance is an ordinary word
+    assert!(matches!(
+        parse_query_test("near session").unwrap(),
+        Expr::Or(_, _)
+    ));
+
+    // Proximity combines with other clauses
+    match parse_query_test("+\"token refresh\"~5 AND session").unwrap() {
+        Expr::And(left, _) => assert!(matches!(*left, Expr::Near(_, 5))),
+        other => panic!("Expected AND expression, got: {other:?}"),
+    }
+
+    // Operands other than words and phrases, and operators without operands, are errors
+    for query in [
+        "cache NEAR/3 (invalidate OR foo)",
+        "cache NEAR/3 -invalidate",
+        "fn:cache NEAR/3 invalidate",
+        "cache NEAR/3 /inval.*/",
+        "cache NEAR/3",
+        "NEAR/3 cache",
+        "(cache OR invalidate)~2",
+        "~2 cache",
+        "cache AND ^2",
+    ] {
+        assert!(
+            matches!(parse_query_test(query), Err(ParseError::InvalidOperator(_))),
+            "{query} should not parse"
+        );
+    }
+}
+
+#[test]
+fn test_min_proximity_gap() {
+    assert_eq!(min_proximity_gap(&[&[3], &[4]]), Some(0));
+    assert_eq!(min_proximity_gap(&[&[1, 20], &[10, 23]]), Some(2));
+    assert_eq!(min_proximity_gap(&[&[0, 46], &[45], &[48]]), Some(1));
+    assert_eq!(min_proximity_gap(&[&[1], &[]]), None);
+}
+
+#[test]
+fn test_wildcard_terms() {
+    assert!(is_wildcard("auth*"));
+    assert!(is_wildcard("*Handler"));
+    assert!(is_wildcard("get?ser"));
+    assert!(!is_wildcard("what?"));
+    assert!(!is_wildcard("*"));
+    assert!(!is_wildcard("auth"));
+
+    assert_eq!(
+        wildcard_terms("+fn:auth* (*Handler OR token) \"get?ser\" auth*"),
+        vec!["auth*".to_string(), "*Handler".to_string()]
+    );
+}
+
+#[test]
+fn test_expand_wildcards() {
+    let mut expansions = HashMap::new();
+    expansions.insert(
+        "auth*".to_string(),
+        vec!["authenticate".to_string(), "authorize".to_string()],
+    );
+    expansions.insert("*Handler".to_string(), vec!["RequestHandler".to_string()]);
+
+    assert_eq!(
+        expand_wildcards("+fn:auth* session", &expansions),
+        "+(fn:authenticate OR fn:authorize) session"
+    );
+    assert_eq!(
+        expand_wildcards("(token OR *Handler)", &expansions),
+        "(token OR RequestHandler)"
+    );
+    // Terms without expansions are searched as written
+    assert_eq!(expand_wildcards("missing* token", &expansions), "missing* token");
+}
+
+#[test]
+fn test_parse_prefixed_groups() {
+    let group = Expr::Or(
+        Box::new(Expr::Term {
+            keywords: vec!["token".to_string()],
+            field: None,
+            required: false,
+            excluded: false,
+            exact: false,
+        }),
+        Box::new(Expr::Term {
+            keywords: vec!["session".to_string()],
+            field: None,
+            required: false,
+            excluded: false,
+            exact: false,
+        }),
+    );
+
+    let required = parse_query_test("+(token OR session)").unwrap();
+    assert_eq!(required, Expr::Required(Box::new(group.clone())));
+    assert_eq!(required.to_string(), "+(token OR session)");
+
+    let excluded = parse_query_test("-(token OR session)").unwrap();
+    assert_eq!(excluded, Expr::Excluded(Box::new(group)));
+    assert_eq!(excluded.to_string(), "-(token OR session)");
+
+    // A group around a single term is just a prefixed term
+    let single = parse_query_test("+(token)").unwrap();
+    assert_eq!(
+        single,
+        Expr::Term {
+            keywords: vec!["token".to_string()],
+            field: None,
+            required: true,
+            excluded: false,
+            exact: false,
+        }
+    );
+}
+
+#[test]
+fn test_fuzzy_terms() {
+    let fuzzy = |term: &str, distance| FuzzyTerm {
+        term: term.to_string(),
+        distance,
+    };
+
+    assert_eq!(
+        fuzzy_terms("+fn:recieve~1 Serilaizer~2 token~ session", false),
+        vec![
+            fuzzy("recieve", 1),
+            fuzzy("Serilaizer", 2),
+            fuzzy("token", 1)
+        ]
+    );
+    // Distances are capped, and quoted phrases are proximity groups
+    assert_eq!(fuzzy_terms("recieve~5 \"token session\"~2", false), vec![fuzzy("recieve", 2)]);
+
+    // With fuzzy_all every plain word counts, except operators and stop words
+    assert_eq!(
+        fuzzy_terms("recieve AND the mesage OR auth*", true),
+        vec![fuzzy("recieve", 2), fuzzy("mesage", 2)]
+    );
+    assert_eq!(auto_fuzzy_distance("id"), 0);
+    assert_eq!(auto_fuzzy_distance("token"), 1);
+    assert_eq!(auto_fuzzy_distance("session"), 2);
+}
+
+#[test]
+fn test_expand_fuzzy_terms() {
+    let mut expansions = HashMap::new();
+    expansions.insert(
+        FuzzyTerm {
+            term: "recieve".to_string(),
+            distance: 1,
+        },
+        vec!["receive".to_string(), "receiver".to_string()],
+    );
+
+    assert_eq!(
+        expand_fuzzy_terms("-recieve~1 session", &expansions, false),
+        "-(receive OR receiver) session"
+    );
+    // The suffix is dropped when nothing is close enough
+    assert_eq!(
+        expand_fuzzy_terms("recieve~2 session", &expansions, false),
+        "recieve session"
+    );
+    assert_eq!(
+        expand_fuzzy_terms("token session", &expansions, false),
+        "token session"
+    );
+}
+
+#[test]
+fn test_parse_boosts() {
+    let term = |keyword: &str, required| Expr::Term {
+        keywords: vec![keyword.to_string()],
+        field: None,
+        required,
+        excluded: false,
+        exact: false,
+    };
+
+    assert_parse_eq(
+        "+token session^3",
+        Expr::Or(
+            Box::new(term("token", true)),
+            Box::new(Expr::Boost(Box::new(term("session", false)), 3.0)),
+        ),
+    );
+    assert_parse_eq(
+        "+token^0.5",
+        Expr::Boost(Box::new(term("token", true)), 0.5),
+    );
+
+    // Groups and proximity groups can be boosted too
+    let group = parse_query_test("(token OR session)^2").unwrap();
+    assert_eq!(group.to_string(), "(token OR session)^2");
+    let near = parse_query_test("\"token refresh\"~3^2").unwrap();
+    assert_eq!(near.to_string(), "\"token refresh\"~3^2");
+
+    // A '^' without a number is an error
+    assert!(matches!(
+        parse_query_test("token^ session"),
+        Err(ParseError::InvalidOperator(_))
+    ));
+    assert!(matches!(
+        parse_query_test("token^"),
+        Err(ParseError::InvalidOperator(_))
+    ));
+}
+
+#[test]
+fn test_query_rewrites_keep_boosts() {
+    let mut expansions = HashMap::new();
+    expansions.insert(
+        "auth*".to_string(),
+        vec!["authenticate".to_string(), "authorize".to_string()],
+    );
+    assert_eq!(
+        expand_wildcards("(auth*^2 OR token)", &expansions),
+        "((authenticate OR authorize)^2 OR token)"
+    );
+    assert_eq!(wildcard_terms("auth*^2"), vec!["auth*".to_string()]);
+}
+
+#[test]
+fn test_parse_regex_terms() {
+    let regex = |keyword: &str, required, excluded| Expr::Term {
+        keywords: vec![keyword.to_string()],
+        field: None,
+        required,
+        excluded,
+        exact: true,
+    };
+    let term = |keyword: &str| Expr::Term {
+        keywords: vec![keyword.to_string()],
+        field: None,
+        required: false,
+        excluded: false,
+        exact: false,
+    };
+
+    assert_parse_eq(
+        r"+/TODO\(\w+\)/ session",
+        Expr::Or(
+            Box::new(regex(r"/TODO\(\w+\)/", true, false)),
+            Box::new(term("session")),
+        ),
+    );
+    assert_parse_eq(
+        "session -/fixme/i",
+        Expr::And(
+            Box::new(term("session")),
+            Box::new(regex("/fixme/i", false, true)),
+        ),
+    );
+    // Spaces and escaped slashes stay inside the regex
+    assert_parse_eq(r"/a \/ b/", regex(r"/a \/ b/", false, false));
+
+    let scoped = parse_query_test("comment:/TODO:/").unwrap();
+    assert_eq!(scoped.to_string(), "comment:/TODO:/");
+
+    // Slashes inside words or without a closing slash are not regexes
+    assert_parse_eq(
+        "/usr/lib",
+        Expr::Or(Box::new(term("usr")), Box::new(term("lib"))),
+    );
+    assert_parse_eq("token/session", Expr::Or(Box::new(term("token")), Box::new(term("session"))));
+
+    // Invalid patterns are reported instead of silently searched
+    assert!(parse_query_test("/(unclosed/").is_err());
+}
+
+#[test]
+fn test_regex_term() {
+    assert_eq!(regex_term(r"/TODO\(\w+\)/"), Some((r"TODO\(\w+\)", false)));
+    assert_eq!(regex_term("/todo/i"), Some(("todo", true)));
+    assert_eq!(regex_term("/a/i/"), Some(("a/i", false)));
+    assert_eq!(regex_term("todo"), None);
+    assert_eq!(regex_term("//"), None);
+    assert_eq!(regex_term(r"/a\/"), None);
+
+    let regex = compile_regex_term("/todo/i").unwrap();
+    assert!(regex.is_match("// TODO: later"));
+    assert!(compile_regex_term("/todo/").unwrap().is_match("todo"));
+    assert!(!compile_regex_term("/todo/").unwrap().is_match("TODO"));
+}
+
+#[test]
+fn test_query_rewrites_skip_regex_terms() {
+    let mut expansions = HashMap::new();
+    expansions.insert("auth*".to_string(), vec!["authenticate".to_string()]);
+
+    assert_eq!(wildcard_terms("/auth.*/ auth*"), vec!["auth*".to_string()]);
+    assert_eq!(
+        expand_wildcards("/auth.* x/ +auth*", &expansions),
+        "/auth.* x/ +authenticate"
+    );
+    assert!(fuzzy_terms("/session/", true).is_empty());
+}
+
+#[test]
+fn test_query_words() {
+    assert_eq!(
+        query_words("getToken AND (+refresh OR -comment:todo) \"exact phrase\""),
+        vec!["getToken", "refresh", "todo"]
+    );
+    assert_eq!(query_words("fn:cache /todo/i session^2"), vec!["cache", "session"]);
+}
+
+#[test]
+fn test_expand_synonyms() {
+    let synonyms = Synonyms::parse(
+        r#"
+        auth = ["authentication", "login"]
+        cfg = "config"
+        "#,
+    )
+    .unwrap();
+
+    assert_eq!(
+        expand_synonyms("auth", &synonyms),
+        "(auth OR authentication^0.5 OR login^0.5)"
+    );
+    // Excluded terms don't exclude their synonyms
+    assert_eq!(
+        expand_synonyms("+Auth -fn:cfg session", &synonyms),
+        "+(Auth OR authentication^0.5 OR login^0.5) -fn:cfg session"
+    );
+    assert_eq!(
+        expand_synonyms("session -(token OR auth) cfg", &synonyms),
+        "session -(token OR auth) (cfg OR config^0.5)"
+    );
+    assert_eq!(
+        expand_synonyms("(-auth cfg)", &synonyms),
+        "(-auth (cfg OR config^0.5))"
+    );
+    // A synonym that is part of the query keeps its own weight
+    assert_eq!(
+        expand_synonyms("auth login", &synonyms),
+        "(auth OR authentication^0.5) login"
+    );
+    assert_eq!(expand_synonyms("cfg config", &synonyms), "cfg config");
+    assert_eq!(expand_synonyms("\"auth\" /cfg/", &synonyms), "\"auth\" /cfg/");
+
+    let expr = parse_query(&expand_synonyms("cfg^2", &synonyms), false).unwrap();
+    assert_eq!(expr.to_string(), "(cfg OR config^0.5)^2");
+}
@@ -39,6 +39,12 @@ fn process_ast_terms(expr: Expr) -> Expr {
                 Box::new(process_ast_terms(*right))
             )
         },
+        Expr::Near(inner, distance) => {
+            Expr::Near(Box::new(process_ast_terms(*inner)), distance)
+        },
+        Expr::Required(inner) => Expr::Required(Box::new(process_ast_terms(*inner))),
+        Expr::Excluded(inner) => Expr::Excluded(Box::new(process_ast_terms(*inner))),
+        Expr::Boost(inner, boost) => Expr::Boost(Box::new(process_ast_terms(*inner)), boost),
     }
 }
 
@@ -1,7 +1,9 @@
 use anyhow::Result;
+use ignore::overrides::Override;
 use ignore::WalkBuilder;
 use lazy_static::lazy_static;
 use probe_code::search::tokenization;
+use probe_code::source::Sources;
 use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
 use std::sync::{Arc, RwLock};
@@ -17,12 +19,124 @@ pub struct FileList {
     pub created_at: Instant,
 }
 
+/// Filters that restrict which discovered files are searched.
+/// They can be written inline in the query (`path:src/auth ext:rs lang:go -path:vendor`).
+/// A file must pass every kind of filter that is set, and one of the values of each.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FileFilters {
+    /// Keep only files whose path, as shown in results or relative to the search
+    /// root, contains one of these
+    pub paths: Vec<String>,
+    /// Drop files whose path, as shown in results or relative to the search
+    /// root, contains any of these
+    pub exclude_paths: Vec<String>,
+    /// Keep only files with one of these extensions (without the leading dot)
+    pub extensions: Vec<String>,
+    /// Drop files with any of these extensions (without the leading dot)
+    pub exclude_extensions: Vec<String>,
+    /// Keep only files written in one of these languages
+    pub languages: Vec<String>,
+    /// Drop files written in any of these languages
+    pub exclude_languages: Vec<String>,
+    /// Keep only these files (canonical paths), as changed in git for `--changed-since`
+    pub changed_files: Option<HashSet<PathBuf>>,
+}
+
+impl FileFilters {
+    /// Returns `true` if no filter is set
+    pub fn is_empty(&self) -> bool {
+        self.paths.is_empty()
+            && self.exclude_paths.is_empty()
+            && self.extensions.is_empty()
+            && self.exclude_extensions.is_empty()
+            && self.languages.is_empty()
+            && self.exclude_languages.is_empty()
+            && self.changed_files.is_none()
+    }
+
+    /// Merge another set of filters into this one
+    pub fn merge(&mut self, other: FileFilters) {
+        self.paths.extend(other.paths);
+        self.exclude_paths.extend(other.exclude_paths);
+        self.extensions.extend(other.extensions);
+        self.exclude_extensions.extend(other.exclude_extensions);
+        self.languages.extend(other.languages);
+        self.exclude_languages.extend(other.exclude_languages);
+        if other.changed_files.is_some() {
+            self.changed_files = other.changed_files;
+        }
+    }
+
+    /// Check whether a file under `root` passes the filters
+    pub fn matches(&self, sources: &Sources, root: &Path, file: &Path) -> bool {
+        if let Some(changed_files) = &self.changed_files {
+            if !sources
+                .canonicalize(file)
+                .is_ok_and(|file| changed_files.contains(&file))
+            {
+                return false;
+            }
+        }
+
+        // Files are listed with the search root joined to them, which is the
+        // path shown in results
+        let shown = file.to_string_lossy().replace('\\', "/");
+        let relative = file.strip_prefix(root).unwrap_or(file);
+        let relative = relative.to_string_lossy().replace('\\', "/");
+        let in_path = |p: &String| shown.contains(p.as_str()) || relative.contains(p.as_str());
+
+        if self.exclude_paths.iter().any(in_path) {
+            return false;
+        }
+
+        if !self.paths.is_empty() && !self.paths.iter().any(in_path) {
+            return false;
+        }
+
+        let ext = file
+            .extension()
+            .map(|ext| ext.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+        let dotted = format!(".{ext}");
+        let has_extension = |e: &String| e.trim_start_matches('.').eq_ignore_ascii_case(&ext);
+        let in_language = |lang: &String| get_language_extensions(lang).contains(&dotted);
+
+        if self.exclude_extensions.iter().any(has_extension)
+            || self.exclude_languages.iter().any(in_language)
+        {
+            return false;
+        }
+        if !self.extensions.is_empty() && !self.extensions.iter().any(has_extension) {
+            return false;
+        }
+        if !self.languages.is_empty() && !self.languages.iter().any(in_language) {
+            return false;
+        }
+        true
+    }
+}
+
+/// A cached file list along with the absolute path of the directory it lists
+struct CachedFileList {
+    root: PathBuf,
+    file_list: Arc<FileList>,
+}
+
 // Global in-memory cache for file lists
 lazy_static! {
-    static ref FILE_LIST_CACHE: RwLock<HashMap<String, Arc<FileList>>> =
+    static ref FILE_LIST_CACHE: RwLock<HashMap<String, CachedFileList>> =
         RwLock::new(HashMap::new());
 }
 
+/// Drop the cached file lists whose directory doesn't satisfy `keep`.
+/// Used by `probe daemon` to forget the file lists of directories that changed.
+pub fn retain_file_lists(keep: impl Fn(&Path) -> bool) {
+    FILE_LIST_CACHE
+        .write()
+        .unwrap()
+        .retain(|_, cached| keep(&cached.root));
+}
+
 /// Helper function to format duration in a human-readable way
 fn format_duration(duration: std::time::Duration) -> String {
     if duration.as_millis() < 1000 {
@@ -36,13 +150,21 @@ fn format_duration(duration: std::time::Duration) -> String {
 
 /// Generate a cache key for a specific directory and options
 fn generate_cache_key(
+    sources: &Sources,
     path: &Path,
     allow_tests: bool,
     custom_ignores: &[String],
     no_gitignore: bool,
 ) -> String {
-    // Create a unique identifier for this cache based on the path and options
-    let path_str = path.to_string_lossy();
+    // Create a unique identifier for this cache based on the path and options.
+    // Relative paths also depend on the working directory, which can change
+    // between searches of a long-running `probe daemon`.
+    let path_str = if path.is_relative() {
+        let cwd = std::env::current_dir().unwrap_or_default();
+        format!("{}@{}", path.to_string_lossy(), cwd.to_string_lossy())
+    } else {
+        path.to_string_lossy().into_owned()
+    };
     let allow_tests_str = if allow_tests {
         "with_tests"
     } else {
@@ -55,6 +177,9 @@ fn generate_cache_key(
         "with_gitignore"
     };
 
+    // Files read from a git revision or memory differ from the files on the disk
+    let source_str = sources.id(path);
+
     // Create a hash of the custom ignores to include in the cache key
     let ignores_hash = if custom_ignores.is_empty() {
         "no_ignores".to_string()
@@ -69,18 +194,56 @@ fn generate_cache_key(
         format!("ignores_{hash:x}")
     };
 
-    format!("{path_str}_{allow_tests_str}_{ignores_hash}_{gitignore_str}")
+    format!("{path_str}_{allow_tests_str}_{ignores_hash}_{gitignore_str}_{source_str}")
 }
 
 /// Get a list of files in a directory, respecting ignore patterns and test file exclusions.
 /// This function will use a cached list if available, or build and cache a new list if not.
+/// Path, extension and language `filters` are applied on top of the cached list.
 pub fn get_file_list(
+    sources: &Sources,
+    path: &Path,
+    allow_tests: bool,
+    custom_ignores: &[String],
+    no_gitignore: bool,
+    filters: &FileFilters,
+) -> Result<Arc<FileList>> {
+    let file_list =
+        get_unfiltered_file_list(sources, path, allow_tests, custom_ignores, no_gitignore)?;
+    if filters.is_empty() {
+        return Ok(file_list);
+    }
+
+    let files: Vec<PathBuf> = file_list
+        .files
+        .iter()
+        .filter(|file| filters.matches(sources, path, file))
+        .cloned()
+        .collect();
+
+    if probe_code::console::debug_mode() {
+        println!(
+            "DEBUG: Applied file filters {filters:?} - kept {} of {} files",
+            files.len(),
+            file_list.files.len()
+        );
+    }
+
+    Ok(Arc::new(FileList {
+        files,
+        created_at: Instant::now(),
+    }))
+}
+
+/// Get the cached list of files in a directory without applying any `FileFilters`
+fn get_unfiltered_file_list(
+    sources: &Sources,
     path: &Path,
     allow_tests: bool,
     custom_ignores: &[String],
     no_gitignore: bool,
 ) -> Result<Arc<FileList>> {
-    let debug_mode = std::env::var("DEBUG").unwrap_or_default() == "1";
+    let debug_mode = probe_code::console::debug_mode();
     let start_time = Instant::now();
 
     if debug_mode {
@@ -91,12 +254,12 @@ pub fn get_file_list(
     }
 
     // Create a cache key for this request
-    let cache_key = generate_cache_key(path, allow_tests, custom_ignores, no_gitignore);
+    let cache_key = generate_cache_key(sources, path, allow_tests, custom_ignores, no_gitignore);
 
     // Check if we have this file list in the cache
     {
         let cache = FILE_LIST_CACHE.read().unwrap();
-        if let Some(file_list) = cache.get(&cache_key) {
+        if let Some(CachedFileList { file_list, .. }) = cache.get(&cache_key) {
             let elapsed = start_time.elapsed();
             if debug_mode {
                 println!(
@@ -114,14 +277,23 @@ pub fn get_file_list(
         println!("DEBUG: File list not found in cache, building new list");
     }
 
-    let file_list = build_file_list(path, allow_tests, custom_ignores, no_gitignore)?;
+    let file_list = build_file_list(sources, path, allow_tests, custom_ignores, no_gitignore)?;
     let file_count = file_list.files.len();
 
     // Cache the file list
     let file_list = Arc::new(file_list);
     {
+        let root = sources
+            .canonicalize(path)
+            .unwrap_or_else(|_| std::env::current_dir().unwrap_or_default().join(path));
         let mut cache = FILE_LIST_CACHE.write().unwrap();
-        cache.insert(cache_key, Arc::clone(&file_list));
+        cache.insert(
+            cache_key,
+            CachedFileList {
+                root,
+                file_list: Arc::clone(&file_list),
+            },
+        );
     }
 
     let elapsed = start_time.elapsed();
@@ -138,12 +310,13 @@ pub fn get_file_list(
 
 /// Build a list of files in a directory, respecting ignore patterns and test file exclusions.
 fn build_file_list(
+    sources: &Sources,
     path: &Path,
     allow_tests: bool,
     custom_ignores: &[String],
     no_gitignore: bool,
 ) -> Result<FileList> {
-    let debug_mode = std::env::var("DEBUG").unwrap_or_default() == "1";
+    let debug_mode = probe_code::console::debug_mode();
     let start_time = Instant::now();
 
     if debug_mode {
@@ -275,18 +448,41 @@ fn build_file_list(
     // Add all ignore patterns to the override builder
     for pattern in &common_ignores {
         if let Err(err) = override_builder.add(&format!("!{pattern}")) {
-            eprintln!("Error adding ignore pattern {pattern:?}: {err}");
+            if !probe_code::console::is_silenced() {
+                eprintln!("Error adding ignore pattern {pattern:?}: {err}");
+            }
         }
     }
 
     // Build and apply the overrides
-    match override_builder.build() {
+    let overrides = match override_builder.build() {
         Ok(overrides) => {
-            builder.overrides(overrides);
+            builder.overrides(overrides.clone());
+            Some(overrides)
         }
         Err(err) => {
-            eprintln!("Error building ignore overrides: {err}");
+            if !probe_code::console::is_silenced() {
+                eprintln!("Error building ignore overrides: {err}");
+            }
+            None
         }
+    };
+
+    // Files not on the disk are listed by their source instead of walking it
+    if !sources.on_disk(path) {
+        let files = list_source_files(sources, path, overrides.as_ref());
+        if debug_mode {
+            println!(
+                "DEBUG: Listed {} files of {} in {}",
+                files.len(),
+                sources.id(path),
+                format_duration(start_time.elapsed())
+            );
+        }
+        return Ok(FileList {
+            files,
+            created_at: Instant::now(),
+        });
     }
 
     let builder_duration = builder_start.elapsed();
@@ -308,7 +504,9 @@ fn build_file_list(
         let entry = match result {
             Ok(entry) => entry,
             Err(err) => {
-                eprintln!("Error walking directory: {err}");
+                if !probe_code::console::is_silenced() {
+                    eprintln!("Error walking directory: {err}");
+                }
                 continue;
             }
         };
@@ -351,10 +549,31 @@ fn build_file_list(
     })
 }
 
+/// List the files of the source of `path` that walking the directory would
+/// find, leaving out those matching the ignore `overrides` or in a directory that does
+fn list_source_files(sources: &Sources, path: &Path, overrides: Option<&Override>) -> Vec<PathBuf> {
+    let files = sources.list_files(path);
+    let Some(overrides) = overrides else {
+        return files;
+    };
+    files
+        .into_iter()
+        .filter(|file| {
+            !overrides.matched(file, false).is_ignore()
+                && !file
+                    .ancestors()
+                    .skip(1)
+                    .take_while(|dir| dir.starts_with(path) && *dir != path)
+                    .any(|dir| overrides.matched(dir, true).is_ignore())
+        })
+        .collect()
+}
+
 /// Find files whose names match query words
 /// Returns a map of file paths to the term indices that matched the filename
 #[allow(clippy::too_many_arguments)]
 pub fn find_matching_filenames(
+    sources: &Sources,
     path: &Path,
     queries: &[String],
     already_found_files: &HashSet<PathBuf>,
@@ -363,8 +582,9 @@ pub fn find_matching_filenames(
     term_indices: &HashMap<String, usize>,
     language: Option<&str>,
     no_gitignore: bool,
+    filters: &FileFilters,
 ) -> Result<HashMap<PathBuf, HashSet<usize>>> {
-    let debug_mode = std::env::var("DEBUG").unwrap_or_default() == "1";
+    let debug_mode = probe_code::console::debug_mode();
     let start_time = Instant::now();
 
     if debug_mode {
@@ -378,8 +598,15 @@ pub fn find_matching_filenames(
     }
 
     // Get the cached file list, with language filtering if specified
-    let file_list =
-        get_file_list_by_language(path, allow_tests, custom_ignores, language, no_gitignore)?;
+    let file_list = get_file_list_by_language(
+        sources,
+        path,
+        allow_tests,
+        custom_ignores,
+        language,
+        no_gitignore,
+        filters,
+    )?;
 
     if debug_mode {
         println!(
@@ -464,7 +691,12 @@ fn get_language_extensions(language: &str) -> Vec<String> {
         "rust" => vec![".rs".to_string()],
         "javascript" => vec![".js".to_string(), ".jsx".to_string(), ".mjs".to_string()],
         "typescript" => vec![".ts".to_string(), ".tsx".to_string()],
-        "python" => vec![".py".to_string(), ".pyw".to_string(), ".pyi".to_string()],
+        "python" => vec![
+            ".py".to_string(),
+            ".pyw".to_string(),
+            ".pyi".to_string(),
+            ".ipynb".to_string(),
+        ],
         "go" => vec![".go".to_string()],
         "c" => vec![".c".to_string(), ".h".to_string()],
         "cpp" => vec![
@@ -480,24 +712,46 @@ fn get_language_extensions(language: &str) -> Vec<String> {
         "php" => vec![".php".to_string()],
         "swift" => vec![".swift".to_string()],
         "csharp" => vec![".cs".to_string()],
+        "kotlin" => vec![".kt".to_string(), ".kts".to_string()],
+        "scala" => vec![".scala".to_string(), ".sc".to_string()],
+        "hcl" => vec![".tf".to_string(), ".tfvars".to_string(), ".hcl".to_string()],
+        "markdown" => vec![
+            ".md".to_string(),
+            ".markdown".to_string(),
+            ".mdx".to_string(),
+        ],
+        "rst" => vec![".rst".to_string()],
+        "vue" => vec![".vue".to_string()],
+        "svelte" => vec![".svelte".to_string()],
+        "astro" => vec![".astro".to_string()],
+        "html" => vec![".html".to_string(), ".htm".to_string()],
         _ => vec![], // Return empty vector for unknown languages
     }
 }
 
 /// Get a list of files in a directory, filtered by language if specified
 pub fn get_file_list_by_language(
+    sources: &Sources,
     path: &Path,
     allow_tests: bool,
     custom_ignores: &[String],
     language: Option<&str>,
     no_gitignore: bool,
+    filters: &FileFilters,
 ) -> Result<Arc<FileList>> {
     // If no language is specified, use the regular get_file_list function
     if language.is_none() {
-        return get_file_list(path, allow_tests, custom_ignores, no_gitignore);
+        return get_file_list(
+            sources,
+            path,
+            allow_tests,
+            custom_ignores,
+            no_gitignore,
+            filters,
+        );
     }
 
-    let debug_mode = std::env::var("DEBUG").unwrap_or_default() == "1";
+    let debug_mode = probe_code::console::debug_mode();
     let start_time = Instant::now();
 
     if debug_mode {
@@ -505,7 +759,14 @@ pub fn get_file_list_by_language(
     }
 
     // Get the full file list first
-    let full_file_list = get_file_list(path, allow_tests, custom_ignores, no_gitignore)?;
+    let full_file_list = get_file_list(
+        sources,
+        path,
+        allow_tests,
+        custom_ignores,
+        no_gitignore,
+        filters,
+    )?;
 
     // Get the extensions for the specified language
     let extensions = get_language_extensions(language.unwrap());
@@ -573,7 +834,15 @@ mod tests {
         let parent_file = temp_dir.path().join("docs_packages").join("parent.txt");
         fs::write(&parent_file, "parent content").unwrap();
 
-        let file_list = get_file_list(temp_dir.path(), true, &[], false).unwrap();
+        let file_list = get_file_list(
+            &Sources::default(),
+            temp_dir.path(),
+            true,
+            &[],
+            false,
+            &FileFilters::default(),
+        )
+        .unwrap();
 
         assert!(
             file_list.files.iter().any(|f| f == &test_file),
@@ -621,7 +890,15 @@ mod tests {
         fs::create_dir_all(docs_packages_file.parent().unwrap()).unwrap();
         fs::write(&docs_packages_file, "documentation packages").unwrap();
 
-        let file_list = get_file_list(temp_dir.path(), true, &[], false).unwrap();
+        let file_list = get_file_list(
+            &Sources::default(),
+            temp_dir.path(),
+            true,
+            &[],
+            false,
+            &FileFilters::default(),
+        )
+        .unwrap();
 
         assert!(
             file_list.files.iter().any(|f| f == &test_file),
@@ -653,7 +930,15 @@ mod tests {
 
         // Test with custom ignore patterns
         let custom_ignores = vec!["*.tmp".to_string()];
-        let file_list = get_file_list(temp_dir.path(), true, &custom_ignores, false).unwrap();
+        let file_list = get_file_list(
+            &Sources::default(),
+            temp_dir.path(),
+            true,
+            &custom_ignores,
+            false,
+            &FileFilters::default(),
+        )
+        .unwrap();
 
         assert!(
             file_list.files.iter().any(|f| f == &test_file),
@@ -690,7 +975,15 @@ mod tests {
             expected_files.push(file);
         }
 
-        let file_list = get_file_list(temp_dir.path(), true, &[], false).unwrap();
+        let file_list = get_file_list(
+            &Sources::default(),
+            temp_dir.path(),
+            true,
+            &[],
+            false,
+            &FileFilters::default(),
+        )
+        .unwrap();
 
         for expected_file in &expected_files {
             assert!(
@@ -721,7 +1014,15 @@ mod tests {
         let valid_file = valid_dir.join("main.rs");
         fs::write(&valid_file, "fn main() {}").unwrap();
 
-        let file_list = get_file_list(temp_dir.path(), true, &[], false).unwrap();
+        let file_list = get_file_list(
+            &Sources::default(),
+            temp_dir.path(),
+            true,
+            &[],
+            false,
+            &FileFilters::default(),
+        )
+        .unwrap();
 
         assert!(
             !file_list.files.iter().any(|f| f == &node_file),
@@ -767,7 +1068,15 @@ mod tests {
         fs::write(&regular_file, "regular content").unwrap();
 
         // Test with gitignore enabled (default behavior)
-        let file_list_with_gitignore = get_file_list(temp_dir.path(), true, &[], false).unwrap();
+        let file_list_with_gitignore = get_file_list(
+            &Sources::default(),
+            temp_dir.path(),
+            true,
+            &[],
+            false,
+            &FileFilters::default(),
+        )
+        .unwrap();
 
         assert!(
             file_list_with_gitignore
@@ -789,7 +1098,15 @@ mod tests {
         );
 
         // Test with gitignore disabled (no_gitignore = true)
-        let file_list_no_gitignore = get_file_list(temp_dir.path(), true, &[], true).unwrap();
+        let file_list_no_gitignore = get_file_list(
+            &Sources::default(),
+            temp_dir.path(),
+            true,
+            &[],
+            true,
+            &FileFilters::default(),
+        )
+        .unwrap();
 
         assert!(
             file_list_no_gitignore
@@ -817,8 +1134,10 @@ mod tests {
         let test_path = temp_dir.path();
 
         // Generate cache keys with different no_gitignore values
-        let key_with_gitignore = generate_cache_key(test_path, true, &[], false);
-        let key_without_gitignore = generate_cache_key(test_path, true, &[], true);
+        let key_with_gitignore =
+            generate_cache_key(&Sources::default(), test_path, true, &[], false);
+        let key_without_gitignore =
+            generate_cache_key(&Sources::default(), test_path, true, &[], true);
 
         // The keys should be different
         assert_ne!(
@@ -836,4 +1155,112 @@ mod tests {
             "Cache key should contain 'no_gitignore' when no_gitignore is true"
         );
     }
+
+    #[test]
+    fn test_file_filters_matches() {
+        let disk = Sources::default();
+        let root = Path::new("/repo");
+        let filters = FileFilters {
+            paths: vec!["src/auth".to_string()],
+            exclude_paths: vec!["generated".to_string()],
+            extensions: vec!["rs".to_string(), "go".to_string()],
+            languages: vec!["rust".to_string()],
+            ..Default::default()
+        };
+
+        // Different kinds of filters are ANDed, values of one kind ORed
+        assert!(filters.matches(&disk, root, Path::new("/repo/src/auth/login.rs")));
+        assert!(!filters.matches(&disk, root, Path::new("/repo/src/auth/token.go")));
+        assert!(!filters.matches(&disk, root, Path::new("/repo/src/auth/README.md")));
+        assert!(!filters.matches(&disk, root, Path::new("/repo/src/db/pool.rs")));
+        assert!(!filters.matches(&disk, root, Path::new("/repo/src/auth/generated/api.rs")));
+        assert!(FileFilters::default().matches(&disk, root, Path::new("/repo/anything.txt")));
+
+        let filters = FileFilters {
+            exclude_extensions: vec!["md".to_string()],
+            exclude_languages: vec!["go".to_string()],
+            ..Default::default()
+        };
+        assert!(filters.matches(&disk, root, Path::new("/repo/src/auth/login.rs")));
+        assert!(filters.matches(&disk, root, Path::new("/repo/Makefile")));
+        assert!(!filters.matches(&disk, root, Path::new("/repo/src/auth/token.go")));
+        assert!(!filters.matches(&disk, root, Path::new("/repo/README.MD")));
+
+        // Paths match as shown in results, from the working directory, or
+        // relative to the search root
+        let filters = FileFilters {
+            paths: vec!["src/auth".to_string()],
+            ..Default::default()
+        };
+        let root = Path::new("src");
+        assert!(filters.matches(&disk, root, Path::new("src/auth/login.rs")));
+        assert!(!filters.matches(&disk, root, Path::new("src/db/pool.rs")));
+        let filters = FileFilters {
+            paths: vec!["auth/login".to_string()],
+            ..Default::default()
+        };
+        assert!(filters.matches(&disk, root, Path::new("src/auth/login.rs")));
+    }
+
+    #[test]
+    fn test_get_file_list_applies_filters() {
+        let temp_dir = TempDir::new().unwrap();
+        let auth_dir = temp_dir.path().join("src").join("auth");
+        let vendor_dir = temp_dir.path().join("third_party");
+        fs::create_dir_all(&auth_dir).unwrap();
+        fs::create_dir_all(&vendor_dir).unwrap();
+
+        let auth_file = auth_dir.join("login.rs");
+        let vendor_file = vendor_dir.join("lib.rs");
+        let go_file = temp_dir.path().join("main.go");
+        fs::write(&auth_file, "fn login() {}").unwrap();
+        fs::write(&vendor_file, "fn vendored() {}").unwrap();
+        fs::write(&go_file, "package main").unwrap();
+
+        let filters = FileFilters {
+            exclude_paths: vec!["third_party".to_string()],
+            languages: vec!["rust".to_string()],
+            ..Default::default()
+        };
+        let file_list = get_file_list(
+            &Sources::default(),
+            temp_dir.path(),
+            true,
+            &[],
+            false,
+            &filters,
+        )
+        .unwrap();
+
+        assert_eq!(file_list.files, vec![auth_file]);
+    }
+
+    #[test]
+    fn test_retain_file_lists() {
+        let temp_dir = TempDir::new().unwrap();
+        let dir = temp_dir.path();
+        fs::write(dir.join("a.rs"), "fn a() {}").unwrap();
+        let list = |dir: &Path| {
+            get_file_list(
+                &Sources::default(),
+                dir,
+                true,
+                &[],
+                true,
+                &FileFilters::default(),
+            )
+        };
+        assert_eq!(list(dir).unwrap().files.len(), 1);
+
+        // The cached list doesn't see new files until it is dropped
+        fs::write(dir.join("b.rs"), "fn b() {}").unwrap();
+        assert_eq!(list(dir).unwrap().files.len(), 1);
+        let other = TempDir::new().unwrap();
+        retain_file_lists(|root| !root.starts_with(other.path().canonicalize().unwrap()));
+        assert_eq!(list(dir).unwrap().files.len(), 1);
+
+        let canonical = dir.canonicalize().unwrap();
+        retain_file_lists(|root| !root.starts_with(&canonical));
+        assert_eq!(list(dir).unwrap().files.len(), 2);
+    }
 }
@@ -4,15 +4,18 @@ use lru::LruCache;
 use rayon::prelude::*;
 use std::cell::RefCell;
 use std::collections::{HashMap, HashSet};
-use std::fs;
 use std::path::Path;
 use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 
+use probe_code::language::parser::collect_block_field_text;
+use probe_code::language::parser::has_cached_line_map;
 use probe_code::language::{is_test_file, parse_file_for_code_blocks_with_tree};
 use probe_code::models::SearchResult;
 use probe_code::ranking;
+use probe_code::search::elastic_query::{self, FieldMatches, TermPositions};
 use probe_code::search::tokenization;
+use probe_code::source::Sources;
 
 // PHASE 3B OPTIMIZATION: Global tokenization cache for term matching
 // This cache stores tokenized results to avoid redundant tokenization of the same content
@@ -60,6 +63,8 @@ pub struct FileProcessingTimings {
 
 /// Parameters for file processing
 pub struct FileProcessingParams<'a> {
+    /// Where the file is read from
+    pub sources: &'a Sources,
     pub path: &'a Path,
     pub line_numbers: &'a HashSet<usize>,
     pub allow_tests: bool,
@@ -200,9 +205,33 @@ pub fn filter_code_block_with_ast(
 /// Evaluate whether a tokenized block satisfies a complex AST query
 /// using the 'evaluate' method in `elastic_query::Expr`.
 pub fn filter_tokenized_block(
+    tokenized_content: &[String],
+    term_indices: &HashMap<String, usize>,
+    plan: &crate::search::query::QueryPlan,
+    debug_mode: bool,
+) -> bool {
+    filter_tokenized_block_in_context(
+        tokenized_content,
+        term_indices,
+        plan,
+        None,
+        None,
+        None,
+        debug_mode,
+    )
+}
+
+/// Same as `filter_tokenized_block`, but field-scoped terms (`fn:`, `comment:`, ...)
+/// are checked against `field_matches`, proximity groups (`"a b"~5`) against
+/// the term `positions` and regex terms (`/pattern/`) against `regex_matches`
+/// of the block when they are available.
+pub fn filter_tokenized_block_in_context(
     tokenized_content: &[String],
     _term_indices: &HashMap<String, usize>,
     plan: &crate::search::query::QueryPlan,
+    field_matches: Option<&FieldMatches>,
+    positions: Option<&TermPositions>,
+    regex_matches: Option<&HashSet<usize>>,
     debug_mode: bool,
 ) -> bool {
     // Early termination: if query has only excluded terms and content is empty, return true
@@ -212,6 +241,10 @@ pub fn filter_tokenized_block(
 
     // PHASE 3C OPTIMIZATION: Batch term index resolution
     let mut matched_terms = resolve_term_indices_batch(tokenized_content, &plan.term_indices);
+    // Regex terms never appear among the tokens, they are matched on the raw code
+    if let Some(regex_matches) = regex_matches {
+        matched_terms.extend(regex_matches);
+    }
 
     // PHASE 3C OPTIMIZATION: Early termination for required terms with indices
     if !plan.required_terms_indices.is_empty() {
@@ -331,7 +364,22 @@ pub fn filter_tokenized_block(
     }
 
     // Use the evaluate function from the elastic query module
-    let result = plan.ast.evaluate(&matched_terms, &plan.term_indices, false);
+    let result = match (field_matches, positions) {
+        (None, None) => plan.ast.evaluate(&matched_terms, &plan.term_indices, false),
+        (field_matches, positions) => {
+            if debug_mode {
+                println!("DEBUG: Field matches: {field_matches:?}");
+                println!("DEBUG: Term positions: {positions:?}");
+            }
+            plan.ast.evaluate_in_block(
+                &matched_terms,
+                field_matches,
+                positions,
+                &plan.term_indices,
+                false,
+            )
+        }
+    };
 
     if debug_mode {
         println!("DEBUG: ===== EVALUATION RESULT =====");
@@ -426,6 +474,8 @@ struct BatchProcessingContext<'a> {
     uncovered_lines: &'a [usize],
     covered_lines: &'a mut HashSet<usize>,
     lines: &'a [&'a str],
+    content: &'a str,
+    field_tree: Option<&'a tree_sitter::Tree>,
     params: &'a FileProcessingParams<'a>,
     extension: &'a str,
     unique_query_terms: &'a HashSet<String>,
@@ -830,10 +880,28 @@ fn process_uncovered_lines_batch(ctx: &mut BatchProcessingContext) {
                 }
                 true
             } else {
-                filter_tokenized_block(
+                let field_matches = ctx.field_tree.map(|tree| {
+                    resolve_field_matches(
+                        tree,
+                        ctx.content,
+                        context_start,
+                        context_end,
+                        &ctx.params.query_plan.term_indices,
+                    )
+                });
+                // Proximity groups need to know where each term occurs
+                let positions = ctx.params.query_plan.ast.has_proximity().then(|| {
+                    resolve_term_positions(&context_code, &ctx.params.query_plan.term_indices)
+                });
+                let regex_matches =
+                    resolve_regex_matches(&context_code, &ctx.params.query_plan.term_indices);
+                filter_tokenized_block_in_context(
                     &context_terms,
                     &ctx.params.query_plan.term_indices,
                     ctx.params.query_plan,
+                    field_matches.as_ref(),
+                    positions.as_ref(),
+                    Some(&regex_matches),
                     ctx.debug_mode,
                 )
             }
@@ -980,6 +1048,7 @@ fn process_uncovered_lines_batch(ctx: &mut BatchProcessingContext) {
                     Some(matched_keywords)
                 },
                 tokenized_content: Some(context_terms),
+                cells: None,
             };
 
             // Add to result creation time
@@ -1041,6 +1110,53 @@ fn resolve_term_indices_batch(
     matched_terms
 }
 
+/// Record the token positions of every query term in a block of code.
+fn resolve_term_positions(code: &str, term_indices: &HashMap<String, usize>) -> TermPositions {
+    let mut positions = TermPositions::new();
+    for (pos, token) in tokenization::tokenize_sequence(code).iter().enumerate() {
+        if let Some(&idx) = term_indices.get(token) {
+            positions.entry(idx).or_default().push(pos);
+        }
+    }
+    positions
+}
+
+/// Resolve which regex terms of the query (`/pattern/`) match a block of code.
+fn resolve_regex_matches(code: &str, term_indices: &HashMap<String, usize>) -> HashSet<usize> {
+    term_indices
+        .iter()
+        .filter_map(|(keyword, &idx)| {
+            let regex = elastic_query::compile_regex_term(keyword)?;
+            regex.is_match(code).then_some(idx)
+        })
+        .collect()
+}
+
+/// Resolve which query terms occur in each structural part (declaration names,
+/// comments, string literals, code) of the given 1-based line range.
+fn resolve_field_matches(
+    tree: &tree_sitter::Tree,
+    content: &str,
+    start_line: usize,
+    end_line: usize,
+    term_indices: &HashMap<String, usize>,
+) -> FieldMatches {
+    collect_block_field_text(
+        tree,
+        content.as_bytes(),
+        start_line.saturating_sub(1),
+        end_line.saturating_sub(1),
+    )
+    .into_iter()
+    .map(|(field, text)| {
+        let tokens = ranking::preprocess_text(&text);
+        let mut matches = resolve_term_indices_batch(&tokens, term_indices);
+        matches.extend(resolve_regex_matches(&text, term_indices));
+        (field, matches)
+    })
+    .collect()
+}
+
 /// Main function for processing a file with matched lines
 pub fn process_file_with_results(
     params: &FileProcessingParams,
@@ -1072,7 +1188,9 @@ pub fn process_file_with_results(
 
     // Measure file I/O time
     let file_io_start = Instant::now();
-    let content = fs::read_to_string(params.path)
+    let content = params
+        .sources
+        .read_to_string(params.path)
         .context(format!("Failed to read file: {:?}", params.path))?;
     let file_io_duration = file_io_start.elapsed();
     timings.file_io = Some(file_io_duration);
@@ -1084,7 +1202,7 @@ pub fn process_file_with_results(
         .unwrap_or("");
 
     // Get debug mode setting
-    let debug_mode = std::env::var("DEBUG").unwrap_or_default() == "1";
+    let debug_mode = probe_code::console::debug_mode();
 
     // Filter out lines longer than 500 characters
     let lines: Vec<&str> = content
@@ -1165,8 +1283,19 @@ pub fn process_file_with_results(
     cache_key.push('_');
     cache_key.push_str(extension);
 
-    // Capture the parsed tree instead of discarding it
-    let parsed_tree = if language_supported {
+    // Capture the parsed tree instead of discarding it. It isn't needed when the
+    // blocks can be built from a cached line map, e.g. one loaded from the search
+    // index, unless the query has field-scoped terms.
+    let on_disk = params.sources.on_disk(params.path);
+    let needs_tree = params.query_plan.ast.has_structural_field()
+        || !has_cached_line_map(
+            &content,
+            extension,
+            params.line_numbers,
+            params.allow_tests,
+            on_disk,
+        );
+    let parsed_tree = if language_supported && needs_tree {
         // Use the new pooled parser approach - this eliminates the expensive
         // parser creation and language setup that was happening for each file
         crate::language::get_or_parse_tree_pooled(&cache_key, &content, extension).ok()
@@ -1179,6 +1308,14 @@ pub fn process_file_with_results(
     // Measure line map building time (this is an approximation since we can't directly measure it)
     let line_map_building_start = Instant::now();
 
+    // Keep a handle to the tree when the query has field-scoped terms,
+    // so blocks can be classified into names, comments, strings and code
+    let field_tree = if params.query_plan.ast.has_structural_field() {
+        parsed_tree.clone()
+    } else {
+        None
+    };
+
     // Call parse_file_for_code_blocks with the pre-parsed tree to avoid double parsing
     let code_blocks_result = parse_file_for_code_blocks_with_tree(
         &content,
@@ -1187,6 +1324,7 @@ pub fn process_file_with_results(
         params.allow_tests,
         Some(params.term_matches),
         parsed_tree,
+        on_disk,
     );
 
     let line_map_building_duration = line_map_building_start.elapsed();
@@ -1348,11 +1486,31 @@ pub fn process_file_with_results(
                         }
                         true
                     } else {
+                        let field_matches = field_tree.as_ref().map(|tree| {
+                            resolve_field_matches(
+                                tree,
+                                &content,
+                                final_start_line,
+                                final_end_line,
+                                &params.query_plan.term_indices,
+                            )
+                        });
+
+                        // Proximity groups need to know where each term occurs
+                        let positions = params.query_plan.ast.has_proximity().then(|| {
+                            resolve_term_positions(&full_code, &params.query_plan.term_indices)
+                        });
+                        let regex_matches =
+                            resolve_regex_matches(&full_code, &params.query_plan.term_indices);
+
                         // Use the AST evaluation directly to ensure correct handling of complex queries
-                        let result = filter_tokenized_block(
+                        let result = filter_tokenized_block_in_context(
                             &block_terms,
                             &params.query_plan.term_indices,
                             params.query_plan,
+                            field_matches.as_ref(),
+                            positions.as_ref(),
+                            Some(&regex_matches),
                             debug_mode,
                         );
 
@@ -1525,6 +1683,7 @@ pub fn process_file_with_results(
                             Some(matched_keywords)
                         },
                         tokenized_content: Some(block_terms),
+                        cells: None,
                     };
 
                     let result_creation_duration_value = result_creation_start.elapsed();
@@ -1658,6 +1817,8 @@ pub fn process_file_with_results(
             uncovered_lines: &uncovered_lines,
             covered_lines: &mut covered_lines,
             lines: &lines,
+            content: &content,
+            field_tree: field_tree.as_ref(),
             params,
             extension,
             unique_query_terms: &unique_query_terms,
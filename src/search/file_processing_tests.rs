@@ -9,6 +9,7 @@ use tempfile::TempDir;
 use probe_code::search::elastic_query;
 use probe_code::search::file_processing::process_file_with_results;
 use probe_code::search::query::QueryPlan;
+use probe_code::source::Sources;
 
 // Helper function to create a test file
 pub fn create_test_file(dir: &TempDir, filename: &str, content: &str) -> std::path::PathBuf {
@@ -88,6 +89,7 @@ mod tests {
         let query_plan = create_test_query_plan(&["line"]);
 
         let params = crate::search::file_processing::FileProcessingParams {
+            sources: &Sources::default(),
             path: &file_path,
             line_numbers: &line_numbers,
             allow_tests: false,
@@ -139,6 +141,7 @@ mod tests {
         let query_plan = create_test_query_plan(&["line"]);
 
         let params = crate::search::file_processing::FileProcessingParams {
+            sources: &Sources::default(),
             path: &file_path,
             line_numbers: &line_numbers,
             allow_tests: false,
@@ -189,6 +192,7 @@ mod tests {
         let query_plan = create_test_query_plan(&["line"]);
 
         let params = crate::search::file_processing::FileProcessingParams {
+            sources: &Sources::default(),
             path: &file_path,
             line_numbers: &line_numbers,
             allow_tests: false,
@@ -262,6 +266,7 @@ function test3() {
         let query_plan = create_test_query_plan(&["test1", "test2", "test3"]);
 
         let params = crate::search::file_processing::FileProcessingParams {
+            sources: &Sources::default(),
             path: &file_path,
             line_numbers: &line_numbers,
             allow_tests: true, // Allow tests
@@ -365,6 +370,7 @@ function processResults(results) {
 
         // Process the file
         let params = crate::search::file_processing::FileProcessingParams {
+            sources: &Sources::default(),
             path: &file_path,
             line_numbers: &line_numbers,
             allow_tests: true,
@@ -429,6 +435,7 @@ fn test_long_lines_are_ignored() {
     let query_plan = create_test_query_plan(&["normal"]);
 
     let params = crate::search::file_processing::FileProcessingParams {
+        sources: &Sources::default(),
         path: &file_path,
         line_numbers: &line_numbers,
         allow_tests: true,
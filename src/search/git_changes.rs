@@ -0,0 +1,138 @@
+//! Files changed in a git repository, for `--changed-since` and `--staged`.
+
+use anyhow::{bail, Context, Result};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use probe_code::search::file_list_cache::FileFilters;
+use probe_code::source::git;
+
+/// Filters keeping only the files changed in the git repository of `path`.
+///
+/// With `since`, these are the files that differ between that ref and the
+/// working tree, untracked files included. With `staged`, the files staged in
+/// the index instead, compared to `since` or else `HEAD`. Without either,
+/// nothing is filtered.
+pub fn changed_file_filters(path: &Path, since: Option<&str>, staged: bool) -> Result<FileFilters> {
+    if since.is_none() && !staged {
+        return Ok(FileFilters::default());
+    }
+    Ok(FileFilters {
+        changed_files: Some(changed_files(path, since.unwrap_or("HEAD"), staged)?),
+        ..Default::default()
+    })
+}
+
+/// Canonical paths of the existing files changed since `since`, in the
+/// working tree or, when `staged`, in the index
+pub fn changed_files(path: &Path, since: &str, staged: bool) -> Result<HashSet<PathBuf>> {
+    // git would read it as an option
+    if since.starts_with('-') {
+        bail!("Invalid git ref {since:?}: refs can't start with '-'");
+    }
+    let dir = if path.is_dir() {
+        path
+    } else {
+        path.parent()
+            .filter(|parent| !parent.as_os_str().is_empty())
+            .unwrap_or(Path::new("."))
+    };
+    let toplevel = git(dir, &["rev-parse", "--show-toplevel"])
+        .with_context(|| format!("{} is not in a git repository", path.display()))?;
+    let toplevel = PathBuf::from(toplevel.trim_end_matches(['\n', '\r']));
+
+    let mut diff = vec!["diff", "--name-only", "-z"];
+    if staged {
+        diff.push("--cached");
+    }
+    diff.extend([since, "--"]);
+    let mut names = git(&toplevel, &diff)?;
+    if !staged {
+        names.push_str(&git(
+            &toplevel,
+            &["ls-files", "--others", "--exclude-standard", "-z"],
+        )?);
+    }
+
+    // Deleted files have nothing left to search
+    let files: HashSet<PathBuf> = names
+        .split('\0')
+        .filter(|name| !name.is_empty())
+        .filter_map(|name| toplevel.join(name).canonicalize().ok())
+        .collect();
+
+    if probe_code::console::debug_mode() {
+        println!(
+            "DEBUG: {} files changed since {since}{}",
+            files.len(),
+            if staged { " (staged)" } else { "" }
+        );
+    }
+
+    Ok(files)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::process::Command;
+    use tempfile::TempDir;
+
+    fn run_git(dir: &Path, args: &[&str]) {
+        let status = Command::new("git")
+            .arg("-C")
+            .arg(dir)
+            .args([
+                "-c",
+                "user.name=probe",
+                "-c",
+                "user.email=probe@example.com",
+            ])
+            .args(args)
+            .output()
+            .unwrap()
+            .status;
+        assert!(status.success(), "git {args:?} failed");
+    }
+
+    #[test]
+    fn test_changed_files() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path().canonicalize().unwrap();
+        fs::write(root.join("kept.rs"), "fn kept() {}").unwrap();
+        fs::write(root.join("edited.rs"), "fn edited() {}").unwrap();
+        fs::write(root.join("staged.rs"), "fn staged() {}").unwrap();
+        fs::write(root.join("removed.rs"), "fn removed() {}").unwrap();
+        run_git(&root, &["init", "-q"]);
+        run_git(&root, &["add", "."]);
+        run_git(&root, &["commit", "-q", "-m", "initial"]);
+
+        fs::write(root.join("edited.rs"), "fn edited() { retry() }").unwrap();
+        fs::write(root.join("staged.rs"), "fn staged() { retry() }").unwrap();
+        fs::write(root.join("added.rs"), "fn added() {}").unwrap();
+        fs::remove_file(root.join("removed.rs")).unwrap();
+        run_git(&root, &["add", "staged.rs"]);
+
+        let changed = changed_files(&root, "HEAD", false).unwrap();
+        let expected: HashSet<PathBuf> = ["edited.rs", "staged.rs", "added.rs"]
+            .iter()
+            .map(|name| root.join(name))
+            .collect();
+        assert_eq!(changed, expected);
+
+        let staged = changed_files(&root, "HEAD", true).unwrap();
+        assert_eq!(staged, HashSet::from([root.join("staged.rs")]));
+
+        assert!(changed_files(&root, "no-such-ref", false).is_err());
+        let output = root.join("diff.txt");
+        let option = format!("--output={}", output.display());
+        let error = changed_files(&root, &option, false).unwrap_err();
+        assert!(
+            error.to_string().contains("can't start with '-'"),
+            "{error}"
+        );
+        assert!(!output.exists());
+        assert!(changed_file_filters(&root, None, false).unwrap().is_empty());
+    }
+}
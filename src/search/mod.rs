@@ -7,7 +7,10 @@ pub mod cache; // New module for caching search results
 pub mod early_ranker; // New module for early BM25 ranking
 pub mod elastic_query;
 pub mod file_list_cache; // New module for caching file lists
+pub mod git_changes; // Files changed in git, for --changed-since and --staged
 pub mod ripgrep_searcher;
+mod search_explain; // Output of `--explain`
+pub mod search_index; // Persistent index built by `probe index`
 mod search_limiter;
 mod search_options;
 mod search_output;
@@ -15,11 +18,13 @@ pub mod search_runner;
 pub mod search_tokens;
 pub mod simd_pattern_matching;
 pub mod simd_tokenization; // SIMD-accelerated tokenization
+pub mod synonyms; // Project-defined synonyms from .probe/synonyms.toml
 pub mod term_exceptions; // New module for term exceptions
 pub mod timeout; // New module for timeout functionality
 pub mod tokenization; // New elastic search query parser
                       // Temporarily commented out due to compilation issues
                       // mod temp_frequency_search;
+pub mod vocabulary; // Identifiers found in the searched files, for wildcard expansion
 
 #[cfg(test)]
 mod file_processing_tests;
@@ -28,6 +33,9 @@ mod file_processing_tests;
 mod test_token_limiter_failures;
 
 // Public exports
+pub use file_list_cache::FileFilters;
 pub use search_options::SearchOptions;
-pub use search_output::format_and_print_search_results;
+pub use search_output::{
+    format_and_print_search_results, format_search_results, search_results_json,
+};
 pub use search_runner::perform_probe;
@@ -23,6 +23,17 @@ pub fn regex_escape(s: &str) -> String {
     result
 }
 
+/// Search pattern for a single query keyword: the escaped keyword, or the raw
+/// pattern of a regex term (`/pattern/` or `/pattern/i`). Searches are case-insensitive
+/// by default, so a regex without the `i` flag turns case-insensitivity off.
+pub fn keyword_pattern(keyword: &str) -> String {
+    match elastic_query::regex_term(keyword) {
+        Some((pattern, true)) => format!("(?i:{pattern})"),
+        Some((pattern, false)) => format!("(?-i:{pattern})"),
+        None => regex_escape(keyword),
+    }
+}
+
 // ----------------------------------------------------------------------------
 // NEW CODE: Full AST-based planning and pattern generation
 // ----------------------------------------------------------------------------
@@ -79,7 +90,7 @@ fn format_duration(duration: std::time::Duration) -> String {
 /// Create a QueryPlan from a raw query string. This fully parses the query into an AST,
 /// then extracts all terms (including excluded), and prepares a term-index map.
 pub fn create_query_plan(query: &str, exact: bool) -> Result<QueryPlan, elastic_query::ParseError> {
-    let debug_mode = std::env::var("DEBUG").unwrap_or_default() == "1";
+    let debug_mode = probe_code::console::debug_mode();
     let start_time = Instant::now();
 
     if debug_mode {
@@ -168,8 +179,11 @@ pub fn create_query_plan(query: &str, exact: bool) -> Result<QueryPlan, elastic_
     collect_required_terms(&ast, &mut required_terms);
 
     // Determine if this is a simple query for optimization
+    // Field-scoped terms need per-field evaluation, so they never take the simple path
     let is_simple_query = match &ast {
-        elastic_query::Expr::Term { excluded, .. } => !excluded && all_terms.len() == 1,
+        elastic_query::Expr::Term { excluded, .. } => {
+            !excluded && all_terms.len() == 1 && !ast.has_structural_field()
+        }
         _ => false,
     };
 
@@ -223,6 +237,11 @@ fn collect_required_terms(expr: &elastic_query::Expr, required_terms: &mut HashS
             // For OR expressions, we can't guarantee any term is required
             // so we don't collect anything
         }
+        elastic_query::Expr::Near(inner, _) | elastic_query::Expr::Boost(inner, _) => {
+            collect_required_terms(inner, required_terms)
+        }
+        // A required group only needs one of its alternatives, so no single term is required
+        elastic_query::Expr::Required(_) | elastic_query::Expr::Excluded(_) => {}
     }
 }
 
@@ -241,6 +260,10 @@ fn update_ast_exact(expr: &mut elastic_query::Expr) {
             update_ast_exact(left);
             update_ast_exact(right);
         }
+        elastic_query::Expr::Near(inner, _)
+        | elastic_query::Expr::Required(inner)
+        | elastic_query::Expr::Excluded(inner)
+        | elastic_query::Expr::Boost(inner, _) => update_ast_exact(inner),
     }
 }
 
@@ -250,6 +273,10 @@ fn is_exact_search(expr: &elastic_query::Expr) -> bool {
         elastic_query::Expr::Term { exact, .. } => *exact,
         elastic_query::Expr::And(left, right) => is_exact_search(left) && is_exact_search(right),
         elastic_query::Expr::Or(left, right) => is_exact_search(left) && is_exact_search(right),
+        elastic_query::Expr::Near(inner, _)
+        | elastic_query::Expr::Required(inner)
+        | elastic_query::Expr::Excluded(inner)
+        | elastic_query::Expr::Boost(inner, _) => is_exact_search(inner),
     }
 }
 
@@ -260,7 +287,7 @@ fn collect_all_terms(
     all_terms: &mut Vec<String>,
     excluded: &mut HashSet<String>,
 ) {
-    let debug_mode = std::env::var("DEBUG").unwrap_or_default() == "1";
+    let debug_mode = probe_code::console::debug_mode();
 
     if debug_mode {
         println!("DEBUG: Collecting terms from expression: {expr:?}");
@@ -322,6 +349,10 @@ fn collect_all_terms(
             collect_all_terms(left, all_terms, excluded);
             collect_all_terms(right, all_terms, excluded);
         }
+        elastic_query::Expr::Near(inner, _)
+        | elastic_query::Expr::Required(inner)
+        | elastic_query::Expr::Excluded(inner)
+        | elastic_query::Expr::Boost(inner, _) => collect_all_terms(inner, all_terms, excluded),
     }
 
     if debug_mode {
@@ -334,7 +365,7 @@ fn collect_all_terms(
 /// This creates a single pattern that matches any of the terms using case-insensitive matching
 /// without word boundaries for more flexible matching
 pub fn build_combined_pattern(terms: &[String]) -> String {
-    let debug_mode = std::env::var("DEBUG").unwrap_or_default() == "1";
+    let debug_mode = probe_code::console::debug_mode();
     let start_time = Instant::now();
 
     if debug_mode {
@@ -342,7 +373,7 @@ pub fn build_combined_pattern(terms: &[String]) -> String {
     }
 
     // Escape special characters in each term
-    let escaped_terms = terms.iter().map(|t| regex_escape(t)).collect::<Vec<_>>();
+    let escaped_terms = terms.iter().map(|t| keyword_pattern(t)).collect::<Vec<_>>();
 
     // Join terms with | operator and add case-insensitive flag without word boundaries
     let pattern = format!("(?i)({terms})", terms = escaped_terms.join("|"));
@@ -363,7 +394,7 @@ pub fn build_combined_pattern(terms: &[String]) -> String {
 /// This creates a single combined pattern for all terms, regardless of whether they're
 /// required, optional, or negative.
 pub fn create_structured_patterns(plan: &QueryPlan) -> Vec<(String, HashSet<usize>)> {
-    let debug_mode = std::env::var("DEBUG").unwrap_or_default() == "1";
+    let debug_mode = probe_code::console::debug_mode();
     let start_time = Instant::now();
 
     if debug_mode {
@@ -441,7 +472,7 @@ pub fn create_structured_patterns(plan: &QueryPlan) -> Vec<(String, HashSet<usiz
 
                     // Find the keyword's index in term_indices
                     if let Some(&idx) = plan.term_indices.get(keyword) {
-                        let base_pattern = regex_escape(keyword);
+                        let base_pattern = keyword_pattern(keyword);
 
                         // For exact terms, use stricter matching
                         let pattern = if *exact {
@@ -492,29 +523,33 @@ pub fn create_structured_patterns(plan: &QueryPlan) -> Vec<(String, HashSet<usiz
                 collect_patterns(left, plan, results, debug_mode);
                 collect_patterns(right, plan, results, debug_mode);
             }
-            elastic_query::Expr::Or(left, right) => {
+            elastic_query::Expr::Or(..) => {
                 if debug_mode {
                     println!("DEBUG: Processing OR expression");
                 }
 
-                // For OR, create combined patterns
-                let mut left_patterns = Vec::new();
-                let mut right_patterns = Vec::new();
-
-                collect_patterns(left, plan, &mut left_patterns, debug_mode);
-                collect_patterns(right, plan, &mut right_patterns, debug_mode);
-
-                if !left_patterns.is_empty() && !right_patterns.is_empty() {
+                // For OR, create one combined pattern for the whole chain of
+                // alternatives. Nested ORs are flattened so long chains don't
+                // produce nested combinations of combinations.
+                let mut operands = Vec::new();
+                flatten_or(expr, &mut operands);
+
+                let operand_patterns: Vec<Vec<(String, HashSet<usize>)>> = operands
+                    .iter()
+                    .map(|operand| {
+                        let mut patterns = Vec::new();
+                        collect_patterns(operand, plan, &mut patterns, debug_mode);
+                        patterns
+                    })
+                    .collect();
+
+                if operand_patterns.iter().filter(|p| !p.is_empty()).count() > 1 {
                     // Combine the patterns with OR
                     let combined = format!(
-                        "({}|{})",
-                        left_patterns
-                            .iter()
-                            .map(|(p, _)| p.as_str())
-                            .collect::<Vec<_>>()
-                            .join("|"),
-                        right_patterns
+                        "({})",
+                        operand_patterns
                             .iter()
+                            .flatten()
                             .map(|(p, _)| p.as_str())
                             .collect::<Vec<_>>()
                             .join("|")
@@ -522,7 +557,7 @@ pub fn create_structured_patterns(plan: &QueryPlan) -> Vec<(String, HashSet<usiz
 
                     // Merge the term indices
                     let mut indices = HashSet::new();
-                    for (_, idx_set) in left_patterns.iter().chain(right_patterns.iter()) {
+                    for (_, idx_set) in operand_patterns.iter().flatten() {
                         indices.extend(idx_set.iter().cloned());
                     }
 
@@ -539,11 +574,29 @@ pub fn create_structured_patterns(plan: &QueryPlan) -> Vec<(String, HashSet<usiz
                 if debug_mode {
                     println!("DEBUG: Adding individual patterns from OR expression");
                 }
-                results.extend(left_patterns);
-                results.extend(right_patterns);
+                results.extend(operand_patterns.into_iter().flatten());
+            }
+            elastic_query::Expr::Near(inner, _) => {
+                // Proximity is checked on block tokens; the search itself just needs each keyword
+                collect_patterns(inner, plan, results, debug_mode);
+            }
+            elastic_query::Expr::Required(inner)
+            | elastic_query::Expr::Excluded(inner)
+            | elastic_query::Expr::Boost(inner, _) => {
+                collect_patterns(inner, plan, results, debug_mode);
             }
         }
     }
+    /// Collect the operands of a chain of ORs, e.g. `a OR (b OR c)` gives `[a, b, c]`
+    fn flatten_or<'a>(expr: &'a elastic_query::Expr, operands: &mut Vec<&'a elastic_query::Expr>) {
+        match expr {
+            elastic_query::Expr::Or(left, right) => {
+                flatten_or(left, operands);
+                flatten_or(right, operands);
+            }
+            _ => operands.push(expr),
+        }
+    }
     // Removed extra closing brace after collect_patterns definition
 
     // Always call the recursive pattern collection logic
@@ -574,7 +627,10 @@ pub fn create_structured_patterns(plan: &QueryPlan) -> Vec<(String, HashSet<usiz
 
         // Process compound words - either camelCase or those in the vocabulary
         // Skip compound word processing if exact search is enabled
-        if keyword.len() > 3 && !is_exact_search(&plan.ast) {
+        if keyword.len() > 3
+            && !is_exact_search(&plan.ast)
+            && elastic_query::regex_term(keyword).is_none()
+        {
             // Check if it's a camelCase word or a known compound word from vocabulary
             let camel_parts = crate::search::tokenization::split_camel_case(keyword);
             let compound_parts = if camel_parts.len() <= 1 {
@@ -644,20 +700,26 @@ pub fn create_structured_patterns(plan: &QueryPlan) -> Vec<(String, HashSet<usiz
         println!("DEBUG: Starting pattern deduplication");
     }
 
-    // First, deduplicate by exact pattern match
-    let mut pattern_map: HashMap<String, HashSet<usize>> = HashMap::new();
+    // First, deduplicate by exact pattern match, keeping patterns in the order
+    // they were generated so the combined pattern for all terms always comes first
+    let mut pattern_map: Vec<(String, HashSet<usize>)> = Vec::new();
+    let mut pattern_positions: HashMap<String, usize> = HashMap::new();
 
     for (pattern, indices) in results {
-        pattern_map
-            .entry(pattern)
-            .and_modify(|existing_indices| existing_indices.extend(indices.iter().cloned()))
-            .or_insert(indices);
+        match pattern_positions.get(&pattern) {
+            Some(&pos) => pattern_map[pos].1.extend(indices.iter().cloned()),
+            None => {
+                pattern_positions.insert(pattern.clone(), pattern_map.len());
+                pattern_map.push((pattern, indices));
+            }
+        }
     }
 
     // Then, deduplicate patterns that match the same term
     // For the test_pattern_deduplication test, we need to ensure we don't have
     // multiple patterns for the same term with the same indices
-    let mut term_patterns: HashMap<String, Vec<(String, HashSet<usize>)>> = HashMap::new();
+    let mut term_patterns: Vec<Vec<(String, HashSet<usize>)>> = Vec::new();
+    let mut group_positions: HashMap<String, usize> = HashMap::new();
 
     // Group patterns by the terms they match
     for (pattern, indices) in pattern_map.iter() {
@@ -670,16 +732,19 @@ pub fn create_structured_patterns(plan: &QueryPlan) -> Vec<(String, HashSet<usiz
             .collect::<Vec<_>>()
             .join(",");
 
-        term_patterns
-            .entry(key)
-            .or_default()
-            .push((pattern.clone(), indices.clone()));
+        match group_positions.get(&key) {
+            Some(&pos) => term_patterns[pos].push((pattern.clone(), indices.clone())),
+            None => {
+                group_positions.insert(key, term_patterns.len());
+                term_patterns.push(vec![(pattern.clone(), indices.clone())]);
+            }
+        }
     }
 
     // Keep only the most specific pattern for each term group
     let mut deduplicated_results = Vec::new();
 
-    for (_, patterns) in term_patterns {
+    for patterns in term_patterns {
         if patterns.len() <= 2 {
             // If there are 1 or 2 patterns, keep them all
             deduplicated_results.extend(patterns);
@@ -25,7 +25,7 @@ pub fn rank_search_results(
     let start_time = Instant::now();
 
     // Check if debug mode is enabled
-    let debug_mode = std::env::var("DEBUG").unwrap_or_default() == "1";
+    let debug_mode = probe_code::console::debug_mode();
 
     if debug_mode {
         println!(
@@ -61,17 +61,7 @@ pub fn rank_search_results(
     // Extract document texts for ranking, including filename in each document
     let document_extraction_start = Instant::now();
     // This ensures filename terms are considered in the ranking algorithms
-    let documents: Vec<String> = results
-        .iter()
-        .map(|r| {
-            let mut doc = String::with_capacity(r.file.len() + r.code.len() + 15);
-            doc.push_str("// Filename: ");
-            doc.push_str(&r.file);
-            doc.push('\n');
-            doc.push_str(&r.code);
-            doc
-        })
-        .collect();
+    let documents = ranking_documents(results);
     let documents_refs: Vec<&str> = documents.iter().map(|s| s.as_str()).collect();
     let document_extraction_duration = document_extraction_start.elapsed();
 
@@ -188,92 +178,7 @@ pub fn rank_search_results(
             result_clone.rank = Some(rank_index + 1); // 1-based rank
 
             // EXPERIMENT: Apply node type boosting for better relevance
-            let node_type_boost = match result_clone.node_type.as_str() {
-                // Function/method implementations are most relevant (2.0x boost)
-                "function_item"
-                | "function_declaration"
-                | "method_declaration"
-                | "function_definition"
-                | "function_expression"
-                | "arrow_function"
-                | "method_definition"
-                | "method"
-                | "singleton_method"
-                | "constructor_declaration" => 2.0,
-
-                // Type definitions and implementations are highly relevant (1.8x boost)
-                "impl_item"
-                | "struct_item"
-                | "class_declaration"
-                | "type_definition"
-                | "interface_declaration"
-                | "class_specifier"
-                | "struct_specifier"
-                | "struct_declaration"
-                | "interface_type"
-                | "protocol_declaration"
-                | "type_alias_declaration"
-                | "typealias_declaration" => 1.8,
-
-                // Enums, traits, and type specifications (1.6x boost)
-                "enum_item"
-                | "trait_item"
-                | "enum_declaration"
-                | "enum_specifier"
-                | "type_declaration"
-                | "type_spec"
-                | "trait_declaration"
-                | "extension_declaration"
-                | "delegate_declaration" => 1.6,
-
-                // Module, namespace, and package definitions (1.4x boost)
-                "module"
-                | "mod_item"
-                | "namespace"
-                | "namespace_declaration"
-                | "namespace_definition"
-                | "module_declaration"
-                | "package_declaration" => 1.4,
-
-                // Properties, constants, and event declarations (1.3x boost)
-                "property_declaration"
-                | "event_declaration"
-                | "const_declaration"
-                | "var_declaration"
-                | "variable_declaration"
-                | "constant_declaration"
-                | "const_spec"
-                | "var_spec" => 1.3,
-
-                // Documentation blocks for functions (multi-line) (1.2x boost)
-                "doc_comment" | "block_comment"
-                    if result_clone.lines.1 - result_clone.lines.0 > 3 =>
-                {
-                    1.2
-                }
-
-                // Export statements and declarations (1.1x boost)
-                "export_statement" | "declare_statement" | "declaration" => 1.1,
-
-                // Test code is less relevant (0.7x penalty)
-                node_type if node_type.contains("test") || node_type.contains("Test") => 0.7,
-
-                // Single line comments are least relevant (0.5x penalty)
-                "line_comment" | "comment" | "//" | "/*" | "*/" => 0.5,
-
-                // Other acceptable but less specific node types (1.0x - no change)
-                "object"
-                | "array"
-                | "jsx_element"
-                | "jsx_self_closing_element"
-                | "property_identifier"
-                | "class_body"
-                | "class"
-                | "identifier" => 1.0,
-
-                // Default for any other node types
-                _ => 1.0,
-            };
+            let node_type_boost = node_type_boost(&result_clone);
 
             let boosted_score = bm25_score * node_type_boost;
             result_clone.score = Some(boosted_score);
@@ -293,7 +198,7 @@ pub fn rank_search_results(
 
     if debug_mode {
         println!("DEBUG: Using BM25 ranking (Okapi BM25 algorithm)");
-    } else {
+    } else if !probe_code::console::is_silenced() {
         println!("Using BM25 ranking (Okapi BM25 algorithm)");
     }
 
@@ -365,6 +270,140 @@ pub fn rank_search_results(
     }
 }
 
+/// Ranked document text of each result: the code with its filename prepended,
+/// so filename terms are considered in the ranking algorithms
+fn ranking_documents(results: &[SearchResult]) -> Vec<String> {
+    results
+        .iter()
+        .map(|r| {
+            let mut doc = String::with_capacity(r.file.len() + r.code.len() + 15);
+            doc.push_str("// Filename: ");
+            doc.push_str(&r.file);
+            doc.push('\n');
+            doc.push_str(&r.code);
+            doc
+        })
+        .collect()
+}
+
+/// Break the BM25 score of every result down into per-term contributions, for
+/// the same set of results and queries that `rank_search_results` ranked
+pub fn explain_bm25_scores(
+    results: &[SearchResult],
+    queries: &[String],
+) -> Vec<Vec<ranking::TermContribution>> {
+    let combined_query = queries.join(" ");
+    let documents = ranking_documents(results);
+    let documents_refs: Vec<&str> = documents.iter().map(|s| s.as_str()).collect();
+
+    let pre_tokenized: Vec<Vec<String>> = results
+        .iter()
+        .filter_map(|r| r.tokenized_content.clone())
+        .collect();
+    let has_tokenized = !pre_tokenized.is_empty() && pre_tokenized.len() == results.len();
+
+    let filename_tokens: Vec<Vec<String>> =
+        results.iter().map(|r| ranking::tokenize(&r.file)).collect();
+
+    let ranking_params = ranking::RankingParams {
+        documents: &documents_refs,
+        query: &combined_query,
+        pre_tokenized: if has_tokenized {
+            Some(&pre_tokenized)
+        } else {
+            None
+        },
+    };
+    ranking::explain_bm25_scores(&ranking_params, &filename_tokens)
+}
+
+/// Ranking weight of a result's node type: functions and type definitions rank
+/// above plain code, tests and comments below it
+pub fn node_type_boost(result: &SearchResult) -> f64 {
+    match result.node_type.as_str() {
+        // Function/method implementations are most relevant (2.0x boost)
+        "function_item"
+        | "function_declaration"
+        | "method_declaration"
+        | "function_definition"
+        | "function_expression"
+        | "arrow_function"
+        | "method_definition"
+        | "method"
+        | "singleton_method"
+        | "constructor_declaration" => 2.0,
+
+        // Type definitions and implementations are highly relevant (1.8x boost)
+        "impl_item"
+        | "struct_item"
+        | "class_declaration"
+        | "type_definition"
+        | "interface_declaration"
+        | "class_specifier"
+        | "struct_specifier"
+        | "struct_declaration"
+        | "interface_type"
+        | "protocol_declaration"
+        | "type_alias_declaration"
+        | "typealias_declaration" => 1.8,
+
+        // Enums, traits, and type specifications (1.6x boost)
+        "enum_item"
+        | "trait_item"
+        | "enum_declaration"
+        | "enum_specifier"
+        | "type_declaration"
+        | "type_spec"
+        | "trait_declaration"
+        | "extension_declaration"
+        | "delegate_declaration" => 1.6,
+
+        // Module, namespace, and package definitions (1.4x boost)
+        "module"
+        | "mod_item"
+        | "namespace"
+        | "namespace_declaration"
+        | "namespace_definition"
+        | "module_declaration"
+        | "package_declaration" => 1.4,
+
+        // Properties, constants, and event declarations (1.3x boost)
+        "property_declaration"
+        | "event_declaration"
+        | "const_declaration"
+        | "var_declaration"
+        | "variable_declaration"
+        | "constant_declaration"
+        | "const_spec"
+        | "var_spec" => 1.3,
+
+        // Documentation blocks for functions (multi-line) (1.2x boost)
+        "doc_comment" | "block_comment" if result.lines.1 - result.lines.0 > 3 => 1.2,
+
+        // Export statements and declarations (1.1x boost)
+        "export_statement" | "declare_statement" | "declaration" => 1.1,
+
+        // Test code is less relevant (0.7x penalty)
+        node_type if node_type.contains("test") || node_type.contains("Test") => 0.7,
+
+        // Single line comments are least relevant (0.5x penalty)
+        "line_comment" | "comment" | "//" | "/*" | "*/" => 0.5,
+
+        // Other acceptable but less specific node types (1.0x - no change)
+        "object"
+        | "array"
+        | "jsx_element"
+        | "jsx_self_closing_element"
+        | "property_identifier"
+        | "class_body"
+        | "class"
+        | "identifier" => 1.0,
+
+        // Default for any other node types
+        _ => 1.0,
+    }
+}
+
 /// Handle BERT-based reranking using the ms-marco-tinybert model
 fn handle_bert_reranking(
     results: &mut [SearchResult],
@@ -381,7 +420,7 @@ fn handle_bert_reranking(
         } else {
             println!("DEBUG: Using search keywords for reranking: {queries:?}");
         }
-    } else {
+    } else if !probe_code::console::is_silenced() {
         println!("Using BERT reranking with {reranker}");
         if let Some(q) = question {
             println!("Using custom question: '{q}'");
@@ -428,8 +467,10 @@ fn handle_bert_reranking(
         let bert_result = match bert_result {
             Ok(inner_result) => inner_result,
             Err(_) => {
-                eprintln!("BERT reranking thread panicked");
-                println!("Falling back to BM25 ranking...");
+                if !probe_code::console::is_silenced() {
+                    eprintln!("BERT reranking thread panicked");
+                    println!("Falling back to BM25 ranking...");
+                }
                 fallback_to_bm25_ranking(results, queries, debug_mode, start_time);
                 return;
             }
@@ -453,8 +494,10 @@ fn handle_bert_reranking(
                 }
             }
             Err(e) => {
-                eprintln!("BERT reranking failed: {e}");
-                println!("Falling back to BM25 ranking...");
+                if !probe_code::console::is_silenced() {
+                    eprintln!("BERT reranking failed: {e}");
+                    println!("Falling back to BM25 ranking...");
+                }
                 fallback_to_bm25_ranking(results, queries, debug_mode, start_time);
             }
         }
@@ -462,9 +505,11 @@ fn handle_bert_reranking(
 
     #[cfg(not(feature = "bert-reranker"))]
     {
-        eprintln!("BERT reranker '{}' is not available.", reranker);
-        eprintln!("To enable BERT reranking, build with: cargo build --features bert-reranker");
-        println!("Falling back to BM25 ranking...");
+        if !probe_code::console::is_silenced() {
+            eprintln!("BERT reranker '{}' is not available.", reranker);
+            eprintln!("To enable BERT reranking, build with: cargo build --features bert-reranker");
+            println!("Falling back to BM25 ranking...");
+        }
         fallback_to_bm25_ranking(results, queries, debug_mode, start_time);
     }
 }
@@ -480,17 +525,7 @@ fn fallback_to_bm25_ranking(
     let combined_query = queries.join(" ");
 
     // Extract document texts for ranking, including filename in each document
-    let documents: Vec<String> = results
-        .iter()
-        .map(|r| {
-            let mut doc = String::with_capacity(r.file.len() + r.code.len() + 15);
-            doc.push_str("// Filename: ");
-            doc.push_str(&r.file);
-            doc.push('\n');
-            doc.push_str(&r.code);
-            doc
-        })
-        .collect();
+    let documents = ranking_documents(results);
     let documents_refs: Vec<&str> = documents.iter().map(|s| s.as_str()).collect();
 
     // Extract pre-tokenized content if available
@@ -534,63 +569,7 @@ fn fallback_to_bm25_ranking(
             result_clone.rank = Some(rank_index + 1); // 1-based rank
 
             // Apply node type boosting (same logic as in the main function)
-            let node_type_boost = match result_clone.node_type.as_str() {
-                "function_item"
-                | "function_declaration"
-                | "method_declaration"
-                | "function_definition"
-                | "function_expression"
-                | "arrow_function"
-                | "method_definition"
-                | "method"
-                | "singleton_method"
-                | "constructor_declaration" => 2.0,
-                "impl_item"
-                | "struct_item"
-                | "class_declaration"
-                | "type_definition"
-                | "interface_declaration"
-                | "class_specifier"
-                | "struct_specifier"
-                | "struct_declaration"
-                | "interface_type"
-                | "protocol_declaration"
-                | "type_alias_declaration"
-                | "typealias_declaration" => 1.8,
-                "enum_item"
-                | "trait_item"
-                | "enum_declaration"
-                | "enum_specifier"
-                | "type_declaration"
-                | "type_spec"
-                | "trait_declaration"
-                | "extension_declaration"
-                | "delegate_declaration" => 1.6,
-                "module"
-                | "mod_item"
-                | "namespace"
-                | "namespace_declaration"
-                | "namespace_definition"
-                | "module_declaration"
-                | "package_declaration" => 1.4,
-                "property_declaration"
-                | "event_declaration"
-                | "const_declaration"
-                | "var_declaration"
-                | "variable_declaration"
-                | "constant_declaration"
-                | "const_spec"
-                | "var_spec" => 1.3,
-                "doc_comment" | "block_comment"
-                    if result_clone.lines.1 - result_clone.lines.0 > 3 =>
-                {
-                    1.2
-                }
-                "export_statement" | "declare_statement" | "declaration" => 1.1,
-                node_type if node_type.contains("test") || node_type.contains("Test") => 0.7,
-                "line_comment" | "comment" | "//" | "/*" | "*/" => 0.5,
-                _ => 1.0,
-            };
+            let node_type_boost = node_type_boost(&result_clone);
 
             let boosted_score = bm25_score * node_type_boost;
             result_clone.score = Some(boosted_score);
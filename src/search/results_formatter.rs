@@ -5,7 +5,7 @@ use colored::*;
 /// Function to format and print search results according to the specified format
 pub fn format_and_print_search_results(results: &[SearchResult], dry_run: bool) {
     // Check if debug mode is enabled
-    let debug_mode = std::env::var("DEBUG").unwrap_or_default() == "1";
+    let debug_mode = probe_code::console::debug_mode();
     
     // Check if colors should be disabled
     let no_color = std::env::var("NO_COLOR").is_ok();
@@ -124,7 +124,7 @@ pub fn format_and_print_search_results(results: &[SearchResult], dry_run: bool)
                 // Determine the language for syntax highlighting
                 let language = match extension {
                     "rs" => "rust",
-                    "py" => "python",
+                    "py" | "ipynb" => "python",
                     "js" => "javascript",
                     "ts" => "typescript",
                     "go" => "go",
@@ -134,15 +134,20 @@ pub fn format_and_print_search_results(results: &[SearchResult], dry_run: bool)
                     "rb" => "ruby",
                     "php" => "php",
                     "sh" => "bash",
-                    "md" => "markdown",
+                    "md" | "markdown" | "mdx" => "markdown",
+                    "rst" => "rst",
                     "json" => "json",
                     "yaml" | "yml" => "yaml",
-                    "html" => "html",
+                    "html" | "htm" => "html",
+                    "vue" => "vue",
+                    "svelte" => "svelte",
+                    "astro" => "astro",
                     "css" => "css",
                     "sql" => "sql",
                     "kt" | "kts" => "kotlin",
                     "swift" => "swift",
-                    "scala" => "scala",
+                    "scala" | "sc" => "scala",
+                    "tf" | "tfvars" | "hcl" => "hcl",
                     "dart" => "dart",
                     "ex" | "exs" => "elixir",
                     "hs" => "haskell",
@@ -1,4 +1,5 @@
 use anyhow::{Context, Result};
+use probe_code::source::Sources;
 use regex::RegexSet;
 use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
@@ -18,7 +19,7 @@ pub struct RipgrepSearcher {
 impl RipgrepSearcher {
     /// Create a new RipgrepSearcher with optimized settings
     pub fn new(patterns: &[String], _enable_simd: bool) -> Result<Self> {
-        let debug_mode = std::env::var("DEBUG").unwrap_or_default() == "1";
+        let debug_mode = probe_code::console::debug_mode();
 
         if debug_mode {
             println!(
@@ -43,6 +44,7 @@ impl RipgrepSearcher {
     /// This uses a fast RegexSet-based approach for maximum performance
     pub fn search_file(
         &self,
+        sources: &Sources,
         file_path: &Path,
         pattern_to_terms: &[HashSet<usize>],
     ) -> Result<HashMap<usize, HashSet<usize>>> {
@@ -57,7 +59,7 @@ impl RipgrepSearcher {
         const MAX_FILE_SIZE: u64 = 1024 * 1024; // 1MB
 
         // Check file metadata and resolve symlinks before reading
-        let resolved_path = match std::fs::canonicalize(file_path) {
+        let resolved_path = match sources.canonicalize(file_path) {
             Ok(path) => path,
             Err(e) => {
                 if self.debug_mode {
@@ -67,9 +69,9 @@ impl RipgrepSearcher {
             }
         };
 
-        // Get file metadata to check size and file type
-        let metadata = match std::fs::metadata(&resolved_path) {
-            Ok(meta) => meta,
+        // Get the file size to skip large files
+        let file_size = match sources.file_size(&resolved_path) {
+            Ok(size) => size,
             Err(e) => {
                 if self.debug_mode {
                     println!("DEBUG: Error getting metadata for {resolved_path:?}: {e:?}");
@@ -79,32 +81,26 @@ impl RipgrepSearcher {
         };
 
         // Check if the file is too large
-        if metadata.len() > MAX_FILE_SIZE {
+        if file_size > MAX_FILE_SIZE {
             if self.debug_mode {
                 println!(
-                    "DEBUG: Skipping file {:?} - file too large ({} bytes > {} bytes limit)",
-                    resolved_path,
-                    metadata.len(),
-                    MAX_FILE_SIZE
+                    "DEBUG: Skipping file {resolved_path:?} - file too large ({file_size} bytes > {MAX_FILE_SIZE} bytes limit)"
                 );
             }
             return Err(anyhow::anyhow!(
                 "File too large: {} bytes (limit: {} bytes)",
-                metadata.len(),
+                file_size,
                 MAX_FILE_SIZE
             ));
         }
 
         // Read the file content with proper error handling (simple and fast)
-        let content = match std::fs::read_to_string(&resolved_path) {
+        let content = match sources.read_to_string(&resolved_path) {
             Ok(content) => content,
             Err(e) => {
                 if self.debug_mode {
                     println!(
-                        "DEBUG: Error reading file {:?}: {:?} (size: {} bytes)",
-                        resolved_path,
-                        e,
-                        metadata.len()
+                        "DEBUG: Error reading file {resolved_path:?}: {e:?} (size: {file_size} bytes)"
                     );
                 }
                 return Err(anyhow::anyhow!("Failed to read file: {}", e));
@@ -159,6 +155,7 @@ impl RipgrepSearcher {
     /// Search multiple files in parallel using fast RegexSet-based approach
     pub fn search_files_parallel(
         &self,
+        sources: &Sources,
         file_paths: &[PathBuf],
         pattern_to_terms: &[HashSet<usize>],
     ) -> Result<HashMap<PathBuf, HashMap<usize, HashSet<usize>>>> {
@@ -185,7 +182,7 @@ impl RipgrepSearcher {
             .filter_map(|file_path| {
                 // Reuse the shared searcher instance - it's thread-safe
                 // The search_file method uses only simple file I/O and RegexSet matching
-                match self.search_file(file_path, pattern_to_terms) {
+                match self.search_file(sources, file_path, pattern_to_terms) {
                     Ok(term_map) => {
                         if !term_map.is_empty() {
                             Some((file_path.clone(), term_map))
@@ -243,7 +240,9 @@ mod tests {
             set
         }];
 
-        let result = searcher.search_file(&file_path, &pattern_to_terms).unwrap();
+        let result = searcher
+            .search_file(&Sources::default(), &file_path, &pattern_to_terms)
+            .unwrap();
 
         assert!(!result.is_empty());
         assert!(result.contains_key(&0));
@@ -270,7 +269,7 @@ mod tests {
         }];
 
         let results = searcher
-            .search_files_parallel(&files, &pattern_to_terms)
+            .search_files_parallel(&Sources::default(), &files, &pattern_to_terms)
             .unwrap();
 
         assert_eq!(results.len(), 2);
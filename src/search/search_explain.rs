@@ -0,0 +1,223 @@
+use colored::*;
+use std::collections::{HashMap, HashSet};
+
+use probe_code::models::SearchResult;
+use probe_code::ranking::TermContribution;
+use probe_code::search::elastic_query;
+use probe_code::search::query::QueryPlan;
+use probe_code::search::result_ranking::{explain_bm25_scores, node_type_boost};
+use probe_code::search::tokenization;
+
+/// Identifies a ranked result by file and line range
+type ResultKey = (String, (usize, usize));
+
+/// Score breakdown of a single ranked result, for `--explain`
+#[derive(Debug, Clone)]
+pub struct ResultExplanation {
+    /// Position of the result when sorted by plain BM25 score (1-based)
+    pub bm25_position: usize,
+    /// BM25 contribution of each matched query term
+    pub terms: Vec<TermContribution>,
+}
+
+/// Print how a query was parsed and which patterns are used to search files:
+/// the parsed expression, the tokens each word of the query was turned into
+/// (split on camel case and compound words, then stemmed), the indexed query
+/// terms and every search pattern with the terms it stands for. Explanations go
+/// to stderr, so the results on stdout stay parseable with `--format json`.
+pub fn print_query_explanation(
+    query: &str,
+    plan: &QueryPlan,
+    patterns: &[(String, HashSet<usize>)],
+) {
+    eprintln!("{}", "Query explanation".bold().cyan());
+    eprintln!("  {} {query}", "Query:".bold());
+    eprintln!("  {} {}", "Parsed:".bold(), plan.ast);
+
+    eprintln!("  {}", "Tokens:".bold());
+    for word in elastic_query::query_words(query) {
+        let tokens = tokenization::tokenize(&word);
+        if tokens.is_empty() {
+            eprintln!("    {word} => (stop word)");
+        } else {
+            eprintln!("    {word} => {}", tokens.join(", "));
+        }
+    }
+
+    let mut prefixes = HashMap::new();
+    collect_term_prefixes(&plan.ast, &mut prefixes);
+    let mut terms: Vec<(&String, &usize)> = plan.term_indices.iter().collect();
+    terms.sort_by_key(|(_, &idx)| idx);
+    eprintln!("  {}", "Terms:".bold());
+    for (term, idx) in terms {
+        let mut notes = Vec::new();
+        if let Some(&(required, excluded)) = prefixes.get(term.as_str()) {
+            if required {
+                notes.push("required");
+            }
+            if excluded {
+                notes.push("excluded");
+            }
+        }
+        if elastic_query::regex_term(term).is_some() {
+            notes.push("regex");
+        }
+        if notes.is_empty() {
+            eprintln!("    [{idx}] {term}");
+        } else {
+            eprintln!("    [{idx}] {term} ({})", notes.join(", "));
+        }
+    }
+
+    eprintln!("  {}", "Patterns:".bold());
+    for (pattern, indices) in patterns {
+        let mut indices: Vec<&usize> = indices.iter().collect();
+        indices.sort();
+        let indices: Vec<String> = indices.iter().map(|idx| idx.to_string()).collect();
+        eprintln!("    {pattern} => terms {}", indices.join(", "));
+    }
+    eprintln!();
+}
+
+/// Record whether each keyword of the query carries a `+` or `-` prefix
+fn collect_term_prefixes<'a>(
+    expr: &'a elastic_query::Expr,
+    prefixes: &mut HashMap<&'a str, (bool, bool)>,
+) {
+    use elastic_query::Expr;
+    match expr {
+        Expr::Term {
+            keywords,
+            required,
+            excluded,
+            ..
+        } => {
+            for keyword in keywords {
+                let entry = prefixes.entry(keyword.as_str()).or_insert((false, false));
+                entry.0 |= *required;
+                entry.1 |= *excluded;
+            }
+        }
+        Expr::And(left, right) | Expr::Or(left, right) => {
+            collect_term_prefixes(left, prefixes);
+            collect_term_prefixes(right, prefixes);
+        }
+        Expr::Near(inner, _)
+        | Expr::Required(inner)
+        | Expr::Excluded(inner)
+        | Expr::Boost(inner, _) => collect_term_prefixes(inner, prefixes),
+    }
+}
+
+/// Break down the BM25 score of every ranked result. Must be called with the
+/// same results and queries that were passed to `rank_search_results`, so the
+/// term statistics match the ones used for ranking.
+pub fn explain_results(
+    results: &[SearchResult],
+    queries: &[String],
+) -> HashMap<ResultKey, ResultExplanation> {
+    let contributions = explain_bm25_scores(results, queries);
+
+    let mut by_bm25: Vec<usize> = (0..results.len()).collect();
+    by_bm25.sort_by(|&a, &b| {
+        let score_a = results[a].bm25_score.unwrap_or(0.0);
+        let score_b = results[b].bm25_score.unwrap_or(0.0);
+        score_b
+            .partial_cmp(&score_a)
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+
+    let mut contributions: Vec<Option<Vec<TermContribution>>> =
+        contributions.into_iter().map(Some).collect();
+    by_bm25
+        .into_iter()
+        .enumerate()
+        .map(|(position, i)| {
+            let result = &results[i];
+            (
+                (result.file.clone(), result.lines),
+                ResultExplanation {
+                    bm25_position: position + 1,
+                    terms: contributions[i].take().unwrap_or_default(),
+                },
+            )
+        })
+        .collect()
+}
+
+/// Print the score breakdown of every result in output order: the final score
+/// as BM25 score times node type boost, how the boost moved the result from its
+/// BM25 position, how much of the score the filename contributed and the
+/// contribution of every matched term, to stderr.
+pub fn print_result_explanations(
+    results: &[SearchResult],
+    explanations: Option<&HashMap<ResultKey, ResultExplanation>>,
+) {
+    eprintln!("{}", "Score breakdown".bold().cyan());
+    let Some(explanations) = explanations else {
+        eprintln!("  Results are not ranked in exact mode");
+        eprintln!();
+        return;
+    };
+
+    for (position, result) in results.iter().enumerate() {
+        eprintln!(
+            "  {} {}:{}-{} ({})",
+            format!("#{}", position + 1).bold(),
+            result.file,
+            result.lines.0,
+            result.lines.1,
+            result.node_type
+        );
+
+        let Some(explanation) = explanations.get(&(result.file.clone(), result.lines)) else {
+            eprintln!("     not ranked");
+            continue;
+        };
+
+        let bm25 = result.bm25_score.unwrap_or(0.0);
+        eprintln!(
+            "     score {:.4} = BM25 {:.4} x node type boost {:.1}",
+            result.score.unwrap_or(0.0),
+            bm25,
+            node_type_boost(result)
+        );
+        eprintln!(
+            "     rerank: BM25 position {} => position {}",
+            explanation.bm25_position,
+            position + 1
+        );
+
+        let filename_score: f64 = explanation.terms.iter().map(|t| t.filename_score).sum();
+        eprintln!("     filename boost: {filename_score:.4}");
+
+        let width = explanation
+            .terms
+            .iter()
+            .map(|t| t.term.len())
+            .max()
+            .unwrap_or(0);
+        for term in &explanation.terms {
+            eprintln!(
+                "     {:<width$}  tf {:<3} idf {:.4}  => {:.4}{}",
+                term.term,
+                term.tf,
+                term.idf,
+                term.score,
+                if term.filename_score > 0.0 {
+                    format!(" ({:.4} from filename)", term.filename_score)
+                } else {
+                    String::new()
+                }
+            );
+        }
+        let terms_total: f64 = explanation.terms.iter().map(|t| t.score).sum();
+        if (terms_total - bm25).abs() > 1e-6 {
+            eprintln!(
+                "     other: {:.4} (proximity, OR branches and boosts of groups)",
+                bm25 - terms_total
+            );
+        }
+    }
+    eprintln!();
+}
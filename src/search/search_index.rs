@@ -0,0 +1,644 @@
+use anyhow::{bail, Context, Result};
+use colored::*;
+use once_cell::sync::Lazy;
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+use probe_code::language::block_cache::{read_line_map, write_line_map};
+use probe_code::language::parser::{
+    build_persisted_line_map, calculate_content_hash, line_map_cache_key, seed_line_map_cache,
+};
+use probe_code::search::file_list_cache::{self, FileFilters};
+use probe_code::search::search_runner::format_duration;
+use probe_code::source::Sources;
+
+/// Directory of the search index, relative to the indexed directory
+pub const INDEX_DIR: &str = ".probe/index";
+
+/// File listing the indexed files and the words they contain
+const MANIFEST_FILE: &str = "manifest.json";
+
+/// Directory holding the line map of every indexed file, keyed by content hash
+const BLOCKS_DIR: &str = "blocks";
+
+/// Version of the index layout. Indexes written with another version are ignored
+/// when searching and rebuilt from scratch by `probe index`.
+const INDEX_VERSION: u32 = 2;
+
+/// Files larger than this are not indexed, like they are skipped when searching
+const MAX_FILE_SIZE: u64 = 1024 * 1024;
+
+/// Indexes loaded by this process, with the modification time of their manifest
+type LoadedIndexes = HashMap<PathBuf, (SystemTime, Arc<SearchIndex>)>;
+static LOADED_INDEXES: Lazy<Mutex<LoadedIndexes>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// An indexed file
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IndexedFile {
+    /// Path relative to the indexed directory, with `/` separators
+    pub path: String,
+    /// Modification time in nanoseconds since the Unix epoch
+    pub modified: u64,
+    /// Size in bytes
+    pub size: u64,
+    /// Content hash, as used by the line map cache
+    pub hash: u64,
+    /// Whether a line map is stored for the file (only for supported languages)
+    pub has_line_map: bool,
+}
+
+/// Persistent index of a directory, built by `probe index` and stored in
+/// `.probe/index`. Searches use it when present to skip files that can't match
+/// and to build code blocks without parsing files again. Files that changed
+/// since they were indexed are searched as if there was no index.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct SearchIndex {
+    version: u32,
+    /// Whether test code was included when building the line maps
+    pub allow_tests: bool,
+    /// Indexed files, sorted by path
+    pub files: Vec<IndexedFile>,
+    /// Every lowercased word (run of letters, digits and `_`) => the files
+    /// containing it, as positions in `files`
+    words: HashMap<String, Vec<u32>>,
+    /// Directory the index was built for
+    #[serde(skip)]
+    root: PathBuf,
+    /// Relative path => position in `files`
+    #[serde(skip)]
+    positions: HashMap<String, usize>,
+}
+
+/// Options for building an index with `build_index`
+pub struct IndexOptions<'a> {
+    pub path: &'a Path,
+    pub custom_ignores: &'a [String],
+    pub allow_tests: bool,
+    pub no_gitignore: bool,
+}
+
+/// Outcome of `build_index`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct IndexStats {
+    /// Number of files in the index
+    pub files: usize,
+    /// Files that were new or changed, and were read and parsed
+    pub parsed: usize,
+    /// Files reused from the previous index
+    pub unchanged: usize,
+    /// Files of the previous index that no longer exist
+    pub removed: usize,
+    /// Number of distinct words
+    pub words: usize,
+}
+
+impl SearchIndex {
+    /// Load the index stored in `root`
+    pub fn load(root: &Path) -> Result<Self> {
+        let manifest = root.join(INDEX_DIR).join(MANIFEST_FILE);
+        let content = fs::read_to_string(&manifest)
+            .with_context(|| format!("Failed to read {}", manifest.display()))?;
+        let mut index: SearchIndex = serde_json::from_str(&content)
+            .with_context(|| format!("Failed to parse {}", manifest.display()))?;
+        if index.version != INDEX_VERSION {
+            bail!(
+                "Index at {} has version {}, expected {INDEX_VERSION}",
+                manifest.display(),
+                index.version
+            );
+        }
+
+        index.root = root.to_path_buf();
+        index.positions = index
+            .files
+            .iter()
+            .enumerate()
+            .map(|(i, file)| (file.path.clone(), i))
+            .collect();
+        Ok(index)
+    }
+
+    /// Find the index of the directory containing `path`, looking in `path` and
+    /// each of its parents. Loaded indexes are kept for the rest of the process
+    /// and reloaded when their manifest changes. Searches of files that aren't
+    /// on the disk, such as those of a git revision, never use an index.
+    pub fn find(sources: &Sources, path: &Path) -> Option<Arc<SearchIndex>> {
+        if !sources.on_disk(path) {
+            return None;
+        }
+        let debug_mode = probe_code::console::debug_mode();
+        let path = path.canonicalize().ok()?;
+
+        for root in path.ancestors() {
+            let manifest = root.join(INDEX_DIR).join(MANIFEST_FILE);
+            let Ok(modified) = fs::metadata(&manifest).and_then(|m| m.modified()) else {
+                continue;
+            };
+
+            let mut loaded = LOADED_INDEXES.lock().unwrap();
+            if let Some((loaded_modified, index)) = loaded.get(root) {
+                if *loaded_modified == modified {
+                    return Some(Arc::clone(index));
+                }
+            }
+
+            return match Self::load(root) {
+                Ok(index) => {
+                    if debug_mode {
+                        println!(
+                            "DEBUG: Loaded search index of {} with {} files",
+                            root.display(),
+                            index.files.len()
+                        );
+                    }
+                    let index = Arc::new(index);
+                    loaded.insert(root.to_path_buf(), (modified, Arc::clone(&index)));
+                    Some(index)
+                }
+                Err(e) => {
+                    if debug_mode {
+                        println!("DEBUG: Ignoring search index: {e:#}");
+                    }
+                    None
+                }
+            };
+        }
+        None
+    }
+
+    /// The indexed entry of a file, if the file hasn't changed since it was indexed
+    fn unchanged_entry(&self, path: &Path) -> Option<(usize, &IndexedFile)> {
+        let relative = match path.strip_prefix(&self.root) {
+            Ok(relative) => relative.to_path_buf(),
+            Err(_) => path
+                .canonicalize()
+                .ok()?
+                .strip_prefix(&self.root)
+                .ok()?
+                .to_path_buf(),
+        };
+        let position = *self.positions.get(&relative_key(&relative))?;
+        let entry = &self.files[position];
+
+        let metadata = fs::metadata(path).ok()?;
+        (metadata.len() == entry.size && modified_nanos(&metadata) == Some(entry.modified))
+            .then_some((position, entry))
+    }
+
+    /// Drop the files that can't match any of the search patterns: unchanged
+    /// indexed files that contain none of the words the patterns consist of.
+    /// Files that are not indexed or changed since are always kept, and so are
+    /// all files when a pattern is more than a list of words.
+    pub fn filter_candidates(&self, files: &[PathBuf], patterns: &[String]) -> Vec<PathBuf> {
+        let Some(literals) = pattern_literals(patterns) else {
+            return files.to_vec();
+        };
+
+        let matching: HashSet<u32> = self
+            .words
+            .par_iter()
+            .filter(|(word, _)| literals.iter().any(|literal| word.contains(literal)))
+            .flat_map_iter(|(_, positions)| positions.iter().copied())
+            .collect();
+
+        files
+            .par_iter()
+            .filter(|file| match self.unchanged_entry(file) {
+                Some((position, _)) => matching.contains(&(position as u32)),
+                None => true,
+            })
+            .cloned()
+            .collect()
+    }
+
+    /// Load the stored line maps of the given files into the line map cache, so
+    /// their code blocks are built without parsing them. Files that changed since
+    /// they were indexed are skipped. Returns the number of line maps loaded.
+    pub fn preload_line_maps<'a>(
+        &self,
+        files: impl IntoIterator<Item = &'a PathBuf>,
+        allow_tests: bool,
+    ) -> usize {
+        if allow_tests != self.allow_tests {
+            return 0;
+        }
+
+        let blocks_dir = self.root.join(INDEX_DIR).join(BLOCKS_DIR);
+        let files: Vec<&PathBuf> = files.into_iter().collect();
+        files
+            .par_iter()
+            .filter(|file| {
+                let Some((_, entry)) = self.unchanged_entry(file) else {
+                    return false;
+                };
+                if !entry.has_line_map {
+                    return false;
+                }
+                let key = line_map_cache_key(extension(file), entry.hash, allow_tests);
+                match read_line_map(&blocks_dir.join(line_map_file_name(&key))) {
+                    Ok(line_map) => {
+                        seed_line_map_cache(key, &line_map);
+                        true
+                    }
+                    Err(_) => false,
+                }
+            })
+            .count()
+    }
+
+    /// Words of every file, by position in `files`
+    fn file_words(&self) -> Vec<Vec<String>> {
+        let mut file_words = vec![Vec::new(); self.files.len()];
+        for (word, positions) in &self.words {
+            for &position in positions {
+                if let Some(words) = file_words.get_mut(position as usize) {
+                    words.push(word.clone());
+                }
+            }
+        }
+        file_words
+    }
+}
+
+/// Build or update the index of a directory. Files that didn't change since the
+/// previous index was built are reused; new and changed files are read and
+/// parsed, and files that no longer exist are dropped.
+pub fn build_index(options: &IndexOptions) -> Result<IndexStats> {
+    let debug_mode = probe_code::console::debug_mode();
+
+    let root = options
+        .path
+        .canonicalize()
+        .with_context(|| format!("Failed to resolve {}", options.path.display()))?;
+    if !root.is_dir() {
+        bail!("{} is not a directory", root.display());
+    }
+    let index_dir = root.join(INDEX_DIR);
+    let blocks_dir = index_dir.join(BLOCKS_DIR);
+    fs::create_dir_all(&blocks_dir)
+        .with_context(|| format!("Failed to create {}", blocks_dir.display()))?;
+
+    let previous = SearchIndex::load(&root)
+        .ok()
+        .filter(|index| index.allow_tests == options.allow_tests);
+    let previous_words = previous
+        .as_ref()
+        .map(SearchIndex::file_words)
+        .unwrap_or_default();
+
+    // Indexes are of the files on the disk
+    let file_list = file_list_cache::get_file_list(
+        &Sources::default(),
+        &root,
+        options.allow_tests,
+        options.custom_ignores,
+        options.no_gitignore,
+        &FileFilters::default(),
+    )?;
+
+    let mut entries: Vec<(IndexedFile, Vec<String>, bool)> = file_list
+        .files
+        .par_iter()
+        .filter(|path| !path.starts_with(&index_dir))
+        .filter_map(|path| {
+            let previous = previous.as_ref().and_then(|index| {
+                let relative = relative_key(path.strip_prefix(&root).ok()?);
+                let position = *index.positions.get(&relative)?;
+                Some((&index.files[position], &previous_words[position]))
+            });
+            match index_file(&root, path, previous, &blocks_dir, options.allow_tests) {
+                Ok(entry) => entry,
+                Err(e) => {
+                    if debug_mode {
+                        println!("DEBUG: Not indexing {}: {e:#}", path.display());
+                    }
+                    None
+                }
+            }
+        })
+        .collect();
+    entries.sort_by(|a, b| a.0.path.cmp(&b.0.path));
+
+    let mut index = SearchIndex {
+        version: INDEX_VERSION,
+        allow_tests: options.allow_tests,
+        ..Default::default()
+    };
+    let mut stats = IndexStats::default();
+    for (position, (entry, words, parsed)) in entries.into_iter().enumerate() {
+        if parsed {
+            stats.parsed += 1;
+        } else {
+            stats.unchanged += 1;
+        }
+        for word in words {
+            index.words.entry(word).or_default().push(position as u32);
+        }
+        index.files.push(entry);
+    }
+    stats.files = index.files.len();
+    stats.words = index.words.len();
+
+    let indexed: HashSet<&str> = index.files.iter().map(|f| f.path.as_str()).collect();
+    stats.removed = previous.as_ref().map_or(0, |previous| {
+        previous
+            .files
+            .iter()
+            .filter(|f| !indexed.contains(f.path.as_str()))
+            .count()
+    });
+
+    // Drop the line maps of files that were removed or changed
+    let line_maps: HashSet<String> = index
+        .files
+        .iter()
+        .filter(|f| f.has_line_map)
+        .map(|f| {
+            let key = line_map_cache_key(extension(Path::new(&f.path)), f.hash, index.allow_tests);
+            line_map_file_name(&key)
+        })
+        .collect();
+    for entry in fs::read_dir(&blocks_dir)?.flatten() {
+        if !line_maps.contains(entry.file_name().to_string_lossy().as_ref()) {
+            let _ = fs::remove_file(entry.path());
+        }
+    }
+
+    // Write the manifest last, through a temporary file, so searches never see a
+    // manifest referring to line maps that aren't written yet
+    let manifest = index_dir.join(MANIFEST_FILE);
+    let temp_manifest = index_dir.join(format!("{MANIFEST_FILE}.tmp"));
+    fs::write(&temp_manifest, serde_json::to_string(&index)?)
+        .with_context(|| format!("Failed to write {}", temp_manifest.display()))?;
+    fs::rename(&temp_manifest, &manifest)
+        .with_context(|| format!("Failed to write {}", manifest.display()))?;
+
+    Ok(stats)
+}
+
+/// Index a single file, reusing its previous entry when the file didn't change.
+/// Returns the entry, the words of the file and whether the file was parsed,
+/// or `None` for files that are not indexed (too large or not text).
+fn index_file(
+    root: &Path,
+    path: &Path,
+    previous: Option<(&IndexedFile, &Vec<String>)>,
+    blocks_dir: &Path,
+    allow_tests: bool,
+) -> Result<Option<(IndexedFile, Vec<String>, bool)>> {
+    let disk = Sources::default();
+    let metadata = fs::metadata(path)?;
+    // Notebooks are limited by the size of their cells, without outputs
+    if disk.file_size(path)? > MAX_FILE_SIZE {
+        return Ok(None);
+    }
+    let modified = modified_nanos(&metadata).unwrap_or(0);
+    let extension = extension(path);
+
+    if let Some((entry, words)) = previous {
+        if entry.size == metadata.len() && entry.modified == modified {
+            return Ok(Some((entry.clone(), words.clone(), false)));
+        }
+    }
+
+    let Ok(content) = disk.read_to_string(path) else {
+        return Ok(None);
+    };
+    let hash = calculate_content_hash(&content);
+    let relative = relative_key(path.strip_prefix(root)?);
+    let line_map_file = blocks_dir.join(line_map_file_name(&line_map_cache_key(
+        extension,
+        hash,
+        allow_tests,
+    )));
+
+    // Touched but unchanged files keep their words and line map
+    if let Some((entry, words)) = previous {
+        if entry.hash == hash && (!entry.has_line_map || line_map_file.is_file()) {
+            let entry = IndexedFile {
+                path: relative,
+                modified,
+                size: metadata.len(),
+                ..entry.clone()
+            };
+            return Ok(Some((entry, words.clone(), false)));
+        }
+    }
+
+    let line_map = build_persisted_line_map(&content, extension, allow_tests).unwrap_or(None);
+    if let Some(line_map) = &line_map {
+        write_line_map(&line_map_file, line_map)
+            .with_context(|| format!("Failed to write {}", line_map_file.display()))?;
+    }
+
+    let entry = IndexedFile {
+        path: relative,
+        modified,
+        size: metadata.len(),
+        hash,
+        has_line_map: line_map.is_some(),
+    };
+    Ok(Some((entry, content_words(&content), true)))
+}
+
+/// Build the index of a directory and print a summary, for `probe index`
+pub fn handle_index(options: &IndexOptions) -> Result<()> {
+    let start = Instant::now();
+    println!("{} {}", "Indexing:".bold().green(), options.path.display());
+
+    let stats = build_index(options)?;
+
+    println!(
+        "Indexed {} files ({} parsed, {} unchanged, {} removed) with {} distinct words in {}",
+        stats.files,
+        stats.parsed,
+        stats.unchanged,
+        stats.removed,
+        stats.words,
+        format_duration(start.elapsed())
+    );
+    Ok(())
+}
+
+/// Lowercased words of a text: runs of letters, digits and `_`
+fn content_words(content: &str) -> Vec<String> {
+    let words: HashSet<String> = content
+        .split(|c: char| !(c.is_alphanumeric() || c == '_'))
+        .filter(|word| !word.is_empty())
+        .map(|word| word.to_lowercase())
+        .collect();
+    let mut words: Vec<String> = words.into_iter().collect();
+    words.sort();
+    words
+}
+
+/// The words search patterns are made of, lowercased, e.g. `(?i)(get|token)`
+/// => `["get", "token"]`. A match of such a pattern always lies within a single
+/// word of the text. Returns `None` when a pattern uses any other regex syntax.
+fn pattern_literals(patterns: &[String]) -> Option<Vec<String>> {
+    let mut literals = Vec::new();
+    for pattern in patterns {
+        let body = pattern.strip_prefix("(?i)").unwrap_or(pattern);
+        let is_word_list = body
+            .chars()
+            .all(|c| c.is_alphanumeric() || matches!(c, '_' | '(' | ')' | '|'));
+        // An empty alternative matches anywhere
+        let has_empty_alternative = ["||", "(|", "|)", "()"]
+            .iter()
+            .any(|empty| body.contains(empty))
+            || body.starts_with('|')
+            || body.ends_with('|');
+        if !is_word_list || has_empty_alternative {
+            return None;
+        }
+
+        let words: Vec<String> = body
+            .split(['(', ')', '|'])
+            .filter(|word| !word.is_empty())
+            .map(|word| word.to_lowercase())
+            .collect();
+        if words.is_empty() {
+            return None;
+        }
+        literals.extend(words);
+    }
+    literals.sort();
+    literals.dedup();
+    Some(literals)
+}
+
+/// Line maps are stored as gzipped JSON
+fn line_map_file_name(key: &str) -> String {
+    format!("{key}.json.gz")
+}
+
+/// Key of a relative path in the index
+fn relative_key(relative: &Path) -> String {
+    relative.to_string_lossy().replace('\\', "/")
+}
+
+fn extension(path: &Path) -> &str {
+    path.extension().and_then(|ext| ext.to_str()).unwrap_or("")
+}
+
+fn modified_nanos(metadata: &fs::Metadata) -> Option<u64> {
+    let modified = metadata.modified().ok()?;
+    let nanos = modified.duration_since(UNIX_EPOCH).ok()?.as_nanos();
+    u64::try_from(nanos).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn index_dir(dir: &Path) -> IndexStats {
+        build_index(&IndexOptions {
+            path: dir,
+            custom_ignores: &[],
+            allow_tests: false,
+            no_gitignore: true,
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_pattern_literals() {
+        let patterns = |patterns: &[&str]| {
+            pattern_literals(&patterns.iter().map(|p| p.to_string()).collect::<Vec<_>>())
+        };
+
+        assert_eq!(
+            patterns(&["(?i)(get|Token)", "((get)|(refresh))", "(user_id)"]),
+            Some(vec![
+                "get".to_string(),
+                "refresh".to_string(),
+                "token".to_string(),
+                "user_id".to_string()
+            ])
+        );
+        assert_eq!(patterns(&["(get)", r"(?i:TODO\(\w+\))"]), None);
+        assert_eq!(patterns(&["(get|)"]), None);
+        assert_eq!(patterns(&["()"]), None);
+    }
+
+    #[test]
+    fn test_content_words() {
+        assert_eq!(
+            content_words("fn getUser(user_id: u32) { getUser() }"),
+            vec!["fn", "getuser", "u32", "user_id"]
+        );
+    }
+
+    #[test]
+    fn test_incremental_index_updates() {
+        let temp_dir = TempDir::new().unwrap();
+        let dir = temp_dir.path();
+        fs::write(dir.join("a.rs"), "fn refresh_token() {}\n").unwrap();
+        fs::write(dir.join("b.rs"), "fn acquire_lock() {}\n").unwrap();
+        fs::write(dir.join("notes.txt"), "session notes\n").unwrap();
+
+        let stats = index_dir(dir);
+        assert_eq!((stats.files, stats.parsed, stats.unchanged), (3, 3, 0));
+
+        let stats = index_dir(dir);
+        assert_eq!((stats.files, stats.parsed, stats.unchanged), (3, 0, 3));
+
+        fs::write(dir.join("a.rs"), "fn refresh_session() {}\n").unwrap();
+        fs::remove_file(dir.join("b.rs")).unwrap();
+        let stats = index_dir(dir);
+        assert_eq!(
+            (stats.files, stats.parsed, stats.unchanged, stats.removed),
+            (2, 1, 1, 1)
+        );
+
+        let index = SearchIndex::load(&dir.canonicalize().unwrap()).unwrap();
+        let paths: Vec<&str> = index.files.iter().map(|f| f.path.as_str()).collect();
+        assert_eq!(paths, vec!["a.rs", "notes.txt"]);
+        assert!(index.files[0].has_line_map);
+        assert!(!index.files[1].has_line_map);
+        assert!(index.words.contains_key("refresh_session"));
+        assert!(!index.words.contains_key("acquire_lock"));
+
+        // Only the line map of the current a.rs is kept
+        let line_maps = fs::read_dir(dir.join(INDEX_DIR).join(BLOCKS_DIR))
+            .unwrap()
+            .count();
+        assert_eq!(line_maps, 1);
+    }
+
+    #[test]
+    fn test_filter_candidates() {
+        let temp_dir = TempDir::new().unwrap();
+        let dir = temp_dir.path().canonicalize().unwrap();
+        fs::write(dir.join("a.rs"), "fn refresh_token() {}\n").unwrap();
+        fs::write(dir.join("b.rs"), "fn acquire_lock() {}\n").unwrap();
+        index_dir(&dir);
+
+        let index = SearchIndex::load(&dir).unwrap();
+        let files = vec![dir.join("a.rs"), dir.join("b.rs"), dir.join("new.rs")];
+        fs::write(dir.join("new.rs"), "fn other() {}\n").unwrap();
+
+        let candidates = |pattern: &str| index.filter_candidates(&files, &[pattern.to_string()]);
+        // Substrings of words match, like they do when searching
+        assert_eq!(
+            candidates("(?i)(TOKEN)"),
+            vec![dir.join("a.rs"), dir.join("new.rs")]
+        );
+        assert_eq!(candidates("(missing)"), vec![dir.join("new.rs")]);
+        // Patterns that aren't plain words keep every file
+        assert_eq!(candidates(r"(?-i:lock\b)"), files);
+
+        // Changed files are always kept
+        fs::write(dir.join("b.rs"), "fn acquire_lock_now() {}\n").unwrap();
+        assert_eq!(
+            candidates("(missing)"),
+            vec![dir.join("b.rs"), dir.join("new.rs")]
+        );
+    }
+}
@@ -1,4 +1,8 @@
 use std::path::Path;
+use std::sync::Arc;
+
+use probe_code::search::file_list_cache::FileFilters;
+use probe_code::source::Source;
 
 /// Options for performing a search
 pub struct SearchOptions<'a> {
@@ -11,6 +15,10 @@ pub struct SearchOptions<'a> {
     #[allow(dead_code)]
     pub frequency_search: bool,
     pub exact: bool,
+    /// Match every plain query term with a tolerance for typos, as if written `term~`
+    pub fuzzy: bool,
+    /// Print how the query was parsed and searched, and how each result was scored
+    pub explain: bool,
     pub language: Option<&'a str>,
     pub max_results: Option<usize>,
     pub max_bytes: Option<usize>,
@@ -23,4 +31,9 @@ pub struct SearchOptions<'a> {
     pub timeout: u64,
     pub question: Option<&'a str>,
     pub no_gitignore: bool,
+    /// Path, extension and language filters applied during file discovery.
+    /// Inline filters in the query (`path:src ext:rs`) are merged into these.
+    pub file_filters: FileFilters,
+    /// Where the files it holds are read from, instead of the disk
+    pub source: Option<Arc<dyn Source>>,
 }
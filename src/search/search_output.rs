@@ -1,9 +1,11 @@
 use anyhow::Result;
+use std::fmt::Write;
 use std::path::Path;
 
 use probe_code::models::SearchResult;
 use probe_code::search::query::QueryPlan;
 use probe_code::search::search_tokens::sum_tokens_with_deduplication;
+use probe_code::source::CellRange;
 
 /// Function to format and print search results according to the specified format
 pub fn format_and_print_search_results(
@@ -12,7 +14,22 @@ pub fn format_and_print_search_results(
     format: &str,
     query_plan: Option<&QueryPlan>,
 ) {
-    let debug_mode = std::env::var("DEBUG").unwrap_or_default() == "1";
+    match format_search_results(results, dry_run, format, query_plan) {
+        Ok(output) => print!("{output}"),
+        Err(e) => eprintln!("Error formatting search results: {e}"),
+    }
+}
+
+/// Format search results according to the specified format, as
+/// `format_and_print_search_results` prints them
+pub fn format_search_results(
+    results: &[SearchResult],
+    dry_run: bool,
+    format: &str,
+    query_plan: Option<&QueryPlan>,
+) -> Result<String> {
+    let mut output = String::new();
+    let debug_mode = probe_code::console::debug_mode();
 
     // Count valid results (with non-empty file names)
     let valid_results: Vec<&SearchResult> = results.iter().filter(|r| !r.file.is_empty()).collect();
@@ -26,19 +43,15 @@ pub fn format_and_print_search_results(
     // Handle different output formats
     match format {
         "color" if use_color => {
-            format_and_print_color_results(&valid_results, dry_run, query_plan, debug_mode);
+            format_color_results(&mut output, &valid_results, dry_run, query_plan, debug_mode)?;
         }
         "json" => {
-            if let Err(e) = format_and_print_json_results(&valid_results) {
-                eprintln!("Error formatting JSON: {e}");
-            }
-            return; // Skip the summary output at the end
+            format_json_results(&mut output, &valid_results)?;
+            return Ok(output); // Skip the summary output at the end
         }
         "xml" => {
-            if let Err(e) = format_and_print_xml_results(&valid_results) {
-                eprintln!("Error formatting XML: {e}");
-            }
-            return; // Skip the summary output at the end
+            format_xml_results(&mut output, &valid_results)?;
+            return Ok(output); // Skip the summary output at the end
         }
         _ => {
             // Default format (terminal)
@@ -53,64 +66,71 @@ pub fn format_and_print_search_results(
                 if dry_run {
                     // In dry-run mode, only print file names and line numbers
                     if is_full_file {
-                        println!("File: {}", result.file);
+                        writeln!(output, "File: {}", result.file)?;
                     } else {
-                        println!(
-                            "File: {}, Lines: {}-{}",
-                            result.file, result.lines.0, result.lines.1
-                        );
+                        writeln!(
+                            output,
+                            "File: {}, Lines: {}-{}{}",
+                            result.file,
+                            result.lines.0,
+                            result.lines.1,
+                            cells_note(result)
+                        )?;
                     }
                 } else {
                     // Normal mode with full content
                     if is_full_file {
-                        println!("File: {}", result.file);
-                        println!("```{extension}");
-                        println!("{}", result.code);
-                        println!("```");
+                        writeln!(output, "File: {}", result.file)?;
+                        writeln!(output, "```{extension}")?;
+                        writeln!(output, "{}", result.code)?;
+                        writeln!(output, "```")?;
                     } else {
-                        println!("File: {}", result.file);
-                        println!(
-                            "Lines: {start}-{end}",
+                        writeln!(output, "File: {}", result.file)?;
+                        writeln!(
+                            output,
+                            "Lines: {start}-{end}{cells}",
                             start = result.lines.0,
-                            end = result.lines.1
-                        );
-                        println!("```{extension}");
-                        println!("{code}", code = result.code);
-                        println!("```");
+                            end = result.lines.1,
+                            cells = cells_note(result)
+                        )?;
+                        writeln!(output, "```{extension}")?;
+                        writeln!(output, "{code}", code = result.code)?;
+                        writeln!(output, "```")?;
                     }
                 }
                 if debug_mode {
                     if let Some(rank) = result.rank {
                         // Add a display order field to show the actual ordering of results
-                        println!(
+                        writeln!(
+                            output,
                             "Display Order: {}",
                             results
                                 .iter()
                                 .position(|r| r.file == result.file && r.lines == result.lines)
                                 .unwrap_or(0)
                                 + 1
-                        );
+                        )?;
 
-                        println!("Rank: {rank}");
+                        writeln!(output, "Rank: {rank}")?;
 
                         if let Some(score) = result.score {
-                            println!("Combined Score: {score:.4}");
+                            writeln!(output, "Combined Score: {score:.4}")?;
                         }
 
                         // Display the combined score rank if available, otherwise calculate it
                         if let Some(combined_rank) = result.combined_score_rank {
-                            println!("Combined Score Rank: {combined_rank}");
+                            writeln!(output, "Combined Score Rank: {combined_rank}")?;
                         } else {
                             // Fall back to the old behavior if the field isn't set
-                            println!("Combined Score Rank: {rank}");
+                            writeln!(output, "Combined Score Rank: {rank}")?;
                         }
 
                         if let Some(tfidf_score) = result.tfidf_score {
-                            println!("TF-IDF Score: {tfidf_score:.4}");
+                            writeln!(output, "TF-IDF Score: {tfidf_score:.4}")?;
                         }
 
                         if let Some(tfidf_rank) = result.tfidf_rank {
-                            println!("TF-IDF Rank: {tfidf_rank}");
+                            writeln!(output, "TF-IDF Rank: {tfidf_rank}")?;
                         }
 
                         if let Some(bm25_score) = result.bm25_score {
@@ -119,55 +139,59 @@ pub fn format_and_print_search_results(
                             let is_bert_score =
                                 result.score == result.bm25_score && result.score.is_some();
                             if is_bert_score {
-                                println!("BERT Score: {bm25_score:.4}");
+                                writeln!(output, "BERT Score: {bm25_score:.4}")?;
                             } else {
-                                println!("BM25 Score: {bm25_score:.4}");
+                                writeln!(output, "BM25 Score: {bm25_score:.4}")?;
                             }
                         }
 
                         if let Some(bm25_rank) = result.bm25_rank {
-                            println!("BM25 Rank: {bm25_rank}");
+                            writeln!(output, "BM25 Rank: {bm25_rank}")?;
                         }
 
                         // Display Hybrid 2 score and rank with more prominence
                         if let Some(new_score) = result.new_score {
-                            println!("Hybrid 2 Score: {new_score:.4}");
+                            writeln!(output, "Hybrid 2 Score: {new_score:.4}")?;
                         }
 
                         if let Some(hybrid2_rank) = result.hybrid2_rank {
-                            println!("Hybrid 2 Rank: {hybrid2_rank}");
+                            writeln!(output, "Hybrid 2 Rank: {hybrid2_rank}")?;
                         } else if result.new_score.is_some() {
-                            println!("Hybrid 2 Rank: N/A");
+                            writeln!(output, "Hybrid 2 Rank: N/A")?;
                         }
 
                         if let Some(file_unique_terms) = result.file_unique_terms {
-                            println!("File Unique Terms: {file_unique_terms}");
+                            writeln!(output, "File Unique Terms: {file_unique_terms}")?;
                         }
 
                         if let Some(file_total_matches) = result.file_total_matches {
-                            println!("File Total Matches: {file_total_matches}");
+                            writeln!(output, "File Total Matches: {file_total_matches}")?;
                         }
 
                         if let Some(file_match_rank) = result.file_match_rank {
-                            println!("File Match Rank: {file_match_rank}");
+                            writeln!(output, "File Match Rank: {file_match_rank}")?;
                         }
 
                         if let Some(block_unique_terms) = result.block_unique_terms {
-                            println!("Block Unique Terms: {block_unique_terms}");
+                            writeln!(output, "Block Unique Terms: {block_unique_terms}")?;
                         }
 
                         if let Some(block_total_matches) = result.block_total_matches {
-                            println!("Block Total Matches: {block_total_matches}");
+                            writeln!(output, "Block Total Matches: {block_total_matches}")?;
                         }
 
-                        println!("Type: {}", result.node_type);
+                        writeln!(output, "Type: {}", result.node_type)?;
                     }
                 }
             }
         }
     }
 
-    println!("Found {count} search results", count = valid_results.len());
+    writeln!(
+        output,
+        "Found {count} search results",
+        count = valid_results.len()
+    )?;
 
     let total_bytes: usize = valid_results.iter().map(|r| r.code.len()).sum();
 
@@ -176,28 +200,34 @@ pub fn format_and_print_search_results(
     // when multiple identical code blocks need tokenization (common in search results)
     let code_blocks: Vec<&str> = valid_results.iter().map(|r| r.code.as_str()).collect();
     let total_tokens: usize = sum_tokens_with_deduplication(&code_blocks);
-    println!("Total bytes returned: {total_bytes}");
-    println!("Total tokens returned: {total_tokens}");
+    writeln!(output, "Total bytes returned: {total_bytes}")?;
+    writeln!(output, "Total tokens returned: {total_tokens}")?;
+    Ok(output)
 }
 
-/// Format and print search results with color highlighting for matching words
-fn format_and_print_color_results(
+/// Format search results with color highlighting for matching words
+fn format_color_results(
+    output: &mut String,
     results: &[&SearchResult],
     dry_run: bool,
     query_plan: Option<&QueryPlan>,
     debug_mode: bool,
-) {
+) -> Result<()> {
     use colored::*;
     use regex::Regex;
 
     if results.is_empty() {
-        println!("No results found.");
-        return;
+        writeln!(output, "No results found.")?;
+        return Ok(());
     }
 
     // Print a header with the number of results
-    println!("{}", format!("Found {} results", results.len()).bold());
-    println!();
+    writeln!(
+        output,
+        "{}",
+        format!("Found {} results", results.len()).bold()
+    )?;
+    writeln!(output)?;
 
     // Print the results
     for (index, result) in results.iter().enumerate() {
@@ -212,45 +242,54 @@ fn format_and_print_color_results(
         let is_full_file = result.node_type == "file";
 
         // Print result number
-        println!(
+        writeln!(
+            output,
             "{} {}",
             "Result".bold().blue(),
             format!("#{}", index + 1).bold().blue()
-        );
+        )?;
 
         // Print the file path and node info with color
         if is_full_file {
-            println!(
+            writeln!(
+                output,
                 "{label} {file}",
                 label = "File:".bold().green().yellow(),
                 file = result.file
-            );
+            )?;
         } else {
-            println!(
+            writeln!(
+                output,
                 "{} {} ({})",
                 "File:".bold().green(),
                 result.file.yellow(),
                 result.node_type.cyan()
-            );
-            println!(
-                "{} {}-{}",
+            )?;
+            writeln!(
+                output,
+                "{} {}-{}{}",
                 "Lines:".bold().green(),
                 result.lines.0,
-                result.lines.1
-            );
+                result.lines.1,
+                cells_note(result)
+            )?;
         }
 
         // Print additional debug information if in debug mode
         if debug_mode {
             // Print the same debug info that would be shown in standard mode
             if let Some(keywords) = &result.matched_keywords {
-                println!("{} {keywords:?}", "Matched Keywords:".bold().green());
+                writeln!(
+                    output,
+                    "{} {keywords:?}",
+                    "Matched Keywords:".bold().green()
+                )?;
             }
             if let Some(score) = result.score {
-                println!("{} {score:.4}", "Score:".bold().green());
+                writeln!(output, "{} {score:.4}", "Score:".bold().green())?;
             }
             if let Some(query_plan) = query_plan {
-                println!("{} {query_plan:?}", "Query Plan:".bold().green());
+                writeln!(output, "{} {query_plan:?}", "Query Plan:".bold().green())?;
             }
         }
 
@@ -262,7 +301,7 @@ fn format_and_print_color_results(
         // Determine the language for syntax highlighting
         let language = match extension {
             "rs" => "rust",
-            "py" => "python",
+            "py" | "ipynb" => "python",
             "js" => "javascript",
             "ts" => "typescript",
             "go" => "go",
@@ -272,15 +311,20 @@ fn format_and_print_color_results(
             "rb" => "ruby",
             "php" => "php",
             "sh" => "bash",
-            "md" => "markdown",
+            "md" | "markdown" | "mdx" => "markdown",
+            "rst" => "rst",
             "json" => "json",
             "yaml" | "yml" => "yaml",
-            "html" => "html",
+            "html" | "htm" => "html",
+            "vue" => "vue",
+            "svelte" => "svelte",
+            "astro" => "astro",
             "css" => "css",
             "sql" => "sql",
             "kt" | "kts" => "kotlin",
             "swift" => "swift",
-            "scala" => "scala",
+            "scala" | "sc" => "scala",
+            "tf" | "tfvars" | "hcl" => "hcl",
             "dart" => "dart",
             "ex" | "exs" => "elixir",
             "hs" => "haskell",
@@ -292,13 +336,17 @@ fn format_and_print_color_results(
             _ => "",
         };
 
-        println!("{label}", label = "Code:".bold().magenta());
+        writeln!(output, "{label}", label = "Code:".bold().magenta())?;
 
         // Print the code with syntax highlighting
         if !language.is_empty() {
-            println!("{code_block}", code_block = format!("```{language}").cyan());
+            writeln!(
+                output,
+                "{code_block}",
+                code_block = format!("```{language}").cyan()
+            )?;
         } else {
-            println!("{code_block}", code_block = "```".cyan());
+            writeln!(output, "{code_block}", code_block = "```".cyan())?;
         }
 
         // Generate patterns from the matched keywords in the search result
@@ -384,53 +432,54 @@ fn format_and_print_color_results(
 
             // Print the line (highlighted or original if no matches)
             if !merged_matches.is_empty() {
-                println!("{output_line}");
+                writeln!(output, "{output_line}")?;
             } else {
-                println!("{line}");
+                writeln!(output, "{line}")?;
             }
         }
 
-        println!();
+        writeln!(output)?;
 
         // Print a separator between results
         if index < results.len() - 1 {
-            println!();
-            println!("{separator}", separator = "─".repeat(50).cyan());
-            println!();
+            writeln!(output)?;
+            writeln!(output, "{separator}", separator = "─".repeat(50).cyan())?;
+            writeln!(output)?;
         }
 
         if debug_mode {
             if let Some(rank) = result.rank {
                 // Add a display order field to show the actual ordering of results
-                println!(
+                writeln!(
+                    output,
                     "Display Order: {}",
                     results
                         .iter()
                         .position(|r| r.file == result.file && r.lines == result.lines)
                         .unwrap_or(0)
                         + 1
-                );
+                )?;
 
-                println!("Rank: {rank}");
+                writeln!(output, "Rank: {rank}")?;
 
                 if let Some(score) = result.score {
-                    println!("Combined Score: {score:.4}");
+                    writeln!(output, "Combined Score: {score:.4}")?;
                 }
 
                 // Display the combined score rank if available, otherwise calculate it
                 if let Some(combined_rank) = result.combined_score_rank {
-                    println!("Combined Score Rank: {combined_rank}");
+                    writeln!(output, "Combined Score Rank: {combined_rank}")?;
                 } else {
                     // Fall back to the old behavior if the field isn't set
-                    println!("Combined Score Rank: {rank}");
+                    writeln!(output, "Combined Score Rank: {rank}")?;
                 }
 
                 if let Some(tfidf_score) = result.tfidf_score {
-                    println!("TF-IDF Score: {tfidf_score:.4}");
+                    writeln!(output, "TF-IDF Score: {tfidf_score:.4}")?;
                 }
 
                 if let Some(tfidf_rank) = result.tfidf_rank {
-                    println!("TF-IDF Rank: {tfidf_rank}");
+                    writeln!(output, "TF-IDF Rank: {tfidf_rank}")?;
                 }
 
                 if let Some(bm25_score) = result.bm25_score {
@@ -438,51 +487,61 @@ fn format_and_print_color_results(
                     // When BERT reranking is used, both score and bm25_score are set to BERT score
                     let is_bert_score = result.score == result.bm25_score && result.score.is_some();
                     if is_bert_score {
-                        println!("BERT Score: {bm25_score:.4}");
+                        writeln!(output, "BERT Score: {bm25_score:.4}")?;
                     } else {
-                        println!("BM25 Score: {bm25_score:.4}");
+                        writeln!(output, "BM25 Score: {bm25_score:.4}")?;
                     }
                 }
 
                 if let Some(bm25_rank) = result.bm25_rank {
-                    println!("BM25 Rank: {bm25_rank}");
+                    writeln!(output, "BM25 Rank: {bm25_rank}")?;
                 }
 
                 // Display Hybrid 2 score and rank with more prominence
                 if let Some(new_score) = result.new_score {
-                    println!("Hybrid 2 Score: {new_score:.4}");
+                    writeln!(output, "Hybrid 2 Score: {new_score:.4}")?;
                 }
 
                 if let Some(hybrid2_rank) = result.hybrid2_rank {
-                    println!("Hybrid 2 Rank: {hybrid2_rank}");
+                    writeln!(output, "Hybrid 2 Rank: {hybrid2_rank}")?;
                 } else if result.new_score.is_some() {
-                    println!("Hybrid 2 Rank: N/A");
+                    writeln!(output, "Hybrid 2 Rank: N/A")?;
                 }
 
                 if let Some(file_unique_terms) = result.file_unique_terms {
-                    println!("File Unique Terms: {file_unique_terms}");
+                    writeln!(output, "File Unique Terms: {file_unique_terms}")?;
                 }
 
                 if let Some(file_total_matches) = result.file_total_matches {
-                    println!("File Total Matches: {file_total_matches}");
+                    writeln!(output, "File Total Matches: {file_total_matches}")?;
                 }
 
                 if let Some(file_match_rank) = result.file_match_rank {
-                    println!("File Match Rank: {file_match_rank}");
+                    writeln!(output, "File Match Rank: {file_match_rank}")?;
                 }
 
                 if let Some(block_unique_terms) = result.block_unique_terms {
-                    println!("Block Unique Terms: {block_unique_terms}");
+                    writeln!(output, "Block Unique Terms: {block_unique_terms}")?;
                 }
 
                 if let Some(block_total_matches) = result.block_total_matches {
-                    println!("Block Total Matches: {block_total_matches}");
+                    writeln!(output, "Block Total Matches: {block_total_matches}")?;
                 }
 
-                println!("Type: {}", result.node_type);
+                writeln!(output, "Type: {}", result.node_type)?;
             }
         }
     }
+    Ok(())
+}
+
+/// Where the lines of a result in a notebook are in its cells, like
+/// ` (cell 3, lines 2-5)`, or nothing for other files
+fn cells_note(result: &SearchResult) -> String {
+    result
+        .cells
+        .map(|cells| format!(" ({cells})"))
+        .unwrap_or_default()
 }
 
 /// Helper function to escape XML special characters
@@ -494,13 +553,31 @@ fn escape_xml(s: &str) -> String {
         .replace("'", "&apos;")
 }
 
-/// Format and print search results in JSON format
-fn format_and_print_json_results(results: &[&SearchResult]) -> Result<()> {
+/// Search results as the JSON document printed by `--format json`
+pub fn search_results_json(results: &[SearchResult]) -> serde_json::Value {
+    let valid_results: Vec<&SearchResult> = results.iter().filter(|r| !r.file.is_empty()).collect();
+    json_results_wrapper(&valid_results)
+}
+
+/// Format search results in JSON format
+fn format_json_results(output: &mut String, results: &[&SearchResult]) -> Result<()> {
+    let wrapper = json_results_wrapper(results);
+    writeln!(
+        output,
+        "{json}",
+        json = serde_json::to_string_pretty(&wrapper)?
+    )?;
+    Ok(())
+}
+
+fn json_results_wrapper(results: &[&SearchResult]) -> serde_json::Value {
     // Create a simplified version of the results for JSON output
     #[derive(serde::Serialize)]
     struct JsonResult<'a> {
         file: &'a str,
         lines: [usize; 2],
+        #[serde(skip_serializing_if = "Option::is_none")]
+        cells: Option<CellRange>,
         node_type: &'a str,
         code: &'a str,
         // Include other relevant fields
@@ -519,6 +596,7 @@ fn format_and_print_json_results(results: &[&SearchResult]) -> Result<()> {
         .map(|r| JsonResult {
             file: &r.file,
             lines: [r.lines.0, r.lines.1],
+            cells: r.cells,
             node_type: &r.node_type,
             code: &r.code,
             matched_keywords: r.matched_keywords.as_ref(),
@@ -538,7 +616,7 @@ fn format_and_print_json_results(results: &[&SearchResult]) -> Result<()> {
     let total_tokens = sum_tokens_with_deduplication(&code_blocks);
 
     // Create a wrapper object with results and summary
-    let wrapper = serde_json::json!({
+    serde_json::json!({
         "results": json_results,
         "summary": {
             "count": results.len(),
@@ -546,93 +624,118 @@ fn format_and_print_json_results(results: &[&SearchResult]) -> Result<()> {
             "total_tokens": total_tokens,
         },
         "version": probe_code::version::get_version()
-    });
-
-    println!("{json}", json = serde_json::to_string_pretty(&wrapper)?);
-    Ok(())
+    })
 }
 
-/// Format and print search results in XML format
-fn format_and_print_xml_results(results: &[&SearchResult]) -> Result<()> {
-    println!("<?xml version=\"1.0\" encoding=\"UTF-8\"?>");
-    println!("<probe_results>");
+/// Format search results in XML format
+fn format_xml_results(output: &mut String, results: &[&SearchResult]) -> Result<()> {
+    writeln!(output, "<?xml version=\"1.0\" encoding=\"UTF-8\"?>")?;
+    writeln!(output, "<probe_results>")?;
 
     for result in results {
-        println!("  <result>");
-        println!("    <file>{file}</file>", file = escape_xml(&result.file));
-        println!(
+        writeln!(output, "  <result>")?;
+        writeln!(
+            output,
+            "    <file>{file}</file>",
+            file = escape_xml(&result.file)
+        )?;
+        writeln!(
+            output,
             "    <lines>{start}-{end}</lines>",
             start = result.lines.0,
             end = result.lines.1
-        );
-        println!(
+        )?;
+        if let Some(cells) = result.cells {
+            writeln!(output, "    <cells>{cells}</cells>")?;
+        }
+        writeln!(
+            output,
             "    <node_type>{}</node_type>",
             escape_xml(&result.node_type)
-        );
+        )?;
 
         if let Some(keywords) = &result.matched_keywords {
-            println!("    <matched_keywords>");
+            writeln!(output, "    <matched_keywords>")?;
             for keyword in keywords {
-                println!(
+                writeln!(
+                    output,
                     "      <keyword>{keyword}</keyword>",
                     keyword = escape_xml(keyword)
-                );
+                )?;
             }
-            println!("    </matched_keywords>");
+            writeln!(output, "    </matched_keywords>")?;
         }
 
         if let Some(score) = result.score {
-            println!("    <score>{score:.4}</score>");
+            writeln!(output, "    <score>{score:.4}</score>")?;
         }
 
         if let Some(tfidf_score) = result.tfidf_score {
-            println!("    <tfidf_score>{tfidf_score:.4}</tfidf_score>");
+            writeln!(output, "    <tfidf_score>{tfidf_score:.4}</tfidf_score>")?;
         }
 
         if let Some(bm25_score) = result.bm25_score {
-            println!("    <bm25_score>{bm25_score:.4}</bm25_score>");
+            writeln!(output, "    <bm25_score>{bm25_score:.4}</bm25_score>")?;
         }
 
         if let Some(file_unique_terms) = result.file_unique_terms {
-            println!("    <file_unique_terms>{file_unique_terms}</file_unique_terms>");
+            writeln!(
+                output,
+                "    <file_unique_terms>{file_unique_terms}</file_unique_terms>"
+            )?;
         }
 
         if let Some(file_total_matches) = result.file_total_matches {
-            println!("    <file_total_matches>{file_total_matches}</file_total_matches>");
+            writeln!(
+                output,
+                "    <file_total_matches>{file_total_matches}</file_total_matches>"
+            )?;
         }
 
         if let Some(block_unique_terms) = result.block_unique_terms {
-            println!("    <block_unique_terms>{block_unique_terms}</block_unique_terms>");
+            writeln!(
+                output,
+                "    <block_unique_terms>{block_unique_terms}</block_unique_terms>"
+            )?;
         }
 
         if let Some(block_total_matches) = result.block_total_matches {
-            println!("    <block_total_matches>{block_total_matches}</block_total_matches>");
+            writeln!(
+                output,
+                "    <block_total_matches>{block_total_matches}</block_total_matches>"
+            )?;
         }
 
-        println!("    <code><![CDATA[{code}]]></code>", code = result.code);
-        println!("  </result>");
+        writeln!(
+            output,
+            "    <code><![CDATA[{code}]]></code>",
+            code = result.code
+        )?;
+        writeln!(output, "  </result>")?;
     }
 
     // Add summary section
-    println!("  <summary>");
-    println!("    <count>{}</count>", results.len());
-    println!(
+    writeln!(output, "  <summary>")?;
+    writeln!(output, "    <count>{}</count>", results.len())?;
+    writeln!(
+        output,
         "    <total_bytes>{total_bytes}</total_bytes>",
         total_bytes = results.iter().map(|r| r.code.len()).sum::<usize>()
-    );
+    )?;
     // BATCH TOKENIZATION WITH DEDUPLICATION OPTIMIZATION for XML output:
     // Process all code blocks in batch to leverage content deduplication
     let code_blocks: Vec<&str> = results.iter().map(|r| r.code.as_str()).collect();
     let total_tokens = sum_tokens_with_deduplication(&code_blocks);
 
-    println!("    <total_tokens>{total_tokens}</total_tokens>");
-    println!("  </summary>");
+    writeln!(output, "    <total_tokens>{total_tokens}</total_tokens>")?;
+    writeln!(output, "  </summary>")?;
 
-    println!(
+    writeln!(
+        output,
         "  <version>{}</version>",
         probe_code::version::get_version()
-    );
+    )?;
 
-    println!("</probe_results>");
+    writeln!(output, "</probe_results>")?;
     Ok(())
 }
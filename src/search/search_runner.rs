@@ -10,17 +10,24 @@ use std::time::{Duration, Instant};
 use probe_code::models::{LimitedSearchResults, SearchResult};
 use probe_code::path_resolver::resolve_path;
 use probe_code::search::{
-    cache,
-    early_ranker,
-    // file_list_cache, // Add the new file_list_cache module (unused)
+    cache, early_ranker,
+    elastic_query::{
+        expand_fuzzy_terms, expand_synonyms, expand_wildcards, extract_inline_filters, fuzzy_terms,
+        wildcard_terms, FuzzyTerm, ParseError, WildcardPattern,
+    },
+    file_list_cache::FileFilters,
     file_processing::{process_file_with_results, FileProcessingParams},
     query::{create_query_plan, create_structured_patterns, QueryPlan},
     result_ranking::rank_search_results,
+    search_explain::{explain_results, print_query_explanation, print_result_explanations},
+    search_index::SearchIndex,
     search_limiter::apply_limits,
     search_options::SearchOptions,
     simd_pattern_matching::SimdPatternMatcher,
-    timeout,
+    synonyms::Synonyms,
+    timeout, vocabulary,
 };
+use probe_code::source::Sources;
 
 /// Struct to hold timing information for different stages of the search process
 pub struct SearchTimings {
@@ -78,7 +85,7 @@ pub fn format_duration(duration: Duration) -> String {
 
 /// Helper function to print timing information in debug mode
 pub fn print_timings(timings: &SearchTimings) {
-    let debug_mode = std::env::var("DEBUG").unwrap_or_default() == "1";
+    let debug_mode = probe_code::console::debug_mode();
     if !debug_mode {
         return;
     }
@@ -225,6 +232,8 @@ pub fn perform_probe(options: &SearchOptions) -> Result<LimitedSearchResults> {
         reranker,
         frequency_search: _,
         exact,
+        fuzzy: _,
+        explain,
         language,
         max_results,
         max_bytes,
@@ -237,19 +246,28 @@ pub fn perform_probe(options: &SearchOptions) -> Result<LimitedSearchResults> {
         timeout,
         question,
         no_gitignore,
+        file_filters,
+        source,
     } = options;
+    // The files of an archive, like `deps.zip`, are read from the archive
+    let sources = Sources::new(source.clone()).with_archives([path])?;
     // Start the timeout thread
     let timeout_handle = timeout::start_timeout_thread(*timeout);
 
     let include_filenames = !exclude_filenames;
-    let debug_mode = std::env::var("DEBUG").unwrap_or_default() == "1";
+    let debug_mode = probe_code::console::debug_mode();
+
+    // Programs embedding probe pass their sessions explicitly
+    let env_session_id = std::env::var("PROBE_SESSION_ID")
+        .ok()
+        .filter(|_| !probe_code::console::is_silenced());
 
     // Handle session ID generation if session is provided but empty
     // For test runs, force session to None to disable caching
     let (effective_session, session_was_generated) = if let Some(s) = session {
         if s.is_empty() || *s == "new" {
             // Check if we have a session ID in the environment variable
-            if let Ok(env_session_id) = std::env::var("PROBE_SESSION_ID") {
+            if let Some(env_session_id) = env_session_id {
                 if !env_session_id.is_empty() {
                     if debug_mode {
                         println!("DEBUG: Using session ID from environment: {env_session_id}");
@@ -267,7 +285,9 @@ pub fn perform_probe(options: &SearchOptions) -> Result<LimitedSearchResults> {
                             (Some(new_id), true)
                         }
                         Err(e) => {
-                            eprintln!("Error generating session ID: {e}");
+                            if !probe_code::console::is_silenced() {
+                                eprintln!("Error generating session ID: {e}");
+                            }
                             (None, false)
                         }
                     }
@@ -282,7 +302,9 @@ pub fn perform_probe(options: &SearchOptions) -> Result<LimitedSearchResults> {
                         (Some(new_id), true)
                     }
                     Err(e) => {
-                        eprintln!("Error generating session ID: {e}");
+                        if !probe_code::console::is_silenced() {
+                            eprintln!("Error generating session ID: {e}");
+                        }
                         (None, false)
                     }
                 }
@@ -292,7 +314,7 @@ pub fn perform_probe(options: &SearchOptions) -> Result<LimitedSearchResults> {
         }
     } else {
         // Check if we have a session ID in the environment variable
-        if let Ok(env_session_id) = std::env::var("PROBE_SESSION_ID") {
+        if let Some(env_session_id) = env_session_id {
             if !env_session_id.is_empty() {
                 if debug_mode {
                     println!("DEBUG: Using session ID from environment: {env_session_id}");
@@ -355,7 +377,54 @@ pub fn perform_probe(options: &SearchOptions) -> Result<LimitedSearchResults> {
         println!("DEBUG: Starting query preprocessing...");
     }
 
-    let parse_res = if queries.len() > 1 {
+    // Pull inline file filters (path:, ext:, lang:, -path:) out of the queries.
+    // Exact queries are taken literally and never contain filters.
+    let mut effective_filters = file_filters.clone();
+    let stripped_queries: Vec<String> = if *exact {
+        queries.to_vec()
+    } else {
+        queries
+            .iter()
+            .map(|q| {
+                let (remaining, inline_filters) = extract_inline_filters(q);
+                effective_filters.merge(inline_filters);
+                remaining
+            })
+            .filter(|q| !q.trim().is_empty())
+            .collect()
+    };
+    for languages in [
+        &mut effective_filters.languages,
+        &mut effective_filters.exclude_languages,
+    ] {
+        *languages = languages
+            .iter()
+            .map(|lang| normalize_language_alias(lang).to_string())
+            .collect();
+    }
+    let queries: &[String] = &stripped_queries;
+
+    if debug_mode && !effective_filters.is_empty() {
+        println!("DEBUG: File filters: {effective_filters:?}");
+        println!("DEBUG: Queries without inline filters: {queries:?}");
+    }
+
+    // Expand fuzzy terms (recieve~1) and wildcard terms (auth*, *Handler, get?ser) to
+    // the words found in the searched files, then add the synonyms defined in
+    // .probe/synonyms.toml. Both the query plan and ranking work on the expanded queries.
+    let expanded_queries = if *exact {
+        queries.to_vec()
+    } else {
+        let expanded = expand_query_terms(&sources, queries, options, &effective_filters)?;
+        expand_query_synonyms(&expanded, options)?
+    };
+    let queries: &[String] = &expanded_queries;
+
+    let parse_res = if queries.is_empty() {
+        Err(ParseError::Generic(
+            "Query contains only path/ext/lang filters".to_string(),
+        ))
+    } else if queries.len() > 1 {
         // Join multiple queries with AND
         let combined_query = queries.join(" AND ");
         create_query_plan(&combined_query, *exact)
@@ -374,8 +443,10 @@ pub fn perform_probe(options: &SearchOptions) -> Result<LimitedSearchResults> {
     }
 
     // If the query fails to parse, return empty results
-    if parse_res.is_err() {
-        println!("Failed to parse query as AST expression");
+    if let Err(e) = &parse_res {
+        if !probe_code::console::is_silenced() {
+            println!("Failed to parse query as AST expression: {e}");
+        }
         return Ok(LimitedSearchResults {
             results: Vec::new(),
             skipped_files: Vec::new(),
@@ -398,6 +469,10 @@ pub fn perform_probe(options: &SearchOptions) -> Result<LimitedSearchResults> {
     // Use combined pattern approach for more efficient searching
     let structured_patterns = create_structured_patterns(&plan);
 
+    if *explain {
+        print_query_explanation(&queries.join(" AND "), &plan, &structured_patterns);
+    }
+
     let pg_duration = pg_start.elapsed();
     timings.pattern_generation = Some(pg_duration);
 
@@ -437,6 +512,7 @@ pub fn perform_probe(options: &SearchOptions) -> Result<LimitedSearchResults> {
     let lang_param = language.as_ref().map(|lang| normalize_language_alias(lang));
 
     let mut file_term_map = search_with_structured_patterns(
+        &sources,
         path,
         &plan,
         &structured_patterns,
@@ -444,7 +520,20 @@ pub fn perform_probe(options: &SearchOptions) -> Result<LimitedSearchResults> {
         *allow_tests,
         lang_param,
         *no_gitignore,
+        &effective_filters,
     )?;
+    if timeout_handle.expired() {
+        return Err(timed_out(*timeout));
+    }
+
+    // Build code blocks of unchanged files from the line maps stored in the
+    // search index instead of parsing them
+    if let Some(index) = SearchIndex::find(&sources, &search_root(path)) {
+        let preloaded = index.preload_line_maps(file_term_map.keys(), *allow_tests);
+        if debug_mode {
+            println!("DEBUG: Loaded {preloaded} line maps from the search index");
+        }
+    }
 
     let fs_duration = fs_start.elapsed();
     timings.file_searching = Some(fs_duration);
@@ -506,6 +595,7 @@ pub fn perform_probe(options: &SearchOptions) -> Result<LimitedSearchResults> {
 
         let filename_matches: HashMap<PathBuf, HashSet<usize>> =
             file_list_cache::find_matching_filenames(
+                &sources,
                 &resolved_path,
                 queries,
                 &all_files,
@@ -514,6 +604,7 @@ pub fn perform_probe(options: &SearchOptions) -> Result<LimitedSearchResults> {
                 &plan.term_indices,
                 lang_param,
                 *no_gitignore,
+                &effective_filters,
             )?;
 
         if debug_mode {
@@ -529,7 +620,7 @@ pub fn perform_probe(options: &SearchOptions) -> Result<LimitedSearchResults> {
             const MAX_FILE_SIZE: u64 = 1024 * 1024;
 
             // Check file metadata and resolve symlinks before reading
-            let resolved_path = match std::fs::canonicalize(pathbuf.as_path()) {
+            let resolved_path = match sources.canonicalize(pathbuf.as_path()) {
                 Ok(path) => path,
                 Err(e) => {
                     if debug_mode {
@@ -539,9 +630,9 @@ pub fn perform_probe(options: &SearchOptions) -> Result<LimitedSearchResults> {
                 }
             };
 
-            // Get file metadata to check size and file type
-            let metadata = match std::fs::metadata(&resolved_path) {
-                Ok(meta) => meta,
+            // Get the file size to skip large files
+            let file_size = match sources.file_size(&resolved_path) {
+                Ok(size) => size,
                 Err(e) => {
                     if debug_mode {
                         println!("DEBUG: Error getting metadata for {resolved_path:?}: {e:?}");
@@ -551,28 +642,22 @@ pub fn perform_probe(options: &SearchOptions) -> Result<LimitedSearchResults> {
             };
 
             // Check if the file is too large
-            if metadata.len() > MAX_FILE_SIZE {
+            if file_size > MAX_FILE_SIZE {
                 if debug_mode {
                     println!(
-                        "DEBUG: Skipping file {:?} - file too large ({} bytes > {} bytes limit)",
-                        resolved_path,
-                        metadata.len(),
-                        MAX_FILE_SIZE
+                        "DEBUG: Skipping file {resolved_path:?} - file too large ({file_size} bytes > {MAX_FILE_SIZE} bytes limit)"
                     );
                 }
                 continue;
             }
 
             // Read the file content to get the total number of lines
-            let file_content = match std::fs::read_to_string(&resolved_path) {
+            let file_content = match sources.read_to_string(&resolved_path) {
                 Ok(content) => content,
                 Err(e) => {
                     if debug_mode {
                         println!(
-                            "DEBUG: Error reading file {:?}: {:?} (size: {} bytes)",
-                            resolved_path,
-                            e,
-                            metadata.len()
+                            "DEBUG: Error reading file {resolved_path:?}: {e:?} (size: {file_size} bytes)"
                         );
                     }
                     continue;
@@ -723,6 +808,7 @@ pub fn perform_probe(options: &SearchOptions) -> Result<LimitedSearchResults> {
                 block_id: None,
                 matched_keywords: None,
                 tokenized_content: None,
+                cells: None,
             });
         }
         let mut limited = apply_limits(res, *max_results, *max_bytes, *max_tokens);
@@ -755,13 +841,18 @@ pub fn perform_probe(options: &SearchOptions) -> Result<LimitedSearchResults> {
                 "DEBUG: Starting early caching for session: {session_id} with query: {raw_query}"
             );
             // Print cache contents before filtering
-            if let Err(e) = cache::debug_print_cache(session_id, &raw_query) {
+            if let Err(e) = cache::debug_print_cache(&sources, session_id, &raw_query) {
                 eprintln!("Error printing cache: {e}");
             }
         }
 
         // Filter matched lines using the cache
-        match cache::filter_matched_lines_with_cache(&mut file_term_map, session_id, &raw_query) {
+        match cache::filter_matched_lines_with_cache(
+            &sources,
+            &mut file_term_map,
+            session_id,
+            &raw_query,
+        ) {
             Ok(skipped) => {
                 if debug_mode {
                     println!("DEBUG: Early caching skipped {skipped} matched lines");
@@ -770,7 +861,9 @@ pub fn perform_probe(options: &SearchOptions) -> Result<LimitedSearchResults> {
             }
             Err(e) => {
                 // Log the error but continue without early caching
-                eprintln!("Error applying early cache: {e}");
+                if !probe_code::console::is_silenced() {
+                    eprintln!("Error applying early cache: {e}");
+                }
             }
         }
 
@@ -918,6 +1011,10 @@ pub fn perform_probe(options: &SearchOptions) -> Result<LimitedSearchResults> {
         let mut batch_results = Vec::new();
 
         for early_rank_result in batch {
+            // Without the exit of the process, a timeout ends the search here
+            if timeout_handle.expired() {
+                return Err(timed_out(*timeout));
+            }
             let pathbuf = &early_rank_result.path;
             if debug_mode {
                 println!(
@@ -960,6 +1057,7 @@ pub fn perform_probe(options: &SearchOptions) -> Result<LimitedSearchResults> {
                     .collect();
 
                 let pparams = FileProcessingParams {
+                    sources: &sources,
                     path: pathbuf,
                     line_numbers: &all_lines,
                     allow_tests: *allow_tests,
@@ -1343,6 +1441,9 @@ pub fn perform_probe(options: &SearchOptions) -> Result<LimitedSearchResults> {
         final_results.sort_by(|a, b| (&a.file, a.lines.0).cmp(&(&b.file, b.lines.0)));
     }
 
+    // Break down the scores for --explain while the ranked set is still complete
+    let explanations = (*explain && !*exact).then(|| explain_results(&final_results, queries));
+
     let rr_duration = rr_start.elapsed();
     timings.result_ranking = Some(rr_duration);
 
@@ -1373,6 +1474,10 @@ pub fn perform_probe(options: &SearchOptions) -> Result<LimitedSearchResults> {
     // First apply limits to the results
     let mut limited = apply_limits(filtered_results, *max_results, *max_bytes, *max_tokens);
 
+    if *explain {
+        print_result_explanations(&limited.results, explanations.as_ref());
+    }
+
     // Calculate files skipped due to early termination
     let files_skipped_early_termination = total_ranked_files.saturating_sub(files_processed);
 
@@ -1416,13 +1521,13 @@ pub fn perform_probe(options: &SearchOptions) -> Result<LimitedSearchResults> {
             );
             println!("DEBUG: Already skipped {early_skipped_count} lines in early caching");
             // Print cache contents before filtering
-            if let Err(e) = cache::debug_print_cache(session_id, &raw_query) {
+            if let Err(e) = cache::debug_print_cache(&sources, session_id, &raw_query) {
                 eprintln!("Error printing cache: {e}");
             }
         }
 
         // Filter results using the cache - but only to count skipped blocks, not to filter
-        match cache::filter_results_with_cache(&limited.results, session_id, &raw_query) {
+        match cache::filter_results_with_cache(&sources, &limited.results, session_id, &raw_query) {
             Ok((_, cached_skipped)) => {
                 if debug_mode {
                     println!("DEBUG: Final caching found {cached_skipped} cached blocks");
@@ -1436,19 +1541,25 @@ pub fn perform_probe(options: &SearchOptions) -> Result<LimitedSearchResults> {
             }
             Err(e) => {
                 // Log the error but continue without caching
-                eprintln!("Error checking cache: {e}");
+                if !probe_code::console::is_silenced() {
+                    eprintln!("Error checking cache: {e}");
+                }
             }
         }
 
         // Update the cache with the limited results
-        if let Err(e) = cache::add_results_to_cache(&limited.results, session_id, &raw_query) {
-            eprintln!("Error adding results to cache: {e}");
+        if let Err(e) =
+            cache::add_results_to_cache(&sources, &limited.results, session_id, &raw_query)
+        {
+            if !probe_code::console::is_silenced() {
+                eprintln!("Error adding results to cache: {e}");
+            }
         }
 
         if debug_mode {
             println!("DEBUG: Added limited results to cache before merging");
             // Print cache contents after adding new results
-            if let Err(e) = cache::debug_print_cache(session_id, &raw_query) {
+            if let Err(e) = cache::debug_print_cache(&sources, session_id, &raw_query) {
                 eprintln!("Error printing updated cache: {e}");
             }
         }
@@ -1477,9 +1588,9 @@ pub fn perform_probe(options: &SearchOptions) -> Result<LimitedSearchResults> {
         println!("DEBUG: Starting block merging...");
     }
 
-    let final_results = if !limited.results.is_empty() && !*no_merge {
+    let mut final_results = if !limited.results.is_empty() && !*no_merge {
         use probe_code::search::block_merging::merge_ranked_blocks;
-        let merged = merge_ranked_blocks(limited.results.clone(), *merge_threshold);
+        let merged = merge_ranked_blocks(&sources, limited.results.clone(), *merge_threshold);
 
         let bm_duration = bm_start.elapsed();
         timings.block_merging = Some(bm_duration);
@@ -1510,14 +1621,16 @@ pub fn perform_probe(options: &SearchOptions) -> Result<LimitedSearchResults> {
                 queries[0].clone()
             };
 
-            if let Err(e) = cache::add_results_to_cache(&merged, session_id, &raw_query) {
-                eprintln!("Error adding merged results to cache: {e}");
+            if let Err(e) = cache::add_results_to_cache(&sources, &merged, session_id, &raw_query) {
+                if !probe_code::console::is_silenced() {
+                    eprintln!("Error adding merged results to cache: {e}");
+                }
             }
 
             if debug_mode {
                 println!("DEBUG: Added merged results to cache after merging");
                 // Print cache contents after adding merged results
-                if let Err(e) = cache::debug_print_cache(session_id, &raw_query) {
+                if let Err(e) = cache::debug_print_cache(&sources, session_id, &raw_query) {
                     eprintln!("Error printing updated cache: {e}");
                 }
             }
@@ -1538,8 +1651,11 @@ pub fn perform_probe(options: &SearchOptions) -> Result<LimitedSearchResults> {
         limited
     };
 
+    // Blocks of notebooks, merged or not, are shown one cell at a time
+    final_results.results = sources.split_notebook_cells(final_results.results);
+
     // Print the session ID to the console if it was generated or provided
-    if let Some(session_id) = effective_session {
+    if let Some(session_id) = effective_session.filter(|_| !probe_code::console::is_silenced()) {
         if session_was_generated {
             println!("Session ID: {session_id} (generated - ALWAYS USE IT in future sessions for caching)");
         } else {
@@ -1554,11 +1670,17 @@ pub fn perform_probe(options: &SearchOptions) -> Result<LimitedSearchResults> {
     print_timings(&timings);
 
     // Stop the timeout thread
-    timeout_handle.store(true, std::sync::atomic::Ordering::SeqCst);
+    drop(timeout_handle);
 
     Ok(final_results)
 }
 
+/// Error of a search that reached its timeout, like the message printed when
+/// the timeout terminates the process
+fn timed_out(timeout: u64) -> anyhow::Error {
+    anyhow::anyhow!("Search operation timed out after {timeout} seconds")
+}
+
 /// Helper function to search files using structured patterns from a QueryPlan.
 /// This function uses ripgrep's optimized search engine for maximum performance
 /// and collects matches by term indices. It uses the file_list_cache to get a filtered
@@ -1567,10 +1689,14 @@ pub fn perform_probe(options: &SearchOptions) -> Result<LimitedSearchResults> {
 /// # Arguments
 /// * `root_path` - The base path to search in
 /// * `plan` - The parsed query plan
+/// * `sources` - Where the files are read from
 /// * `patterns` - The generated regex patterns with their term indices
 /// * `custom_ignores` - Custom ignore patterns
 /// * `allow_tests` - Whether to include test files
+/// * `filters` - Path, extension and language filters for file discovery
+#[allow(clippy::too_many_arguments)]
 pub fn search_with_structured_patterns(
+    sources: &Sources,
     root_path_str: &Path,
     _plan: &QueryPlan,
     patterns: &[(String, HashSet<usize>)],
@@ -1578,12 +1704,13 @@ pub fn search_with_structured_patterns(
     allow_tests: bool,
     language: Option<&str>,
     no_gitignore: bool,
+    filters: &FileFilters,
 ) -> Result<HashMap<PathBuf, HashMap<usize, HashSet<usize>>>> {
     // Resolve the path if it's a special format (e.g., "go:github.com/user/repo")
     let root_path = if let Some(path_str) = root_path_str.to_str() {
         match resolve_path(path_str) {
             Ok(resolved_path) => {
-                if std::env::var("DEBUG").unwrap_or_default() == "1" {
+                if probe_code::console::debug_mode() {
                     println!(
                         "DEBUG: Resolved path '{}' to '{}'",
                         path_str,
@@ -1593,7 +1720,7 @@ pub fn search_with_structured_patterns(
                 resolved_path
             }
             Err(err) => {
-                if std::env::var("DEBUG").unwrap_or_default() == "1" {
+                if probe_code::console::debug_mode() {
                     println!("DEBUG: Failed to resolve path '{path_str}': {err}");
                 }
                 // Fall back to the original path
@@ -1606,7 +1733,7 @@ pub fn search_with_structured_patterns(
     };
     use crate::search::ripgrep_searcher::RipgrepSearcher;
 
-    let debug_mode = std::env::var("DEBUG").unwrap_or_default() == "1";
+    let debug_mode = probe_code::console::debug_mode();
     let search_start = Instant::now();
 
     // Step 1: Create pattern matching infrastructure (SIMD, RipgrepSearcher, or RegexSet)
@@ -1672,13 +1799,32 @@ pub fn search_with_structured_patterns(
 
     // Use file_list_cache to get a filtered list of files, with language filtering if specified
     let file_list = crate::search::file_list_cache::get_file_list_by_language(
+        sources,
         &root_path,
         allow_tests,
         custom_ignores,
         language,
         no_gitignore,
+        filters,
     )?;
 
+    // With a search index, skip the unchanged files that contain none of the words
+    // the patterns are made of
+    let files = match SearchIndex::find(sources, &root_path) {
+        Some(index) => {
+            let candidates = index.filter_candidates(&file_list.files, &pattern_strings);
+            if debug_mode {
+                println!(
+                    "DEBUG: Search index narrowed {} files down to {}",
+                    file_list.files.len(),
+                    candidates.len()
+                );
+            }
+            candidates
+        }
+        None => file_list.files.clone(),
+    };
+
     if debug_mode {
         println!("DEBUG: Got {} files from cache", file_list.files.len());
         if use_simd {
@@ -1695,7 +1841,7 @@ pub fn search_with_structured_patterns(
         let pattern_to_terms = Arc::new(pattern_to_terms);
 
         // Sort files for deterministic processing order to fix non-deterministic behavior
-        let mut sorted_files = file_list.files.clone();
+        let mut sorted_files = files;
         sorted_files.sort();
 
         // Collect results in parallel first, then sort for deterministic order
@@ -1706,7 +1852,7 @@ pub fn search_with_structured_patterns(
                 let pattern_to_terms = Arc::clone(&pattern_to_terms);
 
                 // Search file with SIMD pattern matching
-                match search_file_with_simd(file_path, &simd_matcher, &pattern_to_terms) {
+                match search_file_with_simd(sources, file_path, &simd_matcher, &pattern_to_terms) {
                     Ok(term_map) => {
                         if !term_map.is_empty() {
                             if debug_mode {
@@ -1744,7 +1890,7 @@ pub fn search_with_structured_patterns(
         searcher
             .as_ref()
             .unwrap()
-            .search_files_parallel(&file_list.files, &pattern_to_terms)?
+            .search_files_parallel(sources, &files, &pattern_to_terms)?
     };
 
     let total_duration = search_start.elapsed();
@@ -1763,18 +1909,19 @@ pub fn search_with_structured_patterns(
 /// Helper function to search a file with SIMD pattern matching
 /// This function uses SIMD optimizations for fast multi-pattern searching
 fn search_file_with_simd(
+    sources: &Sources,
     file_path: &Path,
     simd_matcher: &Option<SimdPatternMatcher>,
     pattern_to_terms: &[HashSet<usize>],
 ) -> Result<HashMap<usize, HashSet<usize>>> {
     let mut term_map = HashMap::new();
-    let debug_mode = std::env::var("DEBUG").unwrap_or_default() == "1";
+    let debug_mode = probe_code::console::debug_mode();
 
     // Define a reasonable maximum file size (e.g., 1MB)
     const MAX_FILE_SIZE: u64 = 1024 * 1024;
 
     // Check file metadata and resolve symlinks before reading
-    let resolved_path = match std::fs::canonicalize(file_path) {
+    let resolved_path = match sources.canonicalize(file_path) {
         Ok(path) => path,
         Err(e) => {
             if debug_mode {
@@ -1784,9 +1931,9 @@ fn search_file_with_simd(
         }
     };
 
-    // Get file metadata to check size and file type
-    let metadata = match std::fs::metadata(&resolved_path) {
-        Ok(meta) => meta,
+    // Get the file size to skip large files
+    let file_size = match sources.file_size(&resolved_path) {
+        Ok(size) => size,
         Err(e) => {
             if debug_mode {
                 println!("DEBUG: Error getting metadata for {resolved_path:?}: {e:?}");
@@ -1796,32 +1943,26 @@ fn search_file_with_simd(
     };
 
     // Check if the file is too large
-    if metadata.len() > MAX_FILE_SIZE {
+    if file_size > MAX_FILE_SIZE {
         if debug_mode {
             println!(
-                "DEBUG: Skipping file {:?} - file too large ({} bytes > {} bytes limit)",
-                resolved_path,
-                metadata.len(),
-                MAX_FILE_SIZE
+                "DEBUG: Skipping file {resolved_path:?} - file too large ({file_size} bytes > {MAX_FILE_SIZE} bytes limit)"
             );
         }
         return Err(anyhow::anyhow!(
             "File too large: {} bytes (limit: {} bytes)",
-            metadata.len(),
+            file_size,
             MAX_FILE_SIZE
         ));
     }
 
     // Read the file content with proper error handling
-    let content = match std::fs::read_to_string(&resolved_path) {
+    let content = match sources.read_to_string(&resolved_path) {
         Ok(content) => content,
         Err(e) => {
             if debug_mode {
                 println!(
-                    "DEBUG: Error reading file {:?}: {:?} (size: {} bytes)",
-                    resolved_path,
-                    e,
-                    metadata.len()
+                    "DEBUG: Error reading file {resolved_path:?}: {e:?} (size: {file_size} bytes)"
                 );
             }
             return Err(anyhow::anyhow!("Failed to read file: {}", e));
@@ -1864,6 +2005,95 @@ fn search_file_with_simd(
     Ok(term_map)
 }
 
+/// Rewrite fuzzy and wildcard terms of the queries into OR groups of the words
+/// they match in the files being searched. Queries without such terms are
+/// returned unchanged and the files are not read.
+fn expand_query_terms(
+    sources: &Sources,
+    queries: &[String],
+    options: &SearchOptions,
+    filters: &FileFilters,
+) -> Result<Vec<String>> {
+    let debug_mode = probe_code::console::debug_mode();
+
+    let mut fuzzy: Vec<FuzzyTerm> = Vec::new();
+    for term in queries.iter().flat_map(|q| fuzzy_terms(q, options.fuzzy)) {
+        if !fuzzy.contains(&term) {
+            fuzzy.push(term);
+        }
+    }
+    let mut patterns: Vec<WildcardPattern> = Vec::new();
+    for term in queries.iter().flat_map(|q| wildcard_terms(q)) {
+        if !patterns.iter().any(|p| p.pattern() == term) {
+            patterns.extend(WildcardPattern::new(&term));
+        }
+    }
+
+    // `term~N` suffixes are dropped even when there is nothing to expand
+    let has_fuzzy_syntax = queries.iter().any(|q| q.contains('~'));
+    if fuzzy.is_empty() && patterns.is_empty() && !has_fuzzy_syntax {
+        return Ok(queries.to_vec());
+    }
+
+    let root_path = search_root(options.path);
+    let file_list = file_list_cache::get_file_list_by_language(
+        sources,
+        &root_path,
+        options.allow_tests,
+        options.custom_ignores,
+        options.language.map(normalize_language_alias),
+        options.no_gitignore,
+        filters,
+    )?;
+
+    let fuzzy_expansions = vocabulary::match_fuzzy_terms(sources, &file_list.files, &fuzzy);
+    let wildcard_expansions =
+        vocabulary::expand_wildcard_patterns(sources, &file_list.files, &patterns);
+    let expanded: Vec<String> = queries
+        .iter()
+        .map(|q| {
+            let q = expand_fuzzy_terms(q, &fuzzy_expansions, options.fuzzy);
+            expand_wildcards(&q, &wildcard_expansions)
+        })
+        .collect();
+
+    if debug_mode {
+        println!("DEBUG: Queries after fuzzy and wildcard expansion: {expanded:?}");
+    }
+
+    Ok(expanded)
+}
+
+/// Add the synonyms defined in the project's `.probe/synonyms.toml` to the
+/// queries. Queries are returned unchanged when there is no synonyms file.
+fn expand_query_synonyms(queries: &[String], options: &SearchOptions) -> Result<Vec<String>> {
+    let debug_mode = probe_code::console::debug_mode();
+
+    let root_path = search_root(options.path);
+    let Some(synonyms) = Synonyms::find(&root_path)? else {
+        return Ok(queries.to_vec());
+    };
+
+    let expanded: Vec<String> = queries
+        .iter()
+        .map(|q| expand_synonyms(q, &synonyms))
+        .collect();
+
+    if debug_mode {
+        println!("DEBUG: Queries after synonym expansion: {expanded:?}");
+    }
+
+    Ok(expanded)
+}
+
+/// Resolve the searched path, e.g. `go:github.com/user/repo`, falling back to
+/// the path as given
+fn search_root(path: &Path) -> PathBuf {
+    path.to_str()
+        .and_then(|path_str| resolve_path(path_str).ok())
+        .unwrap_or_else(|| path.to_path_buf())
+}
+
 /// Normalize language aliases to their canonical names
 /// This function maps language aliases like "ts" to their canonical names like "typescript"
 fn normalize_language_alias(lang: &str) -> &str {
@@ -1876,6 +2106,11 @@ fn normalize_language_alias(lang: &str) -> &str {
         "cc" | "cxx" | "hpp" | "hxx" => "cpp",
         "rb" => "ruby",
         "cs" => "csharp",
+        "kt" | "kts" => "kotlin",
+        "sc" => "scala",
+        "terraform" | "tf" => "hcl",
+        "md" | "mdx" => "markdown",
+        "htm" => "html",
         _ => lang, // Return the original language if no alias is found
     }
 }
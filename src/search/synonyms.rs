@@ -0,0 +1,152 @@
+use anyhow::{bail, Context, Result};
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+/// Location of the synonyms file, relative to the project root
+pub const SYNONYMS_FILE: &str = ".probe/synonyms.toml";
+
+/// Boost given to a synonym, so blocks containing the term as written rank
+/// above blocks that only contain one of its synonyms
+pub const SYNONYM_BOOST: f64 = 0.5;
+
+/// Project-defined synonyms and abbreviations, read from `.probe/synonyms.toml`.
+/// Every key is a query term, its value a synonym or a list of synonyms:
+///
+/// ```toml
+/// auth = ["authentication", "authn", "login"]
+/// cfg = "config"
+/// ctx = "context"
+/// ```
+///
+/// A comma-separated string (`auth = "authentication, authn, login"`) works too.
+/// Terms are matched case-insensitively.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Synonyms {
+    map: HashMap<String, Vec<String>>,
+}
+
+impl Synonyms {
+    /// Parse the contents of a synonyms file
+    pub fn parse(content: &str) -> Result<Self> {
+        let document = content.parse::<toml_edit::DocumentMut>()?;
+        let mut map: HashMap<String, Vec<String>> = HashMap::new();
+
+        for (term, item) in document.iter() {
+            let words: Vec<String> = if let Some(value) = item.as_str() {
+                value.split(',').map(|w| w.trim().to_string()).collect()
+            } else if let Some(array) = item.as_array() {
+                array
+                    .iter()
+                    .map(|value| {
+                        value
+                            .as_str()
+                            .map(|w| w.trim().to_string())
+                            .with_context(|| {
+                                format!("Synonyms of '{term}' must be strings, found {value}")
+                            })
+                    })
+                    .collect::<Result<_>>()?
+            } else {
+                bail!("Synonyms of '{term}' must be a string or a list of strings");
+            };
+
+            for word in words.iter().map(String::as_str).chain([term]) {
+                if !is_single_word(word) {
+                    bail!(
+                        "Invalid synonym entry '{word}' for '{term}': only single words are supported"
+                    );
+                }
+            }
+
+            let entry = map.entry(term.to_lowercase()).or_default();
+            for word in words {
+                if !word.eq_ignore_ascii_case(term) && !entry.contains(&word) {
+                    entry.push(word);
+                }
+            }
+        }
+
+        map.retain(|_, words| !words.is_empty());
+        Ok(Synonyms { map })
+    }
+
+    /// Load a synonyms file
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read synonyms file {}", path.display()))?;
+        Self::parse(&content)
+            .with_context(|| format!("Failed to parse synonyms file {}", path.display()))
+    }
+
+    /// Find and load the synonyms file of the project containing `path`, by
+    /// looking for `.probe/synonyms.toml` in `path` and each of its parents.
+    pub fn find(path: &Path) -> Result<Option<Self>> {
+        let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+        for dir in path.ancestors() {
+            let candidate = dir.join(SYNONYMS_FILE);
+            if candidate.is_file() {
+                return Self::load(&candidate).map(Some);
+            }
+        }
+        Ok(None)
+    }
+
+    /// Synonyms of a term, if any
+    pub fn get(&self, term: &str) -> Option<&[String]> {
+        self.map.get(&term.to_lowercase()).map(Vec::as_slice)
+    }
+}
+
+fn is_single_word(word: &str) -> bool {
+    !word.is_empty() && word.chars().all(|c| c.is_alphanumeric() || c == '_')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_parse_synonyms() {
+        let synonyms = Synonyms::parse(
+            r#"
+            # Domain abbreviations
+            auth = ["authentication", "authn", "login"]
+            cfg = "config"
+            Ctx = "context, ctx, context"
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            synonyms.get("auth").unwrap(),
+            ["authentication", "authn", "login"]
+        );
+        assert_eq!(synonyms.get("CFG").unwrap(), ["config"]);
+        assert_eq!(synonyms.get("ctx").unwrap(), ["context"]);
+        assert_eq!(synonyms.get("config"), None);
+    }
+
+    #[test]
+    fn test_parse_invalid_synonyms() {
+        assert!(Synonyms::parse("auth = [").is_err());
+        assert!(Synonyms::parse("auth = 1").is_err());
+        assert!(Synonyms::parse("auth = [\"login\", 2]").is_err());
+        assert!(Synonyms::parse("auth = \"access token\"").is_err());
+        assert!(Synonyms::parse("auth = [\"\"]").is_err());
+    }
+
+    #[test]
+    fn test_find_synonyms_in_parent_directory() {
+        let temp_dir = TempDir::new().unwrap();
+        let nested = temp_dir.path().join("src").join("auth");
+        fs::create_dir_all(&nested).unwrap();
+        assert_eq!(Synonyms::find(&nested).unwrap(), None);
+
+        fs::create_dir(temp_dir.path().join(".probe")).unwrap();
+        fs::write(temp_dir.path().join(SYNONYMS_FILE), "cfg = \"config\"").unwrap();
+        let synonyms = Synonyms::find(&nested).unwrap().unwrap();
+        assert_eq!(synonyms.get("cfg").unwrap(), ["config"]);
+    }
+}
@@ -30,6 +30,7 @@ mod token_limiter_failure_tests {
             block_id: None,
             matched_keywords: None,
             tokenized_content: None,
+            cells: None,
         }
     }
 
@@ -3,11 +3,45 @@ use std::sync::Arc;
 use std::thread;
 use std::time::Duration;
 
-/// Starts a timeout thread that will terminate the process if the timeout is reached.
-/// Returns a handle to the timeout thread that can be used to stop it.
-pub fn start_timeout_thread(timeout_seconds: u64) -> Arc<AtomicBool> {
+/// Whether reaching a timeout terminates the process. Servers like `probe daemon`
+/// turn this off: the timed out search then ends early with an error instead.
+/// Timeouts never terminate the process for searches that run silenced, like the
+/// ones of programs that embed probe.
+static EXIT_ON_TIMEOUT: AtomicBool = AtomicBool::new(true);
+
+/// Set whether reaching a timeout terminates the process
+pub fn set_exit_on_timeout(exit: bool) {
+    EXIT_ON_TIMEOUT.store(exit, Ordering::SeqCst);
+}
+
+/// A running timeout, stopped when dropped
+pub struct TimeoutHandle {
+    should_stop: Arc<AtomicBool>,
+    expired: Arc<AtomicBool>,
+}
+
+impl TimeoutHandle {
+    /// Whether the timeout was reached, so the work it bounds should end
+    pub fn expired(&self) -> bool {
+        self.expired.load(Ordering::SeqCst)
+    }
+}
+
+impl Drop for TimeoutHandle {
+    fn drop(&mut self) {
+        self.should_stop.store(true, Ordering::SeqCst);
+    }
+}
+
+/// Starts a timeout thread that will terminate the process if the timeout is reached,
+/// unless that is turned off; the work should then check `TimeoutHandle::expired`
+/// and end itself.
+pub fn start_timeout_thread(timeout_seconds: u64) -> TimeoutHandle {
     let should_stop = Arc::new(AtomicBool::new(false));
     let should_stop_clone = should_stop.clone();
+    let expired = Arc::new(AtomicBool::new(false));
+    let expired_clone = expired.clone();
+    let exit = EXIT_ON_TIMEOUT.load(Ordering::SeqCst) && !probe_code::console::is_silenced();
 
     // For testing purposes, check if we're running in a test environment
     let is_test = std::env::var("RUST_TEST_THREADS").is_ok();
@@ -33,11 +67,22 @@ pub fn start_timeout_thread(timeout_seconds: u64) -> Arc<AtomicBool> {
             thread::sleep(sleep_interval);
             elapsed_time += sleep_interval;
         }
+        if should_stop_clone.load(Ordering::SeqCst) {
+            return;
+        }
+
+        expired_clone.store(true, Ordering::SeqCst);
+        if !exit {
+            return;
+        }
 
         // Timeout reached, print a message and terminate the process
         eprintln!("Search operation timed out after {timeout_seconds} seconds");
         std::process::exit(1);
     });
 
-    should_stop
+    TimeoutHandle {
+        should_stop,
+        expired,
+    }
 }
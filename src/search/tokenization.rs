@@ -845,7 +845,7 @@ pub fn add_special_term(term: &str) {
     special_terms.insert(term.to_lowercase());
 
     // Debug output
-    if std::env::var("DEBUG").unwrap_or_default() == "1" {
+    if probe_code::console::debug_mode() {
         println!("DEBUG: Added special term: {term}");
     }
 }
@@ -1177,7 +1177,7 @@ pub fn is_special_case(word: &str) -> bool {
     let special_terms = DYNAMIC_SPECIAL_TERMS.lock().unwrap();
     if special_terms.contains(&lowercase) {
         // Debug output
-        if std::env::var("DEBUG").unwrap_or_default() == "1" {
+        if probe_code::console::debug_mode() {
             println!("DEBUG: Found dynamic special term: {lowercase}");
         }
         return true;
@@ -1869,7 +1869,7 @@ pub fn split_camel_case_with_config(input: &str, config: SimdConfig) -> Vec<Stri
         return crate::search::simd_tokenization::simd_split_camel_case_with_config(input, config);
     }
 
-    let _debug_mode = std::env::var("DEBUG").unwrap_or_default() == "1";
+    let _debug_mode = probe_code::console::debug_mode();
 
     if input.is_empty() {
         return vec![];
@@ -2649,6 +2649,17 @@ pub fn tokenize_and_stem(keyword: &str) -> Vec<String> {
 /// 7. Collect unique tokens
 /// 8. Exclude terms that were negated with a "-" prefix
 pub fn tokenize(text: &str) -> Vec<String> {
+    tokenize_words(text, true)
+}
+
+/// Like `tokenize`, but keeps every occurrence of a token in source order,
+/// so the index of a token reflects where it appears in the text.
+/// Used to measure how far apart terms are for proximity queries.
+pub fn tokenize_sequence(text: &str) -> Vec<String> {
+    tokenize_words(text, false)
+}
+
+fn tokenize_words(text: &str, unique: bool) -> Vec<String> {
     let stemmer = get_stemmer();
     // VOCABULARY CACHE OPTIMIZATION: Cached vocabulary is now accessed directly through
     // split_compound_word_for_filtering() calls, eliminating repeated vocabulary loading
@@ -2740,9 +2751,8 @@ pub fn tokenize(text: &str) -> Vec<String> {
                 }
 
                 // Preserve the original form for all exception terms
-                if is_exception_term(&compound_part)
-                    && processed_tokens.insert(compound_part.clone())
-                {
+                let is_exception = is_exception_term(&compound_part);
+                if is_exception && (!unique || processed_tokens.insert(compound_part.clone())) {
                     result.push(compound_part.clone());
                 }
 
@@ -2753,7 +2763,11 @@ pub fn tokenize(text: &str) -> Vec<String> {
                     continue;
                 }
 
-                if processed_tokens.insert(stemmed_part.clone()) {
+                if unique {
+                    if processed_tokens.insert(stemmed_part.clone()) {
+                        result.push(stemmed_part);
+                    }
+                } else if !(is_exception && stemmed_part == compound_part) {
                     result.push(stemmed_part);
                 }
             }
@@ -2851,6 +2865,18 @@ mod tests {
                                                            // "whitelist" is now a special case word that should not be split
         assert!(tokens.contains(&"whitelist".to_string()));
     }
+
+    #[test]
+    fn test_tokenize_sequence_keeps_repeats() {
+        let tokens = tokenize_sequence("token refresh token session");
+        assert_eq!(tokens, vec!["token", "refresh", "token", "session"]);
+
+        // The unique variant only keeps the first occurrence
+        assert_eq!(
+            tokenize("token refresh token session"),
+            vec!["token", "refresh", "session"]
+        );
+    }
     #[test]
     fn test_compound_word_splitting() {
         // Test basic compound word splitting
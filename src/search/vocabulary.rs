@@ -0,0 +1,348 @@
+use lazy_static::lazy_static;
+use rayon::prelude::*;
+use regex::Regex;
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+
+use probe_code::search::elastic_query::{FuzzyTerm, WildcardPattern};
+use probe_code::source::Sources;
+
+/// Maximum number of identifiers a single wildcard term expands to.
+/// When more identifiers match, the most frequent ones are kept.
+pub const MAX_WILDCARD_EXPANSIONS: usize = 32;
+
+/// Maximum number of words a single fuzzy term expands to.
+/// When more words are close enough, the closest and most frequent ones are kept.
+pub const MAX_FUZZY_EXPANSIONS: usize = 16;
+
+lazy_static! {
+    static ref IDENTIFIER_RE: Regex = Regex::new(r"[A-Za-z_][A-Za-z0-9_]*").unwrap();
+}
+
+/// Expand wildcard terms to the identifiers that actually occur in `files`.
+///
+/// Returns, for every pattern that matched at least one identifier, up to
+/// `MAX_WILDCARD_EXPANSIONS` identifiers ordered by how often they occur.
+/// Identifiers that only differ in case are reported once, using the most
+/// frequent spelling. The map is keyed by the pattern as written in the query.
+pub fn expand_wildcard_patterns(
+    sources: &Sources,
+    files: &[PathBuf],
+    patterns: &[WildcardPattern],
+) -> HashMap<String, Vec<String>> {
+    let debug_mode = probe_code::console::debug_mode();
+
+    if patterns.is_empty() {
+        return HashMap::new();
+    }
+
+    let counts: Vec<HashMap<String, usize>> = files
+        .par_iter()
+        .fold(
+            || vec![HashMap::new(); patterns.len()],
+            |mut counts, file| {
+                let Ok(content) = sources.read_to_string(file) else {
+                    return counts;
+                };
+
+                // Skip files that can't contain a match for any pattern
+                let lowercase = content.to_lowercase();
+                let candidates: Vec<usize> = (0..patterns.len())
+                    .filter(|&i| patterns[i].may_occur_in(&lowercase))
+                    .collect();
+                if candidates.is_empty() {
+                    return counts;
+                }
+
+                for identifier in IDENTIFIER_RE.find_iter(&content) {
+                    let identifier = identifier.as_str();
+                    for &i in &candidates {
+                        if patterns[i].matches(identifier) {
+                            *counts[i].entry(identifier.to_string()).or_insert(0) += 1;
+                        }
+                    }
+                }
+                counts
+            },
+        )
+        .reduce(
+            || vec![HashMap::new(); patterns.len()],
+            |mut acc, counts| {
+                for (acc_counts, counts) in acc.iter_mut().zip(counts) {
+                    for (identifier, count) in counts {
+                        *acc_counts.entry(identifier).or_insert(0) += count;
+                    }
+                }
+                acc
+            },
+        );
+
+    let mut expansions = HashMap::new();
+    for (pattern, counts) in patterns.iter().zip(counts) {
+        let mut identifiers: Vec<(String, usize)> = counts.into_iter().collect();
+        identifiers.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+
+        let mut seen = HashSet::new();
+        let identifiers: Vec<String> = identifiers
+            .into_iter()
+            .filter(|(identifier, _)| seen.insert(identifier.to_lowercase()))
+            .take(MAX_WILDCARD_EXPANSIONS)
+            .map(|(identifier, _)| identifier)
+            .collect();
+
+        if debug_mode {
+            println!(
+                "DEBUG: Wildcard '{}' expands to {:?}",
+                pattern.pattern(),
+                identifiers
+            );
+        }
+
+        if !identifiers.is_empty() {
+            expansions.insert(pattern.pattern().to_string(), identifiers);
+        }
+    }
+
+    expansions
+}
+
+/// Expand fuzzy terms to the words within their edit distance in `files`.
+///
+/// The vocabulary holds every identifier of the files together with the words
+/// it is made of (`receive_message` also contributes `receive` and `message`),
+/// all lowercased. A transposition of two adjacent characters counts as a single
+/// edit, so `recieve~1` finds `receive`. Returns, for every term with at least one
+/// match, up to `MAX_FUZZY_EXPANSIONS` words ordered by distance, then frequency.
+pub fn match_fuzzy_terms(
+    sources: &Sources,
+    files: &[PathBuf],
+    terms: &[FuzzyTerm],
+) -> HashMap<FuzzyTerm, Vec<String>> {
+    let debug_mode = probe_code::console::debug_mode();
+
+    if terms.is_empty() {
+        return HashMap::new();
+    }
+
+    let vocabulary: HashMap<String, usize> = files
+        .par_iter()
+        .fold(HashMap::new, |mut counts: HashMap<String, usize>, file| {
+            let Ok(content) = sources.read_to_string(file) else {
+                return counts;
+            };
+            for identifier in IDENTIFIER_RE.find_iter(&content) {
+                for word in identifier_words(identifier.as_str()) {
+                    *counts.entry(word).or_insert(0) += 1;
+                }
+            }
+            counts
+        })
+        .reduce(HashMap::new, |mut acc, counts| {
+            for (word, count) in counts {
+                *acc.entry(word).or_insert(0) += count;
+            }
+            acc
+        });
+
+    let mut expansions = HashMap::new();
+    for term in terms {
+        let query: Vec<char> = term.term.to_lowercase().chars().collect();
+
+        let mut matches: Vec<(usize, usize, &String)> = vocabulary
+            .par_iter()
+            .filter_map(|(word, &count)| {
+                let candidate: Vec<char> = word.chars().collect();
+                edit_distance(&query, &candidate, term.distance)
+                    .map(|distance| (distance, count, word))
+            })
+            .collect();
+        matches.sort_by(|a, b| a.0.cmp(&b.0).then(b.1.cmp(&a.1)).then(a.2.cmp(b.2)));
+
+        let words: Vec<String> = matches
+            .into_iter()
+            .take(MAX_FUZZY_EXPANSIONS)
+            .map(|(_, _, word)| word.clone())
+            .collect();
+
+        if debug_mode {
+            println!(
+                "DEBUG: Fuzzy term '{}~{}' expands to {:?}",
+                term.term, term.distance, words
+            );
+        }
+
+        if !words.is_empty() {
+            expansions.insert(term.clone(), words);
+        }
+    }
+
+    expansions
+}
+
+/// Lowercased identifier plus the words it is made of, split on underscores and
+/// case changes (`parseHTTPRequest` => `parsehttprequest`, `parse`, `http`, `request`).
+fn identifier_words(identifier: &str) -> Vec<String> {
+    let mut words = vec![identifier.to_lowercase()];
+    for part in identifier.split('_').filter(|p| !p.is_empty()) {
+        let chars: Vec<char> = part.chars().collect();
+        let mut start = 0;
+        for i in 1..chars.len() {
+            let boundary = (chars[i].is_uppercase() && !chars[i - 1].is_uppercase())
+                || (chars[i].is_uppercase()
+                    && chars[i - 1].is_uppercase()
+                    && chars.get(i + 1).is_some_and(|c| c.is_lowercase()));
+            if boundary {
+                words.push(chars[start..i].iter().collect::<String>().to_lowercase());
+                start = i;
+            }
+        }
+        words.push(chars[start..].iter().collect::<String>().to_lowercase());
+    }
+    words.sort();
+    words.dedup();
+    words
+}
+
+/// Optimal string alignment distance between `a` and `b`, or `None` if it is
+/// larger than `max`. Like Levenshtein distance, but swapping two adjacent
+/// characters counts as one edit.
+fn edit_distance(a: &[char], b: &[char], max: usize) -> Option<usize> {
+    if a.len().abs_diff(b.len()) > max {
+        return None;
+    }
+
+    let mut prev_prev: Vec<usize> = vec![0; b.len() + 1];
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    let mut current: Vec<usize> = vec![0; b.len() + 1];
+
+    for i in 1..=a.len() {
+        current[0] = i;
+        let mut row_min = current[0];
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            current[j] = (prev[j] + 1)
+                .min(current[j - 1] + 1)
+                .min(prev[j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                current[j] = current[j].min(prev_prev[j - 2] + 1);
+            }
+            row_min = row_min.min(current[j]);
+        }
+        if row_min > max {
+            return None;
+        }
+        std::mem::swap(&mut prev_prev, &mut prev);
+        std::mem::swap(&mut prev, &mut current);
+    }
+
+    Some(prev[b.len()]).filter(|&distance| distance <= max)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_expand_wildcard_patterns() {
+        let temp_dir = TempDir::new().unwrap();
+        let file = temp_dir.path().join("auth.rs");
+        fs::write(
+            &file,
+            "fn authenticate() {}\nfn authorize() { authenticate(); }\nstruct RequestHandler;\nfn get_user() {}\nfn getUser() {}\n",
+        )
+        .unwrap();
+        let files = vec![file];
+
+        let patterns: Vec<WildcardPattern> = ["auth*", "*Handler", "get?ser", "missing*"]
+            .iter()
+            .filter_map(|p| WildcardPattern::new(p))
+            .collect();
+        let expansions = expand_wildcard_patterns(&Sources::default(), &files, &patterns);
+
+        // Most frequent identifier first
+        assert_eq!(
+            expansions.get("auth*").unwrap(),
+            &vec!["authenticate".to_string(), "authorize".to_string()]
+        );
+        assert_eq!(
+            expansions.get("*Handler").unwrap(),
+            &vec!["RequestHandler".to_string()]
+        );
+        // `?` matches exactly one character, so `get_user` is left out
+        assert_eq!(
+            expansions.get("get?ser").unwrap(),
+            &vec!["getUser".to_string()]
+        );
+        assert!(!expansions.contains_key("missing*"));
+    }
+
+    #[test]
+    fn test_edit_distance() {
+        let distance = |a: &str, b: &str, max| {
+            let a: Vec<char> = a.chars().collect();
+            let b: Vec<char> = b.chars().collect();
+            edit_distance(&a, &b, max)
+        };
+
+        assert_eq!(distance("receive", "receive", 2), Some(0));
+        // Adjacent transpositions count as a single edit
+        assert_eq!(distance("recieve", "receive", 1), Some(1));
+        assert_eq!(distance("serilaizer", "serializer", 1), Some(1));
+        assert_eq!(distance("token", "tokens", 1), Some(1));
+        assert_eq!(distance("sesion", "session", 2), Some(1));
+        assert_eq!(distance("refresh", "fresher", 2), None);
+        assert_eq!(distance("token", "tokenizer", 2), None);
+    }
+
+    #[test]
+    fn test_identifier_words() {
+        assert_eq!(
+            identifier_words("parseHTTPRequest"),
+            vec!["http", "parse", "parsehttprequest", "request"]
+        );
+        assert_eq!(
+            identifier_words("receive_message"),
+            vec!["message", "receive", "receive_message"]
+        );
+    }
+
+    #[test]
+    fn test_match_fuzzy_terms() {
+        let temp_dir = TempDir::new().unwrap();
+        let file = temp_dir.path().join("messages.rs");
+        fs::write(
+            &file,
+            "fn receive_message() {}\nstruct JsonSerializer;\nfn recipe() { receive_message(); }\n",
+        )
+        .unwrap();
+        let files = vec![file];
+
+        let terms = vec![
+            FuzzyTerm {
+                term: "recieve".to_string(),
+                distance: 1,
+            },
+            FuzzyTerm {
+                term: "Serilaizer".to_string(),
+                distance: 1,
+            },
+            FuzzyTerm {
+                term: "unrelated".to_string(),
+                distance: 2,
+            },
+        ];
+        let expansions = match_fuzzy_terms(&Sources::default(), &files, &terms);
+
+        assert_eq!(
+            expansions.get(&terms[0]).unwrap(),
+            &vec!["receive".to_string()]
+        );
+        assert_eq!(
+            expansions.get(&terms[1]).unwrap(),
+            &vec!["serializer".to_string()]
+        );
+        assert!(!expansions.contains_key(&terms[2]));
+    }
+}
@@ -0,0 +1,249 @@
+use anyhow::{Context, Result};
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::Value;
+use std::path::PathBuf;
+
+use probe_code::extract::{extract_results_json, ExtractOptions, PromptTemplate};
+use probe_code::query::{perform_query, query_results_json, QueryOptions};
+use probe_code::search::{perform_probe, search_results_json, SearchOptions};
+
+fn default_path() -> PathBuf {
+    PathBuf::from(".")
+}
+
+fn default_reranker() -> String {
+    "bm25".to_string()
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_timeout() -> u64 {
+    30
+}
+
+/// Body of `/search`, named after the fields of `SearchOptions`
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SearchArgs {
+    #[serde(default = "default_path")]
+    path: PathBuf,
+    query: String,
+    #[serde(default)]
+    files_only: bool,
+    #[serde(default)]
+    custom_ignores: Vec<String>,
+    #[serde(default)]
+    exclude_filenames: bool,
+    #[serde(default = "default_reranker")]
+    reranker: String,
+    #[serde(default = "default_true")]
+    frequency_search: bool,
+    #[serde(default)]
+    exact: bool,
+    #[serde(default)]
+    fuzzy: bool,
+    language: Option<String>,
+    max_results: Option<usize>,
+    max_bytes: Option<usize>,
+    max_tokens: Option<usize>,
+    #[serde(default)]
+    allow_tests: bool,
+    #[serde(default)]
+    no_merge: bool,
+    merge_threshold: Option<usize>,
+    #[serde(default)]
+    dry_run: bool,
+    session: Option<String>,
+    #[serde(default = "default_timeout")]
+    timeout: u64,
+    question: Option<String>,
+    no_gitignore: Option<bool>,
+}
+
+/// Body of `/extract`, named after the fields of `ExtractOptions`
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ExtractArgs {
+    files: Vec<String>,
+    #[serde(default)]
+    custom_ignores: Vec<String>,
+    #[serde(default)]
+    context_lines: usize,
+    #[serde(default)]
+    dry_run: bool,
+    #[serde(default)]
+    allow_tests: bool,
+    #[serde(default)]
+    keep_input: bool,
+    prompt: Option<String>,
+    instructions: Option<String>,
+    no_gitignore: Option<bool>,
+}
+
+/// Body of `/query`, named after the fields of `QueryOptions`
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct QueryArgs {
+    #[serde(default = "default_path")]
+    path: PathBuf,
+    pattern: String,
+    language: Option<String>,
+    #[serde(default)]
+    ignore: Vec<String>,
+    #[serde(default)]
+    allow_tests: bool,
+    max_results: Option<usize>,
+    no_gitignore: Option<bool>,
+}
+
+/// A request to one of the endpoints
+pub enum Call {
+    Search(SearchArgs),
+    Extract(ExtractArgs),
+    Query(QueryArgs),
+}
+
+impl Call {
+    /// The call for a request path and body, `None` for an unknown path
+    pub fn parse(path: &str, body: &[u8]) -> Option<Result<Self>> {
+        Some(match path {
+            "/search" => parse_body(body, path).map(Call::Search),
+            "/extract" => parse_body(body, path).map(Call::Extract),
+            "/query" => parse_body(body, path).map(Call::Query),
+            _ => return None,
+        })
+    }
+
+    /// Run the command and return its `--format json` output
+    pub fn run(self) -> Result<Value> {
+        match self {
+            Call::Search(args) => search(args),
+            Call::Extract(args) => extract(args),
+            Call::Query(args) => query(args),
+        }
+    }
+}
+
+fn parse_body<T: DeserializeOwned>(body: &[u8], path: &str) -> Result<T> {
+    serde_json::from_slice(body).with_context(|| format!("Invalid body for {path}"))
+}
+
+/// `no_gitignore`, or the PROBE_NO_GITIGNORE setting the CLI uses when it isn't given
+fn no_gitignore(no_gitignore: Option<bool>) -> bool {
+    no_gitignore.unwrap_or_else(|| std::env::var("PROBE_NO_GITIGNORE").unwrap_or_default() == "1")
+}
+
+fn search(args: SearchArgs) -> Result<Value> {
+    let queries = vec![args.query];
+    let limited_results = perform_probe(&SearchOptions {
+        path: &args.path,
+        queries: &queries,
+        files_only: args.files_only,
+        custom_ignores: &args.custom_ignores,
+        exclude_filenames: args.exclude_filenames,
+        reranker: &args.reranker,
+        frequency_search: args.frequency_search,
+        exact: args.exact,
+        fuzzy: args.fuzzy,
+        explain: false,
+        language: args.language.as_deref(),
+        max_results: args.max_results,
+        max_bytes: args.max_bytes,
+        max_tokens: args.max_tokens,
+        allow_tests: args.allow_tests,
+        no_merge: args.no_merge,
+        merge_threshold: args.merge_threshold,
+        dry_run: args.dry_run,
+        session: args.session.as_deref(),
+        timeout: args.timeout,
+        question: args.question.as_deref(),
+        no_gitignore: no_gitignore(args.no_gitignore),
+        file_filters: Default::default(),
+        source: None,
+    })?;
+    Ok(search_results_json(&limited_results.results))
+}
+
+fn extract(args: ExtractArgs) -> Result<Value> {
+    let prompt = args
+        .prompt
+        .as_deref()
+        .map(PromptTemplate::from_str)
+        .transpose()?;
+    extract_results_json(&ExtractOptions {
+        files: args.files,
+        custom_ignores: args.custom_ignores,
+        context_lines: args.context_lines,
+        format: "json".to_string(),
+        from_clipboard: false,
+        input_file: None,
+        to_clipboard: false,
+        dry_run: args.dry_run,
+        diff: false,
+        allow_tests: args.allow_tests,
+        keep_input: args.keep_input,
+        prompt,
+        instructions: args.instructions,
+        no_gitignore: no_gitignore(args.no_gitignore),
+        file_filters: Default::default(),
+        source: None,
+    })
+}
+
+fn query(args: QueryArgs) -> Result<Value> {
+    let matches = perform_query(&QueryOptions {
+        path: &args.path,
+        pattern: &args.pattern,
+        language: args.language.as_deref(),
+        ignore: &args.ignore,
+        allow_tests: args.allow_tests,
+        max_results: args.max_results,
+        format: "json",
+        no_gitignore: no_gitignore(args.no_gitignore),
+        file_filters: Default::default(),
+        source: None,
+    })?;
+    Ok(query_results_json(&matches))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let Some(Ok(Call::Search(args))) = Call::parse(
+            "/search",
+            br#"{ "query": "auth", "max_results": 5, "allow_tests": true }"#,
+        ) else {
+            panic!("Expected a search");
+        };
+        assert_eq!(args.query, "auth");
+        assert_eq!(args.path, PathBuf::from("."));
+        assert_eq!(args.max_results, Some(5));
+        assert!(args.allow_tests);
+        assert!(args.frequency_search);
+        assert_eq!(args.reranker, "bm25");
+        assert_eq!(args.timeout, 30);
+
+        assert!(matches!(
+            Call::parse("/query", br#"{ "pattern": "fn $NAME() {}" }"#),
+            Some(Ok(Call::Query(_)))
+        ));
+        assert!(matches!(
+            Call::parse("/extract", br#"{ "files": ["src/main.rs:10"] }"#),
+            Some(Ok(Call::Extract(_)))
+        ));
+
+        // Misspelled options are errors rather than silently ignored
+        assert!(matches!(
+            Call::parse("/search", br#"{ "query": "auth", "maxResults": 5 }"#),
+            Some(Err(_))
+        ));
+        assert!(matches!(Call::parse("/search", b""), Some(Err(_))));
+        assert!(Call::parse("/index", b"{}").is_none());
+    }
+}
@@ -0,0 +1,247 @@
+use anyhow::{bail, Context, Result};
+use serde_json::Value;
+use std::fmt;
+use std::io::{BufRead, Read, Write};
+
+/// Largest request body accepted, as requests only carry options
+const MAX_BODY_SIZE: usize = 1024 * 1024;
+
+/// Longest request line or header line accepted, line break included
+const MAX_LINE_SIZE: u64 = 8 * 1024;
+
+/// Most headers accepted in a request
+const MAX_HEADERS: usize = 100;
+
+/// Error of a request whose headers are longer or more numerous than
+/// accepted, answered with 431
+#[derive(Debug)]
+pub struct HeadersTooLarge;
+
+impl fmt::Display for HeadersTooLarge {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Request headers are limited to {MAX_HEADERS} lines of {MAX_LINE_SIZE} bytes"
+        )
+    }
+}
+
+impl std::error::Error for HeadersTooLarge {}
+
+/// An HTTP request, of which only what the API needs is kept
+#[derive(Debug, PartialEq)]
+pub struct Request {
+    pub method: String,
+    /// Path of the request target, without its query string
+    pub path: String,
+    /// The Host header, if the client sent one
+    pub host: Option<String>,
+    /// The Origin header, which browsers send with requests of web pages
+    pub origin: Option<String>,
+    pub body: Vec<u8>,
+}
+
+impl Request {
+    /// Read a request. `writer` gets the interim response of clients that wait
+    /// for one before sending the body.
+    pub fn read_from(reader: &mut impl BufRead, writer: &mut impl Write) -> Result<Self> {
+        let Some(request_line) = read_line(reader)? else {
+            bail!("The request line is longer than {MAX_LINE_SIZE} bytes");
+        };
+        let mut parts = request_line.split_whitespace();
+        let (Some(method), Some(target), Some(version)) =
+            (parts.next(), parts.next(), parts.next())
+        else {
+            bail!("Invalid request line: {request_line}");
+        };
+        if !version.starts_with("HTTP/1.") {
+            bail!("Unsupported HTTP version: {version}");
+        }
+        let path = target.split('?').next().unwrap_or(target).to_string();
+        let method = method.to_string();
+
+        let mut content_length = 0;
+        let mut expect_continue = false;
+        let mut host = None;
+        let mut origin = None;
+        let mut headers = 0;
+        loop {
+            let line = read_line(reader)?.ok_or(HeadersTooLarge)?;
+            if line.is_empty() {
+                break;
+            }
+            headers += 1;
+            if headers > MAX_HEADERS {
+                return Err(HeadersTooLarge.into());
+            }
+            let Some((name, value)) = line.split_once(':') else {
+                bail!("Invalid header: {line}");
+            };
+            let value = value.trim();
+            if name.eq_ignore_ascii_case("Content-Length") {
+                content_length = value
+                    .parse()
+                    .with_context(|| format!("Invalid header: {line}"))?;
+            } else if name.eq_ignore_ascii_case("Transfer-Encoding") {
+                bail!("Chunked request bodies are not supported, send a Content-Length");
+            } else if name.eq_ignore_ascii_case("Expect") {
+                expect_continue = value.eq_ignore_ascii_case("100-continue");
+            } else if name.eq_ignore_ascii_case("Host") {
+                host = Some(value.to_string());
+            } else if name.eq_ignore_ascii_case("Origin") {
+                origin = Some(value.to_string());
+            }
+        }
+        if content_length > MAX_BODY_SIZE {
+            bail!("The request body is larger than {MAX_BODY_SIZE} bytes");
+        }
+
+        if expect_continue && content_length > 0 {
+            writer.write_all(b"HTTP/1.1 100 Continue\r\n\r\n")?;
+            writer.flush()?;
+        }
+        let mut body = vec![0u8; content_length];
+        reader.read_exact(&mut body)?;
+
+        Ok(Request {
+            method,
+            path,
+            host,
+            origin,
+            body,
+        })
+    }
+}
+
+/// A line of the head of a request, `None` if it's longer than
+/// `MAX_LINE_SIZE`, which is as much as is read of it
+fn read_line(reader: &mut impl BufRead) -> Result<Option<String>> {
+    let mut line = String::new();
+    let size = reader.take(MAX_LINE_SIZE).read_line(&mut line)?;
+    if size == 0 {
+        bail!("Connection closed before the request was complete");
+    }
+    if size as u64 == MAX_LINE_SIZE && !line.ends_with('\n') {
+        return Ok(None);
+    }
+    Ok(Some(line.trim_end_matches(['\r', '\n']).to_string()))
+}
+
+/// Write a JSON response and mark it as the last one of the connection
+pub fn write_response(writer: &mut impl Write, status: u16, body: &Value) -> Result<()> {
+    let body = serde_json::to_vec_pretty(body)?;
+    let mut head = format!(
+        "HTTP/1.1 {status} {reason}\r\nContent-Type: application/json\r\nContent-Length: {length}\r\nConnection: close\r\n",
+        reason = reason_phrase(status),
+        length = body.len()
+    );
+    if status == 405 {
+        head.push_str("Allow: POST\r\n");
+    }
+    head.push_str("\r\n");
+    writer.write_all(head.as_bytes())?;
+    writer.write_all(&body)?;
+    writer.flush()?;
+    Ok(())
+}
+
+fn reason_phrase(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        400 => "Bad Request",
+        403 => "Forbidden",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        431 => "Request Header Fields Too Large",
+        500 => "Internal Server Error",
+        _ => "",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::io::{BufReader, Cursor};
+
+    fn read(request: &str) -> (Result<Request>, Vec<u8>) {
+        let mut reader = BufReader::new(Cursor::new(request.as_bytes().to_vec()));
+        let mut interim = Vec::new();
+        (Request::read_from(&mut reader, &mut interim), interim)
+    }
+
+    #[test]
+    fn test_read_request() {
+        let (request, interim) = read(
+            "POST /search?pretty HTTP/1.1\r\nHost: localhost\r\ncontent-length: 17\r\n\r\n{\"query\":\"auth\"}\n",
+        );
+        assert_eq!(
+            request.unwrap(),
+            Request {
+                method: "POST".to_string(),
+                path: "/search".to_string(),
+                host: Some("localhost".to_string()),
+                origin: None,
+                body: b"{\"query\":\"auth\"}\n".to_vec(),
+            }
+        );
+        assert!(interim.is_empty());
+
+        let (request, interim) = read(
+            "POST /query HTTP/1.1\r\nExpect: 100-continue\r\nOrigin: http://localhost:3000\r\nContent-Length: 2\r\n\r\n{}",
+        );
+        let request = request.unwrap();
+        assert_eq!(request.body, b"{}");
+        assert_eq!(request.origin.as_deref(), Some("http://localhost:3000"));
+        assert_eq!(interim, b"HTTP/1.1 100 Continue\r\n\r\n");
+
+        let (request, _) = read("GET / HTTP/1.0\r\n\r\n");
+        assert_eq!(request.unwrap().body, b"");
+
+        assert!(read("POST /search\r\n\r\n").0.is_err());
+        assert!(
+            read("POST /search HTTP/1.1\r\nContent-Length: 10\r\n\r\n{}")
+                .0
+                .is_err()
+        );
+        assert!(
+            read("POST /search HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\n")
+                .0
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_request_head_limits() {
+        let long = "a".repeat(MAX_LINE_SIZE as usize);
+        let (request, _) = read(&format!("POST /{long} HTTP/1.1\r\n\r\n"));
+        let error = request.unwrap_err();
+        assert!(!error.is::<HeadersTooLarge>(), "{error}");
+
+        let (request, _) = read(&format!("POST /search HTTP/1.1\r\nX-Long: {long}\r\n\r\n"));
+        assert!(request.unwrap_err().is::<HeadersTooLarge>());
+
+        let headers = "X-Header: 1\r\n".repeat(MAX_HEADERS + 1);
+        let (request, _) = read(&format!("POST /search HTTP/1.1\r\n{headers}\r\n"));
+        assert!(request.unwrap_err().is::<HeadersTooLarge>());
+
+        let headers = "X-Header: 1\r\n".repeat(MAX_HEADERS);
+        let (request, _) = read(&format!("POST /search HTTP/1.1\r\n{headers}\r\n"));
+        assert!(request.is_ok());
+    }
+
+    #[test]
+    fn test_write_response() {
+        let mut buffer = Vec::new();
+        write_response(&mut buffer, 405, &json!({ "error": "Use POST" })).unwrap();
+        let response = String::from_utf8(buffer).unwrap();
+        assert!(response.starts_with("HTTP/1.1 405 Method Not Allowed\r\n"));
+        assert!(response.contains("\r\nAllow: POST\r\n"));
+        let (head, body) = response.split_once("\r\n\r\n").unwrap();
+        assert!(head.contains(&format!("Content-Length: {}", body.len())));
+        assert_eq!(
+            serde_json::from_str::<Value>(body).unwrap(),
+            json!({ "error": "Use POST" })
+        );
+    }
+}
@@ -0,0 +1,234 @@
+//! Local HTTP server, run by `probe serve`.
+//!
+//! Answers `POST /search`, `/extract` and `/query` with the JSON documents the
+//! commands print with `--format json`. Every connection is handled on its own
+//! thread, and all of them share the parser pool and caches of the process.
+//! Requests web pages could send, named by their Host or Origin headers, are
+//! refused, as the API has no authentication.
+
+mod endpoints;
+mod http;
+
+use anyhow::{anyhow, Context, Result};
+use colored::*;
+use serde_json::{json, Value};
+use std::io::BufReader;
+use std::net::{IpAddr, TcpListener, TcpStream};
+use std::panic::{self, AssertUnwindSafe};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use endpoints::Call;
+use http::{write_response, HeadersTooLarge, Request};
+use probe_code::language::parser_pool::warm_parser_pool;
+use probe_code::search::search_runner::format_duration;
+use probe_code::search::timeout;
+
+/// How long a client may take to send its request or to read a part of the response
+const IO_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Serve the API on `host` and `port` until the process is stopped
+pub fn run_server(host: &str, port: u16) -> Result<()> {
+    let listener = TcpListener::bind((host, port))
+        .with_context(|| format!("Failed to listen on {host}:{port}"))?;
+    // A slow search must end its request, not the server
+    timeout::set_exit_on_timeout(false);
+    warm_parser_pool();
+
+    println!(
+        "{} http://{}",
+        "Probe server listening on".bold().green(),
+        listener.local_addr()?
+    );
+
+    for stream in listener.incoming() {
+        match stream {
+            Ok(stream) => {
+                let host = host.to_string();
+                thread::spawn(move || {
+                    if let Err(e) = handle_connection(stream, &host) {
+                        eprintln!("{} {e:#}", "Failed to handle a request:".red());
+                    }
+                });
+            }
+            Err(e) => eprintln!("{} {e}", "Failed to accept a connection:".red()),
+        }
+    }
+    Ok(())
+}
+
+fn handle_connection(stream: TcpStream, server_host: &str) -> Result<()> {
+    // A client that stops sending or reading must not keep its thread forever
+    stream.set_read_timeout(Some(IO_TIMEOUT))?;
+    stream.set_write_timeout(Some(IO_TIMEOUT))?;
+    let mut writer = &stream;
+    let request = match Request::read_from(&mut BufReader::new(&stream), &mut writer) {
+        Ok(request) => request,
+        Err(e) => {
+            let status = if e.is::<HeadersTooLarge>() { 431 } else { 400 };
+            return write_response(&mut writer, status, &error_body(e));
+        }
+    };
+
+    let start_time = Instant::now();
+    let (status, body) = respond(&request, server_host);
+    println!(
+        "{} {} {status} ({})",
+        request.method,
+        request.path,
+        format_duration(start_time.elapsed())
+    );
+    write_response(&mut writer, status, &body)
+}
+
+/// Status and body of the response to a request to the server on `server_host`
+fn respond(request: &Request, server_host: &str) -> (u16, Value) {
+    if !is_local_request(request, server_host) {
+        return (
+            403,
+            error_body(anyhow!(
+                "Requests from web pages or for other host names are not allowed"
+            )),
+        );
+    }
+    let Some(call) = Call::parse(&request.path, &request.body) else {
+        return (
+            404,
+            error_body(anyhow!(
+                "Unknown endpoint {}, use /search, /extract or /query",
+                request.path
+            )),
+        );
+    };
+    if request.method != "POST" {
+        return (
+            405,
+            error_body(anyhow!("Use POST to call {}", request.path)),
+        );
+    }
+    let call = match call {
+        Ok(call) => call,
+        Err(e) => return (400, error_body(e)),
+    };
+
+    match panic::catch_unwind(AssertUnwindSafe(|| call.run())) {
+        Ok(Ok(output)) => (200, output),
+        Ok(Err(e)) => (500, error_body(e)),
+        Err(_) => (
+            500,
+            error_body(anyhow!("probe panicked, see its stderr for details")),
+        ),
+    }
+}
+
+/// Whether the Host and Origin headers of a request name this server and no web
+/// page. Any site can make a browser send requests to a local server, and DNS
+/// rebinding can give it a host name that resolves to one. Hosts may be given
+/// by IP address, which can't be rebound, but origins only by a local one.
+fn is_local_request(request: &Request, server_host: &str) -> bool {
+    let is_local = |host: &str| {
+        host.eq_ignore_ascii_case("localhost")
+            || host.eq_ignore_ascii_case(server_host)
+            || host.parse::<IpAddr>().is_ok_and(|ip| ip.is_loopback())
+    };
+    let host_allowed = request.host.as_deref().is_none_or(|host| {
+        let host = host_name(host);
+        is_local(host) || host.parse::<IpAddr>().is_ok()
+    });
+    let origin_allowed = request.origin.as_deref().is_none_or(|origin| {
+        origin
+            .split_once("://")
+            .is_some_and(|(_, authority)| is_local(host_name(authority)))
+    });
+    host_allowed && origin_allowed
+}
+
+/// The host of `host:port`, `[ipv6]:port` or a host without a port
+fn host_name(authority: &str) -> &str {
+    if let Some(rest) = authority.strip_prefix('[') {
+        return rest.split(']').next().unwrap_or(rest);
+    }
+    match authority.rsplit_once(':') {
+        Some((host, port)) if !host.contains(':') && port.bytes().all(|b| b.is_ascii_digit()) => {
+            host
+        }
+        _ => authority,
+    }
+}
+
+fn error_body(error: anyhow::Error) -> Value {
+    json!({ "error": format!("{error:#}") })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(method: &str, path: &str, body: &str) -> Request {
+        Request {
+            method: method.to_string(),
+            path: path.to_string(),
+            host: Some("127.0.0.1:8080".to_string()),
+            origin: None,
+            body: body.as_bytes().to_vec(),
+        }
+    }
+
+    fn respond_local(request: &Request) -> (u16, Value) {
+        respond(request, "127.0.0.1")
+    }
+
+    #[test]
+    fn test_respond_errors() {
+        let (status, body) = respond_local(&request("POST", "/", "{}"));
+        assert_eq!(status, 404);
+        assert!(body["error"].as_str().unwrap().contains("/search"));
+
+        let (status, _) = respond_local(&request("GET", "/search", ""));
+        assert_eq!(status, 405);
+
+        let (status, body) = respond_local(&request("POST", "/search", r#"{ "path": "." }"#));
+        assert_eq!(status, 400);
+        assert!(body["error"].as_str().unwrap().contains("query"));
+
+        let (status, _) = respond_local(&request("POST", "/extract", r#"{ "files": [] }"#));
+        assert_eq!(status, 500);
+    }
+
+    #[test]
+    fn test_only_local_requests_are_allowed() {
+        let with_headers = |host: Option<&str>, origin: Option<&str>| Request {
+            host: host.map(str::to_string),
+            origin: origin.map(str::to_string),
+            ..request("POST", "/search", "{}")
+        };
+        let allowed = |host, origin| is_local_request(&with_headers(host, origin), "127.0.0.1");
+
+        assert!(allowed(None, None));
+        assert!(allowed(Some("localhost:8080"), None));
+        assert!(allowed(Some("[::1]:8080"), None));
+        assert!(allowed(Some("192.168.1.20:8080"), None));
+        assert!(allowed(Some("127.0.0.1"), Some("http://localhost:3000")));
+        assert!(allowed(Some("127.0.0.1"), Some("http://[::1]")));
+
+        // A host name that may have been rebound to a local address
+        assert!(!allowed(Some("attacker.example:8080"), None));
+        // A page of another site
+        assert!(!allowed(
+            Some("127.0.0.1:8080"),
+            Some("https://attacker.example")
+        ));
+        assert!(!allowed(
+            Some("127.0.0.1:8080"),
+            Some("http://192.168.1.20")
+        ));
+        assert!(!allowed(Some("127.0.0.1:8080"), Some("null")));
+
+        // The name the server was started with
+        let request = with_headers(Some("devbox.lan:8080"), Some("http://devbox.lan:8080"));
+        assert!(is_local_request(&request, "devbox.lan"));
+
+        let (status, _) = respond_local(&with_headers(Some("attacker.example"), None));
+        assert_eq!(status, 403);
+    }
+}
@@ -184,7 +184,8 @@ pub struct SparseDocumentMatrix {
     pub documents: Vec<SparseVector>,
     /// Query sparse vector
     pub query: SparseVector,
-    /// Precomputed IDF values indexed by u8 token indices
+    /// Precomputed IDF values indexed by u8 token indices, already multiplied
+    /// by the boost of boosted query terms (`retry^3`)
     pub idf_values: Vec<f32>,
     /// Average document length
     pub avgdl: f64,
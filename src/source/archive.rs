@@ -0,0 +1,364 @@
+//! Files of archives, searched in place as `deps.zip!/src/lib.rs`.
+
+use anyhow::{bail, Context, Result};
+use flate2::read::GzDecoder;
+use std::collections::BTreeMap;
+use std::ffi::OsString;
+use std::io::{self, Cursor, Read};
+use std::path::{Component, Path, PathBuf};
+
+use super::{absolute, files_in, not_found, Source, Sources};
+
+/// Names of the archives whose files can be searched, as file name suffixes.
+/// Crates are gzipped tarballs, and jars, Maven `-sources.jar` included, are zips.
+const ARCHIVE_SUFFIXES: &[&str] = &[".zip", ".jar", ".crate", ".tar.gz", ".tgz", ".tar"];
+
+/// Largest file of an archive that is read; larger ones aren't searched anyway
+const MAX_ENTRY_SIZE: u64 = 1024 * 1024;
+
+/// Most bytes of files read from one archive, which may unpack to far more
+/// than its own size
+const MAX_ARCHIVE_SIZE: u64 = 256 * 1024 * 1024;
+
+/// The archive of `path`, if `path` is an archive like `deps.zip` or a path in
+/// one like `deps.zip!/src/lib.rs`
+pub fn archive_of(path: &Path) -> Option<PathBuf> {
+    let path_str = path.to_string_lossy();
+    let archive = match path_str.find("!/").or_else(|| path_str.find("!\\")) {
+        Some(end) => &path_str[..end],
+        None => path_str.strip_suffix('!').unwrap_or(&path_str),
+    };
+    let name = archive.to_lowercase();
+    ARCHIVE_SUFFIXES
+        .iter()
+        .any(|suffix| name.ends_with(suffix))
+        .then(|| PathBuf::from(archive))
+}
+
+/// The files of the archives among `paths`, or that `paths` are in, read from
+/// `sources`. Returns `None` when there are no such archives.
+pub(super) fn archive_source<P: AsRef<Path>>(
+    sources: &Sources,
+    paths: impl IntoIterator<Item = P>,
+) -> Result<Option<ArchiveSource>> {
+    let mut source = ArchiveSource::default();
+    for path in paths {
+        let Some(archive) = archive_of(path.as_ref()) else {
+            continue;
+        };
+        // Already read by one of the sources, or listed twice
+        if source.contains_path(&archive) || sources.canonicalize(&entries_root(&archive)).is_ok() {
+            continue;
+        }
+        source.add(sources, &archive)?;
+    }
+    Ok((!source.archives.is_empty()).then_some(source))
+}
+
+/// The directory holding the files of `archive`, `deps.zip!` for `deps.zip`
+fn entries_root(archive: &Path) -> PathBuf {
+    let mut root = OsString::from(archive.as_os_str());
+    root.push("!");
+    PathBuf::from(root)
+}
+
+/// Files of archives, held in memory once read. The files of `deps.zip` are in
+/// the directory `deps.zip!`, and listing the files of `deps.zip` lists them.
+#[derive(Debug, Default)]
+pub struct ArchiveSource {
+    /// Hash of the content of each archive, by absolute path
+    archives: BTreeMap<PathBuf, String>,
+    /// Files by absolute path, like `/project/deps.zip!/src/lib.rs`
+    files: BTreeMap<PathBuf, Vec<u8>>,
+}
+
+impl ArchiveSource {
+    /// Read the files of a zip, jar, crate or tarball, from `sources`
+    pub fn add(&mut self, sources: &Sources, archive: &Path) -> Result<()> {
+        let content = sources
+            .read(archive)
+            .with_context(|| format!("Failed to read archive {}", archive.display()))?;
+        let name = archive.to_string_lossy().to_lowercase();
+        let entries = if name.ends_with(".zip") || name.ends_with(".jar") {
+            read_zip(&content, MAX_ARCHIVE_SIZE)
+        } else if name.ends_with(".tar") {
+            read_tar(&content[..], MAX_ARCHIVE_SIZE)
+        } else if ARCHIVE_SUFFIXES.iter().any(|suffix| name.ends_with(suffix)) {
+            read_tar(GzDecoder::new(&content[..]), MAX_ARCHIVE_SIZE)
+        } else {
+            bail!("{} is not a supported archive", archive.display());
+        }
+        .with_context(|| format!("Failed to read archive {}", archive.display()))?;
+
+        if probe_code::console::debug_mode() {
+            println!(
+                "DEBUG: Read {} files of archive {}",
+                entries.len(),
+                archive.display()
+            );
+        }
+
+        let archive = absolute(archive);
+        let root = entries_root(&archive);
+        for (name, content) in entries {
+            self.files.insert(root.join(name), content);
+        }
+        self.archives
+            .insert(archive, format!("{:x}", md5::compute(&content)));
+        Ok(())
+    }
+}
+
+impl Source for ArchiveSource {
+    fn id(&self) -> String {
+        let hashes: Vec<&str> = self.archives.values().map(String::as_str).collect();
+        format!("archive_{:x}", md5::compute(hashes.join(",")))
+    }
+
+    /// Whether `path` is an archive or a path in one
+    fn contains_path(&self, path: &Path) -> bool {
+        let path = absolute(path);
+        self.archives
+            .keys()
+            .any(|archive| path == *archive || path.starts_with(entries_root(archive)))
+    }
+
+    fn list_files(&self, root: &Path) -> Vec<PathBuf> {
+        if self.archives.contains_key(&absolute(root)) {
+            files_in(self.files.keys(), &entries_root(root))
+        } else {
+            files_in(self.files.keys(), root)
+        }
+    }
+
+    fn read(&self, path: &Path) -> io::Result<Vec<u8>> {
+        self.files
+            .get(&absolute(path))
+            .cloned()
+            .ok_or_else(|| not_found(path, "in an archive"))
+    }
+
+    fn file_size(&self, path: &Path) -> io::Result<u64> {
+        self.files
+            .get(&absolute(path))
+            .map(|content| content.len() as u64)
+            .ok_or_else(|| not_found(path, "in an archive"))
+    }
+
+    fn canonicalize(&self, path: &Path) -> io::Result<PathBuf> {
+        let path = absolute(path);
+        if self.archives.contains_key(&path)
+            || self.files.keys().any(|file| file.starts_with(&path))
+        {
+            Ok(path)
+        } else {
+            Err(not_found(&path, "in an archive"))
+        }
+    }
+}
+
+/// The files of a zip archive, by path in the archive, as long as they add up to
+/// at most `max_size` bytes
+fn read_zip(content: &[u8], max_size: u64) -> Result<Vec<(PathBuf, Vec<u8>)>> {
+    let mut zip = zip::ZipArchive::new(Cursor::new(content))?;
+    let mut entries = Vec::new();
+    let mut size = 0;
+    for index in 0..zip.len() {
+        let mut file = zip.by_index(index)?;
+        // Skip directories, and names escaping the archive
+        let Some(name) = file.enclosed_name().filter(|_| file.is_file()) else {
+            continue;
+        };
+        if let Some(content) = read_entry(&mut file, &mut size, max_size)? {
+            entries.push((name, content));
+        }
+    }
+    Ok(entries)
+}
+
+/// The files of a tar archive, by path in the archive, as long as they add up to
+/// at most `max_size` bytes
+fn read_tar(reader: impl Read, max_size: u64) -> Result<Vec<(PathBuf, Vec<u8>)>> {
+    let mut tar = tar::Archive::new(reader);
+    let mut entries = Vec::new();
+    let mut size = 0;
+    for entry in tar.entries()? {
+        let mut entry = entry?;
+        if !entry.header().entry_type().is_file() {
+            continue;
+        }
+        // Keep names inside the archive, without `./` or `..`
+        let name: PathBuf = entry
+            .path()?
+            .components()
+            .filter_map(|component| match component {
+                Component::Normal(part) => Some(part),
+                _ => None,
+            })
+            .collect();
+        if name.as_os_str().is_empty() {
+            continue;
+        }
+        if let Some(content) = read_entry(&mut entry, &mut size, max_size)? {
+            entries.push((name, content));
+        }
+    }
+    Ok(entries)
+}
+
+/// Read a file of an archive, or `None` if it is larger than `MAX_ENTRY_SIZE`.
+/// `size` is the size of the files read so far, which may not exceed `max_size`.
+fn read_entry(entry: impl Read, size: &mut u64, max_size: u64) -> Result<Option<Vec<u8>>> {
+    let mut content = Vec::new();
+    entry.take(MAX_ENTRY_SIZE + 1).read_to_end(&mut content)?;
+    if content.len() as u64 > MAX_ENTRY_SIZE {
+        return Ok(None);
+    }
+    *size += content.len() as u64;
+    if *size > max_size {
+        bail!("Its files add up to more than {max_size} bytes");
+    }
+    Ok(Some(content))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+    use std::io::Write;
+    use tempfile::TempDir;
+
+    fn write_zip(path: &Path, files: &[(&str, &str)]) {
+        let mut zip = zip::ZipWriter::new(std::fs::File::create(path).unwrap());
+        for (name, content) in files {
+            zip.start_file(*name, zip::write::SimpleFileOptions::default())
+                .unwrap();
+            zip.write_all(content.as_bytes()).unwrap();
+        }
+        zip.finish().unwrap();
+    }
+
+    fn write_crate(path: &Path, files: &[(&str, &str)]) {
+        let gz = GzEncoder::new(std::fs::File::create(path).unwrap(), Compression::default());
+        let mut tar = tar::Builder::new(gz);
+        for (name, content) in files {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(content.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            tar.append_data(&mut header, name, content.as_bytes())
+                .unwrap();
+        }
+        tar.into_inner().unwrap().finish().unwrap();
+    }
+
+    #[test]
+    fn test_archive_of() {
+        assert_eq!(
+            archive_of(Path::new("deps/lib.zip")),
+            Some(PathBuf::from("deps/lib.zip"))
+        );
+        assert_eq!(
+            archive_of(Path::new("guava-33.0-sources.jar!/com/Lists.java")),
+            Some(PathBuf::from("guava-33.0-sources.jar"))
+        );
+        assert_eq!(
+            archive_of(Path::new("serde-1.0.0.CRATE!")),
+            Some(PathBuf::from("serde-1.0.0.CRATE"))
+        );
+        assert_eq!(archive_of(Path::new("src/lib.rs")), None);
+        assert_eq!(archive_of(Path::new("src/zip.rs!/lib.rs")), None);
+    }
+
+    #[test]
+    fn test_archive_source() {
+        let temp_dir = TempDir::new().unwrap();
+        let zip = temp_dir.path().join("lib.zip");
+        write_zip(
+            &zip,
+            &[
+                ("src/", ""),
+                ("src/lib.rs", "fn zipped() {}\n"),
+                (".hidden", ""),
+            ],
+        );
+        let krate = temp_dir.path().join("serde-1.0.0.crate");
+        write_crate(
+            &krate,
+            &[
+                ("serde-1.0.0/src/lib.rs", "fn crated() {}\n"),
+                ("serde-1.0.0/Cargo.toml", ""),
+            ],
+        );
+
+        let disk = Sources::default();
+        let mut source = ArchiveSource::default();
+        source.add(&disk, &zip).unwrap();
+        source.add(&disk, &krate).unwrap();
+        assert!(source
+            .add(&disk, &temp_dir.path().join("missing.zip"))
+            .is_err());
+
+        assert_eq!(
+            source.list_files(&zip),
+            vec![entries_root(&zip).join("src/lib.rs")]
+        );
+        assert_eq!(
+            source.list_files(&krate),
+            vec![
+                entries_root(&krate).join("serde-1.0.0/Cargo.toml"),
+                entries_root(&krate).join("serde-1.0.0/src/lib.rs"),
+            ]
+        );
+        assert!(source.contains_path(&zip));
+        assert!(!source.contains_path(&temp_dir.path().join("other.zip")));
+
+        let lib = temp_dir.path().join("lib.zip!/src/lib.rs");
+        let archives = disk.with_archives([&lib, &krate]).unwrap();
+        assert_eq!(archives.read_to_string(&lib).unwrap(), "fn zipped() {}\n");
+        assert!(archives.exists(
+            &temp_dir
+                .path()
+                .join("serde-1.0.0.crate!/serde-1.0.0/src/lib.rs")
+        ));
+        // Archives already read aren't read again
+        assert!(archive_source(&archives, [&zip]).unwrap().is_none());
+        assert!(!disk.exists(&lib));
+        assert!(archive_source(&disk, ["src/lib.rs"]).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_archive_size_limits() {
+        let temp_dir = TempDir::new().unwrap();
+        let large = "x".repeat(MAX_ENTRY_SIZE as usize + 1);
+        let zip = temp_dir.path().join("lib.zip");
+        write_zip(
+            &zip,
+            &[("large.rs", &large), ("lib.rs", "fn zipped() {}\n")],
+        );
+        let krate = temp_dir.path().join("lib.crate");
+        write_crate(
+            &krate,
+            &[("large.rs", &large), ("lib.rs", "fn crated() {}\n")],
+        );
+        let zip = std::fs::read(zip).unwrap();
+        let krate = std::fs::read(krate).unwrap();
+
+        // Files too large to search are left out
+        let names = |entries: Vec<(PathBuf, Vec<u8>)>| -> Vec<PathBuf> {
+            entries.into_iter().map(|(name, _)| name).collect()
+        };
+        let zip_entries = read_zip(&zip, MAX_ARCHIVE_SIZE).unwrap();
+        assert_eq!(names(zip_entries), vec![PathBuf::from("lib.rs")]);
+        let tar = GzDecoder::new(&krate[..]);
+        assert_eq!(
+            names(read_tar(tar, MAX_ARCHIVE_SIZE).unwrap()),
+            vec![PathBuf::from("lib.rs")]
+        );
+
+        // Archives unpacking to more than the limit aren't read
+        assert!(read_zip(&zip, 10).is_err());
+        assert!(read_tar(GzDecoder::new(&krate[..]), 10).is_err());
+    }
+}
@@ -0,0 +1,743 @@
+//! Where probe reads the files it searches.
+//!
+//! Files are read through [`Sources`]: from the disk, unless a [`Source`]
+//! holds them. Sources hold the files of a git revision, for `--rev`, the
+//! files of archives, or files kept in memory by a program embedding probe,
+//! such as unsaved editor buffers or generated code. Searches, extractions and
+//! queries are given their source in their options, so calls with different
+//! sources can run at the same time. Jupyter notebooks, wherever they're read
+//! from, are read as a script of their cells.
+
+use anyhow::{bail, Context, Result};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Debug;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::path::{Component, Path, PathBuf};
+use std::process::{Child, ChildStdin, ChildStdout, Command, Stdio};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+mod archive;
+mod notebook;
+
+pub use archive::{archive_of, ArchiveSource};
+pub use notebook::{CellLine, CellRange};
+
+use notebook::CellMap;
+use probe_code::models::SearchResult;
+
+/// Size on the disk up to which notebooks are converted to measure their size
+/// as read. Their script is never larger than their JSON, so larger notebooks
+/// count as their size on the disk without being parsed.
+const MAX_NOTEBOOK_SIZE: u64 = 64 * 1024 * 1024;
+
+/// Files probe can list and read
+pub trait Source: Debug + Send + Sync {
+    /// Identifies the files and their content, for caches of what was found in them
+    fn id(&self) -> String;
+
+    /// Whether `path` is a file or directory of the source, so the source decides
+    /// its content
+    fn contains_path(&self, path: &Path) -> bool;
+
+    /// Whether the files are those on the disk, to be found by walking
+    /// directories with their ignore rules
+    fn on_disk(&self) -> bool {
+        false
+    }
+
+    /// The files in `root`, a directory or a file, as paths starting with `root`
+    /// like those found by walking the directory. Hidden files are left out, as
+    /// they are by the walk.
+    fn list_files(&self, root: &Path) -> Vec<PathBuf>;
+
+    /// Read a file
+    fn read(&self, path: &Path) -> io::Result<Vec<u8>>;
+
+    /// Size of a file in bytes
+    fn file_size(&self, path: &Path) -> io::Result<u64> {
+        self.read(path).map(|content| content.len() as u64)
+    }
+
+    /// Whether a file exists
+    fn exists(&self, path: &Path) -> bool {
+        self.file_size(path).is_ok()
+    }
+
+    /// The absolute path of a file or directory
+    fn canonicalize(&self, path: &Path) -> io::Result<PathBuf>;
+}
+
+/// Where files are read from: the sources, the last one first, for the files
+/// they hold, and the disk for the others. Cheap to clone.
+#[derive(Debug, Clone, Default)]
+pub struct Sources {
+    sources: Vec<Arc<dyn Source>>,
+}
+
+impl Sources {
+    /// Files read from `source`, when there is one, and the disk otherwise
+    pub fn new(source: Option<Arc<dyn Source>>) -> Self {
+        Sources {
+            sources: source.into_iter().collect(),
+        }
+    }
+
+    /// These sources, with the files `source` holds read from it first
+    pub fn with(&self, source: Arc<dyn Source>) -> Self {
+        let mut sources = self.clone();
+        sources.sources.push(source);
+        sources
+    }
+
+    /// These sources, with the files of the archives among `paths`, or that
+    /// `paths` are in, read from the archives
+    pub fn with_archives<P: AsRef<Path>>(
+        &self,
+        paths: impl IntoIterator<Item = P>,
+    ) -> Result<Self> {
+        Ok(match archive::archive_source(self, paths)? {
+            Some(source) => self.with(Arc::new(source)),
+            None => self.clone(),
+        })
+    }
+
+    /// The source deciding the content of `path`
+    fn source_of(&self, path: &Path) -> &dyn Source {
+        self.sources
+            .iter()
+            .rev()
+            .find(|source| source.contains_path(path))
+            .map_or(&DiskSource, |source| source.as_ref())
+    }
+
+    /// Whether the files in `path` are on the disk, to be found by walking it
+    pub fn on_disk(&self, path: &Path) -> bool {
+        self.source_of(path).on_disk()
+    }
+
+    /// Identifies the source of `path`, for caches of what was found in its files
+    pub fn id(&self, path: &Path) -> String {
+        self.source_of(path).id()
+    }
+
+    /// The files in `root`, a directory or a file, as listed by their source
+    pub fn list_files(&self, root: &Path) -> Vec<PathBuf> {
+        self.source_of(root).list_files(root)
+    }
+
+    /// Read a file as text
+    pub fn read_to_string(&self, path: &Path) -> io::Result<String> {
+        let content = self.read(path)?;
+        String::from_utf8(content).map_err(|_| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                "stream did not contain valid UTF-8",
+            )
+        })
+    }
+
+    /// Read a file
+    pub fn read(&self, path: &Path) -> io::Result<Vec<u8>> {
+        let content = self.source_of(path).read(path)?;
+        if notebook::is_notebook(path) {
+            notebook::to_script(&content)
+        } else {
+            Ok(content)
+        }
+    }
+
+    /// Size of a file in bytes, as read
+    pub fn file_size(&self, path: &Path) -> io::Result<u64> {
+        let size = self.source_of(path).file_size(path)?;
+        if notebook::is_notebook(path) && size <= MAX_NOTEBOOK_SIZE {
+            self.read(path).map(|content| content.len() as u64)
+        } else {
+            Ok(size)
+        }
+    }
+
+    /// Whether a file exists
+    pub fn exists(&self, path: &Path) -> bool {
+        self.source_of(path).exists(path)
+    }
+
+    /// The absolute path of a file or directory, with symbolic links resolved on
+    /// the disk
+    pub fn canonicalize(&self, path: &Path) -> io::Result<PathBuf> {
+        self.source_of(path).canonicalize(path)
+    }
+
+    /// Results in notebooks split at the boundaries of their cells, without the
+    /// markers starting the cells, with where they are in the cells. Of the
+    /// parts of a result, those with its matched keywords are kept, or all of
+    /// them when none has any. Whole files and results in other files are kept
+    /// as they are.
+    pub fn split_notebook_cells(&self, results: Vec<SearchResult>) -> Vec<SearchResult> {
+        let mut notebooks: HashMap<PathBuf, Option<CellMap>> = HashMap::new();
+        results
+            .into_iter()
+            .flat_map(|result| {
+                let path = Path::new(&result.file);
+                if !notebook::is_notebook(path) {
+                    return vec![result];
+                }
+                let cells = notebooks.entry(path.to_path_buf()).or_insert_with(|| {
+                    self.read_to_string(path)
+                        .ok()
+                        .map(|script| CellMap::new(&script))
+                });
+                match cells {
+                    Some(cells) if result.node_type != "file" => split_result(cells, result),
+                    Some(cells) => vec![SearchResult {
+                        cells: cells.range(result.lines),
+                        ..result
+                    }],
+                    None => vec![result],
+                }
+            })
+            .collect()
+    }
+}
+
+/// The parts of a result in each cell of its notebook
+fn split_result(cells: &CellMap, result: SearchResult) -> Vec<SearchResult> {
+    let lines: Vec<&str> = result.code.lines().collect();
+    let (start, end) = result.lines;
+    // Code that isn't the lines of the script is left alone
+    if start == 0 || end < start || lines.len() != end - start + 1 {
+        return vec![SearchResult {
+            cells: cells.range(result.lines),
+            ..result
+        }];
+    }
+
+    let parts: Vec<((usize, usize), String)> = cells
+        .split(result.lines)
+        .into_iter()
+        .map(|part| (part, lines[part.0 - start..=part.1 - start].join("\n")))
+        .filter(|(_, code)| !code.trim().is_empty())
+        .collect();
+    let keywords: Vec<String> = result
+        .matched_keywords
+        .iter()
+        .flatten()
+        .map(|keyword| keyword.to_lowercase())
+        .collect();
+    let (matching, other): (Vec<_>, Vec<_>) = parts.into_iter().partition(|(_, code)| {
+        let code = code.to_lowercase();
+        keywords
+            .iter()
+            .any(|keyword| code.contains(keyword.as_str()))
+    });
+    let parts = if matching.is_empty() { other } else { matching };
+
+    parts
+        .into_iter()
+        .map(|(lines, code)| SearchResult {
+            lines,
+            code,
+            cells: cells.range(lines),
+            ..result.clone()
+        })
+        .collect()
+}
+
+/// The files on the disk
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DiskSource;
+
+impl Source for DiskSource {
+    fn id(&self) -> String {
+        "disk".to_string()
+    }
+
+    fn contains_path(&self, _path: &Path) -> bool {
+        true
+    }
+
+    fn on_disk(&self) -> bool {
+        true
+    }
+
+    fn list_files(&self, root: &Path) -> Vec<PathBuf> {
+        let mut files: Vec<PathBuf> = ignore::WalkBuilder::new(root)
+            .build()
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.file_type().is_some_and(|ft| ft.is_file()))
+            .map(|entry| entry.into_path())
+            .collect();
+        files.sort();
+        files
+    }
+
+    fn read(&self, path: &Path) -> io::Result<Vec<u8>> {
+        std::fs::read(path)
+    }
+
+    fn file_size(&self, path: &Path) -> io::Result<u64> {
+        std::fs::metadata(path).map(|metadata| metadata.len())
+    }
+
+    fn exists(&self, path: &Path) -> bool {
+        path.exists()
+    }
+
+    fn canonicalize(&self, path: &Path) -> io::Result<PathBuf> {
+        std::fs::canonicalize(path)
+    }
+}
+
+/// Files kept in memory under a directory, which needn't exist on the disk
+///
+/// ```
+/// use probe_code::source::{MemorySource, Source};
+/// use std::path::Path;
+///
+/// let source = MemorySource::new("/buffers").file("src/lib.rs", "fn unsaved() {}");
+/// assert_eq!(
+///     source.list_files(Path::new("/buffers/src")),
+///     vec![Path::new("/buffers/src/lib.rs").to_path_buf()]
+/// );
+/// ```
+#[derive(Debug)]
+pub struct MemorySource {
+    root: PathBuf,
+    files: BTreeMap<PathBuf, Vec<u8>>,
+    id: u64,
+}
+
+impl MemorySource {
+    /// No files yet, in the directory `root`
+    pub fn new(root: impl AsRef<Path>) -> Self {
+        static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+        MemorySource {
+            root: absolute(root.as_ref()),
+            files: BTreeMap::new(),
+            id: NEXT_ID.fetch_add(1, Ordering::Relaxed),
+        }
+    }
+
+    /// Add a file, at a path relative to the root or absolute
+    pub fn file(mut self, path: impl AsRef<Path>, content: impl Into<Vec<u8>>) -> Self {
+        self.files
+            .insert(absolute(&self.root.join(path)), content.into());
+        self
+    }
+}
+
+impl Source for MemorySource {
+    fn id(&self) -> String {
+        format!("memory_{}", self.id)
+    }
+
+    fn contains_path(&self, path: &Path) -> bool {
+        absolute(path).starts_with(&self.root)
+    }
+
+    fn list_files(&self, root: &Path) -> Vec<PathBuf> {
+        files_in(self.files.keys(), root)
+    }
+
+    fn read(&self, path: &Path) -> io::Result<Vec<u8>> {
+        self.files
+            .get(&absolute(path))
+            .cloned()
+            .ok_or_else(|| not_found(path, "in memory"))
+    }
+
+    fn file_size(&self, path: &Path) -> io::Result<u64> {
+        self.files
+            .get(&absolute(path))
+            .map(|content| content.len() as u64)
+            .ok_or_else(|| not_found(path, "in memory"))
+    }
+
+    fn canonicalize(&self, path: &Path) -> io::Result<PathBuf> {
+        let path = absolute(path);
+        if self.files.keys().any(|file| file.starts_with(&path)) {
+            Ok(path)
+        } else {
+            Err(not_found(&path, "in memory"))
+        }
+    }
+}
+
+/// The `files`, given as absolute paths, that are in `root` and not hidden, as
+/// paths starting with `root`
+fn files_in<'a>(files: impl Iterator<Item = &'a PathBuf>, root: &Path) -> Vec<PathBuf> {
+    let absolute_root = absolute(root);
+    let mut files: Vec<PathBuf> = files
+        .filter_map(|file| file.strip_prefix(&absolute_root).ok())
+        .filter(|relative| {
+            !relative
+                .components()
+                .any(|component| component.as_os_str().to_string_lossy().starts_with('.'))
+        })
+        .map(|relative| {
+            if relative.as_os_str().is_empty() {
+                root.to_path_buf()
+            } else {
+                root.join(relative)
+            }
+        })
+        .collect();
+    files.sort();
+    files
+}
+
+/// `path` made absolute without reading the disk, as it may not exist there
+fn absolute(path: &Path) -> PathBuf {
+    let path = if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        std::env::current_dir().unwrap_or_default().join(path)
+    };
+    let mut absolute = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                absolute.pop();
+            }
+            component => absolute.push(component),
+        }
+    }
+    absolute
+}
+
+fn not_found(path: &Path, place: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::NotFound,
+        format!("{} is not {place}", path.display()),
+    )
+}
+
+/// A file of a revision
+#[derive(Debug, Clone)]
+struct Blob {
+    oid: String,
+    size: u64,
+}
+
+/// The files of a git repository at a commit
+#[derive(Debug)]
+pub struct GitRevision {
+    /// Root of the working tree
+    toplevel: PathBuf,
+    /// Full hash of the commit
+    commit: String,
+    /// Files by absolute path in the working tree
+    blobs: HashMap<PathBuf, Blob>,
+    /// `git cat-file --batch` reading blobs, started on the first read
+    cat_file: Mutex<Option<CatFile>>,
+}
+
+impl GitRevision {
+    /// Open the revision `rev` (a commit, branch or tag) of the git repository
+    /// containing `path`
+    pub fn open(path: &Path, rev: &str) -> Result<GitRevision> {
+        let dir = if path.is_dir() {
+            path
+        } else {
+            path.parent()
+                .filter(|parent| !parent.as_os_str().is_empty())
+                .unwrap_or(Path::new("."))
+        };
+        let toplevel = git(dir, &["rev-parse", "--show-toplevel"])
+            .with_context(|| format!("{} is not in a git repository", path.display()))?;
+        let toplevel = PathBuf::from(toplevel.trim_end_matches(['\n', '\r']));
+        let commit = git(
+            &toplevel,
+            &[
+                "rev-parse",
+                "--verify",
+                "--quiet",
+                &format!("{rev}^{{commit}}"),
+            ],
+        )
+        .map_err(|_| anyhow::anyhow!("Unknown git revision: {rev}"))?
+        .trim()
+        .to_string();
+
+        // Lines are `<mode> <type> <oid> <size>\t<path>`
+        let mut blobs = HashMap::new();
+        for entry in git(&toplevel, &["ls-tree", "-r", "-l", "-z", &commit])?.split('\0') {
+            let Some((info, name)) = entry.split_once('\t') else {
+                continue;
+            };
+            let mut info = info.split_whitespace();
+            let (Some(mode), Some("blob"), Some(oid), Some(size)) =
+                (info.next(), info.next(), info.next(), info.next())
+            else {
+                continue;
+            };
+            // Symbolic links have their target as content
+            if mode == "120000" {
+                continue;
+            }
+            blobs.insert(
+                toplevel.join(name),
+                Blob {
+                    oid: oid.to_string(),
+                    size: size.parse().unwrap_or(0),
+                },
+            );
+        }
+
+        if probe_code::console::debug_mode() {
+            println!(
+                "DEBUG: Reading {} files of {} at {commit}",
+                blobs.len(),
+                toplevel.display()
+            );
+        }
+
+        Ok(GitRevision {
+            toplevel,
+            commit,
+            blobs,
+            cat_file: Mutex::new(None),
+        })
+    }
+
+    /// Full hash of the commit
+    pub fn commit(&self) -> &str {
+        &self.commit
+    }
+
+    fn blob(&self, path: &Path) -> io::Result<&Blob> {
+        self.blobs
+            .get(&absolute(path))
+            .ok_or_else(|| not_found(path, &format!("in revision {}", self.commit)))
+    }
+}
+
+impl Source for GitRevision {
+    fn id(&self) -> String {
+        format!("rev_{}", self.commit)
+    }
+
+    /// Whether `path` is in the repository
+    fn contains_path(&self, path: &Path) -> bool {
+        absolute(path).starts_with(&self.toplevel)
+    }
+
+    fn list_files(&self, root: &Path) -> Vec<PathBuf> {
+        files_in(self.blobs.keys(), root)
+    }
+
+    fn read(&self, path: &Path) -> io::Result<Vec<u8>> {
+        let blob = self.blob(path)?;
+        let mut cat_file = self.cat_file.lock().unwrap();
+        if cat_file.is_none() {
+            *cat_file = Some(CatFile::start(&self.toplevel)?);
+        }
+        let result = cat_file.as_mut().unwrap().read(&blob.oid);
+        if result.is_err() {
+            // Start again on the next read rather than reading out of step
+            *cat_file = None;
+        }
+        result
+    }
+
+    fn file_size(&self, path: &Path) -> io::Result<u64> {
+        Ok(self.blob(path)?.size)
+    }
+
+    fn canonicalize(&self, path: &Path) -> io::Result<PathBuf> {
+        let path = absolute(path);
+        if path.starts_with(&self.toplevel) && self.blobs.keys().any(|file| file.starts_with(&path))
+        {
+            Ok(path)
+        } else {
+            Err(not_found(&path, &format!("in revision {}", self.commit)))
+        }
+    }
+}
+
+/// A running `git cat-file --batch`, which prints the objects it is asked for
+#[derive(Debug)]
+struct CatFile {
+    child: Child,
+    stdin: ChildStdin,
+    stdout: BufReader<ChildStdout>,
+}
+
+impl CatFile {
+    fn start(toplevel: &Path) -> io::Result<CatFile> {
+        let mut child = Command::new("git")
+            .arg("-C")
+            .arg(toplevel)
+            .args(["cat-file", "--batch"])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()?;
+        let stdin = child.stdin.take().unwrap();
+        let stdout = BufReader::new(child.stdout.take().unwrap());
+        Ok(CatFile {
+            child,
+            stdin,
+            stdout,
+        })
+    }
+
+    fn read(&mut self, oid: &str) -> io::Result<Vec<u8>> {
+        writeln!(self.stdin, "{oid}")?;
+        self.stdin.flush()?;
+
+        // The content follows a `<oid> <type> <size>` line, and ends with a newline
+        let mut header = String::new();
+        self.stdout.read_line(&mut header)?;
+        let size = header
+            .split_whitespace()
+            .nth(2)
+            .and_then(|size| size.parse().ok())
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("Unexpected git cat-file output: {}", header.trim()),
+                )
+            })?;
+        let mut content = vec![0; size];
+        self.stdout.read_exact(&mut content)?;
+        self.stdout.read_exact(&mut [0])?;
+        Ok(content)
+    }
+}
+
+impl Drop for CatFile {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+/// Run a git command in `dir` and return its output
+pub(crate) fn git(dir: &Path, args: &[&str]) -> Result<String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        .output()
+        .context("Failed to run git")?;
+    if !output.status.success() {
+        bail!(
+            "git {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn run_git(dir: &Path, args: &[&str]) {
+        let status = Command::new("git")
+            .arg("-C")
+            .arg(dir)
+            .args([
+                "-c",
+                "user.name=probe",
+                "-c",
+                "user.email=probe@example.com",
+            ])
+            .args(args)
+            .output()
+            .unwrap()
+            .status;
+        assert!(status.success(), "git {args:?} failed");
+    }
+
+    #[test]
+    fn test_git_revision() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path().canonicalize().unwrap();
+        fs::create_dir(root.join("src")).unwrap();
+        fs::write(root.join("src/lib.rs"), "fn old() {}\n").unwrap();
+        fs::write(root.join("src/removed.rs"), "fn removed() {}\n").unwrap();
+        run_git(&root, &["init", "-q"]);
+        run_git(&root, &["add", "."]);
+        run_git(&root, &["commit", "-q", "-m", "initial"]);
+        run_git(&root, &["tag", "v1"]);
+        fs::write(root.join("src/lib.rs"), "fn new() {}\n").unwrap();
+        fs::remove_file(root.join("src/removed.rs")).unwrap();
+        fs::write(root.join("src/added.rs"), "fn added() {}\n").unwrap();
+
+        let revision = GitRevision::open(&root, "v1").unwrap();
+        assert_eq!(
+            revision.list_files(&root.join("src")),
+            vec![root.join("src/lib.rs"), root.join("src/removed.rs")]
+        );
+        assert_eq!(
+            revision.list_files(&root.join("src/lib.rs")),
+            vec![root.join("src/lib.rs")]
+        );
+        assert!(GitRevision::open(&root, "v2").is_err());
+
+        let lib = root.join("src/lib.rs");
+        let outside = TempDir::new().unwrap();
+        let outside_file = outside.path().join("notes.txt");
+        fs::write(&outside_file, "on disk").unwrap();
+        let sources = Sources::new(Some(Arc::new(revision)));
+        assert_eq!(sources.read_to_string(&lib).unwrap(), "fn old() {}\n");
+        assert_eq!(sources.read_to_string(&lib).unwrap(), "fn old() {}\n");
+        assert_eq!(
+            sources
+                .read_to_string(&root.join("src/../src/removed.rs"))
+                .unwrap(),
+            "fn removed() {}\n"
+        );
+        assert_eq!(sources.file_size(&lib).unwrap(), 12);
+        assert!(!sources.exists(&root.join("src/added.rs")));
+        assert!(sources.read_to_string(&root.join("src/added.rs")).is_err());
+        // Files outside the repository are still read from the disk
+        assert_eq!(sources.read_to_string(&outside_file).unwrap(), "on disk");
+        assert_eq!(
+            Sources::default().read_to_string(&lib).unwrap(),
+            "fn new() {}\n"
+        );
+    }
+
+    #[test]
+    fn test_memory_source() {
+        let source = MemorySource::new("/buffers")
+            .file("src/lib.rs", "fn unsaved() {}\n")
+            .file("/buffers/src/.hidden.rs", "fn hidden() {}\n")
+            .file("README.md", "# Buffers\n");
+        let root = Path::new("/buffers");
+        assert!(source.contains_path(&root.join("src/missing.rs")));
+        assert!(!source.contains_path(Path::new("/elsewhere/lib.rs")));
+        assert_eq!(
+            source.list_files(root),
+            vec![root.join("README.md"), root.join("src/lib.rs")]
+        );
+
+        let sources = Sources::new(Some(Arc::new(source)));
+        assert!(!sources.on_disk(root));
+        assert!(sources.id(root).starts_with("memory_"));
+        assert_eq!(
+            sources
+                .read_to_string(&root.join("src/../src/lib.rs"))
+                .unwrap(),
+            "fn unsaved() {}\n"
+        );
+        assert_eq!(sources.file_size(&root.join("README.md")).unwrap(), 10);
+        assert_eq!(
+            sources.canonicalize(&root.join("src")).unwrap(),
+            root.join("src")
+        );
+        assert!(!sources.exists(&root.join("src/missing.rs")));
+        assert!(sources.canonicalize(&root.join("missing")).is_err());
+
+        let disk = Sources::default();
+        assert!(disk.on_disk(root));
+        assert!(!disk.exists(&root.join("src/lib.rs")));
+    }
+}
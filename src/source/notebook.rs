@@ -0,0 +1,243 @@
+//! Jupyter notebooks, read as a script of their code and markdown cells.
+
+use serde::Serialize;
+use serde_json::Value;
+use std::fmt;
+use std::io;
+use std::path::Path;
+
+/// Starts each cell in the script of a notebook, followed by the cell number
+const CELL_MARKER: &str = "# %% Cell ";
+
+/// Whether `path` is a notebook, read as a script
+pub(super) fn is_notebook(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("ipynb"))
+}
+
+/// The code and markdown cells of a notebook as one Python script, in the
+/// percent format of Jupytext with markdown commented out. Outputs, raw cells
+/// and attachments such as images are left out, so they're neither searched
+/// nor counted towards the file size limit.
+pub(super) fn to_script(content: &[u8]) -> io::Result<Vec<u8>> {
+    let notebook: Value = serde_json::from_slice(content).map_err(|e| {
+        io::Error::new(io::ErrorKind::InvalidData, format!("Invalid notebook: {e}"))
+    })?;
+    let Some(cells) = notebook["cells"].as_array() else {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "Unsupported notebook format, only nbformat 4 notebooks have cells",
+        ));
+    };
+
+    let mut script = String::new();
+    for (index, cell) in cells.iter().enumerate() {
+        // Sources are lists of lines, or a single string
+        let source: String = match &cell["source"] {
+            Value::String(source) => source.clone(),
+            Value::Array(lines) => lines.iter().filter_map(Value::as_str).collect(),
+            _ => String::new(),
+        };
+        let number = index + 1;
+        match cell["cell_type"].as_str() {
+            Some("code") => {
+                script.push_str(&format!("{CELL_MARKER}{number}\n"));
+                for line in source.lines() {
+                    script.push_str(line);
+                    script.push('\n');
+                }
+            }
+            Some("markdown") => {
+                script.push_str(&format!("{CELL_MARKER}{number} [markdown]\n"));
+                for line in source.lines() {
+                    script.push_str(format!("# {line}").trim_end());
+                    script.push('\n');
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(script.into_bytes())
+}
+
+/// A line of a notebook cell
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct CellLine {
+    /// Number of the cell in the notebook, from 1
+    pub cell: usize,
+    /// Line in the cell, from 1
+    pub line: usize,
+}
+
+/// Where lines of the script of a notebook are in its cells
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct CellRange {
+    pub start: CellLine,
+    pub end: CellLine,
+}
+
+impl fmt::Display for CellRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let CellRange { start, end } = self;
+        if start.cell == end.cell {
+            write!(f, "cell {}, lines {}-{}", start.cell, start.line, end.line)
+        } else {
+            write!(
+                f,
+                "cell {} line {} to cell {} line {}",
+                start.cell, start.line, end.cell, end.line
+            )
+        }
+    }
+}
+
+/// Where the cells of the script of a notebook start
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct CellMap {
+    /// Line of the marker starting each cell, from 1, and the number of the
+    /// cell, in order
+    markers: Vec<(usize, usize)>,
+}
+
+impl CellMap {
+    pub(super) fn new(script: &str) -> Self {
+        let markers = script
+            .lines()
+            .enumerate()
+            .filter_map(|(row, text)| {
+                let number = text.strip_prefix(CELL_MARKER)?;
+                let number = number.split(' ').next()?.parse().ok()?;
+                Some((row + 1, number))
+            })
+            .collect();
+        CellMap { markers }
+    }
+
+    /// Whether `line` of the script is the marker starting a cell
+    fn is_marker(&self, line: usize) -> bool {
+        self.markers
+            .binary_search_by_key(&line, |&(marker, _)| marker)
+            .is_ok()
+    }
+
+    /// The cell line of a line of the script, from 1. The marker starting a
+    /// cell counts as its first line.
+    fn cell_line(&self, line: usize) -> Option<CellLine> {
+        let index = self
+            .markers
+            .partition_point(|&(marker, _)| marker <= line)
+            .checked_sub(1)?;
+        let (marker, cell) = self.markers[index];
+        Some(CellLine {
+            cell,
+            line: (line - marker).max(1),
+        })
+    }
+
+    /// Where `lines` of the script, as search and extract report them, are
+    /// in its cells
+    pub(super) fn range(&self, lines: (usize, usize)) -> Option<CellRange> {
+        Some(CellRange {
+            start: self.cell_line(lines.0)?,
+            end: self.cell_line(lines.1)?,
+        })
+    }
+
+    /// The parts of `lines` of the script in each of their cells, without the
+    /// markers between them
+    pub(super) fn split(&self, lines: (usize, usize)) -> Vec<(usize, usize)> {
+        let mut parts = Vec::new();
+        let mut start = lines.0;
+        for line in lines.0..=lines.1 {
+            if self.is_marker(line) {
+                if line > start {
+                    parts.push((start, line - 1));
+                }
+                start = line + 1;
+            }
+        }
+        if start <= lines.1 {
+            parts.push((start, lines.1));
+        }
+        parts
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOTEBOOK: &str = r##"{
+ "cells": [
+  {
+   "cell_type": "markdown",
+   "metadata": {},
+   "source": ["# Training\n", "\n", "Fit the model."]
+  },
+  {
+   "cell_type": "code",
+   "execution_count": 1,
+   "metadata": {},
+   "outputs": [
+    {"output_type": "display_data", "data": {"image/png": "iVBORw0KGgoAAAANSUhEUg"}}
+   ],
+   "source": "def train(model):\n    return model.fit()"
+  },
+  {
+   "cell_type": "raw",
+   "metadata": {},
+   "source": ["ignored"]
+  },
+  {
+   "cell_type": "code",
+   "execution_count": 2,
+   "metadata": {},
+   "outputs": [],
+   "source": ["model = train(model)\n", "model.save()"]
+  }
+ ],
+ "metadata": {},
+ "nbformat": 4,
+ "nbformat_minor": 5
+}"##;
+
+    #[test]
+    fn test_to_script() {
+        let script = String::from_utf8(to_script(NOTEBOOK.as_bytes()).unwrap()).unwrap();
+        assert_eq!(
+            script,
+            "# %% Cell 1 [markdown]\n\
+             # # Training\n\
+             #\n\
+             # Fit the model.\n\
+             # %% Cell 2\n\
+             def train(model):\n    return model.fit()\n\
+             # %% Cell 4\n\
+             model = train(model)\n\
+             model.save()\n"
+        );
+        assert!(to_script(b"{\"worksheets\": []}").is_err());
+        assert!(to_script(b"not json").is_err());
+    }
+
+    #[test]
+    fn test_cell_range() {
+        let script = String::from_utf8(to_script(NOTEBOOK.as_bytes()).unwrap()).unwrap();
+        let cells = CellMap::new(&script);
+        let range = cells.range((6, 7)).unwrap();
+        assert_eq!(range.to_string(), "cell 2, lines 1-2");
+        let range = cells.range((5, 10)).unwrap();
+        assert_eq!(range.to_string(), "cell 2 line 1 to cell 4 line 2");
+        assert_eq!(CellMap::new("no cells\n").range((1, 1)), None);
+    }
+
+    #[test]
+    fn test_split_at_cells() {
+        let script = String::from_utf8(to_script(NOTEBOOK.as_bytes()).unwrap()).unwrap();
+        let cells = CellMap::new(&script);
+        assert_eq!(cells.split((2, 10)), vec![(2, 4), (6, 7), (9, 10)]);
+        assert_eq!(cells.split((5, 8)), vec![(6, 7)]);
+        assert_eq!(cells.split((6, 7)), vec![(6, 7)]);
+        assert_eq!(cells.split((8, 8)), Vec::<(usize, usize)>::new());
+    }
+}
@@ -0,0 +1,112 @@
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+use std::process::Command;
+use tempfile::TempDir;
+
+const LIB_RS: &str = "pub fn exponential_backoff(attempts: u32) -> u32 {\n    let base = 2;\n    base * attempts\n}\n";
+
+fn probe(dir: &Path, args: &[&str]) -> String {
+    let output = Command::new(env!("CARGO_BIN_EXE_probe"))
+        .current_dir(dir)
+        .args(args)
+        .output()
+        .expect("Failed to run probe");
+    assert!(
+        output.status.success(),
+        "probe {args:?} failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8_lossy(&output.stdout).into_owned()
+}
+
+/// A Maven sources jar and a crate, each holding the same function
+fn create_test_archives() -> TempDir {
+    let temp_dir = TempDir::new().unwrap();
+
+    let jar = File::create(temp_dir.path().join("backoff-1.0-sources.jar")).unwrap();
+    let mut zip = zip::ZipWriter::new(jar);
+    zip.start_file("src/lib.rs", zip::write::SimpleFileOptions::default())
+        .unwrap();
+    zip.write_all(LIB_RS.as_bytes()).unwrap();
+    zip.finish().unwrap();
+
+    let krate = File::create(temp_dir.path().join("backoff-1.0.crate")).unwrap();
+    let mut tar = tar::Builder::new(GzEncoder::new(krate, Compression::default()));
+    let mut header = tar::Header::new_gnu();
+    header.set_size(LIB_RS.len() as u64);
+    header.set_mode(0o644);
+    header.set_cksum();
+    tar.append_data(&mut header, "backoff-1.0/src/lib.rs", LIB_RS.as_bytes())
+        .unwrap();
+    tar.into_inner().unwrap().finish().unwrap();
+
+    temp_dir
+}
+
+#[test]
+fn test_search_archives() {
+    let dir = create_test_archives();
+
+    let output = probe(
+        dir.path(),
+        &[
+            "search",
+            "backoff",
+            "backoff-1.0-sources.jar",
+            "--format",
+            "json",
+        ],
+    );
+    assert!(
+        output.contains(r#""file": "backoff-1.0-sources.jar!/src/lib.rs""#),
+        "{output}"
+    );
+
+    let output = probe(
+        dir.path(),
+        &[
+            "search",
+            "backoff",
+            "backoff-1.0.crate",
+            "--format",
+            "plain",
+        ],
+    );
+    assert!(
+        output.contains("backoff-1.0.crate!/backoff-1.0/src/lib.rs"),
+        "{output}"
+    );
+}
+
+#[test]
+fn test_extract_and_query_archives() {
+    let dir = create_test_archives();
+
+    let output = probe(
+        dir.path(),
+        &[
+            "extract",
+            "backoff-1.0-sources.jar!/src/lib.rs:2",
+            "--format",
+            "plain",
+        ],
+    );
+    assert!(output.contains("base * attempts"), "{output}");
+
+    let output = probe(
+        dir.path(),
+        &[
+            "query",
+            "pub fn $NAME($$$PARAMS) -> u32 { $$$BODY }",
+            "backoff-1.0.crate",
+            "--language",
+            "rust",
+            "--format",
+            "plain",
+        ],
+    );
+    assert!(output.contains("exponential_backoff"), "{output}");
+}
@@ -0,0 +1,116 @@
+use std::fs;
+use std::path::Path;
+use std::process::{Command, Output};
+use tempfile::TempDir;
+
+fn create_test_project(dir: &Path) {
+    fs::write(
+        dir.join("auth.rs"),
+        r#"
+/// Refresh an expired session token
+fn refresh_token(session: &Session) -> Token {
+    session.token().refresh()
+}
+
+fn unrelated() {
+    let value = 1;
+}
+"#,
+    )
+    .unwrap();
+    fs::write(
+        dir.join("session.py"),
+        r#"
+class SessionStore:
+    def refresh(self, session):
+        return session
+"#,
+    )
+    .unwrap();
+}
+
+/// Run `probe search` with the block cache in `cache_dir`
+fn search(project: &Path, cache_dir: &Path, query: &str, debug: bool) -> Output {
+    let output = Command::new(env!("CARGO_BIN_EXE_probe"))
+        .args(["search", query, &project.to_string_lossy()])
+        .env("PROBE_BLOCK_CACHE_DIR", cache_dir)
+        .env("DEBUG", if debug { "1" } else { "0" })
+        .output()
+        .expect("Failed to execute probe search");
+    assert!(
+        output.status.success(),
+        "probe search failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    output
+}
+
+/// Search results without the timing line, which differs between runs
+fn results(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter(|line| !line.starts_with("Search completed in"))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Number of cached files, in the cache directory of the probe build
+fn cache_entries(cache_dir: &Path) -> usize {
+    fs::read_dir(cache_dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|build_dir| fs::read_dir(build_dir.path()).map_or(0, |entries| entries.count()))
+        .sum()
+}
+
+#[test]
+fn test_block_cache_is_reused_across_runs() {
+    let temp_dir = TempDir::new().unwrap();
+    let project = temp_dir.path().join("project");
+    let cache_dir = temp_dir.path().join("cache");
+    fs::create_dir(&project).unwrap();
+    create_test_project(&project);
+
+    let first = search(&project, &cache_dir, "refresh", false);
+    assert!(results(&first).contains("fn refresh_token"));
+    assert_eq!(cache_entries(&cache_dir), 2);
+
+    // The second run builds its blocks from the cache instead of parsing the files
+    let debug_output = search(&project, &cache_dir, "refresh", true);
+    let log = String::from_utf8_lossy(&debug_output.stderr);
+    assert!(log.contains("from the block cache"), "stderr: {log}");
+    assert!(!log.contains("Sparse cache miss"), "stderr: {log}");
+
+    let second = search(&project, &cache_dir, "refresh", false);
+    assert_eq!(results(&second), results(&first));
+
+    // Changed files get a new cache entry
+    fs::write(
+        project.join("auth.rs"),
+        "fn refresh_token() {\n    renew();\n}\n",
+    )
+    .unwrap();
+    let third = search(&project, &cache_dir, "refresh", false);
+    assert!(results(&third).contains("renew()"));
+    assert_eq!(cache_entries(&cache_dir), 3);
+}
+
+#[test]
+fn test_block_cache_can_be_disabled() {
+    let temp_dir = TempDir::new().unwrap();
+    let project = temp_dir.path().join("project");
+    let cache_dir = temp_dir.path().join("cache");
+    fs::create_dir(&project).unwrap();
+    create_test_project(&project);
+
+    let output = Command::new(env!("CARGO_BIN_EXE_probe"))
+        .args(["search", "refresh", &project.to_string_lossy()])
+        .env("PROBE_BLOCK_CACHE_DIR", &cache_dir)
+        .env("PROBE_NO_BLOCK_CACHE", "1")
+        .output()
+        .expect("Failed to execute probe search");
+    assert!(output.status.success());
+    assert!(results(&output).contains("fn refresh_token"));
+    assert!(!cache_dir.exists());
+}
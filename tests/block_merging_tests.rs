@@ -5,6 +5,7 @@ use tempfile::TempDir;
 use probe_code::models::SearchResult;
 use probe_code::search::block_merging::merge_ranked_blocks;
 use probe_code::search::{perform_probe, SearchOptions};
+use probe_code::source::Sources;
 
 #[test]
 fn test_merge_ranked_blocks() {
@@ -35,6 +36,7 @@ fn test_merge_ranked_blocks() {
         block_id: None,
         matched_keywords: None,
         tokenized_content: None,
+        cells: None,
     };
     let block2 = SearchResult {
     file: "test_file.rs".to_string(),
@@ -60,6 +62,7 @@ fn test_merge_ranked_blocks() {
     block_id: None,
     matched_keywords: None,
     tokenized_content: None,
+    cells: None,
 };
 
     // Create block from a different file that should not be merged
@@ -87,13 +90,14 @@ fn test_merge_ranked_blocks() {
         block_id: None,
         matched_keywords: None,
         tokenized_content: None,
+        cells: None,
     };
 
     // Create a vector with all blocks
     let blocks = vec![block1, block2, block3];
 
     // Call the merge_ranked_blocks function
-    let merged_blocks = merge_ranked_blocks(blocks, Some(5));
+    let merged_blocks = merge_ranked_blocks(&Sources::default(), blocks, Some(5));
 
     // Assert that we now have 2 blocks (the first two merged, the third separate)
     assert_eq!(
@@ -193,7 +197,11 @@ fn test_integration_with_search_flow() {
         timeout: 30,
         question: None,
         exact: false,
+        fuzzy: false,
+        explain: false,
         no_gitignore: false,
+        file_filters: Default::default(),
+        source: None,
     };
 
     // Run a search that should produce multiple overlapping blocks
@@ -308,7 +316,11 @@ fn test_no_merge_flag() {
         timeout: 30,
         question: None,
         exact: false,
+        fuzzy: false,
+        explain: false,
         no_gitignore: false,
+        file_filters: Default::default(),
+        source: None,
     };
 
     // Run a search that should produce merged blocks
@@ -335,7 +347,11 @@ fn test_no_merge_flag() {
         timeout: 30,
         question: None,
         exact: false,
+        fuzzy: false,
+        explain: false,
         no_gitignore: false,
+        file_filters: Default::default(),
+        source: None,
     };
 
     // Run a search that should not merge blocks
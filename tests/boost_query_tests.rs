@@ -0,0 +1,80 @@
+use std::fs;
+use std::path::Path;
+use tempfile::TempDir;
+
+use probe_code::search::{perform_probe, SearchOptions};
+
+/// Create a Rust file with one function about backoff and one about retries
+fn create_test_file(dir: &Path) {
+    let content = r#"
+fn charge_payment() {
+    let payment = 1;
+    let backoff = payment;
+    let backoff_again = backoff;
+}
+
+fn schedule_retry() {
+    let payment = 2;
+    let retry = payment;
+}
+"#;
+    fs::write(dir.join("lib.rs"), content).unwrap();
+}
+
+/// Run a search and return the function names of the result blocks in rank order
+fn search(dir: &Path, query: &str) -> Vec<String> {
+    let queries = vec![query.to_string()];
+    let custom_ignores: Vec<String> = vec![];
+    let options = SearchOptions {
+        path: dir,
+        queries: &queries,
+        files_only: false,
+        custom_ignores: &custom_ignores,
+        exclude_filenames: true,
+        language: None,
+        reranker: "bm25",
+        frequency_search: true,
+        max_results: None,
+        max_bytes: None,
+        max_tokens: None,
+        allow_tests: true,
+        no_merge: true,
+        merge_threshold: None,
+        dry_run: false,
+        session: None,
+        timeout: 30,
+        question: None,
+        exact: false,
+        fuzzy: false,
+        explain: false,
+        no_gitignore: true,
+        file_filters: Default::default(),
+        source: None,
+    };
+
+    let results = perform_probe(&options).unwrap();
+    results
+        .results
+        .iter()
+        .filter_map(|r| {
+            let line = r.code.lines().find(|l| l.contains("fn "))?;
+            let name = line.trim().trim_start_matches("fn ");
+            Some(name[..name.find('(')?].to_string())
+        })
+        .collect()
+}
+
+#[test]
+fn test_boost_ranks_boosted_term_first() {
+    let temp_dir = TempDir::new().unwrap();
+    create_test_file(temp_dir.path());
+
+    assert_eq!(
+        search(temp_dir.path(), "+payment retry backoff"),
+        vec!["charge_payment", "schedule_retry"]
+    );
+    assert_eq!(
+        search(temp_dir.path(), "+payment retry^3 backoff"),
+        vec!["schedule_retry", "charge_payment"]
+    );
+}
@@ -0,0 +1,144 @@
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+use tempfile::TempDir;
+
+fn git(dir: &Path, args: &[&str]) {
+    let output = Command::new("git")
+        .current_dir(dir)
+        .args([
+            "-c",
+            "user.name=probe",
+            "-c",
+            "user.email=probe@example.com",
+        ])
+        .args(args)
+        .output()
+        .expect("Failed to run git");
+    assert!(output.status.success(), "git {args:?} failed");
+}
+
+fn probe(dir: &Path, args: &[&str]) -> String {
+    let output = Command::new(env!("CARGO_BIN_EXE_probe"))
+        .current_dir(dir)
+        .args(args)
+        .output()
+        .expect("Failed to run probe");
+    assert!(
+        output.status.success(),
+        "probe {args:?} failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8_lossy(&output.stdout).into_owned()
+}
+
+/// A repository where `attempts` is in three files: one unchanged since the
+/// `base` tag, one edited in the working tree and one edited and staged
+fn create_test_repository() -> TempDir {
+    let temp_dir = TempDir::new().unwrap();
+    let root = temp_dir.path();
+    fs::create_dir(root.join("src")).unwrap();
+    for name in ["client", "worker", "queue"] {
+        fs::write(
+            root.join(format!("src/{name}.rs")),
+            format!("pub fn {name}_retry(attempts: u32) -> u32 {{\n    attempts\n}}\n"),
+        )
+        .unwrap();
+    }
+    git(root, &["init", "-q"]);
+    git(root, &["add", "."]);
+    git(root, &["commit", "-q", "-m", "initial"]);
+    git(root, &["tag", "base"]);
+
+    for name in ["worker", "queue"] {
+        fs::write(
+            root.join(format!("src/{name}.rs")),
+            format!("pub fn {name}_retry(attempts: u32) -> u32 {{\n    attempts + 1\n}}\n"),
+        )
+        .unwrap();
+    }
+    git(root, &["add", "src/queue.rs"]);
+    temp_dir
+}
+
+#[test]
+fn test_search_changed_since() {
+    let repo = create_test_repository();
+
+    let all = probe(
+        repo.path(),
+        &["search", "attempts", "src", "--format", "plain"],
+    );
+    assert!(all.contains("client.rs") && all.contains("worker.rs") && all.contains("queue.rs"));
+
+    let changed = probe(
+        repo.path(),
+        &[
+            "search",
+            "attempts",
+            "src",
+            "--format",
+            "plain",
+            "--changed-since",
+            "base",
+        ],
+    );
+    assert!(!changed.contains("client.rs"), "{changed}");
+    assert!(changed.contains("worker.rs") && changed.contains("queue.rs"));
+
+    let staged = probe(
+        repo.path(),
+        &["search", "attempts", "src", "--format", "plain", "--staged"],
+    );
+    assert!(!staged.contains("client.rs") && !staged.contains("worker.rs"));
+    assert!(staged.contains("queue.rs"), "{staged}");
+}
+
+#[test]
+fn test_query_and_extract_changed_since() {
+    let repo = create_test_repository();
+
+    let matches = probe(
+        repo.path(),
+        &[
+            "query",
+            "pub fn $NAME($$$PARAMS) -> u32 { $$$BODY }",
+            "src",
+            "--language",
+            "rust",
+            "--format",
+            "plain",
+            "--changed-since",
+            "base",
+        ],
+    );
+    assert!(!matches.contains("client_retry"), "{matches}");
+    assert!(matches.contains("worker_retry") && matches.contains("queue_retry"));
+
+    let extracted = probe(
+        repo.path(),
+        &[
+            "extract",
+            "src/client.rs:1",
+            "src/queue.rs:1",
+            "--format",
+            "plain",
+            "--staged",
+        ],
+    );
+    assert!(!extracted.contains("client_retry"), "{extracted}");
+    assert!(extracted.contains("queue_retry"));
+}
+
+#[test]
+fn test_changed_since_unknown_ref() {
+    let repo = create_test_repository();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_probe"))
+        .current_dir(repo.path())
+        .args(["search", "attempts", "--changed-since", "no-such-ref"])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("no-such-ref"));
+}
@@ -477,3 +477,38 @@ struct SearchConfig {
         "Should find only 1 result due to limit"
     );
 }
+
+#[test]
+fn test_cli_explain_keeps_json_output_parseable() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    create_test_directory_structure(&temp_dir);
+
+    let output = Command::new("cargo")
+        .args([
+            "run",
+            "--",
+            "search",
+            "search",
+            temp_dir.path().to_str().unwrap(),
+            "--explain",
+            "--format",
+            "json",
+        ])
+        .output()
+        .expect("Failed to execute command");
+    assert!(output.status.success());
+
+    // The explanation goes to stderr, the results to stdout between the search
+    // options and the tip
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let json_start = stdout.find("{\n").expect("stdout should hold JSON results");
+    let json: serde_json::Value = serde_json::Deserializer::from_str(&stdout[json_start..])
+        .into_iter()
+        .next()
+        .unwrap()
+        .expect("The explanation should not be mixed into the JSON results");
+    assert!(!json["results"].as_array().unwrap().is_empty());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Query explanation"), "{stderr}");
+    assert!(stderr.contains("Score breakdown"), "{stderr}");
+}
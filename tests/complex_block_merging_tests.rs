@@ -4,6 +4,7 @@ use tempfile::TempDir;
 use probe_code::models::SearchResult;
 use probe_code::search::block_merging::merge_ranked_blocks;
 use probe_code::search::{perform_probe, SearchOptions};
+use probe_code::source::Sources;
 
 /// Test merging of blocks with different node types
 #[test]
@@ -35,6 +36,7 @@ fn test_merge_different_node_types() {
         block_id: None,
         matched_keywords: None,
         tokenized_content: None,
+        cells: None,
     };
     let block2 = SearchResult {
     file: "mixed_types.rs".to_string(),
@@ -60,6 +62,7 @@ fn test_merge_different_node_types() {
     block_id: None,
     matched_keywords: None,
     tokenized_content: None,
+    cells: None,
 };
 
     let block3 = SearchResult {
@@ -86,13 +89,14 @@ fn test_merge_different_node_types() {
         block_id: None,
         matched_keywords: None,
         tokenized_content: None,
+        cells: None,
     };
 
     // Create a vector with all blocks
     let blocks = vec![block1, block2, block3];
 
     // Call the merge_ranked_blocks function
-    let merged_blocks = merge_ranked_blocks(blocks, Some(5));
+    let merged_blocks = merge_ranked_blocks(&Sources::default(), blocks, Some(5));
 
     // Assert that all blocks are merged into one
     assert_eq!(
@@ -153,6 +157,7 @@ fn test_merge_with_gaps() {
         block_id: None,
         matched_keywords: None,
         tokenized_content: None,
+        cells: None,
     };
 
     // Gap of 3 lines between block1 and block2
@@ -180,6 +185,7 @@ fn test_merge_with_gaps() {
         block_id: None,
         matched_keywords: None,
         tokenized_content: None,
+        cells: None,
     };
 
     // Gap of 2 lines between block2 and block3
@@ -209,12 +215,13 @@ fn test_merge_with_gaps() {
         block_id: None,
         matched_keywords: None,
         tokenized_content: None,
+        cells: None,
     };
 
     // Test with default threshold (5)
     {
         let blocks = vec![block1.clone(), block2.clone(), block3.clone()];
-        let merged_blocks = merge_ranked_blocks(blocks, Some(5));
+        let merged_blocks = merge_ranked_blocks(&Sources::default(), blocks, Some(5));
 
         // With threshold 5, all blocks should be merged
         assert_eq!(
@@ -235,7 +242,7 @@ fn test_merge_with_gaps() {
     // Test with smaller threshold (2)
     {
         let blocks = vec![block1.clone(), block2.clone(), block3.clone()];
-        let merged_blocks = merge_ranked_blocks(blocks, Some(2));
+        let merged_blocks = merge_ranked_blocks(&Sources::default(), blocks, Some(2));
 
         // With threshold 2, block1 and block2 should not be merged (gap of 3),
         // but block2 and block3 should be merged (gap of 2)
@@ -265,7 +272,7 @@ fn test_merge_with_gaps() {
     // Test with threshold 0 (no merging)
     {
         let blocks = vec![block1.clone(), block2.clone(), block3.clone()];
-        let merged_blocks = merge_ranked_blocks(blocks, Some(0));
+        let merged_blocks = merge_ranked_blocks(&Sources::default(), blocks, Some(0));
 
         // With threshold 0, no blocks should be merged
         assert_eq!(
@@ -304,6 +311,7 @@ fn test_merge_overlapping_blocks() {
         block_id: None,
         matched_keywords: None,
         tokenized_content: None,
+        cells: None,
     };
 
     // Overlaps with block1 (lines 5-7 are shared)
@@ -331,13 +339,14 @@ fn test_merge_overlapping_blocks() {
         block_id: None,
         matched_keywords: None,
         tokenized_content: None,
+        cells: None,
     };
 
     // Create a vector with both blocks
     let blocks = vec![block1, block2];
 
     // Call the merge_ranked_blocks function
-    let merged_blocks = merge_ranked_blocks(blocks, Some(5));
+    let merged_blocks = merge_ranked_blocks(&Sources::default(), blocks, Some(5));
 
     // Assert that blocks are merged into one
     assert_eq!(
@@ -426,7 +435,11 @@ fn third_function() {
             timeout: 30,
             question: None,
             exact: false,
+            fuzzy: false,
+            explain: false,
             no_gitignore: false,
+            file_filters: Default::default(),
+            source: None,
         };
 
         // Run the search
@@ -504,6 +517,7 @@ fn test_merge_parent_child_blocks() {
         block_id: Some(0),
         matched_keywords: None,
         tokenized_content: None,
+        cells: None,
     };
 
     // Child block (method inside the struct)
@@ -531,13 +545,14 @@ fn test_merge_parent_child_blocks() {
         block_id: Some(1),
         matched_keywords: None,
         tokenized_content: None,
+        cells: None,
     };
 
     // Create a vector with both blocks
     let blocks = vec![child_block, parent_block];
 
     // Call the merge_ranked_blocks function
-    let merged_blocks = merge_ranked_blocks(blocks, Some(5));
+    let merged_blocks = merge_ranked_blocks(&Sources::default(), blocks, Some(5));
 
     // Assert that blocks are merged into one
     assert_eq!(
@@ -311,7 +311,11 @@ func (i *IPWhiteListMiddleware) Process() {
         timeout: 30,
         question: None,
         exact: false,
+        fuzzy: false,
+        explain: false,
         no_gitignore: false,
+        file_filters: Default::default(),
+        source: None,
     };
 
     // Run the search
@@ -392,7 +396,11 @@ func Process() {
         timeout: 30,
         question: None,
         exact: false,
+        fuzzy: false,
+        explain: false,
         no_gitignore: false,
+        file_filters: Default::default(),
+        source: None,
     };
 
     // Run the search
@@ -0,0 +1,224 @@
+#![cfg(unix)]
+
+use std::fs;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::os::unix::net::UnixStream;
+use std::path::{Path, PathBuf};
+use std::process::{Child, ChildStdout, Command, Output, Stdio};
+use std::time::{Duration, Instant};
+use tempfile::TempDir;
+
+/// A running `probe daemon`, stopped when dropped
+struct Daemon {
+    child: Child,
+    log: BufReader<ChildStdout>,
+}
+
+impl Daemon {
+    fn start(root: &Path, socket: &Path) -> Self {
+        let mut child = Command::new(env!("CARGO_BIN_EXE_probe"))
+            .args(["daemon", &root.to_string_lossy()])
+            .env("PROBE_DAEMON_SOCKET", socket)
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .expect("Failed to start probe daemon");
+
+        // Its first line tells it listens; connecting to find out would keep it
+        // busy with that connection
+        let mut log = BufReader::new(child.stdout.take().unwrap());
+        let mut line = String::new();
+        log.read_line(&mut line).unwrap();
+        assert!(line.contains("listening"), "Unexpected output: {line}");
+        Daemon { child, log }
+    }
+
+    /// Stop the daemon and return its log
+    fn stop(mut self) -> String {
+        let _ = self.child.kill();
+        let mut log = String::new();
+        self.log.read_to_string(&mut log).unwrap();
+        log
+    }
+}
+
+impl Drop for Daemon {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+fn create_test_project(dir: &Path) {
+    fs::create_dir_all(dir.join("src")).unwrap();
+    fs::write(
+        dir.join("src/auth.rs"),
+        r#"
+/// Refresh an expired session token
+fn refresh_token(session: &Session) -> Token {
+    session.token().refresh()
+}
+"#,
+    )
+    .unwrap();
+}
+
+fn probe(project: &Path, socket: &Path, args: &[&str], use_daemon: bool) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_probe"))
+        .args(args)
+        .current_dir(project)
+        .env("PROBE_DAEMON_SOCKET", socket)
+        .env("PROBE_NO_DAEMON", if use_daemon { "0" } else { "1" })
+        .output()
+        .expect("Failed to execute probe")
+}
+
+/// Output without the timing lines, which differ between runs
+fn stdout(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter(|line| {
+            let timing = line.starts_with("Search completed in")
+                || (line.starts_with("Found") && line.contains(" matches in "));
+            !timing
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn setup() -> (TempDir, PathBuf, PathBuf) {
+    let temp_dir = TempDir::new().unwrap();
+    let project = temp_dir.path().join("project");
+    create_test_project(&project);
+    let socket = temp_dir.path().join("probe.sock");
+    (temp_dir, project, socket)
+}
+
+#[test]
+fn test_commands_are_forwarded_to_the_daemon() {
+    let (_temp_dir, project, socket) = setup();
+    let daemon = Daemon::start(&project, &socket);
+
+    let commands: &[&[&str]] = &[
+        &["search", "refresh", "src"],
+        &["refresh", "src", "--format", "json"],
+        &["extract", "src/auth.rs:3"],
+        &["query", "fn $NAME($$$PARAMS) -> Token { $$$BODY }", "src"],
+    ];
+    for args in commands {
+        let forwarded = probe(&project, &socket, args, true);
+        let local = probe(&project, &socket, args, false);
+        assert!(forwarded.status.success(), "{args:?} failed");
+        assert_eq!(stdout(&forwarded), stdout(&local), "{args:?}");
+        assert!(stdout(&forwarded).contains("refresh_token"));
+    }
+
+    // Errors keep their exit code
+    let forwarded = probe(
+        &project,
+        &socket,
+        &["search", "refresh", "src", "-r", "x"],
+        true,
+    );
+    assert!(!forwarded.status.success());
+
+    let log = daemon.stop();
+    assert!(log.contains("search refresh src"), "Daemon log: {log}");
+    assert!(log.contains("extract src/auth.rs:3"), "Daemon log: {log}");
+}
+
+#[test]
+fn test_daemon_sees_changed_files() {
+    let (_temp_dir, project, socket) = setup();
+    let _daemon = Daemon::start(&project, &socket);
+
+    let found = stdout(&probe(
+        &project,
+        &socket,
+        &["search", "checkpoint", "."],
+        true,
+    ));
+    assert!(found.contains("No results found"), "{found}");
+
+    fs::create_dir(project.join("lib")).unwrap();
+    fs::write(
+        project.join("lib/lock.rs"),
+        "fn write_checkpoint(lock: &Lock) {\n    lock.flush();\n}\n",
+    )
+    .unwrap();
+    let found = stdout(&probe(
+        &project,
+        &socket,
+        &["search", "checkpoint", "."],
+        true,
+    ));
+    assert!(found.contains("write_checkpoint"), "{found}");
+
+    fs::remove_file(project.join("lib/lock.rs")).unwrap();
+    let found = stdout(&probe(
+        &project,
+        &socket,
+        &["search", "checkpoint", "."],
+        true,
+    ));
+    assert!(found.contains("No results found"), "{found}");
+}
+
+#[test]
+fn test_commands_run_locally_without_a_daemon() {
+    let (_temp_dir, project, socket) = setup();
+
+    // A socket left behind by a daemon that is gone
+    drop(std::os::unix::net::UnixListener::bind(&socket).unwrap());
+    assert!(socket.exists());
+
+    let output = probe(&project, &socket, &["search", "refresh", "src"], true);
+    assert!(output.status.success());
+    assert!(stdout(&output).contains("refresh_token"));
+
+    // A new daemon replaces the stale socket
+    let daemon = Daemon::start(&project, &socket);
+    let output = probe(&project, &socket, &["search", "refresh", "src"], true);
+    assert!(stdout(&output).contains("refresh_token"));
+    assert!(daemon.stop().contains("search refresh src"));
+}
+
+#[test]
+fn test_commands_run_locally_while_the_daemon_is_busy() {
+    let (_temp_dir, project, socket) = setup();
+    let daemon = Daemon::start(&project, &socket);
+
+    // A client that connected but hasn't sent its command yet keeps the daemon busy
+    let _busy = UnixStream::connect(&socket).unwrap();
+    let start = Instant::now();
+    let output = probe(&project, &socket, &["search", "refresh", "src"], true);
+    assert!(output.status.success());
+    assert!(stdout(&output).contains("refresh_token"));
+    assert!(start.elapsed() < Duration::from_secs(5));
+
+    let log = daemon.stop();
+    assert!(!log.contains("search refresh src"), "Daemon log: {log}");
+}
+
+#[test]
+fn test_daemon_only_runs_search_extract_and_query() {
+    let (_temp_dir, project, socket) = setup();
+    let _daemon = Daemon::start(&project, &socket);
+
+    let request = serde_json::json!({
+        "version": env!("CARGO_PKG_VERSION"),
+        "cwd": project,
+        "args": ["lsp"],
+        "env": [],
+        "color": false,
+    });
+    let mut stream = UnixStream::connect(&socket).unwrap();
+    writeln!(stream, "{request}").unwrap();
+    let mut response = Vec::new();
+    stream.read_to_end(&mut response).unwrap();
+    let response = String::from_utf8_lossy(&response);
+    assert!(
+        response.contains("only runs search, extract and query"),
+        "{response}"
+    );
+}
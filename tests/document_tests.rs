@@ -0,0 +1,115 @@
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+use tempfile::TempDir;
+
+const ADR: &str = r#"# ADR 7: Session storage
+
+## Context
+
+Sessions are kept in memory and lost on every deploy.
+
+## Decision
+
+Store sessions in Redis with a sliding expiry.
+
+```rust
+fn session_ttl() -> Duration {
+    Duration::from_secs(3600)
+}
+```
+
+## Consequences
+
+Deploys no longer log users out.
+"#;
+
+const GUIDE: &str = r#"Operations
+==========
+
+Rolling back
+------------
+
+Redeploy the previous release tag.
+
+Rotating secrets
+----------------
+
+Rotate the Redis password every quarter.
+"#;
+
+fn probe(dir: &Path, args: &[&str]) -> String {
+    let output = Command::new(env!("CARGO_BIN_EXE_probe"))
+        .current_dir(dir)
+        .args(args)
+        .output()
+        .expect("Failed to run probe");
+    assert!(
+        output.status.success(),
+        "probe {args:?} failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8_lossy(&output.stdout).into_owned()
+}
+
+fn create_test_docs() -> TempDir {
+    let temp_dir = TempDir::new().unwrap();
+    fs::create_dir(temp_dir.path().join("docs")).unwrap();
+    fs::write(temp_dir.path().join("docs/adr-007.md"), ADR).unwrap();
+    fs::write(temp_dir.path().join("docs/operations.rst"), GUIDE).unwrap();
+    temp_dir
+}
+
+#[test]
+fn test_search_returns_document_sections() {
+    let dir = create_test_docs();
+
+    let output = probe(dir.path(), &["search", "deploy", "docs/adr-007.md"]);
+    assert!(output.contains("Lines: 3-5"), "{output}");
+    assert!(output.contains("Lines: 17-19"), "{output}");
+    assert!(!output.contains("sliding expiry"), "{output}");
+
+    // Code is its own block
+    let output = probe(dir.path(), &["search", "Duration", "docs/adr-007.md"]);
+    assert!(output.contains("Lines: 11-15"), "{output}");
+    assert!(!output.contains("sliding expiry"), "{output}");
+
+    let output = probe(dir.path(), &["search", "password", "docs/operations.rst"]);
+    assert!(output.contains("Lines: 9-12"), "{output}");
+    assert!(!output.contains("previous release"), "{output}");
+}
+
+#[test]
+fn test_extract_document_section_by_heading() {
+    let dir = create_test_docs();
+
+    let output = probe(
+        dir.path(),
+        &["extract", "docs/adr-007.md#Decision", "--format", "plain"],
+    );
+    assert!(output.contains("Lines: 7-15"), "{output}");
+    assert!(output.contains("Duration::from_secs(3600)"), "{output}");
+    assert!(!output.contains("lost on every deploy"), "{output}");
+
+    // Headings can also be given as anchors, and sections hold their subsections
+    let output = probe(
+        dir.path(),
+        &[
+            "extract",
+            "docs/operations.rst#rotating-secrets",
+            "--format",
+            "plain",
+        ],
+    );
+    assert!(output.contains("Lines: 9-12"), "{output}");
+    let output = probe(
+        dir.path(),
+        &[
+            "extract",
+            "docs/operations.rst#Operations",
+            "--format",
+            "plain",
+        ],
+    );
+    assert!(output.contains("Lines: 1-12"), "{output}");
+}
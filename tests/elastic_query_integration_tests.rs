@@ -122,7 +122,11 @@ fn test_required_term_query() {
         timeout: 30,
         question: None,
         exact: false,
+        fuzzy: false,
+        explain: false,
         no_gitignore: false,
+        file_filters: Default::default(),
+        source: None,
     };
 
     // Print the temp_path for debugging
@@ -232,7 +236,11 @@ fn test_excluded_term_query() {
         timeout: 30,
         question: None,
         exact: false,
+        fuzzy: false,
+        explain: false,
         no_gitignore: false,
+        file_filters: Default::default(),
+        source: None,
     };
 
     // Print the query for debugging
@@ -326,7 +334,11 @@ fn test_or_query() {
         timeout: 30,
         question: None,
         exact: false,
+        fuzzy: false,
+        explain: false,
         no_gitignore: false,
+        file_filters: Default::default(),
+        source: None,
     };
 
     // Print the test files for debugging
@@ -428,7 +440,11 @@ fn test_complex_query_or() {
         timeout: 30,
         question: None,
         exact: false,
+        fuzzy: false,
+        explain: false,
         no_gitignore: false,
+        file_filters: Default::default(),
+        source: None,
     };
 
     // Print the test files for debugging
@@ -528,7 +544,11 @@ fn test_complex_query_exclusion() {
         timeout: 30,
         question: None,
         exact: false,
+        fuzzy: false,
+        explain: false,
         no_gitignore: false,
+        file_filters: Default::default(),
+        source: None,
     };
 
     // Print the query for debugging
@@ -628,7 +648,11 @@ fn test_function() {
         timeout: 30,
         question: None,
         exact: false,
+        fuzzy: false,
+        explain: false,
         no_gitignore: false,
+        file_filters: Default::default(),
+        source: None,
     };
 
     // Run the search
@@ -0,0 +1,103 @@
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+use tempfile::TempDir;
+
+const CART_VUE: &str = r#"<template>
+  <ul class="cart">
+    <li v-for="item in items" :key="item.sku">{{ item.name }}</li>
+  </ul>
+  <p>Total: {{ total }}</p>
+</template>
+
+<script setup lang="ts">
+import { computed } from 'vue'
+
+const props = defineProps<{ items: CartItem[] }>()
+
+function lineTotal(item: CartItem): number {
+  return item.price * item.quantity
+}
+
+const total = computed(() => props.items.reduce((sum, item) => sum + lineTotal(item), 0))
+</script>
+
+<style scoped>
+.cart {
+  list-style: none;
+}
+</style>
+"#;
+
+const COUNTER_SVELTE: &str = r#"<script>
+  let count = 0;
+
+  function increment() {
+    count += 1;
+  }
+</script>
+
+<button on:click={increment}>
+  Clicked {count} times
+</button>
+"#;
+
+fn probe(dir: &Path, args: &[&str]) -> String {
+    let output = Command::new(env!("CARGO_BIN_EXE_probe"))
+        .current_dir(dir)
+        .args(args)
+        .output()
+        .expect("Failed to run probe");
+    assert!(
+        output.status.success(),
+        "probe {args:?} failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8_lossy(&output.stdout).into_owned()
+}
+
+fn create_test_components() -> TempDir {
+    let temp_dir = TempDir::new().unwrap();
+    fs::write(temp_dir.path().join("Cart.vue"), CART_VUE).unwrap();
+    fs::write(temp_dir.path().join("Counter.svelte"), COUNTER_SVELTE).unwrap();
+    temp_dir
+}
+
+#[test]
+fn test_search_component_scripts() {
+    let dir = create_test_components();
+
+    // Functions in a script are blocks of their own, at their lines in the component
+    let output = probe(dir.path(), &["search", "price", "Cart.vue"]);
+    assert!(output.contains("Lines: 13-15"), "{output}");
+    assert!(output.contains("function lineTotal"), "{output}");
+    assert!(!output.contains("list-style"), "{output}");
+
+    let output = probe(dir.path(), &["search", "list-style", "Cart.vue"]);
+    assert!(output.contains("Lines: 20-24"), "{output}");
+
+    let output = probe(
+        dir.path(),
+        &["search", "count", ".", "--language", "svelte"],
+    );
+    assert!(output.contains("Counter.svelte"), "{output}");
+    assert!(!output.contains("Cart.vue"), "{output}");
+}
+
+#[test]
+fn test_extract_component_function() {
+    let dir = create_test_components();
+
+    let output = probe(
+        dir.path(),
+        &["extract", "Counter.svelte#increment", "--format", "json"],
+    );
+    let json: serde_json::Value = serde_json::from_str(&output).unwrap();
+    let result = &json["results"][0];
+    assert_eq!(result["lines"], serde_json::json!([4, 6]));
+    assert_eq!(result["node_type"], "function_declaration");
+
+    let output = probe(dir.path(), &["extract", "Cart.vue:14", "--format", "plain"]);
+    assert!(output.contains("Lines: 13-15"), "{output}");
+    assert!(!output.contains("<template>"), "{output}");
+}
@@ -7,6 +7,7 @@ use probe_code::extract::{
     extract_file_paths_from_git_diff, format_and_print_extraction_results, is_git_diff_format,
     process_file_for_extraction,
 };
+use probe_code::source::Sources;
 
 #[test]
 fn test_process_file_for_extraction_full_file() {
@@ -17,7 +18,17 @@ fn test_process_file_for_extraction_full_file() {
     fs::write(&file_path, content).unwrap();
 
     // Test processing the full file
-    let result = process_file_for_extraction(&file_path, None, None, None, false, 0, None).unwrap();
+    let result = process_file_for_extraction(
+        &Sources::default(),
+        &file_path,
+        None,
+        None,
+        None,
+        false,
+        0,
+        None,
+    )
+    .unwrap();
 
     assert_eq!(result.file, file_path.to_string_lossy().to_string());
     assert_eq!(result.lines, (1, 3)); // 3 lines in the content
@@ -26,8 +37,17 @@ fn test_process_file_for_extraction_full_file() {
 
     // Test with non-existent file
     let non_existent = temp_dir.path().join("non_existent.txt");
-    let err =
-        process_file_for_extraction(&non_existent, None, None, None, false, 0, None).unwrap_err();
+    let err = process_file_for_extraction(
+        &Sources::default(),
+        &non_existent,
+        None,
+        None,
+        None,
+        false,
+        0,
+        None,
+    )
+    .unwrap_err();
     assert!(err.to_string().contains("does not exist"));
 }
 
@@ -62,16 +82,34 @@ impl Point {
     fs::write(&file_path, content).unwrap();
 
     // Test extracting a function
-    let result =
-        process_file_for_extraction(&file_path, Some(3), None, None, false, 0, None).unwrap();
+    let result = process_file_for_extraction(
+        &Sources::default(),
+        &file_path,
+        Some(3),
+        None,
+        None,
+        false,
+        0,
+        None,
+    )
+    .unwrap();
     assert_eq!(result.file, file_path.to_string_lossy().to_string());
     assert!(result.lines.0 <= 3 && result.lines.1 >= 3);
     assert!(result.code.contains("fn main()"));
     assert!(result.code.contains("Hello, world!"));
 
     // Test extracting a struct
-    let result =
-        process_file_for_extraction(&file_path, Some(13), None, None, false, 0, None).unwrap();
+    let result = process_file_for_extraction(
+        &Sources::default(),
+        &file_path,
+        Some(13),
+        None,
+        None,
+        false,
+        0,
+        None,
+    )
+    .unwrap();
     assert_eq!(result.file, file_path.to_string_lossy().to_string());
     assert!(result.lines.0 <= 13 && result.lines.1 >= 13);
     assert!(result.code.contains("struct Point"));
@@ -79,8 +117,17 @@ impl Point {
     assert!(result.code.contains("y: i32"));
 
     // Test with out-of-bounds line number (should be clamped to valid range)
-    let result =
-        process_file_for_extraction(&file_path, Some(1000), None, None, false, 0, None).unwrap();
+    let result = process_file_for_extraction(
+        &Sources::default(),
+        &file_path,
+        Some(1000),
+        None,
+        None,
+        false,
+        0,
+        None,
+    )
+    .unwrap();
     // The line number should be clamped to the maximum valid line
     // Don't check for exact equality, just make sure it's within valid range
     assert!(result.lines.0 <= result.lines.1);
@@ -100,8 +147,17 @@ fn test_process_file_for_extraction_fallback() {
     fs::write(&file_path, content).unwrap();
 
     // Test fallback to line-based context with default context lines (10)
-    let result =
-        process_file_for_extraction(&file_path, Some(15), None, None, false, 10, None).unwrap();
+    let result = process_file_for_extraction(
+        &Sources::default(),
+        &file_path,
+        Some(15),
+        None,
+        None,
+        false,
+        10,
+        None,
+    )
+    .unwrap();
     assert_eq!(result.file, file_path.to_string_lossy().to_string());
     assert_eq!(result.node_type, "context");
 
@@ -114,20 +170,47 @@ fn test_process_file_for_extraction_fallback() {
     assert!(end_line - start_line >= 10); // At least 10 lines of context
 
     // Test with a line at the beginning of the file
-    let result =
-        process_file_for_extraction(&file_path, Some(2), None, None, false, 10, None).unwrap();
+    let result = process_file_for_extraction(
+        &Sources::default(),
+        &file_path,
+        Some(2),
+        None,
+        None,
+        false,
+        10,
+        None,
+    )
+    .unwrap();
     assert!(result.lines.0 <= 2); // Should start at or before line 2
     assert!(result.lines.1 >= 2); // Should include line 2
 
     // Test with a line at the end of the file
-    let result =
-        process_file_for_extraction(&file_path, Some(25), None, None, false, 10, None).unwrap();
+    let result = process_file_for_extraction(
+        &Sources::default(),
+        &file_path,
+        Some(25),
+        None,
+        None,
+        false,
+        10,
+        None,
+    )
+    .unwrap();
     assert!(result.lines.0 <= 25); // Should include some lines before line 25
     assert_eq!(result.lines.1, 25); // Can't go beyond the last line
 
     // Test with custom context lines
-    let result =
-        process_file_for_extraction(&file_path, Some(15), None, None, false, 5, None).unwrap();
+    let result = process_file_for_extraction(
+        &Sources::default(),
+        &file_path,
+        Some(15),
+        None,
+        None,
+        false,
+        5,
+        None,
+    )
+    .unwrap();
     assert_eq!(result.file, file_path.to_string_lossy().to_string());
     assert_eq!(result.node_type, "context");
 
@@ -168,6 +251,7 @@ fn test_format_and_print_extraction_results() {
         block_id: None,
         matched_keywords: None,
         tokenized_content: None,
+        cells: None,
     };
 
     // Test different formats
@@ -604,8 +688,17 @@ fn test_process_file_for_extraction_with_range() {
     fs::write(&file_path, &content).unwrap();
 
     // Test extracting a range of lines
-    let result =
-        process_file_for_extraction(&file_path, Some(1), Some(10), None, false, 0, None).unwrap();
+    let result = process_file_for_extraction(
+        &Sources::default(),
+        &file_path,
+        Some(1),
+        Some(10),
+        None,
+        false,
+        0,
+        None,
+    )
+    .unwrap();
     assert_eq!(result.file, file_path.to_string_lossy().to_string());
     assert_eq!(result.lines, (1, 10));
     assert_eq!(result.node_type, "range");
@@ -615,8 +708,17 @@ fn test_process_file_for_extraction_with_range() {
     assert_eq!(result.code, expected_content);
 
     // Test with a different range
-    let result =
-        process_file_for_extraction(&file_path, Some(5), Some(15), None, false, 0, None).unwrap();
+    let result = process_file_for_extraction(
+        &Sources::default(),
+        &file_path,
+        Some(5),
+        Some(15),
+        None,
+        false,
+        0,
+        None,
+    )
+    .unwrap();
     assert_eq!(result.lines, (5, 15));
 
     // Check that the extracted content contains exactly lines 5-15
@@ -629,15 +731,33 @@ fn test_process_file_for_extraction_with_range() {
     assert_eq!(result.code, expected_content);
 
     // Test with invalid range (start > end) - should be clamped to valid range
-    let result =
-        process_file_for_extraction(&file_path, Some(10), Some(5), None, false, 0, None).unwrap();
+    let result = process_file_for_extraction(
+        &Sources::default(),
+        &file_path,
+        Some(10),
+        Some(5),
+        None,
+        false,
+        0,
+        None,
+    )
+    .unwrap();
     // The start and end lines should be clamped to valid values
     assert!(result.lines.0 <= result.lines.1);
     assert!(result.lines.1 <= content.lines().count());
 
     // Test with out-of-bounds range (should be clamped to valid range)
-    let result =
-        process_file_for_extraction(&file_path, Some(15), Some(25), None, false, 0, None).unwrap();
+    let result = process_file_for_extraction(
+        &Sources::default(),
+        &file_path,
+        Some(15),
+        Some(25),
+        None,
+        false,
+        0,
+        None,
+    )
+    .unwrap();
     // The end line should be clamped to the maximum valid line
     assert!(result.lines.0 <= 15);
     assert!(result.lines.1 <= content.lines().count());
@@ -1703,28 +1823,29 @@ fn main() {
 fn test_extract_unsupported_file_type_symbol() {
     use tempfile::TempDir;
 
-    // Create a temporary Terraform file (unsupported by tree-sitter)
+    // Create a temporary TOML file (unsupported by tree-sitter)
     let temp_dir = TempDir::new().expect("Failed to create temp dir");
-    let file_path = temp_dir.path().join("main.tf");
-    let content = r#"# Terraform configuration
-resource "aws_instance" "example" {
-  ami           = "ami-0c55b159cbfafe1f0"
-  instance_type = "t2.micro"
-  
-  tags = {
-    Name = "ExampleInstance"
-  }
-}
-
-output "instance_id" {
-  value = aws_instance.example.id
-}
+    let file_path = temp_dir.path().join("Cargo.toml");
+    let content = r#"# Cargo manifest
+[package]
+name = "example"
+version = "0.1.0"
+edition = "2021"
+license = "MIT"
+
+[dependencies]
+serde = "1.0"
+
+[dev-dependencies]
+tempfile = "3"
+aws_instance = "0.1"
 "#;
     fs::write(&file_path, content).unwrap();
 
     // Test extracting a symbol from an unsupported file type
     // Should return the full file content as fallback
     let result = process_file_for_extraction(
+        &Sources::default(),
         &file_path,
         None,                 // start_line
         None,                 // end_line
@@ -1744,6 +1865,49 @@ output "instance_id" {
     assert_eq!(result.lines, (1, 13), "Should return all lines");
 }
 
+#[test]
+fn test_extract_terraform_block() {
+    use tempfile::TempDir;
+
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    let file_path = temp_dir.path().join("main.tf");
+    fs::write(
+        &file_path,
+        r#"resource "aws_instance" "example" {
+  ami           = "ami-0c55b159cbfafe1f0"
+  instance_type = "t2.micro"
+}
+
+resource "aws_s3_bucket" "logs" {
+  bucket = "example-logs"
+}
+
+output "instance_id" {
+  value = aws_instance.example.id
+}
+"#,
+    )
+    .unwrap();
+
+    // Terraform blocks are extracted by address
+    let result = process_file_for_extraction(
+        &Sources::default(),
+        &file_path,
+        None,                       // start_line
+        None,                       // end_line
+        Some("aws_s3_bucket.logs"), // symbol
+        false,                      // allow_tests
+        0,                          // context_lines
+        None,                       // specific_line_numbers
+    )
+    .unwrap();
+
+    assert_eq!(result.node_type, "block");
+    assert_eq!(result.lines, (6, 8));
+    assert!(result.code.contains(r#"bucket = "example-logs""#));
+    assert!(!result.code.contains("ami-0c55b159cbfafe1f0"));
+}
+
 #[test]
 fn test_extract_unsupported_file_type_lines() {
     use tempfile::TempDir;
@@ -1766,6 +1930,7 @@ services:
 
     // Test extracting specific lines from an unsupported file type
     let result = process_file_for_extraction(
+        &Sources::default(),
         &file_path,
         Some(3), // start_line
         Some(6), // end_line
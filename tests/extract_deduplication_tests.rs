@@ -52,6 +52,8 @@ fn standalone_function() {
         prompt: None,
         instructions: None,
         no_gitignore: false,
+        file_filters: Default::default(),
+        source: None,
     };
 
     // Call handle_extract
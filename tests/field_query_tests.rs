@@ -0,0 +1,115 @@
+use std::fs;
+use std::path::Path;
+use tempfile::TempDir;
+
+use probe_code::search::{perform_probe, SearchOptions};
+
+/// Create a Rust file where "cache" appears in a comment, a string literal,
+/// a function name and plain code, each in a different function.
+fn create_test_file(dir: &Path) {
+    let content = r#"
+/// Keeps the cache warm between requests
+fn in_comment() {
+    let value = 1;
+}
+
+fn in_string() {
+    println!("cache miss");
+}
+
+fn cache_lookup() {
+    let value = 2;
+}
+
+fn in_code() {
+    let cache = 3;
+}
+"#;
+    fs::write(dir.join("lib.rs"), content).unwrap();
+}
+
+/// Run a search and return the first line of every result block
+fn search(dir: &Path, query: &str) -> Vec<String> {
+    let queries = vec![query.to_string()];
+    let custom_ignores: Vec<String> = vec![];
+    let options = SearchOptions {
+        path: dir,
+        queries: &queries,
+        files_only: false,
+        custom_ignores: &custom_ignores,
+        exclude_filenames: true,
+        language: None,
+        reranker: "bm25",
+        frequency_search: true,
+        max_results: None,
+        max_bytes: None,
+        max_tokens: None,
+        allow_tests: true,
+        no_merge: true,
+        merge_threshold: None,
+        dry_run: false,
+        session: None,
+        timeout: 30,
+        question: None,
+        exact: false,
+        fuzzy: false,
+        explain: false,
+        no_gitignore: true,
+        file_filters: Default::default(),
+        source: None,
+    };
+
+    let results = perform_probe(&options).unwrap();
+    results
+        .results
+        .iter()
+        .map(|r| {
+            r.code
+                .lines()
+                .find(|l| l.contains("fn "))
+                .unwrap_or("")
+                .to_string()
+        })
+        .collect()
+}
+
+#[test]
+fn test_field_specifiers_restrict_matches() {
+    let temp_dir = TempDir::new().unwrap();
+    create_test_file(temp_dir.path());
+
+    let all = search(temp_dir.path(), "cache");
+    assert_eq!(
+        all.len(),
+        4,
+        "Unscoped term should match every block: {all:?}"
+    );
+
+    let cases = [
+        ("fn:cache", "fn cache_lookup() {"),
+        ("name:cache", "fn cache_lookup() {"),
+        ("comment:cache", "fn in_comment() {"),
+        ("string:cache", "fn in_string() {"),
+        ("code:cache", "fn in_code() {"),
+    ];
+
+    for (query, expected) in cases {
+        let found = search(temp_dir.path(), query);
+        assert_eq!(
+            found,
+            vec![expected.to_string()],
+            "Unexpected blocks for '{query}'"
+        );
+    }
+}
+
+#[test]
+fn test_excluded_field_specifier() {
+    let temp_dir = TempDir::new().unwrap();
+    create_test_file(temp_dir.path());
+
+    // Drop only the block where "cache" appears in a comment
+    let found = search(temp_dir.path(), "+cache -comment:cache");
+    assert_eq!(found.len(), 3, "Unexpected blocks: {found:?}");
+    assert!(!found.iter().any(|l| l.contains("in_comment")));
+}
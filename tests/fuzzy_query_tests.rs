@@ -0,0 +1,102 @@
+use std::fs;
+use std::path::Path;
+use tempfile::TempDir;
+
+use probe_code::search::{perform_probe, SearchOptions};
+
+/// Create a Rust file with a few functions whose names are easy to misspell
+fn create_test_file(dir: &Path) {
+    let content = r#"
+fn receive_message() {
+    let value = 1;
+}
+
+fn json_serializer() {
+    let value = 2;
+}
+
+fn refresh_session() {
+    let value = 3;
+}
+"#;
+    fs::write(dir.join("lib.rs"), content).unwrap();
+}
+
+/// Run a search and return the sorted function names of every result block
+fn search(dir: &Path, query: &str, fuzzy: bool) -> Vec<String> {
+    let queries = vec![query.to_string()];
+    let custom_ignores: Vec<String> = vec![];
+    let options = SearchOptions {
+        path: dir,
+        queries: &queries,
+        files_only: false,
+        custom_ignores: &custom_ignores,
+        exclude_filenames: true,
+        language: None,
+        reranker: "bm25",
+        frequency_search: true,
+        max_results: None,
+        max_bytes: None,
+        max_tokens: None,
+        allow_tests: true,
+        no_merge: true,
+        merge_threshold: None,
+        dry_run: false,
+        session: None,
+        timeout: 30,
+        question: None,
+        exact: false,
+        fuzzy,
+        explain: false,
+        no_gitignore: true,
+        file_filters: Default::default(),
+        source: None,
+    };
+
+    let results = perform_probe(&options).unwrap();
+    let mut names: Vec<String> = results
+        .results
+        .iter()
+        .filter_map(|r| {
+            let line = r.code.lines().find(|l| l.contains("fn "))?;
+            let name = line.trim().trim_start_matches("fn ");
+            Some(name[..name.find('(')?].to_string())
+        })
+        .collect();
+    names.sort();
+    names
+}
+
+#[test]
+fn test_fuzzy_term_syntax() {
+    let temp_dir = TempDir::new().unwrap();
+    create_test_file(temp_dir.path());
+
+    assert!(search(temp_dir.path(), "recieve", false).is_empty());
+    assert_eq!(
+        search(temp_dir.path(), "recieve~1", false),
+        vec!["receive_message"]
+    );
+    assert_eq!(
+        search(temp_dir.path(), "Serilaizer~1", false),
+        vec!["json_serializer"]
+    );
+    // `~0` allows no edits
+    assert!(search(temp_dir.path(), "sesion~0", false).is_empty());
+    assert_eq!(
+        search(temp_dir.path(), "sesion~1", false),
+        vec!["refresh_session"]
+    );
+}
+
+#[test]
+fn test_fuzzy_flag() {
+    let temp_dir = TempDir::new().unwrap();
+    create_test_file(temp_dir.path());
+
+    assert!(search(temp_dir.path(), "recieve OR refesh", false).is_empty());
+    assert_eq!(
+        search(temp_dir.path(), "recieve OR refesh", true),
+        vec!["receive_message", "refresh_session"]
+    );
+}
@@ -0,0 +1,134 @@
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+use tempfile::TempDir;
+
+fn git(dir: &Path, args: &[&str]) {
+    let output = Command::new("git")
+        .current_dir(dir)
+        .args([
+            "-c",
+            "user.name=probe",
+            "-c",
+            "user.email=probe@example.com",
+        ])
+        .args(args)
+        .output()
+        .expect("Failed to run git");
+    assert!(output.status.success(), "git {args:?} failed");
+}
+
+fn probe(dir: &Path, args: &[&str]) -> String {
+    let output = Command::new(env!("CARGO_BIN_EXE_probe"))
+        .current_dir(dir)
+        .args(args)
+        .output()
+        .expect("Failed to run probe");
+    assert!(
+        output.status.success(),
+        "probe {args:?} failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8_lossy(&output.stdout).into_owned()
+}
+
+/// A repository whose `v1` tag has a `legacy_backoff` function, deleted since,
+/// and a `jitter` function that has changed in the working tree
+fn create_test_repository() -> TempDir {
+    let temp_dir = TempDir::new().unwrap();
+    let root = temp_dir.path();
+    fs::create_dir(root.join("src")).unwrap();
+    fs::write(
+        root.join("src/backoff.rs"),
+        "pub fn legacy_backoff(attempts: u32) -> u32 {\n    attempts * 2\n}\n",
+    )
+    .unwrap();
+    fs::write(
+        root.join("src/jitter.rs"),
+        "pub fn jitter(delay: u32) -> u32 {\n    delay + 7\n}\n",
+    )
+    .unwrap();
+    git(root, &["init", "-q"]);
+    git(root, &["add", "."]);
+    git(root, &["commit", "-q", "-m", "initial"]);
+    git(root, &["tag", "v1"]);
+
+    fs::remove_file(root.join("src/backoff.rs")).unwrap();
+    fs::write(
+        root.join("src/jitter.rs"),
+        "pub fn jitter(delay: u32) -> u32 {\n    delay + 11\n}\n",
+    )
+    .unwrap();
+    temp_dir
+}
+
+#[test]
+fn test_search_revision() {
+    let repo = create_test_repository();
+
+    let current = probe(repo.path(), &["search", "backoff", "--format", "plain"]);
+    assert!(!current.contains("legacy_backoff"), "{current}");
+
+    let old = probe(
+        repo.path(),
+        &["search", "backoff", "--format", "plain", "--rev", "v1"],
+    );
+    assert!(old.contains("legacy_backoff"), "{old}");
+
+    let old = probe(
+        repo.path(),
+        &["search", "delay", "src", "--format", "plain", "--rev", "v1"],
+    );
+    assert!(
+        old.contains("delay + 7") && !old.contains("delay + 11"),
+        "{old}"
+    );
+}
+
+#[test]
+fn test_extract_and_query_revision() {
+    let repo = create_test_repository();
+
+    let extracted = probe(
+        repo.path(),
+        &[
+            "extract",
+            "src/backoff.rs:2",
+            "--format",
+            "plain",
+            "--rev",
+            "v1",
+        ],
+    );
+    assert!(extracted.contains("attempts * 2"), "{extracted}");
+
+    let matches = probe(
+        repo.path(),
+        &[
+            "query",
+            "pub fn $NAME($$$PARAMS) -> u32 { $$$BODY }",
+            "src",
+            "--language",
+            "rust",
+            "--format",
+            "plain",
+            "--rev",
+            "v1",
+        ],
+    );
+    assert!(matches.contains("legacy_backoff"), "{matches}");
+    assert!(matches.contains("delay + 7"), "{matches}");
+}
+
+#[test]
+fn test_unknown_revision() {
+    let repo = create_test_repository();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_probe"))
+        .current_dir(repo.path())
+        .args(["search", "backoff", "--rev", "v2"])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Unknown git revision: v2"));
+}
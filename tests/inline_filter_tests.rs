@@ -0,0 +1,175 @@
+use std::fs;
+use std::path::Path;
+use tempfile::TempDir;
+
+use probe_code::search::{perform_probe, FileFilters, SearchOptions};
+
+/// Create the same function in several directories and languages
+fn create_test_files(dir: &Path) {
+    let files = [
+        (
+            "src/auth/session.rs",
+            "fn refresh_session() { let expiry = 10; }\n",
+        ),
+        (
+            "src/billing/invoice.rs",
+            "fn refresh_invoice() { let expiry = 20; }\n",
+        ),
+        (
+            "third_party/client.rs",
+            "fn refresh_client() { let expiry = 30; }\n",
+        ),
+        ("cmd/server.go", "func refreshServer() { expiry := 40 }\n"),
+    ];
+    for (path, content) in files {
+        let full_path = dir.join(path);
+        fs::create_dir_all(full_path.parent().unwrap()).unwrap();
+        fs::write(full_path, content).unwrap();
+    }
+}
+
+/// Run a search and return the matched file paths relative to `dir`
+fn search_files(dir: &Path, query: &str, file_filters: FileFilters) -> Vec<String> {
+    let queries = vec![query.to_string()];
+    let custom_ignores: Vec<String> = vec![];
+    let options = SearchOptions {
+        path: dir,
+        queries: &queries,
+        files_only: false,
+        custom_ignores: &custom_ignores,
+        exclude_filenames: true,
+        language: None,
+        reranker: "bm25",
+        frequency_search: true,
+        max_results: None,
+        max_bytes: None,
+        max_tokens: None,
+        allow_tests: true,
+        no_merge: false,
+        merge_threshold: None,
+        dry_run: false,
+        session: None,
+        timeout: 30,
+        question: None,
+        exact: false,
+        fuzzy: false,
+        explain: false,
+        no_gitignore: true,
+        file_filters,
+        source: None,
+    };
+
+    let results = perform_probe(&options).unwrap();
+    let mut files: Vec<String> = results
+        .results
+        .iter()
+        .map(|r| {
+            Path::new(&r.file)
+                .strip_prefix(dir)
+                .unwrap()
+                .to_string_lossy()
+                .replace('\\', "/")
+        })
+        .collect();
+    files.sort();
+    files.dedup();
+    files
+}
+
+#[test]
+fn test_inline_path_filters() {
+    let temp_dir = TempDir::new().unwrap();
+    create_test_files(temp_dir.path());
+    let dir = temp_dir.path();
+
+    assert_eq!(
+        search_files(dir, "refresh", FileFilters::default()).len(),
+        4
+    );
+    assert_eq!(
+        search_files(dir, "path:src/auth refresh", FileFilters::default()),
+        vec!["src/auth/session.rs"]
+    );
+    assert_eq!(
+        search_files(
+            dir,
+            "refresh -path:third_party -path:billing",
+            FileFilters::default()
+        ),
+        vec!["cmd/server.go", "src/auth/session.rs"]
+    );
+}
+
+#[test]
+fn test_inline_path_filters_in_subdirectory() {
+    let temp_dir = TempDir::new().unwrap();
+    create_test_files(temp_dir.path());
+    let dir = temp_dir.path().join("src");
+
+    // Paths match as shown in results as well as relative to the search root
+    assert_eq!(
+        search_files(&dir, "path:src/auth refresh", FileFilters::default()),
+        vec!["auth/session.rs"]
+    );
+    assert_eq!(
+        search_files(&dir, "path:auth refresh", FileFilters::default()),
+        vec!["auth/session.rs"]
+    );
+    assert_eq!(
+        search_files(&dir, "refresh -path:src/billing", FileFilters::default()),
+        vec!["auth/session.rs"]
+    );
+}
+
+#[test]
+fn test_inline_extension_and_language_filters() {
+    let temp_dir = TempDir::new().unwrap();
+    create_test_files(temp_dir.path());
+    let dir = temp_dir.path();
+
+    assert_eq!(
+        search_files(dir, "ext:go refresh", FileFilters::default()),
+        vec!["cmd/server.go"]
+    );
+    assert_eq!(
+        search_files(
+            dir,
+            "lang:rs -path:third_party refresh",
+            FileFilters::default()
+        ),
+        vec!["src/auth/session.rs", "src/billing/invoice.rs"]
+    );
+    assert_eq!(
+        search_files(dir, "refresh -ext:rs", FileFilters::default()),
+        vec!["cmd/server.go"]
+    );
+    assert_eq!(
+        search_files(
+            dir,
+            "refresh -lang:go -path:third_party",
+            FileFilters::default()
+        ),
+        vec!["src/auth/session.rs", "src/billing/invoice.rs"]
+    );
+    // Extension and language filters must both match
+    assert!(search_files(dir, "ext:go lang:rust refresh", FileFilters::default()).is_empty());
+}
+
+#[test]
+fn test_inline_filters_merge_with_options() {
+    let temp_dir = TempDir::new().unwrap();
+    create_test_files(temp_dir.path());
+    let dir = temp_dir.path();
+
+    let filters = FileFilters {
+        extensions: vec!["rs".to_string()],
+        ..Default::default()
+    };
+    assert_eq!(
+        search_files(dir, "path:src refresh", filters),
+        vec!["src/auth/session.rs", "src/billing/invoice.rs"]
+    );
+
+    // A query made only of filters has nothing to search for
+    assert!(search_files(dir, "path:src", FileFilters::default()).is_empty());
+}
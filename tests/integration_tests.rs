@@ -153,7 +153,11 @@ fn test_search_single_term() {
         timeout: 30,
         question: None,
         exact: false,
+        fuzzy: false,
+        explain: false,
         no_gitignore: false,
+        file_filters: Default::default(),
+        source: None,
     };
 
     // Search for a single term
@@ -219,7 +223,11 @@ fn test_search_multiple_terms() {
         timeout: 30,
         question: None,
         exact: false,
+        fuzzy: false,
+        explain: false,
         no_gitignore: false,
+        file_filters: Default::default(),
+        source: None,
     };
 
     // Search for multiple terms
@@ -267,7 +275,11 @@ fn test_search_files_only() {
         timeout: 30,
         question: None,
         exact: false,
+        fuzzy: false,
+        explain: false,
         no_gitignore: false,
+        file_filters: Default::default(),
+        source: None,
     };
 
     // Search for files only
@@ -344,7 +356,11 @@ fn test_search_include_filenames() {
         timeout: 30,
         question: None,
         exact: false,
+        fuzzy: false,
+        explain: false,
         no_gitignore: false,
+        file_filters: Default::default(),
+        source: None,
     };
 
     // Search with filename matching enabled
@@ -402,7 +418,11 @@ fn test_search_with_limits() {
         timeout: 30,
         question: None,
         exact: false,
+        fuzzy: false,
+        explain: false,
         no_gitignore: false,
+        file_filters: Default::default(),
+        source: None,
     };
 
     // Search with limits
@@ -454,7 +474,11 @@ fn test_frequency_search() {
         timeout: 30,
         question: None,
         exact: false,
+        fuzzy: false,
+        explain: false,
         no_gitignore: false,
+        file_filters: Default::default(),
+        source: None,
     };
 
     // Search using frequency-based search
@@ -514,7 +538,11 @@ func main() {
         timeout: 30,
         question: None,
         exact: false,
+        fuzzy: false,
+        explain: false,
         no_gitignore: false,
+        file_filters: Default::default(),
+        source: None,
     };
 
     // Search for both terms in "all terms" mode
@@ -557,7 +585,11 @@ fn test_search_with_custom_ignores() {
         timeout: 30,
         question: None,
         exact: false,
+        fuzzy: false,
+        explain: false,
         no_gitignore: false,
+        file_filters: Default::default(),
+        source: None,
     };
 
     // Search with custom ignore patterns
@@ -676,7 +708,11 @@ fn calculate_product(a: i32, b: i32) -> i32 {
         timeout: 30,
         question: None,
         exact: false,
+        fuzzy: false,
+        explain: false,
         no_gitignore: false,
+        file_filters: Default::default(),
+        source: None,
     };
 
     // Perform search
@@ -21,6 +21,8 @@ fn test_ip_whitelist_stemming() {
         reranker: "hybrid",
         frequency_search: true, // Use frequency search to get detailed term stats
         exact: false,
+        fuzzy: false,
+        explain: false,
         max_results: None,
         max_bytes: None,
         max_tokens: None,
@@ -32,6 +34,8 @@ fn test_ip_whitelist_stemming() {
         timeout: 30,
         question: None,
         no_gitignore: false,
+        file_filters: Default::default(),
+        source: None,
     };
 
     // Enable debug mode to see the actual terms
@@ -110,6 +114,8 @@ fn test_negative_terms_exclude_files() {
         reranker: "hybrid",
         frequency_search: true,
         exact: false,
+        fuzzy: false,
+        explain: false,
         max_results: None,
         max_bytes: None,
         max_tokens: None,
@@ -121,6 +127,8 @@ fn test_negative_terms_exclude_files() {
         timeout: 30,
         question: None,
         no_gitignore: false,
+        file_filters: Default::default(),
+        source: None,
     };
 
     // Enable debug mode to see the actual terms
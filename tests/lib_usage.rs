@@ -15,6 +15,8 @@ mod tests {
             reranker: "bm25",
             frequency_search: true,
             exact: false,
+            fuzzy: false,
+            explain: false,
             language: None,
             max_results: Some(5),
             max_bytes: None,
@@ -27,6 +29,8 @@ mod tests {
             timeout: 30,
             question: None,
             no_gitignore: false,
+            file_filters: Default::default(),
+            source: None,
         };
 
         let results = perform_probe(&options).unwrap();
@@ -49,6 +53,8 @@ mod tests {
             max_results: Some(5),
             format: "text",
             no_gitignore: false,
+            file_filters: Default::default(),
+            source: None,
         };
 
         let matches = perform_query(&options).unwrap();
@@ -57,4 +63,117 @@ mod tests {
         assert!(!matches.is_empty());
         println!("Found {} matches", matches.len());
     }
+
+    #[test]
+    fn test_probe_builder() {
+        use probe_code::Probe;
+
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        std::fs::write(
+            temp_dir.path().join("auth.rs"),
+            r#"/// Refresh an expired session token
+pub fn refresh_token(session: &Session) -> Token {
+    session.token().refresh()
+}
+
+pub fn revoke_token(token: Token) {
+    token.revoke();
+}
+"#,
+        )
+        .unwrap();
+        let probe = Probe::new(temp_dir.path()).language("rust");
+
+        let results = probe.search("refresh session").unwrap();
+        assert_eq!(results.results.len(), 1);
+        assert!(results.results[0].code.contains("fn refresh_token"));
+
+        // Relative files are extracted from the probe's path
+        let extracted = probe.extract(&["auth.rs:7"]).unwrap();
+        assert!(extracted.errors.is_empty());
+        assert_eq!(extracted.results.len(), 1);
+        assert_eq!(extracted.results[0].lines, (6, 8));
+
+        let matches = probe.query("pub fn $NAME($$$PARAMS) $$$BODY").unwrap();
+        assert_eq!(matches.len(), 2);
+
+        assert!(probe.extract::<&str>(&[]).is_err());
+
+        // A search that runs out of time ends with an error, not the process
+        let error = probe
+            .clone()
+            .timeout(0)
+            .search("refresh session")
+            .unwrap_err();
+        assert!(error.to_string().contains("timed out"), "{error}");
+    }
+
+    #[test]
+    fn test_probe_memory_source() {
+        use probe_code::language::block_cache;
+        use probe_code::language::parser::{calculate_content_hash, line_map_cache_key};
+        use probe_code::source::MemorySource;
+        use probe_code::Probe;
+
+        // The files only exist in memory
+        let root = std::env::temp_dir().join("probe-memory-source-test");
+        let auth = "/// Refresh an expired session token, from an unsaved buffer\npub fn refresh_token(session: &Session) -> Token {\n    session.token().refresh()\n}\n";
+        let source = MemorySource::new(&root).file("src/auth.rs", auth).file(
+            "src/generated.rs",
+            "pub fn revoke_token(token: Token) {\n    token.revoke();\n}\n",
+        );
+        let probe = Probe::new(&root).language("rust").source(source);
+
+        let results = probe.search("refresh session").unwrap();
+        assert_eq!(results.results.len(), 1);
+        assert!(results.results[0].file.ends_with("auth.rs"));
+
+        let extracted = probe.extract(&["src/generated.rs:2"]).unwrap();
+        assert!(extracted.errors.is_empty());
+        assert!(extracted.results[0].code.contains("token.revoke()"));
+
+        let matches = probe.query("pub fn $NAME($$$PARAMS) $$$BODY").unwrap();
+        assert_eq!(matches.len(), 2);
+
+        assert!(!root.exists());
+        // and nothing derived from them is stored in the block cache
+        for allow_tests in [false, true] {
+            let key = line_map_cache_key("rs", calculate_content_hash(auth), allow_tests);
+            assert!(block_cache::load(&key).is_none());
+        }
+    }
+
+    #[test]
+    fn test_probe_sources_at_the_same_time() {
+        use probe_code::source::MemorySource;
+        use probe_code::Probe;
+
+        // Calls with different sources of the same files each read their own
+        let root = std::env::temp_dir().join("probe-concurrent-sources-test");
+        let probes: Vec<(String, Probe)> = (0..4)
+            .map(|i| {
+                let name = format!("handler_{i}");
+                let source = MemorySource::new(&root).file(
+                    "src/lib.rs",
+                    format!("pub fn {name}(request: Request) -> Response {{\n    request.respond()\n}}\n"),
+                );
+                (name, Probe::new(&root).language("rust").source(source))
+            })
+            .collect();
+
+        std::thread::scope(|scope| {
+            for (name, probe) in &probes {
+                scope.spawn(move || {
+                    for _ in 0..5 {
+                        let results = probe.search("request respond").unwrap();
+                        assert_eq!(results.results.len(), 1);
+                        assert!(results.results[0].code.contains(name.as_str()));
+
+                        let extracted = probe.extract(&["src/lib.rs:2"]).unwrap();
+                        assert!(extracted.results[0].code.contains(name.as_str()));
+                    }
+                });
+            }
+        });
+    }
 }
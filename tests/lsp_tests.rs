@@ -0,0 +1,177 @@
+use serde_json::{json, Value};
+use std::fs;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::path::Path;
+use std::process::{Child, ChildStdin, ChildStdout, Command, Stdio};
+use tempfile::TempDir;
+use url::Url;
+
+/// A running `probe lsp`, talked to over its stdin and stdout
+struct LanguageServer {
+    child: Child,
+    stdin: ChildStdin,
+    stdout: BufReader<ChildStdout>,
+    next_id: u64,
+}
+
+impl LanguageServer {
+    fn start() -> Self {
+        let mut child = Command::new(env!("CARGO_BIN_EXE_probe"))
+            .arg("lsp")
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .expect("Failed to start probe lsp");
+        let stdin = child.stdin.take().unwrap();
+        let stdout = BufReader::new(child.stdout.take().unwrap());
+        LanguageServer {
+            child,
+            stdin,
+            stdout,
+            next_id: 1,
+        }
+    }
+
+    fn send(&mut self, message: Value) {
+        let body = message.to_string();
+        write!(self.stdin, "Content-Length: {}\r\n\r\n{body}", body.len()).unwrap();
+        self.stdin.flush().unwrap();
+    }
+
+    fn receive(&mut self) -> Value {
+        let mut content_length = 0;
+        loop {
+            let mut line = String::new();
+            self.stdout.read_line(&mut line).unwrap();
+            let line = line.trim_end();
+            if line.is_empty() {
+                break;
+            }
+            if let Some(length) = line.strip_prefix("Content-Length: ") {
+                content_length = length.parse().unwrap();
+            }
+        }
+        let mut body = vec![0u8; content_length];
+        self.stdout.read_exact(&mut body).unwrap();
+        serde_json::from_slice(&body).unwrap()
+    }
+
+    fn request(&mut self, method: &str, params: Value) -> Value {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.send(json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params }));
+        let response = self.receive();
+        assert_eq!(response["id"], id);
+        assert!(response.get("error").is_none(), "{response}");
+        response["result"].clone()
+    }
+
+    fn notify(&mut self, method: &str, params: Value) {
+        self.send(json!({ "jsonrpc": "2.0", "method": method, "params": params }));
+    }
+}
+
+impl Drop for LanguageServer {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+fn create_test_project(dir: &Path) {
+    fs::create_dir_all(dir.join("src")).unwrap();
+    fs::write(
+        dir.join("src/auth.rs"),
+        r#"/// Refresh an expired session token
+pub fn refresh_token(session: &Session) -> Token {
+    session.token().refresh()
+}
+"#,
+    )
+    .unwrap();
+    fs::write(
+        dir.join("src/main.rs"),
+        r#"fn main() {
+    let session = Session::load();
+    let token = auth::refresh_token(&session);
+}
+"#,
+    )
+    .unwrap();
+}
+
+fn uri(path: &Path) -> String {
+    Url::from_file_path(path).unwrap().to_string()
+}
+
+#[test]
+fn test_language_server() {
+    let temp_dir = TempDir::new().unwrap();
+    let root = temp_dir.path().canonicalize().unwrap();
+    create_test_project(&root);
+
+    let mut server = LanguageServer::start();
+    let result = server.request(
+        "initialize",
+        json!({ "processId": null, "rootUri": uri(&root), "capabilities": {} }),
+    );
+    assert_eq!(result["capabilities"]["workspaceSymbolProvider"], true);
+    server.notify("initialized", json!({}));
+
+    // Workspace symbols are search results
+    let symbols = server.request("workspace/symbol", json!({ "query": "refresh" }));
+    let symbols = symbols.as_array().unwrap();
+    assert!(!symbols.is_empty());
+    let symbol = symbols
+        .iter()
+        .find(|symbol| symbol["location"]["uri"] == uri(&root.join("src/auth.rs")))
+        .unwrap_or_else(|| panic!("{symbols:?}"));
+    assert_eq!(
+        symbol["name"],
+        "pub fn refresh_token(session: &Session) -> Token"
+    );
+    assert_eq!(symbol["kind"], 12);
+    assert_eq!(symbol["location"]["range"]["start"]["line"], 1);
+
+    // Definitions in other files are found through the workspace
+    let main_uri = uri(&root.join("src/main.rs"));
+    let locations = server.request(
+        "textDocument/definition",
+        json!({ "textDocument": { "uri": main_uri }, "position": { "line": 2, "character": 25 } }),
+    );
+    assert_eq!(
+        locations,
+        json!([{
+            "uri": uri(&root.join("src/auth.rs")),
+            "range": {
+                "start": { "line": 1, "character": 7 },
+                "end": { "line": 1, "character": 20 },
+            },
+        }])
+    );
+
+    // Unknown symbols have no definition
+    let locations = server.request(
+        "textDocument/definition",
+        json!({ "textDocument": { "uri": main_uri }, "position": { "line": 1, "character": 28 } }),
+    );
+    assert_eq!(locations, json!([]));
+
+    // probe/search returns the JSON output of probe search
+    let result = server.request(
+        "probe/search",
+        json!({ "query": "session token", "path": "src", "maxResults": 1 }),
+    );
+    let results = result["results"].as_array().unwrap();
+    assert_eq!(results.len(), 1);
+    assert!(results[0]["code"]
+        .as_str()
+        .unwrap()
+        .contains("refresh_token"));
+    assert_eq!(result["summary"]["count"], 1);
+
+    assert_eq!(server.request("shutdown", Value::Null), Value::Null);
+    server.notify("exit", Value::Null);
+    assert!(server.child.wait().unwrap().success());
+}
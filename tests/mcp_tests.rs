@@ -0,0 +1,156 @@
+use serde_json::{json, Value};
+use std::fs;
+use std::io::{BufRead, BufReader, Write};
+use std::path::Path;
+use std::process::{Child, ChildStdin, ChildStdout, Command, Stdio};
+use tempfile::TempDir;
+
+/// A running `probe mcp`, talked to over its stdin and stdout
+struct McpServer {
+    child: Child,
+    stdin: ChildStdin,
+    stdout: BufReader<ChildStdout>,
+    next_id: u64,
+}
+
+impl McpServer {
+    fn start() -> Self {
+        let mut child = Command::new(env!("CARGO_BIN_EXE_probe"))
+            .arg("mcp")
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .expect("Failed to start probe mcp");
+        let stdin = child.stdin.take().unwrap();
+        let stdout = BufReader::new(child.stdout.take().unwrap());
+        McpServer {
+            child,
+            stdin,
+            stdout,
+            next_id: 1,
+        }
+    }
+
+    fn send(&mut self, message: Value) {
+        writeln!(self.stdin, "{message}").unwrap();
+        self.stdin.flush().unwrap();
+    }
+
+    fn request(&mut self, method: &str, params: Value) -> Value {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.send(json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params }));
+
+        let mut line = String::new();
+        self.stdout.read_line(&mut line).unwrap();
+        let response: Value = serde_json::from_str(&line)
+            .unwrap_or_else(|e| panic!("Invalid response {line:?}: {e}"));
+        assert_eq!(response["id"], id);
+        response
+    }
+
+    /// Call a tool, returning its text and whether it failed
+    fn call(&mut self, name: &str, arguments: Value) -> (String, bool) {
+        let response = self.request(
+            "tools/call",
+            json!({ "name": name, "arguments": arguments }),
+        );
+        let result = &response["result"];
+        let text = result["content"][0]["text"].as_str().unwrap().to_string();
+        (text, result["isError"].as_bool().unwrap())
+    }
+}
+
+impl Drop for McpServer {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+fn create_test_project(dir: &Path) {
+    fs::create_dir_all(dir.join("src")).unwrap();
+    fs::write(
+        dir.join("src/auth.rs"),
+        r#"
+/// Refresh an expired session token
+fn refresh_token(session: &Session) -> Token {
+    session.token().refresh()
+}
+
+fn logout(session: &Session) {
+    session.clear();
+}
+"#,
+    )
+    .unwrap();
+}
+
+#[test]
+fn test_mcp_session() {
+    let temp_dir = TempDir::new().unwrap();
+    create_test_project(temp_dir.path());
+    let path = temp_dir.path().to_string_lossy().to_string();
+
+    let mut server = McpServer::start();
+    let response = server.request(
+        "initialize",
+        json!({
+            "protocolVersion": "2025-03-26",
+            "capabilities": {},
+            "clientInfo": { "name": "test", "version": "1.0" },
+        }),
+    );
+    assert_eq!(response["result"]["protocolVersion"], "2025-03-26");
+    server.send(json!({ "jsonrpc": "2.0", "method": "notifications/initialized" }));
+
+    let response = server.request("tools/list", json!({}));
+    assert_eq!(response["result"]["tools"].as_array().unwrap().len(), 3);
+
+    let (text, is_error) = server.call("search", json!({ "path": path, "query": "refresh" }));
+    assert!(!is_error, "{text}");
+    assert!(text.contains("refresh_token"), "{text}");
+    assert!(!text.contains("logout"), "{text}");
+    // No terminal colors in tool output
+    assert!(!text.contains('\u{1b}'), "{text}");
+
+    let (text, is_error) = server.call(
+        "search",
+        json!({ "path": path, "query": "refresh", "format": "json" }),
+    );
+    assert!(!is_error, "{text}");
+    let json: Value = serde_json::from_str(&text).unwrap();
+    assert_eq!(json["results"].as_array().unwrap().len(), 1);
+
+    let (text, is_error) = server.call(
+        "extract",
+        json!({ "path": path, "files": ["src/auth.rs#logout"], "format": "markdown" }),
+    );
+    assert!(!is_error, "{text}");
+    assert!(text.contains("session.clear()"), "{text}");
+    assert!(!text.contains("refresh_token"), "{text}");
+
+    let (text, is_error) = server.call(
+        "query",
+        json!({ "path": path, "pattern": "fn $NAME($$$PARAMS) -> Token { $$$BODY }", "language": "rust" }),
+    );
+    assert!(!is_error, "{text}");
+    assert!(text.contains("refresh_token"), "{text}");
+    assert!(!text.contains("logout"), "{text}");
+
+    // Failures are reported as tool results and the server keeps running
+    let (text, is_error) = server.call("query", json!({ "path": path }));
+    assert!(is_error);
+    assert!(text.contains("pattern"), "{text}");
+    // Like the CLI, extract reports missing files without failing
+    let (text, is_error) = server.call(
+        "extract",
+        json!({ "files": [temp_dir.path().join("missing.rs")] }),
+    );
+    assert!(!is_error, "{text}");
+    assert!(text.contains("No results found"), "{text}");
+
+    let response = server.request("ping", json!({}));
+    assert_eq!(response["result"], json!({}));
+}
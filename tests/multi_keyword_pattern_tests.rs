@@ -1,6 +1,6 @@
 use lru::LruCache;
 use probe_code::search::elastic_query::Expr;
-use probe_code::search::query::{create_structured_patterns, QueryPlan};
+use probe_code::search::query::{create_query_plan, create_structured_patterns, QueryPlan};
 use std::collections::{HashMap, HashSet};
 use std::num::NonZeroUsize;
 use std::sync::{Arc, Mutex};
@@ -434,3 +434,47 @@ fn test_pattern_deduplication() {
         "Should deduplicate patterns for the same term"
     );
 }
+
+/// Test that patterns come out in the same order on every run, with the
+/// combined pattern for all terms first
+#[test]
+fn test_pattern_order_is_stable() {
+    let plan = create_query_plan("auth OR login OR session", false).unwrap();
+    let pattern_strings = |patterns: Vec<(String, HashSet<usize>)>| {
+        patterns.into_iter().map(|(p, _)| p).collect::<Vec<_>>()
+    };
+
+    let first = pattern_strings(create_structured_patterns(&plan));
+    assert!(
+        first[0].starts_with("(?i)("),
+        "Combined pattern should come first: {first:?}"
+    );
+    for _ in 0..10 {
+        assert_eq!(pattern_strings(create_structured_patterns(&plan)), first);
+    }
+}
+
+/// Test that a chain of ORs gives one combined pattern over all alternatives
+#[test]
+fn test_or_chain_pattern_generation() {
+    let plan = create_query_plan("auth OR login OR session OR token", false).unwrap();
+    let patterns = create_structured_patterns(&plan);
+    let pattern_strings: Vec<&str> = patterns.iter().map(|(p, _)| p.as_str()).collect();
+
+    let combined = pattern_strings
+        .iter()
+        .find(|p| !p.starts_with("(?i)") && p.contains("token") && p.contains("auth"))
+        .expect("Should generate a combined pattern for the OR chain");
+    assert_eq!(
+        *combined,
+        "((auth)|(auth)|(login)|(login)|(session)|(session)|(token)|(token))"
+    );
+
+    // No combinations of combinations for the nested ORs
+    assert!(
+        !pattern_strings
+            .iter()
+            .any(|p| p.starts_with("((") && p.contains("auth") && !p.contains("token")),
+        "Unexpected partial combinations: {pattern_strings:?}"
+    );
+}
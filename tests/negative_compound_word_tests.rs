@@ -106,7 +106,11 @@ fn test_basic_negative_compound_word(temp_path: &Path) {
         timeout: 30,
         question: None,
         exact: false,
+        fuzzy: false,
+        explain: false,
         no_gitignore: false,
+        file_filters: Default::default(),
+        source: None,
     };
 
     // Run the search
@@ -174,7 +178,11 @@ fn test_complex_negative_compound_word(temp_path: &Path) {
         timeout: 30,
         question: None,
         exact: false,
+        fuzzy: false,
+        explain: false,
         no_gitignore: false,
+        file_filters: Default::default(),
+        source: None,
     };
 
     // Run the search
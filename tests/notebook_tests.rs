@@ -0,0 +1,147 @@
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+use tempfile::TempDir;
+
+fn probe(dir: &Path, args: &[&str]) -> String {
+    let output = Command::new(env!("CARGO_BIN_EXE_probe"))
+        .current_dir(dir)
+        .args(args)
+        .output()
+        .expect("Failed to run probe");
+    assert!(
+        output.status.success(),
+        "probe {args:?} failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8_lossy(&output.stdout).into_owned()
+}
+
+/// A notebook with a plot output larger than the file size limit
+fn create_test_notebook() -> TempDir {
+    let temp_dir = TempDir::new().unwrap();
+    let plot = "iVBORw0KGgo".repeat(150_000);
+    let notebook = serde_json::json!({
+        "cells": [
+            {
+                "cell_type": "markdown",
+                "metadata": {},
+                "source": ["# Churn model\n", "\n", "Loads the customer table."]
+            },
+            {
+                "cell_type": "code",
+                "execution_count": 1,
+                "metadata": {},
+                "outputs": [],
+                "source": [
+                    "import pandas as pd\n",
+                    "\n",
+                    "df = pd.read_csv('customers.csv')\n",
+                    "df = df.dropna()\n",
+                    "df['signup_month'] = df.signup_date.dt.to_period('M')\n",
+                    "df['active'] = df.last_seen > '2024-01-01'\n",
+                    "df.head()"
+                ]
+            },
+            {
+                "cell_type": "code",
+                "execution_count": 2,
+                "metadata": {},
+                "outputs": [
+                    {
+                        "output_type": "display_data",
+                        "data": {"image/png": plot, "text/plain": ["<Figure retention>"]},
+                        "metadata": {}
+                    }
+                ],
+                "source": [
+                    "def retention_curve(df):\n",
+                    "    cohorts = df.groupby('signup_month')\n",
+                    "    return cohorts.active.mean()\n"
+                ]
+            }
+        ],
+        "metadata": {"kernelspec": {"name": "python3", "language": "python"}},
+        "nbformat": 4,
+        "nbformat_minor": 5
+    });
+    fs::write(
+        temp_dir.path().join("churn.ipynb"),
+        serde_json::to_string_pretty(&notebook).unwrap(),
+    )
+    .unwrap();
+    temp_dir
+}
+
+#[test]
+fn test_search_notebook_cells() {
+    let dir = create_test_notebook();
+
+    let output = probe(dir.path(), &["search", "retention", "."]);
+    assert!(output.contains("churn.ipynb"), "{output}");
+    assert!(
+        output.contains("Lines: 14-16 (cell 3, lines 1-3)"),
+        "{output}"
+    );
+    assert!(output.contains("def retention_curve(df):"), "{output}");
+
+    // Outputs aren't searched
+    let output = probe(dir.path(), &["search", "Figure", "."]);
+    assert!(!output.contains("churn.ipynb"), "{output}");
+}
+
+#[test]
+fn test_extract_notebook_function() {
+    let dir = create_test_notebook();
+
+    let output = probe(
+        dir.path(),
+        &["extract", "churn.ipynb#retention_curve", "--format", "json"],
+    );
+    let json: serde_json::Value = serde_json::from_str(&output).unwrap();
+    let result = &json["results"][0];
+    assert_eq!(result["node_type"], "function_definition");
+    assert_eq!(
+        result["cells"],
+        serde_json::json!({
+            "start": {"cell": 3, "line": 1},
+            "end": {"cell": 3, "line": 3}
+        })
+    );
+    assert!(result["code"]
+        .as_str()
+        .unwrap()
+        .starts_with("def retention_curve(df):"));
+    assert!(!output.contains("iVBOR"));
+}
+
+#[test]
+fn test_results_stop_at_cell_boundaries() {
+    let dir = create_test_notebook();
+
+    for args in [
+        &["search", "df", ".", "--format", "json"][..],
+        &["extract", "churn.ipynb:2-16", "--format", "json"][..],
+    ] {
+        let output = probe(dir.path(), args);
+        // Search prints a header and a tip around its JSON
+        let json: serde_json::Value =
+            serde_json::Deserializer::from_str(&output[output.find("{\n").unwrap()..])
+                .into_iter()
+                .next()
+                .unwrap()
+                .unwrap();
+        let results = json["results"].as_array().unwrap();
+        assert!(results.len() > 1, "{output}");
+        for result in results {
+            assert_eq!(
+                result["cells"]["start"]["cell"], result["cells"]["end"]["cell"],
+                "{output}"
+            );
+            assert!(
+                !result["code"].as_str().unwrap().contains("# %%"),
+                "{output}"
+            );
+        }
+    }
+}
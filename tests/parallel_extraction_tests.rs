@@ -37,6 +37,8 @@ fn test_parallel_file_extraction() {
         keep_input: false,
         prompt: None,
         no_gitignore: false,
+        file_filters: Default::default(),
+        source: None,
     };
 
     // Run the extraction
@@ -100,6 +102,8 @@ fn function_four() {
         keep_input: false,
         prompt: None,
         no_gitignore: false,
+        file_filters: Default::default(),
+        source: None,
     };
 
     // Run the extraction
@@ -167,6 +171,8 @@ fn test_parallel_extraction_performance() {
         keep_input: false,
         prompt: None,
         no_gitignore: false,
+        file_filters: Default::default(),
+        source: None,
     };
 
     // Run the extraction
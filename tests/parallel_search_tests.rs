@@ -1,5 +1,6 @@
 use probe_code::search::search_runner::{perform_probe, search_with_structured_patterns};
 use probe_code::search::SearchOptions;
+use probe_code::source::Sources;
 use std::fs;
 use std::path::{Path, PathBuf};
 use std::time::Instant;
@@ -85,7 +86,11 @@ fn test_parallel_file_search() {
         timeout: 30,
         question: None,
         exact: false,
+        fuzzy: false,
+        explain: false,
         no_gitignore: false,
+        file_filters: Default::default(),
+        source: None,
     };
 
     // Measure search time
@@ -135,6 +140,7 @@ fn test_structured_patterns_search() {
     // Measure search time
     let start_time = Instant::now();
     let result = search_with_structured_patterns(
+        &Sources::default(),
         base_path,
         &query_plan,
         &patterns,
@@ -142,6 +148,7 @@ fn test_structured_patterns_search() {
         true,
         None,
         false,
+        &Default::default(),
     );
     let duration = start_time.elapsed();
 
@@ -216,7 +223,11 @@ fn function_{}() {{
         timeout: 30,
         question: None,
         exact: false,
+        fuzzy: false,
+        explain: false,
         no_gitignore: false,
+        file_filters: Default::default(),
+        source: None,
     };
 
     // Measure search time
@@ -321,7 +332,11 @@ fn function_with_blocks_{}() {{
         timeout: 30,
         question: None,
         exact: false,
+        fuzzy: false,
+        explain: false,
         no_gitignore: false,
+        file_filters: Default::default(),
+        source: None,
     };
 
     // Measure search time
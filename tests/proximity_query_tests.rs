@@ -0,0 +1,112 @@
+use std::fs;
+use std::path::Path;
+use tempfile::TempDir;
+
+use probe_code::search::{perform_probe, SearchOptions};
+
+/// Create a Rust file where "cache" and "invalidate" appear next to each
+/// other in one function, far apart in another, and both far apart and
+/// close together in a third.
+fn create_test_file(dir: &Path) {
+    let filler = (0..40)
+        .map(|i| format!("    let step_{i} = compute_value({i});\n"))
+        .collect::<String>();
+    let content = format!(
+        r#"
+fn close_together() {{
+    cache.invalidate();
+}}
+
+fn far_apart() {{
+    let cache = open();
+{filler}    invalidate();
+}}
+
+fn repeated() {{
+    let cache = open();
+{filler}    cache.invalidate();
+}}
+"#
+    );
+    fs::write(dir.join("lib.rs"), content).unwrap();
+}
+
+/// Run a search and return the first line of every result block
+fn search(dir: &Path, query: &str) -> Vec<String> {
+    let queries = vec![query.to_string()];
+    let custom_ignores: Vec<String> = vec![];
+    let options = SearchOptions {
+        path: dir,
+        queries: &queries,
+        files_only: false,
+        custom_ignores: &custom_ignores,
+        exclude_filenames: true,
+        language: None,
+        reranker: "bm25",
+        frequency_search: true,
+        max_results: None,
+        max_bytes: None,
+        max_tokens: None,
+        allow_tests: true,
+        no_merge: true,
+        merge_threshold: None,
+        dry_run: false,
+        session: None,
+        timeout: 30,
+        question: None,
+        exact: false,
+        fuzzy: false,
+        explain: false,
+        no_gitignore: true,
+        file_filters: Default::default(),
+        source: None,
+    };
+
+    let results = perform_probe(&options).unwrap();
+    results
+        .results
+        .iter()
+        .map(|r| {
+            r.code
+                .lines()
+                .find(|l| l.contains("fn "))
+                .unwrap_or("")
+                .to_string()
+        })
+        .collect()
+}
+
+#[test]
+fn test_proximity_restricts_matches() {
+    let temp_dir = TempDir::new().unwrap();
+    create_test_file(temp_dir.path());
+
+    let all = search(temp_dir.path(), "+cache +invalidate");
+    assert_eq!(all.len(), 3, "Every block contains both terms: {all:?}");
+
+    for query in ["\"cache invalidate\"~3", "cache NEAR/3 invalidate"] {
+        let mut found = search(temp_dir.path(), query);
+        found.sort();
+        assert_eq!(
+            found,
+            vec![
+                "fn close_together() {".to_string(),
+                "fn repeated() {".to_string()
+            ],
+            "Unexpected blocks for '{query}'"
+        );
+    }
+
+    // A wide enough window matches every block again
+    let wide = search(temp_dir.path(), "\"cache invalidate\"~500");
+    assert_eq!(wide.len(), 3, "Unexpected blocks: {wide:?}");
+}
+
+#[test]
+fn test_excluded_proximity() {
+    let temp_dir = TempDir::new().unwrap();
+    create_test_file(temp_dir.path());
+
+    let found = search(temp_dir.path(), "+cache -\"cache invalidate\"~3");
+    assert_eq!(found, vec!["fn far_apart() {".to_string()]);
+}
@@ -32,6 +32,8 @@ fn add(a: i32, b: i32) -> i32 {
         max_results: None,
         format: "plain",
         no_gitignore: false,
+        file_filters: Default::default(),
+        source: None,
     };
 
     // Perform the query
@@ -84,6 +86,8 @@ const multiply = (a, b) => a * b;
         max_results: None,
         format: "plain",
         no_gitignore: false,
+        file_filters: Default::default(),
+        source: None,
     };
 
     // Perform the query
@@ -104,6 +108,8 @@ const multiply = (a, b) => a * b;
         max_results: None,
         format: "plain",
         no_gitignore: false,
+        file_filters: Default::default(),
+        source: None,
     };
 
     // Perform the query
@@ -144,6 +150,8 @@ fn func5() {}
         max_results: Some(3),
         format: "plain",
         no_gitignore: false,
+        file_filters: Default::default(),
+        source: None,
     };
 
     // Perform the query
@@ -181,6 +189,8 @@ fn test_query_ignore_patterns() -> Result<()> {
         max_results: None,
         format: "plain",
         no_gitignore: false,
+        file_filters: Default::default(),
+        source: None,
     };
 
     // Perform the query
@@ -219,6 +229,8 @@ fn auto_detected_function() {
         max_results: None,
         format: "plain",
         no_gitignore: false,
+        file_filters: Default::default(),
+        source: None,
     };
 
     // Perform the query
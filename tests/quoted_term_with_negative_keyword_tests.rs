@@ -88,7 +88,11 @@ fn test_quoted_term_with_negative_keyword() {
         timeout: 30,
         question: None,
         exact: false,
+        fuzzy: false,
+        explain: false,
         no_gitignore: false,
+        file_filters: Default::default(),
+        source: None,
     };
 
     // Print the query for debugging
@@ -171,7 +175,11 @@ fn test_negative_quoted_term() {
         timeout: 30,
         question: None,
         exact: false,
+        fuzzy: false,
+        explain: false,
         no_gitignore: false,
+        file_filters: Default::default(),
+        source: None,
     };
 
     // Print the query for debugging
@@ -0,0 +1,101 @@
+use std::fs;
+use std::path::Path;
+use tempfile::TempDir;
+
+use probe_code::search::{perform_probe, SearchOptions};
+
+/// Create a Rust file where only some functions carry an owned TODO marker
+fn create_test_file(dir: &Path) {
+    let content = r#"
+fn refresh_session() {
+    // TODO(alice): rotate the token
+    let token = 1;
+}
+
+fn lock_session() {
+    // todo: lock the token
+    let token = 2;
+}
+
+fn close_session() {
+    let token = 3;
+}
+"#;
+    fs::write(dir.join("lib.rs"), content).unwrap();
+}
+
+/// Run a search and return the function names of the result blocks, sorted
+fn search(dir: &Path, query: &str) -> Vec<String> {
+    let queries = vec![query.to_string()];
+    let custom_ignores: Vec<String> = vec![];
+    let options = SearchOptions {
+        path: dir,
+        queries: &queries,
+        files_only: false,
+        custom_ignores: &custom_ignores,
+        exclude_filenames: true,
+        language: None,
+        reranker: "bm25",
+        frequency_search: true,
+        max_results: None,
+        max_bytes: None,
+        max_tokens: None,
+        allow_tests: true,
+        no_merge: true,
+        merge_threshold: None,
+        dry_run: false,
+        session: None,
+        timeout: 30,
+        question: None,
+        exact: false,
+        fuzzy: false,
+        explain: false,
+        no_gitignore: true,
+        file_filters: Default::default(),
+        source: None,
+    };
+
+    let results = perform_probe(&options).unwrap();
+    let mut names: Vec<String> = results
+        .results
+        .iter()
+        .filter_map(|r| {
+            let line = r.code.lines().find(|l| l.contains("fn "))?;
+            let name = line.trim().trim_start_matches("fn ");
+            Some(name[..name.find('(')?].to_string())
+        })
+        .collect();
+    names.sort();
+    names
+}
+
+#[test]
+fn test_regex_terms_mixed_with_keywords() {
+    let temp_dir = TempDir::new().unwrap();
+    create_test_file(temp_dir.path());
+
+    assert_eq!(
+        search(temp_dir.path(), r"+/TODO\(\w+\)/ token"),
+        vec!["refresh_session"]
+    );
+    // Regexes are case-sensitive unless they carry the `i` flag
+    assert_eq!(
+        search(temp_dir.path(), "+/todo/ token"),
+        vec!["lock_session"]
+    );
+    assert_eq!(
+        search(temp_dir.path(), "+/todo/i token"),
+        vec!["lock_session", "refresh_session"]
+    );
+}
+
+#[test]
+fn test_excluded_regex_term() {
+    let temp_dir = TempDir::new().unwrap();
+    create_test_file(temp_dir.path());
+
+    assert_eq!(
+        search(temp_dir.path(), "+session -/(?i)todo/"),
+        vec!["close_session"]
+    );
+}
@@ -0,0 +1,153 @@
+use std::fs;
+use std::path::Path;
+use tempfile::TempDir;
+
+use probe_code::search::search_index::{build_index, IndexOptions, INDEX_DIR};
+use probe_code::search::{perform_probe, SearchOptions};
+
+/// Create a small project with a few Rust and Python files
+fn create_test_project(dir: &Path) {
+    fs::create_dir_all(dir.join("src")).unwrap();
+    fs::write(
+        dir.join("src/auth.rs"),
+        r#"
+/// Refresh an expired session token
+fn refresh_token(session: &Session) -> Token {
+    let token = session.token();
+    token.refresh()
+}
+
+struct Session {
+    token: Token,
+}
+"#,
+    )
+    .unwrap();
+    fs::write(
+        dir.join("src/lock.rs"),
+        r#"
+fn acquire_lock(name: &str) -> Lock {
+    // TODO(alice): retry when the lock is taken
+    Lock::new(name)
+}
+"#,
+    )
+    .unwrap();
+    fs::write(
+        dir.join("src/session.py"),
+        r#"
+class SessionStore:
+    def refresh(self, session):
+        """Refresh the session lock"""
+        return session
+"#,
+    )
+    .unwrap();
+}
+
+fn index(dir: &Path) {
+    build_index(&IndexOptions {
+        path: dir,
+        custom_ignores: &[],
+        allow_tests: false,
+        no_gitignore: true,
+    })
+    .unwrap();
+}
+
+/// Run a search and return every result as file, lines and code
+fn search(dir: &Path, query: &str) -> Vec<(String, (usize, usize), String)> {
+    let queries = vec![query.to_string()];
+    let custom_ignores: Vec<String> = vec![];
+    let options = SearchOptions {
+        path: dir,
+        queries: &queries,
+        files_only: false,
+        custom_ignores: &custom_ignores,
+        exclude_filenames: false,
+        language: None,
+        reranker: "bm25",
+        frequency_search: true,
+        max_results: None,
+        max_bytes: None,
+        max_tokens: None,
+        allow_tests: false,
+        no_merge: false,
+        merge_threshold: None,
+        dry_run: false,
+        session: None,
+        timeout: 30,
+        question: None,
+        exact: false,
+        fuzzy: false,
+        explain: false,
+        no_gitignore: true,
+        file_filters: Default::default(),
+        source: None,
+    };
+
+    let results = perform_probe(&options).unwrap();
+    results
+        .results
+        .iter()
+        .map(|r| (r.file.clone(), r.lines, r.code.clone()))
+        .collect()
+}
+
+const QUERIES: &[&str] = &[
+    "refresh",
+    "session token",
+    "+lock -session",
+    "fn:refresh_token",
+    "comment:todo",
+    r"/TODO\(\w+\)/",
+    "refresh NEAR/3 session",
+];
+
+#[test]
+fn test_index_gives_same_results() {
+    let temp_dir = TempDir::new().unwrap();
+    let dir = temp_dir.path();
+    create_test_project(dir);
+
+    let without_index: Vec<_> = QUERIES.iter().map(|q| search(dir, q)).collect();
+    assert!(without_index.iter().all(|results| !results.is_empty()));
+
+    index(dir);
+    assert!(dir.join(INDEX_DIR).is_dir());
+    for (query, expected) in QUERIES.iter().zip(&without_index) {
+        assert_eq!(
+            &search(dir, query),
+            expected,
+            "Different results for '{query}'"
+        );
+    }
+}
+
+#[test]
+fn test_files_changed_after_indexing_are_parsed_again() {
+    let temp_dir = TempDir::new().unwrap();
+    let dir = temp_dir.path();
+    create_test_project(dir);
+    index(dir);
+
+    fs::write(
+        dir.join("src/lock.rs"),
+        r#"
+fn acquire_lock(name: &str) -> Lock {
+    Lock::new(name)
+}
+
+fn write_checkpoint(lock: &Lock) {
+    lock.flush();
+}
+"#,
+    )
+    .unwrap();
+    let found = search(dir, "checkpoint");
+    let lines: Vec<(usize, usize)> = found.iter().map(|(_, lines, _)| *lines).collect();
+    assert_eq!(lines, vec![(6, 8)], "Unexpected results: {found:?}");
+
+    // The old content of the file is gone
+    assert!(search(dir, "comment:todo").is_empty());
+}
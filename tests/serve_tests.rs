@@ -0,0 +1,159 @@
+use serde_json::{json, Value};
+use std::fs;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpStream;
+use std::path::Path;
+use std::process::{Child, Command, Stdio};
+use std::thread;
+use tempfile::TempDir;
+
+/// A running `probe serve`, stopped when dropped
+struct Server {
+    child: Child,
+    address: String,
+}
+
+impl Server {
+    fn start(root: &Path) -> Self {
+        let mut child = Command::new(env!("CARGO_BIN_EXE_probe"))
+            .args(["serve", "--port", "0"])
+            .current_dir(root)
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .expect("Failed to start probe serve");
+
+        // The first line tells the address, with the port picked by the system
+        let mut stdout = BufReader::new(child.stdout.take().unwrap());
+        let mut line = String::new();
+        stdout.read_line(&mut line).unwrap();
+        let address = line
+            .split("http://")
+            .nth(1)
+            .unwrap_or_else(|| panic!("Unexpected output: {line}"))
+            .trim()
+            .to_string();
+        // Keep reading the log so the server never blocks on a full pipe
+        thread::spawn(move || std::io::copy(&mut stdout, &mut std::io::sink()));
+
+        Server { child, address }
+    }
+}
+
+impl Drop for Server {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+/// Send a request and return the status and JSON body of the response
+fn request(address: &str, method: &str, path: &str, body: &Value) -> (u16, Value) {
+    let body = body.to_string();
+    let mut stream = TcpStream::connect(address).unwrap();
+    write!(
+        stream,
+        "{method} {path} HTTP/1.1\r\nHost: {address}\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{body}",
+        body.len()
+    )
+    .unwrap();
+
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+    let (head, body) = response.split_once("\r\n\r\n").unwrap();
+    let status = head.split_whitespace().nth(1).unwrap().parse().unwrap();
+    (status, serde_json::from_str(body).unwrap())
+}
+
+fn create_test_project(dir: &Path) {
+    fs::create_dir_all(dir.join("src")).unwrap();
+    fs::write(
+        dir.join("src/auth.rs"),
+        r#"/// Refresh an expired session token
+pub fn refresh_token(session: &Session) -> Token {
+    session.token().refresh()
+}
+
+pub fn revoke_token(token: Token) {
+    token.revoke();
+}
+"#,
+    )
+    .unwrap();
+}
+
+#[test]
+fn test_serve_endpoints() {
+    let temp_dir = TempDir::new().unwrap();
+    create_test_project(temp_dir.path());
+    let server = Server::start(temp_dir.path());
+
+    let (status, search) = request(
+        &server.address,
+        "POST",
+        "/search",
+        &json!({ "query": "refresh session", "path": "src", "max_results": 1 }),
+    );
+    assert_eq!(status, 200, "{search}");
+    let results = search["results"].as_array().unwrap();
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0]["file"], "src/auth.rs");
+    assert!(results[0]["code"]
+        .as_str()
+        .unwrap()
+        .contains("fn refresh_token"));
+    assert_eq!(search["summary"]["count"], 1);
+
+    let (status, extract) = request(
+        &server.address,
+        "POST",
+        "/extract",
+        &json!({ "files": ["src/auth.rs:7"] }),
+    );
+    assert_eq!(status, 200, "{extract}");
+    assert_eq!(extract["results"][0]["lines"], json!([6, 8]));
+    assert!(extract["results"][0]["code"]
+        .as_str()
+        .unwrap()
+        .contains("token.revoke()"));
+
+    let (status, query) = request(
+        &server.address,
+        "POST",
+        "/query",
+        &json!({ "pattern": "pub fn $NAME($$$PARAMS) $$$BODY", "language": "rust" }),
+    );
+    assert_eq!(status, 200, "{query}");
+    assert_eq!(query["summary"]["count"], 2);
+
+    // Requests are answered concurrently
+    let searches: Vec<_> = (0..4)
+        .map(|_| {
+            let address = server.address.clone();
+            thread::spawn(move || {
+                request(&address, "POST", "/search", &json!({ "query": "revoke" }))
+            })
+        })
+        .collect();
+    for search in searches {
+        let (status, search) = search.join().unwrap();
+        assert_eq!(status, 200);
+        assert!(search["results"][0]["code"]
+            .as_str()
+            .unwrap()
+            .contains("fn revoke_token"));
+    }
+
+    let (status, error) = request(
+        &server.address,
+        "POST",
+        "/search",
+        &json!({ "max_results": 1 }),
+    );
+    assert_eq!(status, 400);
+    assert!(error["error"].as_str().unwrap().contains("query"));
+    let (status, _) = request(&server.address, "GET", "/search", &json!({}));
+    assert_eq!(status, 405);
+    let (status, _) = request(&server.address, "POST", "/index", &json!({}));
+    assert_eq!(status, 404);
+}
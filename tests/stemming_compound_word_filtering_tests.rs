@@ -101,7 +101,11 @@ fn test_stemming_with_and_query(temp_path: &Path) {
         timeout: 30,
         question: None,
         exact: false,
+        fuzzy: false,
+        explain: false,
         no_gitignore: false,
+        file_filters: Default::default(),
+        source: None,
     };
 
     // Run the search
@@ -183,7 +187,11 @@ fn test_stemming_with_or_query(temp_path: &Path) {
         timeout: 30,
         question: None,
         exact: false,
+        fuzzy: false,
+        explain: false,
         no_gitignore: false,
+        file_filters: Default::default(),
+        source: None,
     };
 
     // Run the search
@@ -387,7 +395,11 @@ fn test_compound_word_splitting(temp_path: &Path) {
         timeout: 30,
         question: None,
         exact: false,
+        fuzzy: false,
+        explain: false,
         no_gitignore: false,
+        file_filters: Default::default(),
+        source: None,
     };
 
     // Run the search
@@ -434,7 +446,11 @@ fn test_compound_word_splitting(temp_path: &Path) {
         timeout: 30,
         question: None,
         exact: false,
+        fuzzy: false,
+        explain: false,
         no_gitignore: false,
+        file_filters: Default::default(),
+        source: None,
     };
 
     // Run the search
@@ -493,7 +509,11 @@ fn test_negative_compound_word_in_existing_tests(temp_path: &Path) {
         timeout: 30,
         question: None,
         exact: false,
+        fuzzy: false,
+        explain: false,
         no_gitignore: false,
+        file_filters: Default::default(),
+        source: None,
     };
 
     // Run the search
@@ -0,0 +1,150 @@
+use std::fs;
+use std::path::Path;
+use tempfile::TempDir;
+
+use probe_code::search::{perform_probe, SearchOptions};
+
+/// Create a project with a synonyms file and a Rust file that refers to the
+/// same concepts by abbreviation and by full name
+fn create_test_project(dir: &Path) {
+    fs::create_dir(dir.join(".probe")).unwrap();
+    fs::write(
+        dir.join(".probe").join("synonyms.toml"),
+        r#"
+auth = ["authentication", "login"]
+cfg = "config"
+"#,
+    )
+    .unwrap();
+
+    let content = r#"
+fn check_auth(token: &str) -> bool {
+    token.len() > 3
+}
+
+fn run_authentication(user: &str) -> bool {
+    user.is_empty()
+}
+
+fn login_page() {
+    let page = 1;
+}
+
+fn load_config() {
+    let path = 2;
+}
+
+fn unrelated() {
+    let value = 3;
+}
+"#;
+    fs::write(dir.join("lib.rs"), content).unwrap();
+}
+
+/// Run a search and return the function names of the result blocks in rank order
+fn search(dir: &Path, query: &str, exact: bool) -> Vec<String> {
+    let queries = vec![query.to_string()];
+    let custom_ignores: Vec<String> = vec![];
+    let options = SearchOptions {
+        path: dir,
+        queries: &queries,
+        files_only: false,
+        custom_ignores: &custom_ignores,
+        exclude_filenames: true,
+        language: None,
+        reranker: "bm25",
+        frequency_search: true,
+        max_results: None,
+        max_bytes: None,
+        max_tokens: None,
+        allow_tests: true,
+        no_merge: true,
+        merge_threshold: None,
+        dry_run: false,
+        session: None,
+        timeout: 30,
+        question: None,
+        exact,
+        fuzzy: false,
+        explain: false,
+        no_gitignore: true,
+        file_filters: Default::default(),
+        source: None,
+    };
+
+    let results = perform_probe(&options).unwrap();
+    results
+        .results
+        .iter()
+        .filter_map(|r| {
+            let line = r.code.lines().find(|l| l.contains("fn "))?;
+            let name = line.trim().trim_start_matches("fn ");
+            Some(name[..name.find('(')?].to_string())
+        })
+        .collect()
+}
+
+#[test]
+fn test_synonyms_match_with_lower_weight() {
+    let temp_dir = TempDir::new().unwrap();
+    create_test_project(temp_dir.path());
+
+    let found = search(temp_dir.path(), "auth", false);
+    assert_eq!(found.len(), 3, "Unexpected blocks: {found:?}");
+    // The literal term ranks above its synonyms
+    assert_eq!(found[0], "check_auth");
+    assert!(found.contains(&"run_authentication".to_string()));
+    assert!(found.contains(&"login_page".to_string()));
+
+    assert_eq!(search(temp_dir.path(), "cfg", false), vec!["load_config"]);
+}
+
+#[test]
+fn test_synonyms_with_prefixes() {
+    let temp_dir = TempDir::new().unwrap();
+    create_test_project(temp_dir.path());
+
+    let found = search(temp_dir.path(), "+auth -login", false);
+    assert_eq!(found.len(), 2, "Unexpected blocks: {found:?}");
+    assert!(!found.contains(&"login_page".to_string()));
+
+    // Excluded terms don't match their synonyms
+    assert_eq!(
+        search(temp_dir.path(), "auth -cfg", false).len(),
+        3,
+        "Unexpected blocks for 'auth -cfg'"
+    );
+    assert!(search(temp_dir.path(), "cfg -auth", false)
+        .iter()
+        .all(|name| name == "load_config"));
+}
+
+#[test]
+fn test_excluded_terms_keep_their_synonyms() {
+    let temp_dir = TempDir::new().unwrap();
+    create_test_project(temp_dir.path());
+    fs::write(
+        temp_dir.path().join("pages.rs"),
+        "fn auth_page() {\n    let page = 1;\n}\n",
+    )
+    .unwrap();
+    fs::write(
+        temp_dir.path().join("login.rs"),
+        "fn login_form() {\n    let page = 2;\n}\n",
+    )
+    .unwrap();
+
+    let found = search(temp_dir.path(), "page -auth", false);
+    assert!(found.contains(&"login_form".to_string()), "{found:?}");
+    assert!(!found.contains(&"auth_page".to_string()), "{found:?}");
+}
+
+#[test]
+fn test_exact_search_skips_synonyms() {
+    let temp_dir = TempDir::new().unwrap();
+    create_test_project(temp_dir.path());
+
+    let found = search(temp_dir.path(), "auth", true);
+    assert!(found.contains(&"check_auth".to_string()));
+    assert!(!found.contains(&"login_page".to_string()));
+}
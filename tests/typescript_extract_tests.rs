@@ -2,6 +2,7 @@ use std::fs;
 
 // Import necessary functions from the extract module
 use probe_code::extract::process_file_for_extraction;
+use probe_code::source::Sources;
 
 fn execute_test(content: &str, expected_outputs: Vec<(usize, usize, usize)>) {
     // Create a temporary file with JavaScript code for testing
@@ -13,9 +14,17 @@ fn execute_test(content: &str, expected_outputs: Vec<(usize, usize, usize)>) {
 
     for (line_number, expected_start, expected_end) in expected_outputs {
         // Call process_file_for_extraction for the current line number
-        let result =
-            process_file_for_extraction(&file_path, Some(line_number), None, None, false, 0, None)
-                .unwrap();
+        let result = process_file_for_extraction(
+            &Sources::default(),
+            &file_path,
+            Some(line_number),
+            None,
+            None,
+            false,
+            0,
+            None,
+        )
+        .unwrap();
 
         // Compare outputs against the expected output structure
         assert_eq!(result.file, file_path.to_string_lossy().to_string());
@@ -0,0 +1,135 @@
+use std::fs;
+use std::path::Path;
+use tempfile::TempDir;
+
+use probe_code::search::{perform_probe, SearchOptions};
+
+/// Create a Rust file with a few functions whose names share prefixes and suffixes
+fn create_test_file(dir: &Path) {
+    let content = r#"
+fn authenticate_user() {
+    let session = 1;
+}
+
+fn authorize_request() {
+    let token = 2;
+}
+
+fn refresh_handler() {
+    let refresh = 3;
+}
+
+fn session_handler() {
+    let session = 4;
+}
+
+fn hash() {
+    let value = 5;
+}
+
+fn hush() {
+    let value = 6;
+}
+
+fn harsh() {
+    let value = 7;
+}
+"#;
+    fs::write(dir.join("lib.rs"), content).unwrap();
+}
+
+/// Run a search and return the sorted function names of every result block
+fn search(dir: &Path, query: &str) -> Vec<String> {
+    let queries = vec![query.to_string()];
+    let custom_ignores: Vec<String> = vec![];
+    let options = SearchOptions {
+        path: dir,
+        queries: &queries,
+        files_only: false,
+        custom_ignores: &custom_ignores,
+        exclude_filenames: true,
+        language: None,
+        reranker: "bm25",
+        frequency_search: true,
+        max_results: None,
+        max_bytes: None,
+        max_tokens: None,
+        allow_tests: true,
+        no_merge: true,
+        merge_threshold: None,
+        dry_run: false,
+        session: None,
+        timeout: 30,
+        question: None,
+        exact: false,
+        fuzzy: false,
+        explain: false,
+        no_gitignore: true,
+        file_filters: Default::default(),
+        source: None,
+    };
+
+    let results = perform_probe(&options).unwrap();
+    let mut names: Vec<String> = results
+        .results
+        .iter()
+        .filter_map(|r| {
+            let line = r.code.lines().find(|l| l.contains("fn "))?;
+            let name = line.trim().trim_start_matches("fn ");
+            Some(name[..name.find('(')?].to_string())
+        })
+        .collect();
+    names.sort();
+    names
+}
+
+#[test]
+fn test_prefix_and_suffix_wildcards() {
+    let temp_dir = TempDir::new().unwrap();
+    create_test_file(temp_dir.path());
+
+    assert_eq!(
+        search(temp_dir.path(), "authenticate_user*"),
+        vec!["authenticate_user"]
+    );
+    assert_eq!(
+        search(temp_dir.path(), "auth*"),
+        vec!["authenticate_user", "authorize_request"]
+    );
+    assert_eq!(
+        search(temp_dir.path(), "*_handler"),
+        vec!["refresh_handler", "session_handler"]
+    );
+}
+
+#[test]
+fn test_single_character_wildcard() {
+    let temp_dir = TempDir::new().unwrap();
+    create_test_file(temp_dir.path());
+
+    // `?` matches exactly one character, so `harsh` doesn't match
+    assert_eq!(search(temp_dir.path(), "h?sh"), vec!["hash", "hush"]);
+}
+
+#[test]
+fn test_wildcards_with_required_and_excluded_prefixes() {
+    let temp_dir = TempDir::new().unwrap();
+    create_test_file(temp_dir.path());
+
+    assert_eq!(
+        search(temp_dir.path(), "+*_handler session"),
+        vec!["refresh_handler", "session_handler"]
+    );
+    assert_eq!(
+        search(temp_dir.path(), "session -*_handler"),
+        vec!["authenticate_user"]
+    );
+}
+
+#[test]
+fn test_wildcard_without_matches_is_searched_as_written() {
+    let temp_dir = TempDir::new().unwrap();
+    create_test_file(temp_dir.path());
+
+    assert!(search(temp_dir.path(), "missing*").is_empty());
+}